@@ -0,0 +1,88 @@
+// The meshes of Rocket League's fields, which rl_ball_sym builds its fields out of
+// They're their own crate so that builds of rl_ball_sym without any of the built-in fields don't embed them, or even depend on them
+//
+// Each mesh is two files, as they're stored in the folders next to this crate's src:
+// the vertex ids of every triangle, as little endian i32s, and the vertices, as little endian f32s, three to a vertex
+
+#[derive(Clone, Copy, Debug)]
+pub struct MeshAsset {
+    // the name of the folder it's in
+    pub field: &'static str,
+    pub name: &'static str,
+    pub ids: &'static [u8],
+    pub vertices: &'static [u8],
+}
+
+#[cfg(any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+macro_rules! mesh {
+    ($field:literal, $name:literal) => {
+        MeshAsset {
+            field: $field,
+            name: $name,
+            ids: include_bytes!(concat!("../", $field, "/", $name, "_ids.bin")),
+            vertices: include_bytes!(concat!("../", $field, "/", $name, "_vertices.bin")),
+        }
+    };
+}
+
+#[cfg(feature = "soccar")]
+pub const SOCCAR: [MeshAsset; 4] = [mesh!("soccar", "soccar_corner"), mesh!("soccar", "soccar_goal"), mesh!("soccar", "soccar_ramps_0"), mesh!("soccar", "soccar_ramps_1")];
+
+#[cfg(feature = "hoops")]
+pub const HOOPS: [MeshAsset; 5] = [mesh!("hoops", "hoops_corner"), mesh!("hoops", "hoops_net"), mesh!("hoops", "hoops_rim"), mesh!("hoops", "hoops_ramps_0"), mesh!("hoops", "hoops_ramps_1")];
+
+#[cfg(feature = "dropshot")]
+pub const DROPSHOT: [MeshAsset; 1] = [mesh!("dropshot", "dropshot")];
+
+#[cfg(feature = "throwback")]
+pub const THROWBACK: [MeshAsset; 10] = [
+    mesh!("throwback", "throwback_back_ramps_lower"),
+    mesh!("throwback", "throwback_back_ramps_upper"),
+    mesh!("throwback", "throwback_corner_ramps_lower"),
+    mesh!("throwback", "throwback_corner_ramps_upper"),
+    mesh!("throwback", "throwback_corner_wall_0"),
+    mesh!("throwback", "throwback_corner_wall_1"),
+    mesh!("throwback", "throwback_corner_wall_2"),
+    mesh!("throwback", "throwback_goal"),
+    mesh!("throwback", "throwback_side_ramps_lower"),
+    mesh!("throwback", "throwback_side_ramps_upper"),
+];
+
+// every mesh whose field's feature is on
+pub fn meshes() -> Vec<&'static MeshAsset> {
+    #[allow(unused_mut)]
+    let mut meshes = Vec::new();
+
+    #[cfg(feature = "soccar")]
+    meshes.extend(&SOCCAR);
+    #[cfg(feature = "hoops")]
+    meshes.extend(&HOOPS);
+    #[cfg(feature = "dropshot")]
+    meshes.extend(&DROPSHOT);
+    #[cfg(feature = "throwback")]
+    meshes.extend(&THROWBACK);
+
+    meshes
+}
+
+pub fn find(field: &str, name: &str) -> Option<&'static MeshAsset> {
+    meshes().into_iter().find(|mesh| mesh.field == field && mesh.name == name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn whole_triangles_and_vertices() {
+        assert_eq!(meshes().len(), 20);
+
+        for mesh in meshes() {
+            assert!(!mesh.ids.is_empty() && mesh.ids.len().is_multiple_of(12), "{}", mesh.name);
+            assert!(!mesh.vertices.is_empty() && mesh.vertices.len().is_multiple_of(12), "{}", mesh.name);
+        }
+
+        assert!(find("soccar", "soccar_goal").is_some());
+        assert!(find("hoops", "soccar_goal").is_none());
+    }
+}
@@ -1,10 +1,17 @@
 use std::sync::Mutex;
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use glam::vec3a;
 use lazy_static::lazy_static;
 use rl_ball_sym::{
     load_dropshot, load_hoops, load_soccar, load_soccar_throwback,
-    simulation::{ball::Ball, game::Game},
+    simulation::{
+        ball::Ball,
+        game::{Accuracy, Game, Integrator},
+        geometry::Sphere,
+        grid::Grid,
+        ground::GroundBall,
+    },
 };
 
 lazy_static! {
@@ -50,6 +57,74 @@ fn get_ball_prediction_struct_benchmark(c: &mut Criterion) {
     c.bench_function("get_ball_prediction/soccar", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
 }
 
+fn get_ball_prediction_struct_grid_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    game.field.use_grid_broadphase(Grid::DEFAULT_CELL_SIZE);
+
+    c.bench_function("get_ball_prediction/soccar_grid", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
+}
+
+fn broadphase_collide_benchmark(c: &mut Criterion) {
+    let bvh_game = load_soccar();
+    let mut grid_game = bvh_game.clone();
+    grid_game.field.use_grid_broadphase(Grid::DEFAULT_CELL_SIZE);
+
+    // a ball sitting in a corner touches a bunch of triangles
+    let sphere = Sphere {
+        center: vec3a(3900., 4900., 100.),
+        radius: bvh_game.ball.collision_radius,
+    };
+
+    let mut group = c.benchmark_group("broadphase_collide");
+    group.bench_function("bvh", |b| b.iter(|| bvh_game.collide(black_box(&sphere))));
+    group.bench_function("grid", |b| b.iter(|| grid_game.collide(black_box(&sphere))));
+    group.finish();
+}
+
+fn accuracy_collide_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+    game.field.build_coarse_collision_mesh();
+
+    let sphere = Sphere {
+        center: vec3a(3900., 4900., 100.),
+        radius: game.ball.collision_radius,
+    };
+
+    let mut group = c.benchmark_group("accuracy_collide");
+    group.bench_function("full", |b| b.iter(|| game.collide_with_accuracy(black_box(&sphere), Accuracy::Full)));
+    group.bench_function("coarse", |b| b.iter(|| game.collide_with_accuracy(black_box(&sphere), Accuracy::Coarse)));
+    group.bench_function("floor_only", |b| b.iter(|| game.collide_with_accuracy(black_box(&sphere), Accuracy::FloorOnly)));
+    group.finish();
+}
+
+fn get_ground_prediction_struct_benchmark(c: &mut Criterion) {
+    let game = load_soccar();
+
+    c.bench_function("get_ground_prediction/soccar", |b| b.iter(|| GroundBall::get_ground_prediction_struct(black_box(&game))));
+}
+
+fn get_ball_prediction_struct_analytic_benchmark(c: &mut Criterion) {
+    let mut game = load_soccar();
+
+    let mut group = c.benchmark_group("floor_bounces");
+    group.bench_function("stepped", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut game))));
+    group.bench_function("analytic", |b| b.iter(|| Ball::get_ball_prediction_struct_analytic(black_box(&mut game))));
+    group.finish();
+}
+
+fn integrator_benchmark(c: &mut Criterion) {
+    let mut euler_game = load_soccar();
+    euler_game.ball.update(0., vec3a(0., 0., 200.), vec3a(300., 0., 1200.), vec3a(0., 0., 0.));
+
+    let mut rk4_game = euler_game.clone();
+    rk4_game.integrator = Integrator::Rk4;
+
+    let mut group = c.benchmark_group("integrator");
+    group.bench_function("euler", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut euler_game.clone()))));
+    group.bench_function("rk4", |b| b.iter(|| Ball::get_ball_prediction_struct(black_box(&mut rk4_game.clone()))));
+    group.finish();
+}
+
 fn get_ball_prediction_struct_hoops_benchmark(c: &mut Criterion) {
     let mut game = load_hoops();
 
@@ -69,5 +144,18 @@ fn get_ball_prediction_struct_throwback(c: &mut Criterion) {
 }
 
 criterion_group!(init, init_benchmark, load_soccar_benchmark, load_hoops_benchmark, load_dropshot_benchmark, load_soccar_throwback_benchmark,);
-criterion_group!(prediction, get_ball_prediction_struct_with_time_benchmark, get_ball_prediction_struct_benchmark, get_ball_prediction_struct_hoops_benchmark, get_ball_prediction_struct_dropshot, get_ball_prediction_struct_throwback);
+criterion_group!(
+    prediction,
+    get_ball_prediction_struct_with_time_benchmark,
+    get_ball_prediction_struct_benchmark,
+    get_ball_prediction_struct_grid_benchmark,
+    broadphase_collide_benchmark,
+    accuracy_collide_benchmark,
+    get_ground_prediction_struct_benchmark,
+    get_ball_prediction_struct_analytic_benchmark,
+    integrator_benchmark,
+    get_ball_prediction_struct_hoops_benchmark,
+    get_ball_prediction_struct_dropshot,
+    get_ball_prediction_struct_throwback
+);
 criterion_main!(init, prediction);
@@ -18,6 +18,7 @@ pub fn main() {
     }
 }
 
+#[allow(static_mut_refs)]
 fn get_output(ball_location: Vec3A, ball_velocity: Vec3A, ball_angular_velocity: Vec3A, time: f32) {
     let game: &mut Game;
 
@@ -37,6 +38,11 @@ fn get_output(ball_location: Vec3A, ball_velocity: Vec3A, ball_angular_velocity:
     // this is a list of 720 slices
     // it goes 6 seconds into the future with 120 slices per second
     let ball_prediction: BallPrediction = Ball::get_ball_prediction_struct(game);
-    assert_eq!(ball_prediction.num_slices, 720);
-    assert_eq!(ball_prediction.slices[ball_prediction.num_slices - 1].time.round() as i32, game.ball.time.round() as i32);
+    // unless the ball escaped, which the random starting points outside of the field's corners can do
+    assert_eq!(ball_prediction.num_slices, ball_prediction.escape.map_or(720, |escape| escape.slice));
+
+    // a ball that escaped straight away doesn't have any slices at all
+    if let Some(last) = ball_prediction.slices.last() {
+        assert_eq!(last.time.round() as i32, game.ball.time.round() as i32);
+    }
 }
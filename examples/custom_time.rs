@@ -18,6 +18,7 @@ pub fn main() {
     }
 }
 
+#[allow(static_mut_refs)]
 fn get_output(ball_location: Vec3A, ball_velocity: Vec3A, ball_angular_velocity: Vec3A, time: f32) {
     let game: &mut Game;
 
@@ -41,6 +42,11 @@ fn get_output(ball_location: Vec3A, ball_velocity: Vec3A, ball_angular_velocity:
     // it generates 120 slices per second
     let prediction_time = 12.;
     let ball_prediction: BallPrediction = Ball::get_ball_prediction_struct_for_time(game, &prediction_time);
-    assert_eq!(ball_prediction.num_slices, (120. * prediction_time).round() as usize);
-    assert_eq!(ball_prediction.slices[ball_prediction.num_slices - 1].time.round() as i32, game.ball.time.round() as i32);
+    // unless the ball escaped, which the random starting points outside of the field's corners can do
+    assert_eq!(ball_prediction.num_slices, ball_prediction.escape.map_or((120. * prediction_time).round() as usize, |escape| escape.slice));
+
+    // a ball that escaped straight away doesn't have any slices at all
+    if let Some(last) = ball_prediction.slices.last() {
+        assert_eq!(last.time.round() as i32, game.ball.time.round() as i32);
+    }
 }
@@ -0,0 +1,50 @@
+// C interface to rl_ball_sym, for when it's built with the `ffi` feature
+// build the library to link against with `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`)
+#ifndef RL_BALL_SYM_H
+#define RL_BALL_SYM_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct RlbsGame RlbsGame;
+
+typedef struct {
+    float x;
+    float y;
+    float z;
+} RlbsVec3;
+
+typedef struct {
+    float time;
+    RlbsVec3 location;
+    RlbsVec3 velocity;
+    RlbsVec3 angular_velocity;
+} RlbsBallSlice;
+
+// every loaded field has to be freed with rlbs_free
+// each of these is only in the library if its field's feature was on when it was built
+RlbsGame *rlbs_load_soccar(void);
+RlbsGame *rlbs_load_hoops(void);
+RlbsGame *rlbs_load_dropshot(void);
+RlbsGame *rlbs_load_throwback(void);
+void rlbs_free(RlbsGame *game);
+
+void rlbs_set_ball(RlbsGame *game, RlbsBallSlice state);
+RlbsBallSlice rlbs_get_ball(const RlbsGame *game);
+
+// fills `slices` with up to `len` slices, 1/120th of a second apart, and moves the ball to the end of them
+// returns how many were written, which is less than `len` if the ball escaped the field, with the slices stopping just before it did
+size_t rlbs_predict(RlbsGame *game, RlbsBallSlice *slices, size_t len);
+
+// with the `plugins` feature, loads the plugin at `path` (see rl_ball_sym_plugin.h) and runs it after every step of the ball
+// returns 0 if it was added, and -1 if it couldn't be loaded
+int rlbs_add_plugin(RlbsGame *game, const char *path);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
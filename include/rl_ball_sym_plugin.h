@@ -0,0 +1,41 @@
+// The interface for physics plugins, which rl_ball_sym loads at runtime when it's built with the `plugins` feature
+// A plugin is a shared library that exports `rlbs_plugin`, which gives back the table of its hooks
+//
+// Plugins are loaded with `Plugin::load` and `Game::add_plugin` in Rust, or `rlbs_add_plugin` through the C interface,
+// and run after every step of the ball, in the order they were added
+#ifndef RL_BALL_SYM_PLUGIN_H
+#define RL_BALL_SYM_PLUGIN_H
+
+#include <stdint.h>
+
+#include "rl_ball_sym.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// plugins built for any other version won't be loaded
+#define RLBS_PLUGIN_ABI_VERSION 1
+
+typedef struct {
+    uint32_t abi_version;
+    // given to every hook, the plugin can keep whatever it wants behind it
+    void *state;
+    // run after every step of the ball, with the normal of what it was touching at the start of the step (or null),
+    // and can change its location, velocity and angular velocity, but not its time
+    // predictions can run on more than one thread at once, so this has to be safe to call from any thread at the same time
+    // can be null
+    void (*step)(void *state, RlbsBallSlice *ball, const RlbsVec3 *contact_normal, float dt);
+    // run once, when every game the plugin was added to is gone
+    // can be null
+    void (*free)(void *state);
+} RlbsPlugin;
+
+// has to be exported by every plugin
+RlbsPlugin rlbs_plugin(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,152 @@
+// Python bindings, built into a module called `rl_ball_sym`:
+//
+//     import rl_ball_sym as rlbs
+//
+//     game = rlbs.load_soccar()
+//     game.set_ball(0., [0., 0., 1000.], [500., 0., 0.], [0., 0., 0.])
+//     slices = game.get_ball_prediction_array()  # a (720, 10) float32 numpy array
+use glam::Vec3A;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::prelude::*;
+use sim::simulation::ball::{Ball, BallPrediction};
+use sim::simulation::game::Game;
+
+// the columns of each row from `get_ball_prediction_array`
+const COLUMNS: usize = 10;
+
+#[pyclass(name = "Ball")]
+#[derive(Clone)]
+struct PyBall(Ball);
+
+#[pymethods]
+impl PyBall {
+    #[getter]
+    fn time(&self) -> f32 {
+        self.0.time
+    }
+
+    #[getter]
+    fn location(&self) -> [f32; 3] {
+        self.0.location.to_array()
+    }
+
+    #[getter]
+    fn velocity(&self) -> [f32; 3] {
+        self.0.velocity.to_array()
+    }
+
+    #[getter]
+    fn angular_velocity(&self) -> [f32; 3] {
+        self.0.angular_velocity.to_array()
+    }
+
+    #[getter]
+    fn radius(&self) -> f32 {
+        self.0.radius
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Ball(time={}, location={:?}, velocity={:?}, angular_velocity={:?})", self.0.time, self.location(), self.velocity(), self.angular_velocity())
+    }
+}
+
+#[pyclass(name = "BallPrediction")]
+struct PyBallPrediction(BallPrediction);
+
+#[pymethods]
+impl PyBallPrediction {
+    #[getter]
+    fn num_slices(&self) -> usize {
+        self.0.num_slices
+    }
+
+    #[getter]
+    fn slices(&self) -> Vec<PyBall> {
+        self.0.slices.iter().copied().map(PyBall).collect()
+    }
+
+    // one row per slice: time, location, velocity, angular velocity
+    fn as_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        to_array(&self.0).into_pyarray(py)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.num_slices
+    }
+}
+
+fn to_array(prediction: &BallPrediction) -> Array2<f32> {
+    let data = prediction.slices.iter().flat_map(|ball| [ball.time].into_iter().chain(ball.location.to_array()).chain(ball.velocity.to_array()).chain(ball.angular_velocity.to_array())).collect();
+
+    Array2::from_shape_vec((prediction.slices.len(), COLUMNS), data).unwrap()
+}
+
+#[pyclass(name = "Game")]
+struct PyGame(Game);
+
+#[pymethods]
+impl PyGame {
+    #[getter]
+    fn ball(&self) -> PyBall {
+        PyBall(self.0.ball)
+    }
+
+    fn set_ball(&mut self, time: f32, location: [f32; 3], velocity: [f32; 3], angular_velocity: [f32; 3]) {
+        self.0.ball.update(time, Vec3A::from(location), Vec3A::from(velocity), Vec3A::from(angular_velocity));
+    }
+
+    // the standard 6 seconds of slices, unless a number of slices or an amount of time is given
+    // Like in Rust, this moves the ball to the last slice
+    #[pyo3(signature = (num_slices = None, time = None))]
+    fn get_ball_prediction_struct(&mut self, num_slices: Option<usize>, time: Option<f32>) -> PyBallPrediction {
+        PyBallPrediction(predict(&mut self.0, num_slices, time))
+    }
+
+    // the same as `get_ball_prediction_struct().as_array()`, without making a Python object for every slice
+    #[pyo3(signature = (num_slices = None, time = None))]
+    fn get_ball_prediction_array<'py>(&mut self, py: Python<'py>, num_slices: Option<usize>, time: Option<f32>) -> Bound<'py, PyArray2<f32>> {
+        to_array(&predict(&mut self.0, num_slices, time)).into_pyarray(py)
+    }
+}
+
+fn predict(game: &mut Game, num_slices: Option<usize>, time: Option<f32>) -> BallPrediction {
+    match (num_slices, time) {
+        (Some(num_slices), _) => Ball::get_ball_prediction_struct_for_slices(game, num_slices),
+        (None, Some(time)) => Ball::get_ball_prediction_struct_for_time(game, &time),
+        (None, None) => Ball::get_ball_prediction_struct(game),
+    }
+}
+
+#[pyfunction]
+fn load_soccar() -> PyGame {
+    PyGame(sim::load_soccar())
+}
+
+#[pyfunction]
+fn load_hoops() -> PyGame {
+    PyGame(sim::load_hoops())
+}
+
+#[pyfunction]
+fn load_dropshot() -> PyGame {
+    PyGame(sim::load_dropshot())
+}
+
+#[pyfunction]
+fn load_soccar_throwback() -> PyGame {
+    PyGame(sim::load_soccar_throwback())
+}
+
+#[pymodule]
+fn rl_ball_sym(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(load_soccar, m)?)?;
+    m.add_function(wrap_pyfunction!(load_hoops, m)?)?;
+    m.add_function(wrap_pyfunction!(load_dropshot, m)?)?;
+    m.add_function(wrap_pyfunction!(load_soccar_throwback, m)?)?;
+    m.add_class::<PyGame>()?;
+    m.add_class::<PyBall>()?;
+    m.add_class::<PyBallPrediction>()?;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use crate::simulation::ball::BallPrediction;
+use crate::simulation::game::Game;
+use crate::simulation::mesh::Mesh;
+
+// How many vertices go around the tube at each slice
+const TUBE_SEGMENTS: usize = 8;
+
+// Line segments, each pair of ids is one line
+#[derive(Clone, Debug, Default)]
+pub struct Lines {
+    pub ids: Vec<i32>,
+    pub vertices: Vec<f32>,
+}
+
+impl Lines {
+    pub fn num_lines(&self) -> usize {
+        self.ids.len() / 2
+    }
+}
+
+// A tube that follows the ball's path, as a triangle mesh
+pub fn trajectory_mesh(prediction: &BallPrediction, radius: f32) -> Mesh {
+    let points: Vec<Vec3A> = prediction.slices.iter().map(|slice| slice.location).collect();
+
+    if points.len() < 2 {
+        return Mesh::default();
+    }
+
+    let mut vertices = Vec::with_capacity(points.len() * TUBE_SEGMENTS * 3);
+    let mut ids = Vec::with_capacity((points.len() - 1) * TUBE_SEGMENTS * 6);
+
+    // carry the ring's orientation along the path so the tube doesn't twist
+    let mut side = Vec3A::ZERO;
+
+    for (i, point) in points.iter().enumerate() {
+        let tangent = (points[(i + 1).min(points.len() - 1)] - points[i.saturating_sub(1)]).normalize_or_zero();
+        let tangent = if tangent == Vec3A::ZERO {
+            Vec3A::Z
+        } else {
+            tangent
+        };
+
+        side = (side - tangent * side.dot(tangent)).normalize_or_zero();
+        if side == Vec3A::ZERO {
+            let reference = if tangent.z.abs() < 0.9 {
+                Vec3A::Z
+            } else {
+                Vec3A::X
+            };
+            side = tangent.cross(reference).normalize();
+        }
+
+        let up = tangent.cross(side);
+
+        for j in 0..TUBE_SEGMENTS {
+            let angle = j as f32 / TUBE_SEGMENTS as f32 * core::f32::consts::TAU;
+            let vertex = *point + (side * angle.cos() + up * angle.sin()) * radius;
+            vertices.extend_from_slice(&vertex.to_array());
+        }
+    }
+
+    for i in 0..points.len() - 1 {
+        let ring = (i * TUBE_SEGMENTS) as i32;
+        let next_ring = ring + TUBE_SEGMENTS as i32;
+
+        for j in 0..TUBE_SEGMENTS as i32 {
+            let next_j = (j + 1) % TUBE_SEGMENTS as i32;
+
+            ids.extend_from_slice(&[ring + j, next_ring + j, ring + next_j]);
+            ids.extend_from_slice(&[ring + next_j, next_ring + j, next_ring + next_j]);
+        }
+    }
+
+    Mesh {
+        ids,
+        vertices,
+    }
+}
+
+// The whole field as the ball collides with it, every mirrored and instanced piece put together, with the shared vertices merged
+// `to_obj` or `to_gltf` on it gives a file that can be opened next to the real arena to check the collision geometry
+pub fn field_mesh(game: &Game) -> Mesh {
+    let mut mesh = Mesh::default();
+    let mut vertex_ids: BTreeMap<[u32; 3], i32> = BTreeMap::new();
+
+    for tri in game.field.collision_mesh.primitives() {
+        for p in tri.p {
+            let id = *vertex_ids.entry(p.to_array().map(f32::to_bits)).or_insert_with(|| {
+                mesh.vertices.extend_from_slice(&p.to_array());
+                (mesh.vertices.len() / 3 - 1) as i32
+            });
+
+            mesh.ids.push(id);
+        }
+    }
+
+    mesh
+}
+
+// Every unique edge of the field's collision mesh
+pub fn field_wireframe(game: &Game) -> Lines {
+    let mut lines = Lines::default();
+
+    // exact positions are fine for finding duplicates, since the shared edges come from the same vertex data
+    let mut vertex_ids: BTreeMap<[u32; 3], i32> = BTreeMap::new();
+    let mut edges = BTreeSet::new();
+
+    let mut vertex_id = |p: Vec3A, vertices: &mut Vec<f32>| {
+        let key = p.to_array().map(f32::to_bits);
+        *vertex_ids.entry(key).or_insert_with(|| {
+            vertices.extend_from_slice(&p.to_array());
+            (vertices.len() / 3 - 1) as i32
+        })
+    };
+
+    for tri in game.field.collision_mesh.primitives() {
+        let ids = tri.p.map(|p| vertex_id(p, &mut lines.vertices));
+
+        for i in 0..3 {
+            let (a, b) = (ids[i], ids[(i + 1) % 3]);
+            let key = (a.min(b), a.max(b));
+
+            if a != b && edges.insert(key) {
+                lines.ids.extend_from_slice(&[key.0, key.1]);
+            }
+        }
+    }
+
+    lines
+}
+
+// The most points that RLBot's renderer will accept in a single polyline
+#[cfg(feature = "rlbot")]
+pub const RLBOT_MAX_POLYLINE_POINTS: usize = 100;
+
+// The path of the ball, split into chunks that can each be passed straight to RLBot's `draw_polyline_3d`
+// Neighboring chunks share their end points so the line doesn't have gaps in it
+#[cfg(feature = "rlbot")]
+pub fn rlbot_polylines(prediction: &BallPrediction) -> Vec<Vec<[f32; 3]>> {
+    rlbot_polylines_with_limit(prediction, RLBOT_MAX_POLYLINE_POINTS)
+}
+
+#[cfg(feature = "rlbot")]
+pub fn rlbot_polylines_with_limit(prediction: &BallPrediction, max_points: usize) -> Vec<Vec<[f32; 3]>> {
+    assert!(max_points >= 2, "A polyline needs at least 2 points");
+
+    let points: Vec<[f32; 3]> = prediction.slices.iter().map(|slice| slice.location.to_array()).collect();
+
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    // the last point of each chunk is repeated as the first point of the next one
+    let mut polylines = Vec::with_capacity(points.len() / (max_points - 1) + 1);
+    let mut start = 0;
+
+    while start < points.len() - 1 {
+        let end = (start + max_points).min(points.len());
+        polylines.push(points[start..end].to_vec());
+        start = end - 1;
+    }
+
+    polylines
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn trajectory_tube() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(500., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 100);
+        let tube = trajectory_mesh(&prediction, 10.);
+
+        assert_eq!(tube.vertices.len(), 100 * TUBE_SEGMENTS * 3);
+        assert_eq!(tube.ids.len(), 99 * TUBE_SEGMENTS * 6);
+        assert!(tube.ids.iter().all(|id| (*id as usize) < tube.vertices.len() / 3));
+
+        // every vertex is on the tube's surface
+        for (i, vertex) in tube.vertices.chunks(3).enumerate() {
+            let center = prediction.slices[i / TUBE_SEGMENTS].location;
+            assert!((Vec3A::from_slice(vertex).distance(center) - 10.).abs() < 1e-3);
+        }
+
+        assert!(trajectory_mesh(&BallPrediction::default(), 10.).ids.is_empty());
+    }
+
+    #[test]
+    fn wireframe() {
+        let game = load_soccar();
+        let lines = field_wireframe(&game);
+
+        // every triangle has 3 edges, and most of them are shared
+        assert!(lines.num_lines() > 0);
+        assert!(lines.num_lines() <= game.field.collision_mesh.num_leaves as usize * 3);
+        assert!(lines.ids.iter().all(|id| (*id as usize) < lines.vertices.len() / 3));
+    }
+
+    #[test]
+    fn field_mesh_export() {
+        let game = load_soccar();
+        let mesh = field_mesh(&game);
+
+        assert_eq!(mesh.ids.len(), game.field.collision_mesh.num_leaves as usize * 3);
+        assert!(mesh.vertices.len() < mesh.ids.len() * 3);
+
+        // it's the same field once it's read back
+        let read = Mesh::from_obj(&mesh.to_obj()).unwrap();
+        assert_eq!(read.to_triangles().len(), game.field.collision_mesh.num_leaves as usize);
+        assert!(mesh.to_gltf().starts_with(r#"{"asset":{"version":"2.0""#));
+    }
+
+    #[test]
+    #[cfg(feature = "rlbot")]
+    fn rlbot_polyline_chunks() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(500., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let polylines = rlbot_polylines(&prediction);
+
+        assert!(polylines.iter().all(|polyline| polyline.len() >= 2 && polyline.len() <= RLBOT_MAX_POLYLINE_POINTS));
+
+        // the chunks join up
+        for pair in polylines.windows(2) {
+            assert_eq!(pair[0].last(), pair[1].first());
+        }
+
+        // and cover every slice exactly once (apart from the shared points)
+        let total: usize = polylines.iter().map(Vec::len).sum();
+        assert_eq!(total - (polylines.len() - 1), prediction.num_slices);
+        assert_eq!(polylines.last().unwrap().last().unwrap(), &prediction.slices.last().unwrap().location.to_array());
+
+        assert!(rlbot_polylines_with_limit(&BallPrediction::default(), 2).is_empty());
+        assert_eq!(rlbot_polylines_with_limit(&prediction, 2).len(), prediction.num_slices - 1);
+    }
+}
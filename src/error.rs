@@ -0,0 +1,73 @@
+use core::error::Error;
+use core::fmt;
+use std::io;
+
+// Why a field couldn't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    // one of the meshes of the built-in fields couldn't be decoded, either the ones built into the crate or ones read from a folder
+    InvalidAsset {
+        name: &'static str,
+        message: String,
+    },
+    // with the tuning feature, one of the environment variables that override the physics constants isn't valid
+    InvalidTuning(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidAsset {
+                name,
+                message,
+            } => write!(f, "The {name} mesh is broken: {message}"),
+            LoadError::InvalidTuning(message) => write!(f, "Invalid tuning override: {message}"),
+            LoadError::Io(error) => write!(f, "Couldn't read the field: {error}"),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read_mesh;
+
+    #[test]
+    fn broken_meshes() {
+        let bytes = |numbers: &[f32]| numbers.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+        let ids = |ids: &[i32]| ids.iter().flat_map(|id| id.to_le_bytes()).collect::<Vec<u8>>();
+        let triangle = bytes(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+
+        assert!(read_mesh("triangle", &ids(&[0, 1, 2]), &triangle).is_ok());
+
+        // cut off partway through a triangle, pointing past the last vertex, and with a NaN in it
+        for (ids, vertices) in [(ids(&[0, 1]), triangle.clone()), (ids(&[0, 1, 3]), triangle.clone()), (ids(&[0, 1, 2]), bytes(&[0., 0., 0., 1., f32::NAN, 0., 0., 1., 0.]))] {
+            let error = read_mesh("broken", &ids, &vertices).unwrap_err();
+            assert!(matches!(error, LoadError::InvalidAsset { name: "broken", .. }), "{error:?}");
+            assert!(error.to_string().starts_with("The broken mesh is broken: "), "{error}");
+        }
+    }
+
+    #[test]
+    fn io_errors() {
+        let error = LoadError::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), "Couldn't read the field: missing");
+    }
+}
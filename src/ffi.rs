@@ -0,0 +1,263 @@
+// A C interface for bots that aren't written in Rust, declared in include/rl_ball_sym.h
+//
+// Fields are loaded into an opaque handle that has to be given back to `rlbs_free` once it's no longer needed,
+// and predictions are written into a buffer that the caller owns, so nothing else has to be freed on the other side.
+//
+// The crate is only built as a Rust library by default, so the C library has to be asked for when it's built:
+// `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`)
+use glam::Vec3A;
+
+use crate::simulation::ball::Ball;
+use crate::simulation::game::Game;
+#[cfg(feature = "dropshot")]
+use crate::load_dropshot;
+#[cfg(feature = "hoops")]
+use crate::load_hoops;
+#[cfg(feature = "throwback")]
+use crate::load_soccar_throwback;
+#[cfg(feature = "soccar")]
+use crate::load_soccar;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct RlbsVec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl From<Vec3A> for RlbsVec3 {
+    fn from(v: Vec3A) -> Self {
+        Self {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<RlbsVec3> for Vec3A {
+    fn from(v: RlbsVec3) -> Self {
+        Vec3A::new(v.x, v.y, v.z)
+    }
+}
+
+// The state of the ball at one point in time, both for setting the ball and for each slice of a prediction
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct RlbsBallSlice {
+    pub time: f32,
+    pub location: RlbsVec3,
+    pub velocity: RlbsVec3,
+    pub angular_velocity: RlbsVec3,
+}
+
+impl From<&Ball> for RlbsBallSlice {
+    fn from(ball: &Ball) -> Self {
+        Self {
+            time: ball.time,
+            location: ball.location.into(),
+            velocity: ball.velocity.into(),
+            angular_velocity: ball.angular_velocity.into(),
+        }
+    }
+}
+
+#[cfg(any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+fn into_handle(game: Game) -> *mut Game {
+    Box::into_raw(Box::new(game))
+}
+
+#[cfg(feature = "soccar")]
+#[no_mangle]
+pub extern "C" fn rlbs_load_soccar() -> *mut Game {
+    into_handle(load_soccar())
+}
+
+#[cfg(feature = "hoops")]
+#[no_mangle]
+pub extern "C" fn rlbs_load_hoops() -> *mut Game {
+    into_handle(load_hoops())
+}
+
+#[cfg(feature = "dropshot")]
+#[no_mangle]
+pub extern "C" fn rlbs_load_dropshot() -> *mut Game {
+    into_handle(load_dropshot())
+}
+
+#[cfg(feature = "throwback")]
+#[no_mangle]
+pub extern "C" fn rlbs_load_throwback() -> *mut Game {
+    into_handle(load_soccar_throwback())
+}
+
+/// # Safety
+///
+/// `game` has to be null or a handle from one of the `rlbs_load_*` functions that hasn't been freed yet, and it can't be used again afterwards
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// # Safety
+///
+/// `game` has to be null or a live handle from one of the `rlbs_load_*` functions, that isn't being used by another thread
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_set_ball(game: *mut Game, state: RlbsBallSlice) {
+    if let Some(game) = game.as_mut() {
+        game.ball.update(state.time, state.location.into(), state.velocity.into(), state.angular_velocity.into());
+    }
+}
+
+/// # Safety
+///
+/// `game` has to be null or a live handle from one of the `rlbs_load_*` functions, that isn't being used by another thread
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_get_ball(game: *const Game) -> RlbsBallSlice {
+    game.as_ref().map(|game| RlbsBallSlice::from(&game.ball)).unwrap_or_default()
+}
+
+/// Fills `slices` with up to `len` slices of the ball's path, one every 1/120th of a second, and returns how many were written
+///
+/// That's fewer than `len` when the ball escapes the field, in which case the slices stop just before it did, like `BallPrediction::escape`
+/// Just like `Ball::get_ball_prediction_struct`, this moves the ball in `game` to the end of the prediction
+///
+/// # Safety
+///
+/// `game` has to be null or a live handle from one of the `rlbs_load_*` functions, that isn't being used by another thread,
+/// and `slices` has to be null or point to space for at least `len` slices
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_predict(game: *mut Game, slices: *mut RlbsBallSlice, len: usize) -> usize {
+    let Some(game) = game.as_mut() else {
+        return 0;
+    };
+
+    if slices.is_null() {
+        return 0;
+    }
+
+    let slices = std::slice::from_raw_parts_mut(slices, len);
+    let prediction = Ball::get_ball_prediction_struct_for_slices(game, len);
+
+    for (slice, ball) in slices.iter_mut().zip(&prediction.slices) {
+        *slice = RlbsBallSlice::from(ball);
+    }
+
+    prediction.slices.len()
+}
+
+/// Loads the plugin at `path` and runs it after every step of the ball in `game`, see include/rl_ball_sym_plugin.h
+///
+/// Returns 0 if it was added, and -1 if it couldn't be loaded
+///
+/// # Safety
+///
+/// `game` has to be null or a live handle from one of the `rlbs_load_*` functions, that isn't being used by another thread,
+/// `path` has to be null or a null-terminated string, and the plugin has to follow include/rl_ball_sym_plugin.h
+#[cfg(feature = "plugins")]
+#[no_mangle]
+pub unsafe extern "C" fn rlbs_add_plugin(game: *mut Game, path: *const std::ffi::c_char) -> i32 {
+    let Some(game) = game.as_mut() else {
+        return -1;
+    };
+
+    if path.is_null() {
+        return -1;
+    }
+
+    let Ok(path) = std::ffi::CStr::from_ptr(path).to_str() else {
+        return -1;
+    };
+
+    match crate::simulation::plugin::Plugin::load(path) {
+        Ok(plugin) => {
+            game.add_plugin(plugin);
+            0
+        }
+        Err(_) => -1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn predict_into_buffer() {
+        let game = rlbs_load_soccar();
+
+        let state = RlbsBallSlice {
+            time: 0.,
+            location: RlbsVec3 {
+                x: 0.,
+                y: 0.,
+                z: 1000.,
+            },
+            velocity: RlbsVec3 {
+                x: 500.,
+                y: 0.,
+                z: 0.,
+            },
+            ..Default::default()
+        };
+
+        let mut slices = vec![RlbsBallSlice::default(); 240];
+
+        unsafe {
+            rlbs_set_ball(game, state);
+            assert_eq!(rlbs_get_ball(game).location, state.location);
+
+            assert_eq!(rlbs_predict(game, slices.as_mut_ptr(), slices.len()), slices.len());
+        }
+
+        // the same path as from the Rust side
+        let mut expected = load_soccar();
+        expected.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(500., 0., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut expected, slices.len());
+
+        for (slice, ball) in slices.iter().zip(&prediction.slices) {
+            assert_eq!(*slice, RlbsBallSlice::from(ball));
+        }
+
+        unsafe {
+            assert_eq!(rlbs_get_ball(game), *slices.last().unwrap());
+            rlbs_free(game);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn predict_stops_when_the_ball_escapes() {
+        let game = rlbs_load_soccar();
+        let mut slices = vec![RlbsBallSlice::default(); 240];
+
+        // under the field, and falling
+        let mut expected = load_soccar();
+        let bottom = expected.field.collision_mesh.global_box.min.z;
+        expected.ball.update(0., Vec3A::new(0., 0., bottom - 200.), Vec3A::new(0., 0., -1000.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut expected, slices.len());
+        assert!(prediction.escape.is_some());
+
+        unsafe {
+            rlbs_set_ball(game, RlbsBallSlice::from(&expected.ball));
+            assert_eq!(rlbs_predict(game, slices.as_mut_ptr(), slices.len()), prediction.num_slices);
+            rlbs_free(game);
+        }
+    }
+
+    #[test]
+    fn null_handles() {
+        let mut slices = [RlbsBallSlice::default(); 4];
+
+        unsafe {
+            assert_eq!(rlbs_predict(std::ptr::null_mut(), slices.as_mut_ptr(), slices.len()), 0);
+            assert_eq!(rlbs_get_ball(std::ptr::null()), RlbsBallSlice::default());
+            rlbs_set_ball(std::ptr::null_mut(), RlbsBallSlice::default());
+            rlbs_free(std::ptr::null_mut());
+        }
+    }
+}
@@ -1,141 +1,708 @@
-use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, ErrorKind};
+// Default is written out by hand for the types where it always has been, even when it could be derived
+#![allow(clippy::derivable_impls)]
 
+// Without the std feature, only the simulation itself is built, and the float math goes through libm (see `num_traits::Float`)
+// `Float` is only used when nothing else in the build links std, which isn't the case with the dev-dependencies, hence the `allow(unused_imports)` on it
+// The tests always have std, so they can keep using `vec!` and `format!` without importing them
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+pub mod debug;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
 pub mod linear_algebra;
+#[cfg(feature = "minimal")]
+pub mod minimal;
+pub mod prelude;
 pub mod simulation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub use error::LoadError;
+pub use simulation::{geometry, physics};
+
+// Bumped whenever the same starting ball can give a different prediction, like the checksums in the `prediction_checksum` test and the conformance cases
+// Saved predictions from a different version shouldn't be mixed with new ones
+pub const PHYSICS_VERSION: u32 = 3;
 
 use glam::vec3a;
 use simulation::ball::Ball;
-use simulation::field::{initialize_dropshot, initialize_hoops, initialize_soccar, initialize_throwback};
-use simulation::game::Game;
+#[cfg(feature = "std")]
+use simulation::bvh::Bvh;
+use simulation::field::initialize_custom_meshes;
+#[cfg(feature = "hoops")]
+use simulation::field::initialize_hoops_instanced;
+#[cfg(feature = "soccar")]
+use simulation::field::initialize_soccar_instanced;
+#[cfg(feature = "throwback")]
+use simulation::field::initialize_throwback_instanced;
+#[cfg(feature = "throwback")]
+use simulation::field::throwback_material_tags;
+#[cfg(feature = "std")]
+use simulation::field::{initialize_dropshot, initialize_dropshot_tiles, initialize_hoops, initialize_soccar, initialize_throwback_with_material_tags, InitializeThrowbackParams};
+use simulation::game::{Field, Game, GameMode};
+#[cfg(feature = "std")]
+use simulation::goal::Goal;
+use simulation::heuristics::FieldHeuristics;
+#[cfg(any(feature = "soccar", feature = "hoops", feature = "throwback"))]
+use simulation::instanced::{BvhCache, InstancedBvh};
+use simulation::material::{Material, MaterialId, SurfaceMaterials, DEFAULT_MATERIAL};
 use simulation::mesh::Mesh;
+#[cfg(feature = "std")]
+use simulation::puck::Puck;
+#[cfg(feature = "std")]
+use simulation::throwback::ThrowbackLayout;
+#[cfg(feature = "std")]
+use simulation::tuning::Tuning;
 
-use crate::simulation::field::InitializeThrowbackParams;
-
-fn read_mesh(ids_dat: Vec<u8>, vertices_dat: Vec<u8>) -> Mesh {
-    let mut ids_dat = Cursor::new(ids_dat);
-    let mut vertices_dat = Cursor::new(vertices_dat);
-    let mut ids: Vec<i32> = Vec::new();
-    let mut vertices: Vec<f32> = Vec::new();
-
-    loop {
-        ids.push(match ids_dat.read_i32::<LittleEndian>() {
-            Ok(num) => num,
-            Err(error) => match error.kind() {
-                ErrorKind::UnexpectedEof => break,
-                other_error => {
-                    panic!("Problem parsing file: {:?}", other_error)
-                }
-            },
-        });
+#[cfg(feature = "std")]
+// the files are read in place, straight out of the binary, and every number is 4 bytes so the lists never have to grow
+fn read_mesh(name: &'static str, ids_dat: &[u8], vertices_dat: &[u8]) -> Result<Mesh, LoadError> {
+    let invalid = |message: String| LoadError::InvalidAsset {
+        name,
+        message,
+    };
+
+    // three numbers for every triangle and every vertex
+    if !ids_dat.len().is_multiple_of(12) || !vertices_dat.len().is_multiple_of(12) {
+        return Err(invalid(format!("{} bytes of ids and {} bytes of vertices aren't whole triangles and vertices", ids_dat.len(), vertices_dat.len())));
     }
 
-    loop {
-        vertices.push(match vertices_dat.read_f32::<LittleEndian>() {
-            Ok(num) => num,
-            Err(error) => match error.kind() {
-                ErrorKind::UnexpectedEof => break,
-                other_error => {
-                    panic!("Problem parsing file: {:?}", other_error)
-                }
-            },
-        });
+    let ids: Vec<i32> = ids_dat.chunks_exact(4).map(LittleEndian::read_i32).collect();
+    let vertices: Vec<f32> = vertices_dat.chunks_exact(4).map(LittleEndian::read_f32).collect();
+
+    let num_vertices = vertices.len() / 3;
+    if let Some(id) = ids.iter().find(|&&id| id < 0 || id as usize >= num_vertices) {
+        return Err(invalid(format!("vertex {id} doesn't exist, there are {num_vertices}")));
+    }
+
+    if vertices.iter().any(|x| !x.is_finite()) {
+        return Err(invalid("some of the vertices aren't finite".to_string()));
     }
 
-    Mesh {
+    Ok(Mesh {
         ids,
         vertices,
+    })
+}
+
+#[cfg(feature = "std")]
+// with the tuning feature, the physics constants can be overridden with environment variables
+fn tuning() -> Result<Tuning, LoadError> {
+    #[cfg(feature = "tuning")]
+    return Tuning::from_env().map_err(LoadError::InvalidTuning);
+
+    #[cfg(not(feature = "tuning"))]
+    Ok(Tuning::default())
+}
+
+#[cfg(feature = "std")]
+// the loaders that don't return a `Result` can only fail if the assets built into the crate are broken, or the tuning overrides are
+fn loaded<T>(result: Result<T, LoadError>) -> T {
+    result.unwrap_or_else(|error| panic!("{error}"))
+}
+
+#[cfg(feature = "std")]
+// Where the meshes of the built-in fields are read from
+#[derive(Clone, Copy)]
+enum Assets<'a> {
+    // built in, from rl_ball_sym_assets, with only the fields whose features are on
+    #[cfg(any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+    Embedded,
+    // a copy of the assets folder, laid out the same way, which can be swapped out without rebuilding anything
+    Dir(&'a Path),
+}
+
+#[cfg(feature = "std")]
+fn read_mesh_file(dir: &Path, name: &'static str, field: &str) -> Result<Mesh, LoadError> {
+    let read = |suffix: &str| {
+        let path = dir.join(field).join(format!("{name}{suffix}"));
+        std::fs::read(&path).map_err(|error| io::Error::new(error.kind(), format!("{}: {error}", path.display())))
+    };
+
+    read_mesh(name, &read("_ids.bin")?, &read("_vertices.bin")?)
+}
+
+#[cfg(feature = "std")]
+// the mesh called `name` from the `field` folder of the assets, which is `$list[$index]` in rl_ball_sym_assets
+macro_rules! mesh {
+    ($assets:expr, $field:literal, $list:ident[$index:literal], $name:literal) => {
+        match $assets {
+            #[cfg(feature = $field)]
+            Assets::Embedded => {
+                let asset = &rl_ball_sym_assets::$list[$index];
+
+                if asset.name == $name {
+                    read_mesh($name, asset.ids, asset.vertices)
+                } else {
+                    Err(LoadError::InvalidAsset {
+                        name: $name,
+                        message: format!("rl_ball_sym_assets has {} in its place", asset.name),
+                    })
+                }
+            }
+            // only reachable if a loader for a field that isn't built in asks for its meshes
+            #[cfg(all(not(feature = $field), any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback")))]
+            Assets::Embedded => Err(LoadError::InvalidAsset {
+                name: $name,
+                message: concat!("the ", $field, " feature is off, so its meshes aren't built in").to_string(),
+            }),
+            Assets::Dir(dir) => read_mesh_file(dir, $name, $field),
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+fn soccar_meshes(assets: Assets) -> Result<[Mesh; 4], LoadError> {
+    let soccar_corner: Mesh = mesh!(assets, "soccar", SOCCAR[0], "soccar_corner")?;
+    let soccar_goal: Mesh = mesh!(assets, "soccar", SOCCAR[1], "soccar_goal")?;
+    let soccar_ramps_0: Mesh = mesh!(assets, "soccar", SOCCAR[2], "soccar_ramps_0")?;
+    let soccar_ramps_1: Mesh = mesh!(assets, "soccar", SOCCAR[3], "soccar_ramps_1")?;
+
+    Ok([soccar_corner, soccar_goal, soccar_ramps_0, soccar_ramps_1])
+}
+
+#[cfg(feature = "std")]
+fn hoops_meshes(assets: Assets) -> Result<[Mesh; 5], LoadError> {
+    let hoops_corner: Mesh = mesh!(assets, "hoops", HOOPS[0], "hoops_corner")?;
+    let hoops_net: Mesh = mesh!(assets, "hoops", HOOPS[1], "hoops_net")?;
+    let hoops_rim: Mesh = mesh!(assets, "hoops", HOOPS[2], "hoops_rim")?;
+    let hoops_ramps_0: Mesh = mesh!(assets, "hoops", HOOPS[3], "hoops_ramps_0")?;
+    let hoops_ramps_1: Mesh = mesh!(assets, "hoops", HOOPS[4], "hoops_ramps_1")?;
+
+    Ok([hoops_corner, hoops_net, hoops_rim, hoops_ramps_0, hoops_ramps_1])
+}
+
+#[cfg(feature = "std")]
+fn with_throwback_meshes<T>(assets: Assets, f: impl FnOnce(InitializeThrowbackParams) -> T) -> Result<T, LoadError> {
+    let back_ramps_lower: Mesh = mesh!(assets, "throwback", THROWBACK[0], "throwback_back_ramps_lower")?;
+    let back_ramps_upper: Mesh = mesh!(assets, "throwback", THROWBACK[1], "throwback_back_ramps_upper")?;
+    let corner_ramps_lower: Mesh = mesh!(assets, "throwback", THROWBACK[2], "throwback_corner_ramps_lower")?;
+    let corner_ramps_upper: Mesh = mesh!(assets, "throwback", THROWBACK[3], "throwback_corner_ramps_upper")?;
+    let corner_wall_0: Mesh = mesh!(assets, "throwback", THROWBACK[4], "throwback_corner_wall_0")?;
+    let corner_wall_1: Mesh = mesh!(assets, "throwback", THROWBACK[5], "throwback_corner_wall_1")?;
+    let corner_wall_2: Mesh = mesh!(assets, "throwback", THROWBACK[6], "throwback_corner_wall_2")?;
+    let goal: Mesh = mesh!(assets, "throwback", THROWBACK[7], "throwback_goal")?;
+    let side_ramps_lower: Mesh = mesh!(assets, "throwback", THROWBACK[8], "throwback_side_ramps_lower")?;
+    let side_ramps_upper: Mesh = mesh!(assets, "throwback", THROWBACK[9], "throwback_side_ramps_upper")?;
+
+    Ok(f(InitializeThrowbackParams {
+        back_ramps_lower: &back_ramps_lower,
+        back_ramps_upper: &back_ramps_upper,
+        corner_ramps_lower: &corner_ramps_lower,
+        corner_ramps_upper: &corner_ramps_upper,
+        corner_wall_0: &corner_wall_0,
+        corner_wall_1: &corner_wall_1,
+        corner_wall_2: &corner_wall_2,
+        goal: &goal,
+        side_ramps_lower: &side_ramps_lower,
+        side_ramps_upper: &side_ramps_upper,
+    }))
+}
+
+#[cfg(any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+// the BVH of `field` saved at `path`, or the one from `build` if there isn't one that can be loaded, which is saved there for next time
+// a file saved for a different field is rebuilt and overwritten, like any other file that can't be loaded
+fn bvh_from_file(path: &Path, field: &str, build: impl FnOnce() -> Result<Bvh, LoadError>) -> Result<Bvh, LoadError> {
+    if let Some(bvh) = std::fs::read(path).ok().and_then(|bytes| Bvh::from_bytes_with_tag(&bytes, field).ok()) {
+        return Ok(bvh);
     }
+
+    let bvh = build()?;
+
+    // a cache that can't be written only makes the next load slower
+    let _ = std::fs::write(path, bvh.to_bytes_with_tag(field));
+
+    Ok(bvh)
 }
 
+#[cfg(feature = "soccar")]
 pub fn load_soccar() -> Game {
-    let soccar_corner: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_corner_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_corner_vertices.bin").to_vec());
-    let soccar_goal: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_goal_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_goal_vertices.bin").to_vec());
-    let soccar_ramps_0: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_ramps_0_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_ramps_0_vertices.bin").to_vec());
-    let soccar_ramps_1: Mesh = read_mesh(include_bytes!("../assets/soccar/soccar_ramps_1_ids.bin").to_vec(), include_bytes!("../assets/soccar/soccar_ramps_1_vertices.bin").to_vec());
+    loaded(try_load_soccar())
+}
 
-    let collision_mesh = initialize_soccar(&soccar_corner, &soccar_goal, &soccar_ramps_0, &soccar_ramps_1);
+#[cfg(feature = "soccar")]
+// Every `load_*` function has a `try_load_*` version, which gives back an error instead of panicking
+pub fn try_load_soccar() -> Result<Game, LoadError> {
+    soccar_from(soccar_bvh(Assets::Embedded)?)
+}
+
+#[cfg(feature = "soccar")]
+// Like `load_soccar`, but the field's BVH is loaded from `path` if it was saved there before, and saved there once it's built otherwise
+// Each field needs a file of its own
+pub fn load_soccar_with_bvh_file<P: AsRef<Path>>(path: P) -> Game {
+    loaded(try_load_soccar_with_bvh_file(path))
+}
+
+#[cfg(feature = "soccar")]
+pub fn try_load_soccar_with_bvh_file<P: AsRef<Path>>(path: P) -> Result<Game, LoadError> {
+    soccar_from(bvh_from_file(path.as_ref(), "soccar", || soccar_bvh(Assets::Embedded))?)
+}
+
+#[cfg(feature = "std")]
+// Like `try_load_soccar`, but the meshes are read from `dir` instead of the ones built into the crate
+// It's laid out like the crate's assets folder, with a folder for each field (soccar/soccar_corner_ids.bin and so on),
+// so that the meshes can be shipped or patched without rebuilding anything
+pub fn try_load_soccar_from_dir<P: AsRef<Path>>(dir: P) -> Result<Game, LoadError> {
+    soccar_from(soccar_bvh(Assets::Dir(dir.as_ref()))?)
+}
+
+#[cfg(feature = "std")]
+fn soccar_bvh(assets: Assets) -> Result<Bvh, LoadError> {
+    let [soccar_corner, soccar_goal, soccar_ramps_0, soccar_ramps_1] = soccar_meshes(assets)?;
+
+    Ok(initialize_soccar(&soccar_corner, &soccar_goal, &soccar_ramps_0, &soccar_ramps_1))
+}
+
+#[cfg(feature = "std")]
+fn soccar_from(collision_mesh: Bvh) -> Result<Game, LoadError> {
+    let heuristics = FieldHeuristics::measure(&collision_mesh);
 
     let ball = Ball::initialize_soccar();
 
     let gravity = vec3a(0., 0., -650.);
 
-    Game {
+    Ok(Game {
+        mode: GameMode::Soccar,
         gravity,
-        collision_mesh,
+        field: Field {
+            collision_mesh: Arc::new(collision_mesh),
+            tuning: tuning()?,
+            heuristics,
+            goals: Goal::soccar().to_vec(),
+            ..Default::default()
+        },
         ball,
+        ..Default::default()
+    })
+}
+
+#[cfg(feature = "soccar")]
+// a soccar field with a puck instead of a ball, use `Puck::get_puck_prediction_struct` to predict it
+pub fn load_snowday() -> Game {
+    loaded(try_load_snowday())
+}
+
+#[cfg(feature = "soccar")]
+pub fn try_load_snowday() -> Result<Game, LoadError> {
+    try_load_soccar().map(snowday_from)
+}
+
+#[cfg(feature = "std")]
+pub fn try_load_snowday_from_dir<P: AsRef<Path>>(dir: P) -> Result<Game, LoadError> {
+    try_load_soccar_from_dir(dir).map(snowday_from)
+}
+
+#[cfg(feature = "std")]
+fn snowday_from(soccar: Game) -> Game {
+    Game {
+        mode: GameMode::Snowday,
+        puck: Puck::initialize_snowday(),
+        ..soccar
     }
 }
 
+#[cfg(feature = "hoops")]
 pub fn load_hoops() -> Game {
-    let hoops_corner: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_corner_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_corner_vertices.bin").to_vec());
-    let hoops_net: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_net_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_net_vertices.bin").to_vec());
-    let hoops_rim: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_rim_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_rim_vertices.bin").to_vec());
-    let hoops_ramps_0: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_ramps_0_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_ramps_0_vertices.bin").to_vec());
-    let hoops_ramps_1: Mesh = read_mesh(include_bytes!("../assets/hoops/hoops_ramps_1_ids.bin").to_vec(), include_bytes!("../assets/hoops/hoops_ramps_1_vertices.bin").to_vec());
+    loaded(try_load_hoops())
+}
+
+#[cfg(feature = "hoops")]
+pub fn try_load_hoops() -> Result<Game, LoadError> {
+    hoops_from(hoops_bvh(Assets::Embedded)?)
+}
+
+#[cfg(feature = "hoops")]
+// `load_soccar_with_bvh_file` for hoops
+pub fn load_hoops_with_bvh_file<P: AsRef<Path>>(path: P) -> Game {
+    loaded(try_load_hoops_with_bvh_file(path))
+}
 
-    let collision_mesh = initialize_hoops(&hoops_corner, &hoops_net, &hoops_rim, &hoops_ramps_0, &hoops_ramps_1);
+#[cfg(feature = "hoops")]
+pub fn try_load_hoops_with_bvh_file<P: AsRef<Path>>(path: P) -> Result<Game, LoadError> {
+    hoops_from(bvh_from_file(path.as_ref(), "hoops", || hoops_bvh(Assets::Embedded))?)
+}
+
+#[cfg(feature = "std")]
+// `try_load_soccar_from_dir` for hoops
+pub fn try_load_hoops_from_dir<P: AsRef<Path>>(dir: P) -> Result<Game, LoadError> {
+    hoops_from(hoops_bvh(Assets::Dir(dir.as_ref()))?)
+}
+
+#[cfg(feature = "std")]
+fn hoops_bvh(assets: Assets) -> Result<Bvh, LoadError> {
+    let [hoops_corner, hoops_net, hoops_rim, hoops_ramps_0, hoops_ramps_1] = hoops_meshes(assets)?;
+
+    Ok(initialize_hoops(&hoops_corner, &hoops_net, &hoops_rim, &hoops_ramps_0, &hoops_ramps_1))
+}
+
+#[cfg(feature = "std")]
+fn hoops_from(collision_mesh: Bvh) -> Result<Game, LoadError> {
+    let heuristics = FieldHeuristics::measure(&collision_mesh);
 
     let ball = Ball::initialize_hoops();
 
     let gravity = vec3a(0., 0., -650.);
 
-    Game {
+    Ok(Game {
+        mode: GameMode::Hoops,
         gravity,
-        collision_mesh,
+        field: Field {
+            collision_mesh: Arc::new(collision_mesh),
+            tuning: tuning()?,
+            heuristics,
+            ..Default::default()
+        },
         ball,
-    }
+        ..Default::default()
+    })
 }
 
+#[cfg(feature = "dropshot")]
 pub fn load_dropshot() -> Game {
-    let dropshot: Mesh = read_mesh(include_bytes!("../assets/dropshot/dropshot_ids.bin").to_vec(), include_bytes!("../assets/dropshot/dropshot_vertices.bin").to_vec());
+    loaded(try_load_dropshot())
+}
+
+#[cfg(feature = "dropshot")]
+pub fn try_load_dropshot() -> Result<Game, LoadError> {
+    dropshot_from(dropshot_bvh(Assets::Embedded)?)
+}
+
+#[cfg(feature = "dropshot")]
+// `load_soccar_with_bvh_file` for dropshot
+pub fn load_dropshot_with_bvh_file<P: AsRef<Path>>(path: P) -> Game {
+    loaded(try_load_dropshot_with_bvh_file(path))
+}
 
-    let collision_mesh = initialize_dropshot(&dropshot);
+#[cfg(feature = "dropshot")]
+pub fn try_load_dropshot_with_bvh_file<P: AsRef<Path>>(path: P) -> Result<Game, LoadError> {
+    dropshot_from(bvh_from_file(path.as_ref(), "dropshot", || dropshot_bvh(Assets::Embedded))?)
+}
+
+#[cfg(feature = "std")]
+// `try_load_soccar_from_dir` for dropshot
+pub fn try_load_dropshot_from_dir<P: AsRef<Path>>(dir: P) -> Result<Game, LoadError> {
+    dropshot_from(dropshot_bvh(Assets::Dir(dir.as_ref()))?)
+}
+
+#[cfg(feature = "std")]
+fn dropshot_bvh(assets: Assets) -> Result<Bvh, LoadError> {
+    let dropshot: Mesh = mesh!(assets, "dropshot", DROPSHOT[0], "dropshot")?;
+
+    Ok(initialize_dropshot(&dropshot))
+}
+
+#[cfg(feature = "std")]
+fn dropshot_from(collision_mesh: Bvh) -> Result<Game, LoadError> {
+    let heuristics = FieldHeuristics::measure(&collision_mesh);
 
     let ball = Ball::initialize_dropshot();
 
     let gravity = vec3a(0., 0., -650.);
 
-    Game {
+    Ok(Game {
+        mode: GameMode::Dropshot,
         gravity,
-        collision_mesh,
+        field: Field {
+            collision_mesh: Arc::new(collision_mesh),
+            tuning: tuning()?,
+            heuristics,
+            ..Default::default()
+        },
         ball,
-    }
+        dropshot_tiles: Some(initialize_dropshot_tiles()),
+        ..Default::default()
+    })
 }
 
+#[cfg(feature = "throwback")]
 pub fn load_soccar_throwback() -> Game {
-    let back_ramps_lower: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_back_ramps_lower_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_back_ramps_lower_vertices.bin").to_vec());
-    let back_ramps_upper: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_back_ramps_upper_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_back_ramps_upper_vertices.bin").to_vec());
-    let corner_ramps_lower: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_ramps_lower_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_ramps_lower_vertices.bin").to_vec());
-    let corner_ramps_upper: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_ramps_upper_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_ramps_upper_vertices.bin").to_vec());
-    let corner_wall_0: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_wall_0_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_wall_0_vertices.bin").to_vec());
-    let corner_wall_1: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_wall_1_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_wall_1_vertices.bin").to_vec());
-    let corner_wall_2: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_corner_wall_2_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_corner_wall_2_vertices.bin").to_vec());
-    let goal: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_goal_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_goal_vertices.bin").to_vec());
-    let side_ramps_lower: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_side_ramps_lower_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_side_ramps_lower_vertices.bin").to_vec());
-    let side_ramps_upper: Mesh = read_mesh(include_bytes!("../assets/throwback/throwback_side_ramps_upper_ids.bin").to_vec(), include_bytes!("../assets/throwback/throwback_side_ramps_upper_vertices.bin").to_vec());
-
-    let params = InitializeThrowbackParams {
-        back_ramps_lower: &back_ramps_lower,
-        back_ramps_upper: &back_ramps_upper,
-        corner_ramps_lower: &corner_ramps_lower,
-        corner_ramps_upper: &corner_ramps_upper,
-        corner_wall_0: &corner_wall_0,
-        corner_wall_1: &corner_wall_1,
-        corner_wall_2: &corner_wall_2,
-        goal: &goal,
-        side_ramps_lower: &side_ramps_lower,
-        side_ramps_upper: &side_ramps_upper,
+    loaded(try_load_soccar_throwback())
+}
+
+#[cfg(feature = "throwback")]
+pub fn try_load_soccar_throwback() -> Result<Game, LoadError> {
+    throwback_from(throwback_bvh(Assets::Embedded)?)
+}
+
+#[cfg(feature = "throwback")]
+// `load_soccar_with_bvh_file` for throwback
+pub fn load_soccar_throwback_with_bvh_file<P: AsRef<Path>>(path: P) -> Game {
+    loaded(try_load_soccar_throwback_with_bvh_file(path))
+}
+
+#[cfg(feature = "throwback")]
+pub fn try_load_soccar_throwback_with_bvh_file<P: AsRef<Path>>(path: P) -> Result<Game, LoadError> {
+    let mut material_tags = None;
+    let collision_mesh = bvh_from_file(path.as_ref(), "throwback", || {
+        let (collision_mesh, tags) = throwback_bvh(Assets::Embedded)?;
+        material_tags = Some(tags);
+        Ok(collision_mesh)
+    })?;
+
+    // the meshes only have to be read again for the tags when the BVH came from the file
+    let material_tags = match material_tags {
+        Some(tags) => tags,
+        None => with_throwback_meshes(Assets::Embedded, throwback_material_tags)?,
     };
-    let collision_mesh = initialize_throwback(params);
+
+    throwback_from((collision_mesh, material_tags))
+}
+
+#[cfg(feature = "std")]
+// `try_load_soccar_from_dir` for throwback
+pub fn try_load_soccar_throwback_from_dir<P: AsRef<Path>>(dir: P) -> Result<Game, LoadError> {
+    throwback_from(throwback_bvh(Assets::Dir(dir.as_ref()))?)
+}
+
+#[cfg(feature = "std")]
+// the field, with the ramps' materials tagged from the same meshes
+fn throwback_bvh(assets: Assets) -> Result<(Bvh, Vec<MaterialId>), LoadError> {
+    with_throwback_meshes(assets, initialize_throwback_with_material_tags)
+}
+
+#[cfg(feature = "std")]
+fn throwback_from((collision_mesh, material_tags): (Bvh, Vec<MaterialId>)) -> Result<Game, LoadError> {
+    let heuristics = FieldHeuristics::measure(&collision_mesh);
+    let throwback = heuristics.as_ref().and_then(|heuristics| ThrowbackLayout::measure(&collision_mesh, heuristics));
+
+    let ball = Ball::initialize_soccar();
+
+    let gravity = vec3a(0., 0., -650.);
+
+    Ok(Game {
+        mode: GameMode::Throwback,
+        gravity,
+        field: Field {
+            collision_mesh: Arc::new(collision_mesh),
+            tuning: tuning()?,
+            heuristics,
+            throwback,
+            goals: Goal::throwback().to_vec(),
+            materials: Some(Arc::new(SurfaceMaterials::from_tags(material_tags))),
+            ..Default::default()
+        },
+        ball,
+        ..Default::default()
+    })
+}
+
+#[cfg(feature = "std")]
+// Every built-in field, as it comes from its `load_*` function
+#[derive(Clone)]
+pub struct Fields {
+    pub soccar: Game,
+    pub hoops: Game,
+    pub dropshot: Game,
+    pub throwback: Game,
+    pub snowday: Game,
+}
+
+#[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+// Loads every built-in field at once, for servers that host more than one game mode
+// Each field with its own mesh is built on its own thread, and the modes played on the soccar field are copied from it instead of building it again
+// The builds share the lists that the triangles are sorted with, so loading the fields again (or one after another) doesn't allocate them again
+pub fn load_all() -> Fields {
+    loaded(try_load_all())
+}
+
+#[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+pub fn try_load_all() -> Result<Fields, LoadError> {
+    all_from(Assets::Embedded)
+}
+
+#[cfg(feature = "std")]
+// `try_load_soccar_from_dir` for every field
+pub fn try_load_all_from_dir<P: AsRef<Path>>(dir: P) -> Result<Fields, LoadError> {
+    all_from(Assets::Dir(dir.as_ref()))
+}
+
+#[cfg(feature = "std")]
+fn all_from(assets: Assets) -> Result<Fields, LoadError> {
+    let soccar = || soccar_from(soccar_bvh(assets)?);
+    let hoops = || hoops_from(hoops_bvh(assets)?);
+    let dropshot = || dropshot_from(dropshot_bvh(assets)?);
+    let throwback = || throwback_from(throwback_bvh(assets)?);
+
+    // there aren't any threads to spread the work over in the browser
+    #[cfg(target_arch = "wasm32")]
+    return Ok(fields_from(soccar()?, hoops()?, dropshot()?, throwback()?));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::scope(|scope| {
+        let hoops = scope.spawn(hoops);
+        let dropshot = scope.spawn(dropshot);
+        let throwback = scope.spawn(throwback);
+        let soccar = soccar()?;
+
+        Ok(fields_from(soccar, hoops.join().unwrap()?, dropshot.join().unwrap()?, throwback.join().unwrap()?))
+    })
+}
+
+#[cfg(feature = "std")]
+fn fields_from(soccar: Game, hoops: Game, dropshot: Game, throwback: Game) -> Fields {
+    Fields {
+        snowday: snowday_from(soccar.clone()),
+        soccar,
+        hoops,
+        dropshot,
+        throwback,
+    }
+}
+
+#[cfg(feature = "std")]
+// The built-in field for a game mode, None for custom fields and for fields that were left out with their feature
+pub(crate) fn try_load_mode(mode: GameMode) -> Option<Result<Game, LoadError>> {
+    match mode {
+        #[cfg(feature = "soccar")]
+        GameMode::Soccar => Some(try_load_soccar()),
+        #[cfg(feature = "hoops")]
+        GameMode::Hoops => Some(try_load_hoops()),
+        #[cfg(feature = "dropshot")]
+        GameMode::Dropshot => Some(try_load_dropshot()),
+        #[cfg(feature = "throwback")]
+        GameMode::Throwback => Some(try_load_soccar_throwback()),
+        #[cfg(feature = "soccar")]
+        GameMode::Snowday => Some(try_load_snowday()),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn load_mode(mode: GameMode) -> Option<Game> {
+    try_load_mode(mode).map(loaded)
+}
+
+#[cfg(feature = "soccar")]
+// Just the collision meshes of the fields, where each mirrored mesh is only stored once
+// They give the same collisions as the `collision_mesh` of the matching `Game`, see `InstancedBvh`, and a game can collide with one instead with `Field::use_instanced_broadphase`
+pub fn load_soccar_instanced() -> InstancedBvh {
+    load_soccar_instanced_cached(&mut BvhCache::default())
+}
+
+#[cfg(feature = "hoops")]
+pub fn load_hoops_instanced() -> InstancedBvh {
+    load_hoops_instanced_cached(&mut BvhCache::default())
+}
+
+#[cfg(feature = "throwback")]
+pub fn load_throwback_instanced() -> InstancedBvh {
+    load_throwback_instanced_cached(&mut BvhCache::default())
+}
+
+#[cfg(feature = "soccar")]
+// The same as the functions above, but any mesh that was already built into `cache` (by another field, or an earlier load) is shared instead of built again
+pub fn load_soccar_instanced_cached(cache: &mut BvhCache) -> InstancedBvh {
+    let [soccar_corner, soccar_goal, soccar_ramps_0, soccar_ramps_1] = loaded(soccar_meshes(Assets::Embedded));
+    initialize_soccar_instanced(&soccar_corner, &soccar_goal, &soccar_ramps_0, &soccar_ramps_1, cache)
+}
+
+#[cfg(feature = "hoops")]
+pub fn load_hoops_instanced_cached(cache: &mut BvhCache) -> InstancedBvh {
+    let [hoops_corner, hoops_net, hoops_rim, hoops_ramps_0, hoops_ramps_1] = loaded(hoops_meshes(Assets::Embedded));
+    initialize_hoops_instanced(&hoops_corner, &hoops_net, &hoops_rim, &hoops_ramps_0, &hoops_ramps_1, cache)
+}
+
+#[cfg(feature = "throwback")]
+pub fn load_throwback_instanced_cached(cache: &mut BvhCache) -> InstancedBvh {
+    loaded(with_throwback_meshes(Assets::Embedded, |params| initialize_throwback_instanced(params, cache)))
+}
+
+#[cfg(all(feature = "soccar", feature = "hoops", feature = "throwback"))]
+// The instanced collision meshes of every field that has one, sharing any meshes that they have in common
+#[derive(Clone)]
+pub struct InstancedFields {
+    pub soccar: InstancedBvh,
+    pub hoops: InstancedBvh,
+    pub throwback: InstancedBvh,
+}
+
+#[cfg(all(feature = "soccar", feature = "hoops", feature = "throwback"))]
+pub fn load_all_instanced() -> InstancedFields {
+    let mut cache = BvhCache::default();
+
+    InstancedFields {
+        soccar: load_soccar_instanced_cached(&mut cache),
+        hoops: load_hoops_instanced_cached(&mut cache),
+        throwback: load_throwback_instanced_cached(&mut cache),
+    }
+}
+
+#[cfg(feature = "std")]
+// A field from a Wavefront OBJ file, like a workshop map, with a standard soccar ball and gravity
+pub fn load_custom<P: AsRef<Path>>(path: P) -> io::Result<Game> {
+    Ok(load_custom_mesh(&Mesh::from_obj(&std::fs::read_to_string(path)?)?))
+}
+
+pub fn load_custom_mesh(mesh: &Mesh) -> Game {
+    load_custom_meshes(&[(mesh, None)])
+}
+
+// A field from several meshes, where the ones with a material bounce the ball like it instead of like the rest of the field
+pub fn load_custom_meshes(meshes: &[(&Mesh, Option<Material>)]) -> Game {
+    let collision_mesh = initialize_custom_meshes(&meshes.iter().map(|(mesh, _)| *mesh).collect::<Vec<_>>());
+    let heuristics = FieldHeuristics::measure(&collision_mesh);
+
+    // each mesh with a material gets its own id
+    let mut materials = SurfaceMaterials::default();
+    for (_, material) in meshes {
+        let id = match material {
+            Some(material) => {
+                let id = materials.materials.len() as MaterialId + 1;
+                materials.set(id, *material);
+                id
+            }
+            None => DEFAULT_MATERIAL,
+        };
+
+        materials.tags.push(id);
+    }
 
     let ball = Ball::initialize_soccar();
 
     let gravity = vec3a(0., 0., -650.);
 
     Game {
+        mode: GameMode::Custom,
         gravity,
-        collision_mesh,
+        field: Field {
+            collision_mesh: Arc::new(collision_mesh),
+            #[cfg(feature = "std")]
+            tuning: loaded(tuning()),
+            heuristics,
+            materials: (!materials.materials.is_empty()).then(|| Arc::new(materials)),
+            ..Default::default()
+        },
         ball,
+        ..Default::default()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    // the meshes are only read from the folder, so this works with any (or none) of the fields built in
+    fn from_dir_without_embedding() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+
+        let hoops = try_load_hoops_from_dir(&assets).unwrap();
+        assert_eq!(hoops.mode, GameMode::Hoops);
+        assert!(!hoops.field.collision_mesh.primitives().is_empty());
     }
 }
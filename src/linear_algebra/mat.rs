@@ -1,5 +1,7 @@
 use glam::Mat3A;
 
+// only the built-in fields are put together with it
+#[cfg_attr(not(any(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback")), allow(dead_code))]
 pub(crate) trait MatrixExt {
     fn dot(&self, other: Self) -> Self;
 }
@@ -10,11 +10,67 @@ pub fn dot(matrix: Mat3A, vector: Vec3A) -> Vec3A {
     matrix.transpose().mul_vec3a(vector)
 }
 
+// The classic bit-hack inverse square root, refined with two Newton-Raphson iterations
+// The relative error is around 5e-6, which is plenty for clamping speeds and normalizing contact normals
+#[cfg_attr(not(feature = "fast-math"), allow(dead_code))]
+pub(crate) fn fast_recip_sqrt(x: f32) -> f32 {
+    let half_x = 0.5 * x;
+    let mut y = f32::from_bits(0x5f37_59df - (x.to_bits() >> 1));
+
+    y *= 1.5 - half_x * y * y;
+    y *= 1.5 - half_x * y * y;
+
+    y
+}
+
+#[cfg(feature = "fast-math")]
+#[inline]
+pub(crate) fn length_recip(v: Vec3A) -> f32 {
+    fast_recip_sqrt(v.length_squared())
+}
+
+#[cfg(not(feature = "fast-math"))]
+#[inline]
+pub(crate) fn length_recip(v: Vec3A) -> f32 {
+    v.length_recip()
+}
+
+#[cfg(feature = "fast-math")]
+#[inline]
+pub(crate) fn length(v: Vec3A) -> f32 {
+    let length_squared = v.length_squared();
+    length_squared * fast_recip_sqrt(length_squared)
+}
+
+#[cfg(not(feature = "fast-math"))]
+#[inline]
+pub(crate) fn length(v: Vec3A) -> f32 {
+    v.length()
+}
+
+#[cfg(feature = "fast-math")]
+#[inline]
+pub(crate) fn normalize_or_zero(v: Vec3A) -> Vec3A {
+    let length_squared = v.length_squared();
+
+    if length_squared > f32::MIN_POSITIVE {
+        v * fast_recip_sqrt(length_squared)
+    } else {
+        Vec3A::ZERO
+    }
+}
+
+#[cfg(not(feature = "fast-math"))]
+#[inline]
+pub(crate) fn normalize_or_zero(v: Vec3A) -> Vec3A {
+    v.normalize_or_zero()
+}
+
 #[cfg(test)]
 mod test {
     use glam::{const_mat3a, const_vec3a, Mat3A, Vec3A};
 
-    use crate::linear_algebra::math::dot;
+    use crate::linear_algebra::math::{dot, fast_recip_sqrt};
 
     #[allow(clippy::approx_constant)]
     const MAT: Mat3A = const_mat3a!([-0.0, -0.16666667, -0.16666667], [0.16666667, 0.083333336, 0.083333336], [0.0, -0.7071068, 0.7071068]);
@@ -25,4 +81,12 @@ mod test {
     fn test_dot() {
         assert_eq!(dot(MAT, VEC), RES);
     }
+
+    #[test]
+    fn test_fast_recip_sqrt() {
+        for x in [1e-6, 0.25, 1., 2., 91.25, 4000. * 4000., 1e12] {
+            let exact = 1. / f32::sqrt(x);
+            assert!(((fast_recip_sqrt(x) - exact) / exact).abs() < 1e-5);
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+// A rough prediction for the standard soccar field, without any meshes or BVH, for when not loading a field matters more than accuracy,
+// like a scoreboard that only needs to show roughly where the ball is headed
+// The rest of the crate (the BVH, `Game`, the field builders) is still compiled in, but with `default-features = false` none of the field meshes are
+//
+// The field is a box with its corners cut off at 45 degrees, and a goal cut into each back wall
+// The curved ramps between the walls, the floor and the ceiling aren't there, so bounces near the edges are only roughly right
+
+const RADIUS: f32 = 93.15;
+const MASS: f32 = 30.;
+// a solid sphere, with the ball's visual radius like the real one
+const MOI: f32 = 0.4 * MASS * 91.25 * 91.25;
+const GRAVITY: f32 = -650.;
+const DRAG: f32 = -0.0305;
+const RESTITUTION: f32 = 0.6;
+const FRICTION: f32 = 2.;
+const V_MAX: f32 = 4000.;
+const W_MAX: f32 = 6.;
+const DT: f32 = 1. / 120.;
+
+const SIDE_WALL: f32 = 4096.;
+const BACK_WALL: f32 = 5120.;
+const CEILING: f32 = 2044.;
+// the corners are the planes where |x| + |y| is this
+const CORNER: f32 = 8064.;
+const GOAL_HALF_WIDTH: f32 = 892.755;
+const GOAL_HEIGHT: f32 = 642.775;
+const GOAL_BACK: f32 = 6000.;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MinimalBall {
+    pub time: f32,
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+}
+
+// the surface the ball is touching the most, as its normal and how far the ball's center is from it
+fn contact(location: Vec3A) -> Option<(Vec3A, f32)> {
+    let [x, y, z] = location.to_array();
+    let (sx, sy) = (x.signum(), y.signum());
+
+    let in_mouth = x.abs() < GOAL_HALF_WIDTH && z < GOAL_HEIGHT;
+    let in_goal = y.abs() > BACK_WALL;
+
+    // planes that aren't there are infinitely far away
+    let planes = if in_goal {
+        [(Vec3A::Z, z), (Vec3A::new(-sx, 0., 0.), GOAL_HALF_WIDTH - x.abs()), (-Vec3A::Z, GOAL_HEIGHT - z), (Vec3A::new(0., -sy, 0.), GOAL_BACK - y.abs()), (Vec3A::ZERO, f32::INFINITY)]
+    } else {
+        let corner_normal = Vec3A::new(-sx, -sy, 0.) / 2f32.sqrt();
+        let back_wall = if in_mouth { f32::INFINITY } else { BACK_WALL - y.abs() };
+
+        [(Vec3A::Z, z), (-Vec3A::Z, CEILING - z), (Vec3A::new(-sx, 0., 0.), SIDE_WALL - x.abs()), (corner_normal, (CORNER - x.abs() - y.abs()) / 2f32.sqrt()), (Vec3A::new(0., -sy, 0.), back_wall)]
+    };
+
+    planes.into_iter().filter(|(_, distance)| *distance < RADIUS).min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+impl MinimalBall {
+    pub fn new(time: f32, location: Vec3A, velocity: Vec3A, angular_velocity: Vec3A) -> Self {
+        Self {
+            time,
+            location,
+            velocity,
+            angular_velocity,
+        }
+    }
+
+    // one step of `dt` seconds, through the air and then off of whatever the ball ends up touching
+    pub fn step(&mut self, dt: f32) {
+        self.velocity += (self.velocity * DRAG + Vec3A::new(0., 0., GRAVITY)) * dt;
+        self.location += self.velocity * dt;
+
+        if let Some((normal, distance)) = contact(self.location) {
+            // the same impulse as the full simulation's bounce
+            let loc = normal * -RADIUS;
+            let v_perp = normal * self.velocity.dot(normal).min(0.);
+            let v_para = self.velocity - v_perp - loc.cross(self.angular_velocity);
+            let ratio = v_perp.length() / v_para.length().max(0.0001);
+            let m_reduced = 1. / (1. / MASS + RADIUS * RADIUS / MOI);
+
+            let j = v_perp * (-(1. + RESTITUTION) * MASS) - (FRICTION * ratio).min(1.) * m_reduced * v_para;
+
+            self.velocity += j / MASS;
+            self.angular_velocity += loc.cross(j) / MOI;
+            self.location += normal * (RADIUS - distance);
+        }
+
+        self.velocity = self.velocity.clamp_length_max(V_MAX);
+        self.angular_velocity = self.angular_velocity.clamp_length_max(W_MAX);
+        self.time += dt;
+    }
+
+    // `num_slices` slices, one every 1/120th of a second, like `Ball::get_ball_prediction_struct_for_slices`
+    pub fn predict(&self, num_slices: usize) -> Vec<MinimalBall> {
+        let mut ball = *self;
+
+        (0..num_slices)
+            .map(|_| {
+                ball.step(DT);
+                ball
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn close_to_the_full_simulation() {
+        use crate::load_soccar;
+        use crate::simulation::ball::Ball;
+
+        // bouncing around the middle of the field, away from the ramps
+        let (location, velocity) = (vec3a(0., 0., 1000.), vec3a(600., -400., 300.));
+
+        let mut game = load_soccar();
+        game.ball.update(0., location, velocity, Vec3A::ZERO);
+        let full = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+        let minimal = MinimalBall::new(0., location, velocity, Vec3A::ZERO).predict(240);
+
+        for (full, minimal) in full.slices.iter().zip(&minimal) {
+            assert!(full.location.distance(minimal.location) < 20., "{} and {}", full.location, minimal.location);
+        }
+    }
+
+    #[test]
+    fn walls_and_corners() {
+        // straight into the side wall, high enough to miss the ramp
+        let ball = MinimalBall::new(0., vec3a(3500., 0., 1000.), vec3a(2000., 0., 0.), Vec3A::ZERO).predict(60);
+        assert!(ball.last().unwrap().velocity.x < 0.);
+        assert!(ball.iter().all(|slice| slice.location.x <= SIDE_WALL - RADIUS + 1.));
+
+        // diagonally into a corner comes back out diagonally
+        let ball = MinimalBall::new(0., vec3a(3000., 3000., 1000.), vec3a(1500., 1500., 0.), Vec3A::ZERO).predict(90);
+        let last = ball.last().unwrap();
+        assert!(last.velocity.x < 0. && last.velocity.y < 0.);
+        assert!((last.velocity.x - last.velocity.y).abs() < 1.);
+    }
+
+    #[test]
+    fn goals() {
+        // rolling into the goal goes past the back wall, but not the back of the goal
+        let ball = MinimalBall::new(0., vec3a(0., 4000., RADIUS), vec3a(0., 2000., 0.), Vec3A::ZERO).predict(240);
+        assert!(ball.iter().any(|slice| slice.location.y > BACK_WALL + RADIUS));
+        assert!(ball.iter().all(|slice| slice.location.y <= GOAL_BACK - RADIUS + 1.));
+
+        // but beside it, the back wall is in the way
+        let ball = MinimalBall::new(0., vec3a(2000., 4000., RADIUS), vec3a(0., 2000., 0.), Vec3A::ZERO).predict(240);
+        assert!(ball.iter().all(|slice| slice.location.y <= BACK_WALL - RADIUS + 1.));
+    }
+}
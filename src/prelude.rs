@@ -0,0 +1,51 @@
+// The supported API, which only changes in a breaking way with a new major version
+// Everything else that's public (the BVH, the field builders, the math helpers) is free to change in any release,
+// and is hidden from the documentation unless the `internals` feature is on
+//
+//   use rl_ball_sym::prelude::*;
+
+pub use glam::{vec3a, Vec3A};
+
+#[cfg(feature = "minimal")]
+pub use crate::minimal::MinimalBall;
+
+pub use crate::simulation::ball::{Ball, BallPrediction, BounceEvent};
+pub use crate::simulation::builder::GameBuilder;
+pub use crate::simulation::car::{Car, Hitbox};
+pub use crate::simulation::checksum::{ChecksumError, ChecksumMismatch};
+pub use crate::simulation::config::{ConfigError, PredictionConfig};
+#[cfg(feature = "std")]
+pub use crate::simulation::conformance::{conformance_cases, run_conformance, ConformanceCase, ConformanceFailure, ConformanceOutput};
+#[cfg(feature = "f64")]
+pub use crate::simulation::double::DBallState;
+pub use crate::simulation::escape::EscapeEvent;
+pub use crate::simulation::fallible::{PredictionError, PredictionLimits};
+pub use crate::simulation::game::{Accuracy, ContactModel, Field, Game, GameMode, Integrator};
+pub use crate::simulation::geometry::{Ray, Sphere};
+pub use crate::simulation::goal::{Goal, GoalCriteria, Team};
+pub use crate::simulation::hoops::Funnel;
+pub use crate::simulation::material::{Material, MaterialId};
+pub use crate::simulation::mesh::Mesh;
+pub use crate::simulation::mutators::{DragModel, Mutators};
+pub use crate::simulation::physics::{self, BallState, BounceParams, FlightParams};
+#[cfg(feature = "plugins")]
+pub use crate::simulation::plugin::{Plugin, PluginError, PluginTable, PLUGIN_ABI_VERSION};
+#[cfg(feature = "rlbot")]
+pub use crate::simulation::rlbot::{PacketPhysics, TickPacket};
+#[cfg(feature = "std")]
+pub use crate::simulation::storage::{read_predictions, write_predictions, PredictionHeader};
+pub use crate::simulation::summary::PredictionSummary;
+pub use crate::simulation::throwback::ThrowbackLayout;
+#[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+pub use crate::{load_all, try_load_all};
+pub use crate::{load_custom_mesh, load_custom_meshes, PHYSICS_VERSION};
+#[cfg(feature = "std")]
+pub use crate::{load_custom, try_load_all_from_dir, try_load_dropshot_from_dir, try_load_hoops_from_dir, try_load_snowday_from_dir, try_load_soccar_from_dir, try_load_soccar_throwback_from_dir, Fields, LoadError};
+#[cfg(feature = "dropshot")]
+pub use crate::{load_dropshot, try_load_dropshot};
+#[cfg(feature = "soccar")]
+pub use crate::{load_snowday, load_soccar, try_load_snowday, try_load_soccar};
+#[cfg(feature = "hoops")]
+pub use crate::{load_hoops, try_load_hoops};
+#[cfg(feature = "throwback")]
+pub use crate::{load_soccar_throwback, try_load_soccar_throwback};
@@ -1,7 +1,76 @@
+// The modules hidden from the documentation are internals, which can change in any release, see `prelude` for the supported API
+pub mod analysis;
+pub mod analytic;
+pub mod annotated;
 pub mod ball;
+pub mod bounce;
+pub mod builder;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
 pub mod bvh;
+#[cfg(feature = "tuning")]
+pub mod calibration;
+pub mod car;
+pub mod checksum;
+pub mod config;
+#[cfg(feature = "std")]
+pub mod conformance;
+#[cfg(feature = "f64")]
+pub mod double;
+pub mod dropshot;
+#[cfg(any(test, feature = "debug"))]
+pub mod energy;
+pub mod escape;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod fallible;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
 pub mod field;
+pub mod flags;
+pub mod floor;
 pub mod game;
 pub mod geometry;
+pub mod goal;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
+pub mod grid;
+pub mod ground;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
+pub mod heuristics;
+pub mod hoops;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
+pub mod instanced;
+#[cfg(any(test, feature = "debug"))]
+pub mod jitter;
+pub mod material;
 pub mod mesh;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
 pub mod morton;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod mutators;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod physics;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod puck;
+pub mod pure;
+#[cfg(feature = "rlbot")]
+pub mod rlbot;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
+pub mod quality;
+#[cfg(feature = "brute-force-check")]
+mod reference;
+#[cfg_attr(not(feature = "internals"), doc(hidden))]
+pub mod sdf;
+#[cfg(feature = "serde")]
+pub mod snapshot;
+pub mod soa;
+#[cfg(feature = "std")]
+pub mod storage;
+pub mod summary;
+// there aren't any threads to stream from in the browser
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod stream;
+pub mod table;
+pub mod throwback;
+pub mod tuning;
@@ -0,0 +1,708 @@
+use alloc::vec::Vec;
+
+use glam::{Mat3A, Vec3, Vec3A};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::analytic::Flight;
+use super::ball::{Ball, BallPrediction};
+use super::game::Game;
+use super::geometry::Ray;
+use super::goal::Team;
+
+// A flat wall of the field, as any point on it and the normal pointing into the field
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Wall {
+    pub point: Vec3A,
+    pub normal: Vec3A,
+}
+
+// A way to get the ball from the start to the target by bouncing it off of a wall
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WallPass {
+    // the velocity the ball needs to leave the start with
+    pub launch_velocity: Vec3A,
+    // where the center of the ball is when it touches the wall, and how fast it's going then
+    pub impact: Vec3A,
+    pub incoming_velocity: Vec3A,
+    pub time_to_wall: f32,
+    // the time from leaving the start to being closest to the target
+    pub time_to_target: f32,
+    // how close the ball gets to the target
+    pub miss_distance: f32,
+}
+
+// how close the ball has to get for the pass to count, and how long a pass can take
+const PASS_TOLERANCE: f32 = 5.;
+const MAX_PASS_TIME: f32 = 4.;
+
+// the speeds used for first guesses at how long the pass takes, in the order they're tried
+const GUESS_SPEEDS: [f32; 4] = [2000., 2500., 1500., 3000.];
+
+const MAX_ITERATIONS: usize = 50;
+// the change in launch velocity used to measure how the pass responds to it
+const DERIVATIVE_STEP: f32 = 0.5;
+
+// Simulates a launch, and gives back the pass if the ball bounces off of the wall before touching anything else
+// The miss is measured between slices, so it changes smoothly with the launch velocity
+fn simulate(game: &Game, start: Vec3A, launch_velocity: Vec3A, wall: &Wall, target: Vec3A) -> Option<(Vec3A, WallPass)> {
+    let mut game = game.clone();
+    game.ball.update(0., start, launch_velocity, Vec3A::ZERO);
+
+    let mut impact: Option<(Vec3A, Vec3A, f32)> = None;
+    // the point on the path that's closest to the target, and when the ball is there
+    let mut closest: Option<(Vec3A, f32)> = None;
+
+    let num_slices = (MAX_PASS_TIME / Ball::SIMULATION_DT).round() as usize;
+
+    for _ in 0..num_slices {
+        let before = game.ball;
+        let contact = Ball::step_with_contact(&mut game, Ball::SIMULATION_DT);
+
+        if impact.is_none() {
+            // the first contact has to be the wall, bouncing off of anything else first isn't part of the plan
+            if let Some(contact) = contact {
+                if contact.direction.dot(wall.normal) > 0.99 {
+                    impact = Some((before.location, before.velocity, before.time));
+                } else if before.velocity.dot(contact.direction) < 0. {
+                    return None;
+                }
+            }
+
+            continue;
+        }
+
+        let segment = game.ball.location - before.location;
+        let t = ((target - before.location).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+        let point = before.location + segment * t;
+
+        match closest {
+            Some((closest_point, _)) if closest_point.distance(target) <= point.distance(target) => {
+                // it's well past the target
+                if point.distance(target) > closest_point.distance(target) + 500. {
+                    break;
+                }
+            }
+            _ => closest = Some((point, before.time + t * Ball::SIMULATION_DT)),
+        }
+    }
+
+    let (impact, incoming_velocity, time_to_wall) = impact?;
+    let (closest_point, time_to_target) = closest?;
+
+    Some((
+        closest_point - target,
+        WallPass {
+            launch_velocity,
+            impact,
+            incoming_velocity,
+            time_to_wall,
+            time_to_target,
+            miss_distance: closest_point.distance(target),
+        },
+    ))
+}
+
+// Finds where on the wall the ball has to hit, and how fast it has to be launched from `start`, to bounce off of it and pass through `target`
+// The bounce (and everything else) comes from the simulation, so spin, friction and gravity are all accounted for, not just a mirror reflection
+// The search starts from the mirror image of the target, and returns None if it can't get the ball within a few uu of the target
+pub fn solve_wall_pass(game: &Game, start: Vec3A, wall: &Wall, target: Vec3A) -> Option<WallPass> {
+    let normal = wall.normal.normalize();
+    let wall = Wall {
+        point: wall.point,
+        normal,
+    };
+
+    // the plane the center of the ball is on when it touches the wall
+    let plane_point = wall.point + normal * game.ball.collision_radius;
+    let side = |p: Vec3A| (p - plane_point).dot(normal);
+
+    if side(start) <= 0. || side(target) <= 0. {
+        return None;
+    }
+
+    // a perfectly bouncy wall would mirror the arc, so the first guess is the arc that goes through the target's reflection
+    let mirrored = target - normal * (2. * side(target));
+
+    let flight = Flight::new(game.gravity, game.field.tuning.drag(), Ball::SIMULATION_DT);
+
+    // if a guess hits something else first, or doesn't lead anywhere, a faster or slower one might
+    GUESS_SPEEDS.into_iter().find_map(|speed| {
+        let steps_to_target = (mirrored.distance(start) / speed / Ball::SIMULATION_DT).round() as i32;
+        refine(game, start, &wall, target, flight.launch_velocity(start, mirrored, steps_to_target.max(1)))
+    })
+}
+
+// Levenberg-Marquardt on the miss, with the launch velocity as the unknowns
+fn refine(game: &Game, start: Vec3A, wall: &Wall, target: Vec3A, mut launch_velocity: Vec3A) -> Option<WallPass> {
+    let (mut miss, mut pass) = simulate(game, start, launch_velocity, wall, target)?;
+
+    let mut damping = 1e-3;
+
+    for _ in 0..MAX_ITERATIONS {
+        if pass.miss_distance < PASS_TOLERANCE / 2. {
+            break;
+        }
+
+        let mut columns = [Vec3A::ZERO; 3];
+        for (axis, column) in columns.iter_mut().enumerate() {
+            let mut nudged = launch_velocity;
+            nudged[axis] += DERIVATIVE_STEP;
+
+            *column = (simulate(game, start, nudged, wall, target)?.0 - miss) / DERIVATIVE_STEP;
+        }
+
+        let jacobian = Mat3A::from_cols(columns[0], columns[1], columns[2]);
+        let normal_matrix = jacobian.transpose() * jacobian;
+        let gradient = jacobian.transpose() * miss;
+
+        let mut improved = false;
+
+        while damping < 1e6 {
+            let diagonal = Vec3::new(normal_matrix.x_axis.x, normal_matrix.y_axis.y, normal_matrix.z_axis.z);
+            let damped = normal_matrix + Mat3A::from_diagonal(diagonal * damping);
+            let candidate = launch_velocity - damped.inverse() * gradient;
+
+            match simulate(game, start, candidate, wall, target) {
+                Some((candidate_miss, candidate_pass)) if candidate_pass.miss_distance < pass.miss_distance => {
+                    (launch_velocity, miss, pass) = (candidate, candidate_miss, candidate_pass);
+                    damping /= 3.;
+                    improved = true;
+                    break;
+                }
+                _ => damping *= 3.,
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    Some(pass).filter(|pass| pass.miss_distance < PASS_TOLERANCE)
+}
+
+// When the ball leaves the ceiling, and where it is as it falls through each height
+#[derive(Clone, Debug, PartialEq)]
+pub struct CeilingDrop {
+    // the last slice that's touching the ceiling
+    pub detach_slice: usize,
+    pub detach_time: f32,
+    pub detach_location: Vec3A,
+    // only the heights that the ball falls through before the prediction ends, in the order they were given
+    pub crossings: Vec<HeightCrossing>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightCrossing {
+    pub height: f32,
+    pub time: f32,
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+}
+
+// contacts with a normal pointing at least this far down are the ceiling
+const CEILING_NORMAL_Z: f32 = -0.7;
+
+impl BallPrediction {
+    // The first time the ball touches the ceiling, when it comes off of it, and when it falls through each of `heights` afterwards
+    // Crossings are interpolated between slices
+    pub fn ceiling_drop(&self, game: &Game, heights: &[f32]) -> Option<CeilingDrop> {
+        let on_ceiling = |slice: &Ball| game.collide(&slice.hitbox()).is_some_and(|contact| contact.direction.z < CEILING_NORMAL_Z);
+
+        let first_touch = self.slices.iter().position(on_ceiling)?;
+        let detach_slice = first_touch + self.slices[first_touch..].iter().take_while(|slice| on_ceiling(slice)).count() - 1;
+        let detach = &self.slices[detach_slice];
+
+        let after = &self.slices[detach_slice..];
+        let crossings = heights
+            .iter()
+            .filter_map(|&height| {
+                after.windows(2).find_map(|pair| {
+                    let (a, b) = (&pair[0], &pair[1]);
+
+                    if a.location.z >= height && b.location.z < height {
+                        let t = (a.location.z - height) / (a.location.z - b.location.z);
+
+                        Some(HeightCrossing {
+                            height,
+                            time: a.time + (b.time - a.time) * t,
+                            location: a.location.lerp(b.location, t),
+                            velocity: a.velocity.lerp(b.velocity, t),
+                        })
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect();
+
+        Some(CeilingDrop {
+            detach_slice,
+            detach_time: detach.time,
+            detach_location: detach.location,
+            crossings,
+        })
+    }
+}
+
+// A stretch of slices where the ball touches the field the whole time, like a bounce or a roll
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContactInterval {
+    pub start_slice: usize,
+    // the last slice that's still touching
+    pub end_slice: usize,
+    pub start_time: f32,
+    pub end_time: f32,
+    // the normal of the surface at the first slice
+    pub normal: Vec3A,
+    // how fast the ball was moving into the surface just before it touched, to tell hard bounces from soft landings
+    pub impact_speed: f32,
+}
+
+impl ContactInterval {
+    pub fn duration(&self) -> f32 {
+        self.end_time - self.start_time
+    }
+}
+
+// A ball resting or rolling on the field keeps getting nudged just out of contact by the penetration correction,
+// so touches with only this many slices between them are part of the same interval
+const CONTACT_GAP_SLICES: usize = 3;
+
+impl BallPrediction {
+    // Every time the ball touches the field, in order, with touches that carry on over consecutive slices merged into one interval
+    // A contact that's already going at the first slice starts there
+    pub fn contact_intervals(&self, game: &Game) -> Vec<ContactInterval> {
+        let mut intervals: Vec<ContactInterval> = Vec::new();
+
+        for (i, slice) in self.slices.iter().enumerate() {
+            let Some(contact) = game.collide(&slice.hitbox()) else {
+                continue;
+            };
+
+            match intervals.last_mut() {
+                Some(interval) if i - interval.end_slice <= CONTACT_GAP_SLICES + 1 => {
+                    interval.end_slice = i;
+                    interval.end_time = slice.time;
+                }
+                _ => {
+                    let before = if i == 0 {
+                        slice
+                    } else {
+                        &self.slices[i - 1]
+                    };
+
+                    intervals.push(ContactInterval {
+                        start_slice: i,
+                        end_slice: i,
+                        start_time: slice.time,
+                        end_time: slice.time,
+                        normal: contact.direction,
+                        impact_speed: (-before.velocity.dot(contact.direction)).max(0.),
+                    });
+                }
+            }
+        }
+
+        intervals
+    }
+}
+
+impl BallPrediction {
+    // The first time the center of the ball crosses the plane of points `p` where `normal.dot(p) == offset`, in either direction
+    // The crossing is interpolated between the slices on either side of it, and is None if the ball never gets there
+    pub fn time_to_plane(&self, normal: Vec3A, offset: f32) -> Option<f32> {
+        let distance = |slice: &Ball| normal.dot(slice.location) - offset;
+
+        let first = self.slices.first()?;
+        if distance(first) == 0. {
+            return Some(first.time);
+        }
+
+        self.slices.windows(2).find_map(|pair| {
+            let (a, b) = (&pair[0], &pair[1]);
+            let (d_a, d_b) = (distance(a), distance(b));
+
+            (d_a * d_b <= 0.).then(|| a.time + (b.time - a.time) * (d_a / (d_a - d_b)))
+        })
+    }
+
+    // the first time the center of the ball goes through height `z`, on the way up or down
+    pub fn time_to_height(&self, z: f32) -> Option<f32> {
+        self.time_to_plane(Vec3A::Z, z)
+    }
+}
+
+impl BallPrediction {
+    // If the field blocks the view of the ball from the camera at each slice, in the same order as the slices
+    // This only checks the line to the center of the ball, so a ball that's partly hidden counts as visible
+    pub fn occluded_from(&self, game: &Game, camera: Vec3A) -> Vec<bool> {
+        self.slices.iter().map(|slice| is_occluded(game, camera, slice)).collect()
+    }
+}
+
+pub(crate) fn is_occluded(game: &Game, camera: Vec3A, ball: &Ball) -> bool {
+    let to_ball = ball.location - camera;
+    let distance = to_ball.length();
+
+    // the camera is inside of the ball
+    if distance <= ball.radius {
+        return false;
+    }
+
+    // stopping at the surface of the ball, so the surface it's resting on doesn't count
+    !game.line_of_sight(camera, camera + to_ball * (1. - ball.radius / distance))
+}
+
+// where the center of the ball is at `time`, moving in a straight line between slices
+// `time` has to be within the slices
+fn location_at(slices: &[Ball], time: f32) -> Vec3A {
+    let i = slices.partition_point(|slice| slice.time < time);
+    if i == 0 {
+        return slices[0].location;
+    }
+
+    let (before, after) = (&slices[i - 1], &slices[i.min(slices.len() - 1)]);
+    let span = after.time - before.time;
+    if span <= 0. {
+        return after.location;
+    }
+
+    before.location.lerp(after.location, (time - before.time) / span)
+}
+
+// The time and distance of when the centers of the balls in two predictions are the closest, like two balls in a multiball mode,
+// or the ball and a prediction of anything else that's about as big as it
+// Both move in a straight line between their slices, and only the time that both predictions cover counts,
+// so if they don't overlap at all this is (NaN, infinity)
+// Subtract the radii from the distance to get the gap between their surfaces
+pub fn closest_approach(a: &BallPrediction, b: &BallPrediction) -> (f32, f32) {
+    let (Some(first_a), Some(first_b), Some(last_a), Some(last_b)) = (a.slices.first(), b.slices.first(), a.slices.last(), b.slices.last()) else {
+        return (f32::NAN, f32::INFINITY);
+    };
+
+    let start = first_a.time.max(first_b.time);
+    let end = last_a.time.min(last_b.time);
+    if start > end {
+        return (f32::NAN, f32::INFINITY);
+    }
+
+    // the distance between them is a straight line between any two of these times
+    let mut times: Vec<f32> = a.slices.iter().chain(&b.slices).map(|slice| slice.time).filter(|time| (start..=end).contains(time)).collect();
+    times.extend([start, end]);
+    times.sort_unstable_by(f32::total_cmp);
+    times.dedup();
+
+    let offset_at = |time| location_at(&b.slices, time) - location_at(&a.slices, time);
+    let mut closest = (start, offset_at(start).length());
+
+    for pair in times.windows(2) {
+        let (t0, t1) = (pair[0], pair[1]);
+        let (p0, p1) = (offset_at(t0), offset_at(t1));
+
+        // the closest point of the segment from p0 to p1 to the origin
+        let d = p1 - p0;
+        let fraction = if d.length_squared() > 0. {
+            (-p0.dot(d) / d.length_squared()).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let distance = (p0 + d * fraction).length();
+        if distance < closest.1 {
+            closest = (t0 + (t1 - t0) * fraction, distance);
+        }
+    }
+
+    closest
+}
+
+// how many rays are cast across and up the goal mouth
+const VISIBILITY_COLUMNS: usize = 24;
+const VISIBILITY_ROWS: usize = 8;
+
+// How much of `team`'s goal mouth can be seen from the point, from 0 to 1, weighted by the solid angle each part of the mouth takes up
+// Only the field blocks the view, cars and the ball don't
+pub fn goal_visible_from(game: &Game, point: Vec3A, team: Team) -> f32 {
+    let Some(goal) = game.field.goals.get(team.index()) else {
+        return 0.;
+    };
+
+    let across = goal.direction.cross(Vec3A::Z);
+    let cell_width = goal.width / VISIBILITY_COLUMNS as f32;
+    let cell_height = goal.height / VISIBILITY_ROWS as f32;
+
+    let mut total = 0.;
+    let mut visible = 0.;
+
+    for i in 0..VISIBILITY_COLUMNS {
+        for j in 0..VISIBILITY_ROWS {
+            let target = goal.location + across * ((i as f32 + 0.5) * cell_width - goal.width / 2.) + Vec3A::Z * ((j as f32 + 0.5) * cell_height);
+            let to_target = target - point;
+            let distance = to_target.length();
+
+            // the solid angle of a small patch is its area, times how much it faces the point, over the distance squared
+            let facing = to_target.dot(goal.direction) / distance;
+            if facing <= 0. {
+                continue;
+            }
+
+            let weight = facing / (distance * distance);
+            total += weight;
+
+            // stopping just short of the goal line, so the goal's frame around the mouth doesn't count
+            let ray = Ray {
+                start: point,
+                direction: to_target * (1. - 1e-4),
+            };
+
+            if game.field.collision_mesh.raycast(&ray).is_none() {
+                visible += weight;
+            }
+        }
+    }
+
+    if total > 0. {
+        visible / total
+    } else {
+        0.
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::goal::Goal;
+    use crate::simulation::mesh::Mesh;
+    use crate::{load_custom_mesh, load_soccar};
+
+    fn side_wall() -> Wall {
+        Wall {
+            point: vec3a(4096., 0., 0.),
+            normal: vec3a(-1., 0., 0.),
+        }
+    }
+
+    #[test]
+    fn side_wall_pass() {
+        let game = load_soccar();
+
+        for (start, target) in [(vec3a(2000., -2000., 300.), vec3a(2500., 1500., 500.)), (vec3a(0., -3000., 100.), vec3a(1000., 0., 800.)), (vec3a(3000., 2000., 1000.), vec3a(3000., -1000., 200.))] {
+            check_pass(&game, start, target);
+        }
+    }
+
+    fn check_pass(game: &Game, start: Vec3A, target: Vec3A) {
+        let pass = solve_wall_pass(game, start, &side_wall(), target).unwrap();
+
+        assert!(pass.miss_distance < PASS_TOLERANCE);
+        assert!((pass.impact.x - (4096. - game.ball.collision_radius)).abs() < 20., "{pass:?}");
+        assert!(pass.incoming_velocity.x > 0.);
+        assert!(pass.time_to_wall < pass.time_to_target);
+
+        // launching the ball like it says really does get it there
+        let mut game = game.clone();
+        game.ball.update(0., start, pass.launch_velocity, Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &MAX_PASS_TIME);
+        let closest = prediction.slices.iter().map(|slice| slice.location.distance(target)).fold(f32::MAX, f32::min);
+        // the pass is measured between slices, so the nearest slice can be up to half a step further away
+        let half_step = pass.launch_velocity.length() * Ball::SIMULATION_DT / 2.;
+        assert!(closest < PASS_TOLERANCE + half_step, "{closest}");
+    }
+
+    #[test]
+    fn ceiling_drop() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(0., 500., 1800.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let drop = prediction.ceiling_drop(&game, &[1500., 1000., 3000., 500.]).unwrap();
+
+        // the ceiling is at 2044
+        assert!(drop.detach_location.z > 2044. - game.ball.collision_radius - 5.);
+        assert!(prediction.slices[drop.detach_slice + 1].velocity.z < 0.);
+
+        // 3000 is above the ceiling, so it can't be fallen through
+        let heights: Vec<f32> = drop.crossings.iter().map(|crossing| crossing.height).collect();
+        assert_eq!(heights, vec![1500., 1000., 500.]);
+
+        for pair in drop.crossings.windows(2) {
+            assert!(pair[0].time < pair[1].time);
+            assert!(pair[0].velocity.z > pair[1].velocity.z);
+        }
+
+        for crossing in &drop.crossings {
+            assert!((crossing.location.z - crossing.height).abs() < 1e-3);
+            assert!(crossing.time > drop.detach_time);
+        }
+
+        // a ball that never reaches the ceiling
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(0., 500., 0.), Vec3A::ZERO);
+        assert_eq!(Ball::get_ball_prediction_struct(&mut game).ceiling_drop(&game, &[500.]), None);
+    }
+
+    #[test]
+    fn impossible_pass() {
+        let game = load_soccar();
+
+        // the target is behind the wall
+        assert_eq!(solve_wall_pass(&game, vec3a(2000., 0., 300.), &side_wall(), vec3a(5000., 0., 300.)), None);
+    }
+
+    #[test]
+    fn open_goal() {
+        let game = load_soccar();
+
+        // right in front of it
+        assert_eq!(goal_visible_from(&game, vec3a(0., -2000., 300.), Team::Blue), 1.);
+        // from the other side of the side wall, or from behind the goal line
+        assert_eq!(goal_visible_from(&game, vec3a(4500., -3000., 300.), Team::Blue), 0.);
+        assert_eq!(goal_visible_from(&game, vec3a(0., 2000., 300.), Team::Orange), 1.);
+        assert_eq!(goal_visible_from(&game, vec3a(0., 5500., 300.), Team::Orange), 0.);
+    }
+
+    #[test]
+    fn half_blocked_goal() {
+        // a wall that covers the positive x half of blue's goal from the center of the field
+        let wall = Mesh {
+            ids: vec![0, 1, 2, 0, 2, 3],
+            vertices: vec![0., -3000., -10., 1000., -3000., -10., 1000., -3000., 1000., 0., -3000., 1000.],
+        };
+
+        let mut game = load_custom_mesh(&wall);
+        game.field.goals = Goal::soccar().to_vec();
+
+        let visible = goal_visible_from(&game, vec3a(0.5, 0., 300.), Team::Blue);
+        assert!((visible - 0.5).abs() < 0.05, "{visible}");
+        assert_eq!(goal_visible_from(&game, vec3a(0.5, 0., 300.), Team::Orange), 1.);
+    }
+
+    #[test]
+    fn bounces_then_rolls() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(300., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &10.);
+        let intervals = prediction.contact_intervals(&game);
+
+        assert!(intervals.len() > 2, "{intervals:?}");
+
+        // the first bounce is about when falling from that height should hit the floor, and it's hard
+        let first = intervals[0];
+        let fall = (2. * (1000. - game.ball.collision_radius) / 650f32).sqrt();
+        assert!((first.start_time - fall).abs() < 0.05, "{first:?}");
+        assert!(first.impact_speed > 900., "{first:?}");
+        assert!((first.normal - Vec3A::Z).length() < 1e-3);
+
+        // each bounce is softer than the last, until the ball settles and rolls until the end
+        assert!(intervals.windows(2).all(|pair| pair[1].impact_speed < pair[0].impact_speed && pair[1].start_slice > pair[0].end_slice + 1));
+        assert!(intervals.last().unwrap().end_slice >= prediction.num_slices - 1 - CONTACT_GAP_SLICES);
+        assert!(intervals.last().unwrap().duration() > 1.);
+    }
+
+    #[test]
+    fn rolling_is_one_contact() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., game.ball.collision_radius), vec3a(500., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+        let intervals = prediction.contact_intervals(&game);
+
+        assert_eq!(intervals.len(), 1, "{intervals:?}");
+        assert_eq!((intervals[0].start_slice, intervals[0].end_slice), (0, prediction.num_slices - 1));
+    }
+
+    #[test]
+    fn crossing_heights_and_planes() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(500., 0., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+        // falling from rest, the center of the ball reaches 500 uu after about sqrt(2 * 500 / 650) seconds
+        let time = prediction.time_to_height(500.).unwrap();
+        assert!((time - (2. * 500. / 650_f32).sqrt()).abs() < 0.02, "{time}");
+
+        // it lands between two slices, not on one
+        let slice = prediction.slices.iter().position(|slice| slice.time >= time).unwrap();
+        assert!(prediction.slices[slice - 1].location.z > 500. && prediction.slices[slice].location.z <= 500.);
+
+        // the same plane, from the other side
+        assert_eq!(prediction.time_to_plane(-Vec3A::Z, -500.), Some(time));
+
+        // a wall that it's moving towards
+        let wall = prediction.time_to_plane(Vec3A::X, 300.).unwrap();
+        assert!((wall - 300. / 500.).abs() < 0.05, "{wall}");
+
+        // it starts below the ceiling and never reaches it
+        assert_eq!(prediction.time_to_height(1500.), None);
+        assert_eq!(BallPrediction::default().time_to_height(0.), None);
+    }
+
+    #[test]
+    fn occlusion_from_camera() {
+        let mut game = load_soccar();
+
+        // the ball rolls from the middle of the field into the blue goal, and the camera is behind the goal's back wall, looking in
+        game.ball.update(0., vec3a(0., -4000., game.ball.collision_radius), vec3a(0., -1500., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &1.);
+
+        let behind_goal = vec3a(0., -6500., 300.);
+        let occluded = prediction.occluded_from(&game, behind_goal);
+        assert_eq!(occluded.len(), prediction.num_slices);
+        assert!(occluded.iter().all(|occluded| *occluded));
+
+        // from the stands above the middle of the field, the ball is always in view
+        let above = vec3a(0., 0., 1800.);
+        assert!(prediction.occluded_from(&game, above).iter().all(|occluded| !occluded));
+
+        // and from outside of the side wall, it never is
+        let outside = vec3a(5000., -4500., 500.);
+        assert!(prediction.occluded_from(&game, outside).iter().all(|occluded| *occluded));
+    }
+
+    #[test]
+    fn closest_approach_of_two_balls() {
+        let mut game = load_soccar();
+
+        // flying at each other over the middle of the field, a little to the side of each other
+        game.ball.update(0., vec3a(-1000., 0., 1000.), vec3a(1000., 0., 0.), Vec3A::ZERO);
+        let a = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+        game.ball.update(0., vec3a(1000., 300., 1000.), vec3a(-1000., 0., 0.), Vec3A::ZERO);
+        let b = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+
+        let (time, distance) = closest_approach(&a, &b);
+        assert!((distance - 300.).abs() < 1., "{distance}");
+        assert!((time - 1.).abs() < 0.05, "{time}");
+        assert_eq!(closest_approach(&b, &a), (time, distance));
+
+        // between slices, both move in a straight line
+        let slice = |time, location| Ball {
+            time,
+            location,
+            ..Default::default()
+        };
+        let a = BallPrediction {
+            slices: vec![slice(0., vec3a(0., 0., 0.)), slice(1., vec3a(10., 0., 0.))],
+            ..Default::default()
+        };
+        let b = BallPrediction {
+            slices: vec![slice(0., vec3a(10., 1., 0.)), slice(1., vec3a(0., 1., 0.))],
+            ..Default::default()
+        };
+        assert_eq!(closest_approach(&a, &b), (0.5, 1.));
+
+        // only the time both of them cover counts
+        let later = BallPrediction {
+            slices: vec![slice(2., Vec3A::ZERO), slice(3., Vec3A::ZERO)],
+            ..Default::default()
+        };
+        let (time, distance) = closest_approach(&a, &later);
+        assert!(time.is_nan() && distance == f32::INFINITY);
+        assert_eq!(closest_approach(&a, &BallPrediction::default()).1, f32::INFINITY);
+    }
+}
@@ -0,0 +1,315 @@
+use alloc::vec::Vec;
+
+use glam::{DVec3, Vec3, Vec3A};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::{Ball, BallPrediction, BounceEvent};
+use super::flags;
+use super::game::{Accuracy, ContactModel, Game, Integrator};
+use super::geometry::{Aabb, Ray};
+use super::mutators::DragModel;
+
+// Closed-form prediction for balls that can only ever touch a flat floor
+//
+// Between bounces, each simulation step is `v += (v * DRAG + g) * dt; x += v * dt`,
+// which is a linear recurrence with the closed-form solution
+//   v_n = a^n (v_0 - v_inf) + v_inf
+//   x_n = x_0 + dt * ((v_0 - v_inf) * a * (1 - a^n) / (1 - a) + n * v_inf)
+// where a = 1 + DRAG * dt and v_inf = -g / DRAG
+// Bounces use the exact same impulse as the stepped simulation,
+// so the only difference is how the floating point error accumulates.
+// (1 - a) is tiny, so the arcs are evaluated in f64 to avoid catastrophic cancellation
+
+const FLAT_TOLERANCE: f32 = 0.01;
+
+pub(crate) struct Flight {
+    a: f64,
+    v_inf: DVec3,
+    dt: f64,
+}
+
+impl Flight {
+    pub(crate) fn new(gravity: Vec3A, drag: f32, dt: f32) -> Self {
+        let drag = drag as f64;
+
+        Self {
+            a: 1. + drag * dt as f64,
+            v_inf: -Vec3::from(gravity).as_dvec3() / drag,
+            dt: dt as f64,
+        }
+    }
+
+    // the state of the ball after n steps in the air
+    fn after(&self, location: Vec3A, velocity: Vec3A, n: i32) -> (Vec3A, Vec3A) {
+        let a_n = self.a.powi(n);
+        let dv = Vec3::from(velocity).as_dvec3() - self.v_inf;
+
+        let new_velocity = dv * a_n + self.v_inf;
+        let displacement = self.dt * (dv * (self.a * (1. - a_n) / (1. - self.a)) + self.v_inf * n as f64);
+
+        (location + Vec3A::from(displacement.as_vec3()), Vec3A::from(new_velocity.as_vec3()))
+    }
+
+    // the velocity that takes the ball from `from` to `to` in n steps, if nothing is in the way
+    pub(crate) fn launch_velocity(&self, from: Vec3A, to: Vec3A, n: i32) -> Vec3A {
+        let a_n = self.a.powi(n);
+        let displacement = Vec3::from(to - from).as_dvec3();
+
+        let dv = (displacement / self.dt - self.v_inf * n as f64) / (self.a * (1. - a_n) / (1. - self.a));
+
+        Vec3A::from((dv + self.v_inf).as_vec3())
+    }
+}
+
+// If nothing but a flat floor is within reach of the ball for the next `num_slices` steps, returns the height of that floor
+pub fn reachable_floor_height(game: &Game, num_slices: usize) -> Option<f32> {
+    let ball = &game.ball;
+
+    // the closed form is the default step, so anything that changes how the ball steps falls back to stepping it,
+    // like a plugin, surfaces made of something else, or a different integrator or accuracy
+    if !game.is_pure() || game.field.materials.is_some() || game.integrator != Integrator::default() || game.accuracy != Accuracy::default() {
+        return None;
+    }
+
+    // only straight down gravity, the game's drag, spin that neither slows down on its own nor curves the ball, and no rolling resistance keep the ball's motion separable like this
+    if game.gravity.x != 0. || game.gravity.y != 0. || game.gravity.z >= 0. || ball.drag_model != DragModel::Linear || ball.angular_damping != 0. || ball.magnus != 0. || ball.rolling_resistance != 0. {
+        return None;
+    }
+
+    let g = -game.gravity.z;
+    let time = (num_slices + 1) as f32 * Ball::SIMULATION_DT;
+    let r = ball.collision_radius;
+
+    // drag only ever slows the ball down, so these are upper bounds
+    // the friction from a bounce can turn, at most, all of the spin into horizontal speed
+    let horizontal_speed = Vec3A::new(ball.velocity.x, ball.velocity.y, 0.).length() + ball.angular_velocity.length().min(Ball::W_MAX) * r;
+    let max_height = ball.location.z + ball.velocity.z.max(0.).powi(2) / (2. * g);
+
+    let reach = horizontal_speed * time + r;
+    let query_box = Aabb {
+        min: Vec3A::new(ball.location.x - reach, ball.location.y - reach, f32::MIN),
+        max: Vec3A::new(ball.location.x + reach, ball.location.y + reach, max_height + r),
+    };
+
+    // the ball could fall through a hole in dropshot's floor
+    if game.dropshot_tiles.as_ref().is_some_and(|tiles| tiles.has_hole_in(&query_box)) {
+        return None;
+    }
+
+    let tris = game.field.collision_mesh.intersect_aabb(&query_box);
+    let floor = tris.first()?.p[0].z;
+
+    let is_floor = |p: &Vec3A| (p.z - floor).abs() < FLAT_TOLERANCE;
+    // the floor has to be underneath the entire reachable area, or the ball could fall through a gap
+    let mut covered = Aabb {
+        min: Vec3A::splat(f32::MAX),
+        max: Vec3A::splat(f32::MIN),
+    };
+
+    for tri in &tris {
+        if !tri.p.iter().all(is_floor) {
+            return None;
+        }
+
+        covered = covered.add(&tri.into());
+    }
+
+    if covered.min.x > query_box.min.x || covered.min.y > query_box.min.y || covered.max.x < query_box.max.x || covered.max.y < query_box.max.y || ball.location.z - r < floor - FLAT_TOLERANCE {
+        return None;
+    }
+
+    // the speed limit would break up the closed form
+    if (ball.velocity.length_squared() + 2. * g * (ball.location.z - floor)).sqrt() >= Ball::V_MAX {
+        return None;
+    }
+
+    Some(floor)
+}
+
+impl Ball {
+    // Same as get_ball_prediction_struct_for_slices, but uses the closed-form solution when only the floor is in reach
+    pub fn get_ball_prediction_struct_analytic_for_slices(game: &mut Game, num_slices: usize) -> BallPrediction {
+        let floor = match reachable_floor_height(game, num_slices) {
+            Some(floor) => floor,
+            None => return Ball::get_ball_prediction_struct_for_slices(game, num_slices),
+        };
+
+        let dt = Ball::SIMULATION_DT;
+        let flight = Flight::new(game.gravity, game.field.tuning.drag(), dt);
+        let mut slices = Vec::with_capacity(num_slices);
+        let mut bounces = Vec::new();
+
+        let floor_contact = |ball: &Ball| {
+            let separation = ball.location.z - floor;
+
+            (separation <= ball.collision_radius).then(|| Ray {
+                start: ball.location - Vec3A::Z * separation,
+                direction: Vec3A::Z,
+            })
+        };
+
+        // spin is only changed by bounces
+        game.ball.limit_velocities();
+
+        while slices.len() < num_slices {
+            if let Some(contact) = floor_contact(&game.ball) {
+                let before = game.ball;
+
+                if game.contact_model == ContactModel::Game || game.ball.velocity.z <= 0. {
+                    game.ball.collide(&contact, &game.field.tuning, game.contact_model, dt);
+                } else {
+                    // already moving away from the floor, so it's just a normal step that can't sink into it
+                    (game.ball.location, game.ball.velocity) = flight.after(game.ball.location, game.ball.velocity, 1);
+                    game.ball.correct_penetration(&contact, game.contact_model);
+                }
+
+                game.ball.limit_velocities();
+                bounces.extend(BounceEvent::check(&before, &game.ball, Some(contact)));
+                game.ball.time += dt;
+                game.ball.flags = flags::slice_flags(&game.arena(), &game.ball, floor_contact(&game.ball));
+                slices.push(game.ball);
+                continue;
+            }
+
+            // the ball is in the air, so fill in slices until it's touching the floor again
+            let (start_location, start_velocity) = (game.ball.location, game.ball.velocity);
+            let mut n = 0;
+
+            while slices.len() < num_slices {
+                n += 1;
+
+                let (location, velocity) = flight.after(start_location, start_velocity, n);
+                game.ball.location = location;
+                game.ball.velocity = velocity;
+                game.ball.time += dt;
+                let contact = floor_contact(&game.ball);
+                game.ball.flags = flags::slice_flags(&game.arena(), &game.ball, contact);
+                slices.push(game.ball);
+
+                if contact.is_some() {
+                    break;
+                }
+            }
+        }
+
+        BallPrediction {
+            num_slices: slices.len(),
+            slices,
+            bounces,
+            // only the floor is in reach
+            escape: None,
+        }
+    }
+
+    pub fn get_ball_prediction_struct_analytic(game: &mut Game) -> BallPrediction {
+        Ball::get_ball_prediction_struct_analytic_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::material::Material;
+
+    fn compare(mut game: Game, tolerance: f32) {
+        let mut analytic_game = game.clone();
+
+        let stepped = Ball::get_ball_prediction_struct(&mut game);
+        let analytic = Ball::get_ball_prediction_struct_analytic(&mut analytic_game);
+
+        assert_eq!(stepped.num_slices, analytic.num_slices);
+        assert_eq!(stepped.bounces.len(), analytic.bounces.len());
+
+        for (a, b) in stepped.slices.iter().zip(&analytic.slices) {
+            assert!((a.time - b.time).abs() < 1e-4);
+            assert!((a.location - b.location).length() < tolerance, "{} vs {}", a.location, b.location);
+        }
+    }
+
+    #[test]
+    fn floor_is_reachable_in_midfield() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 500.), vec3a(100., -100., 0.), Vec3A::ZERO);
+
+        assert_eq!(reachable_floor_height(&game, 720), Some(0.));
+    }
+
+    #[test]
+    fn walls_are_reachable() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(3800., 0., 500.), vec3a(500., 0., 0.), Vec3A::ZERO);
+
+        assert_eq!(reachable_floor_height(&game, 720), None);
+    }
+
+    #[test]
+    fn bouncing_matches_stepped() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 800.), vec3a(100., 200., 300.), vec3a(1., -2., 0.5));
+
+        compare(game, 1.);
+    }
+
+    #[test]
+    fn resting_matches_stepped() {
+        let game = load_soccar();
+
+        compare(game, 0.1);
+    }
+
+    #[test]
+    fn falls_back_near_walls() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(3800., 4800., 800.), vec3a(1000., 200., 300.), vec3a(1., -2., 0.5));
+
+        compare(game, f32::EPSILON);
+    }
+
+    #[test]
+    fn falls_back_with_other_settings() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 800.), vec3a(100., 200., 300.), vec3a(1., -2., 0.5));
+        assert!(reachable_floor_height(&game, 720).is_some());
+
+        let mut rk4 = game.clone();
+        rk4.integrator = Integrator::Rk4;
+
+        let mut floor_only = game.clone();
+        floor_only.set_accuracy(Accuracy::FloorOnly);
+
+        // even if nothing is tagged with it
+        let mut bouncy = game.clone();
+        bouncy.field.set_material(
+            1,
+            Material {
+                restitution: 0.9,
+                ..Material::from_tuning(&game.field.tuning)
+            },
+        );
+
+        for game in [rk4, floor_only, bouncy] {
+            assert_eq!(reachable_floor_height(&game, 720), None);
+
+            let mut analytic_game = game.clone();
+            let analytic = Ball::get_ball_prediction_struct_analytic(&mut analytic_game);
+            let stepped = Ball::get_ball_prediction_struct(&mut game.clone());
+
+            assert_eq!(analytic.checksum(), stepped.checksum());
+            assert_eq!(analytic.bounces, stepped.bounces);
+        }
+    }
+
+    #[test]
+    fn split_impulse_matches_stepped() {
+        let mut game = load_soccar();
+        game.contact_model = ContactModel::SplitImpulse;
+        game.ball.update(0., vec3a(0., 0., 800.), vec3a(100., 200., 300.), vec3a(1., -2., 0.5));
+
+        compare(game, 1.);
+    }
+}
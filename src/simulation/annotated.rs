@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+
+use super::ball::{Ball, BallPrediction};
+
+// A prediction with user data attached to every slice
+// The annotations are kept in their own array, so annotations[i] belongs to prediction.slices[i]
+#[derive(Clone, Default)]
+pub struct AnnotatedPrediction<T> {
+    pub prediction: BallPrediction,
+    pub annotations: Vec<T>,
+}
+
+impl BallPrediction {
+    pub fn annotate_with<T, F: FnMut(&Ball) -> T>(self, annotate: F) -> AnnotatedPrediction<T> {
+        let annotations = self.slices.iter().map(annotate).collect();
+
+        AnnotatedPrediction {
+            prediction: self,
+            annotations,
+        }
+    }
+}
+
+impl<T> AnnotatedPrediction<T> {
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<(&Ball, &T)> {
+        Some((self.prediction.slices.get(index)?, self.annotations.get(index)?))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Ball, &T)> {
+        self.prediction.slices.iter().zip(&self.annotations)
+    }
+
+    // derive new data from each slice and its current annotation
+    pub fn map<U, F: FnMut(&Ball, &T) -> U>(self, mut f: F) -> AnnotatedPrediction<U> {
+        let annotations = self.prediction.slices.iter().zip(&self.annotations).map(|(slice, annotation)| f(slice, annotation)).collect();
+
+        AnnotatedPrediction {
+            prediction: self.prediction,
+            annotations,
+        }
+    }
+
+    // add another annotation on top of the current one
+    pub fn annotate_with<U, F: FnMut(&Ball, &T) -> U>(self, mut annotate: F) -> AnnotatedPrediction<(T, U)> {
+        let annotations = self.prediction.slices.iter().zip(self.annotations).map(|(slice, annotation)| {
+            let new = annotate(slice, &annotation);
+            (annotation, new)
+        });
+
+        AnnotatedPrediction {
+            annotations: annotations.collect(),
+            prediction: self.prediction,
+        }
+    }
+
+    pub fn into_parts(self) -> (BallPrediction, Vec<T>) {
+        (self.prediction, self.annotations)
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn annotations_line_up_with_slices() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(0., 1000., 0.), vec3a(0., 0., 0.));
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let num_slices = prediction.num_slices;
+
+        let target = vec3a(0., 5120., 100.);
+        let annotated = prediction.annotate_with(|slice| slice.location.distance(target));
+
+        assert_eq!(annotated.len(), num_slices);
+
+        for (slice, distance) in annotated.iter() {
+            assert_eq!(*distance, slice.location.distance(target));
+        }
+
+        let annotated = annotated.annotate_with(|slice, distance| slice.time > 1. && *distance < 3000.);
+        let (slice, (distance, reachable)) = annotated.get(num_slices - 1).unwrap();
+        assert!(slice.time > 1.);
+        assert_eq!(*reachable, *distance < 3000.);
+
+        let (prediction, annotations) = annotated.map(|_, (_, reachable)| *reachable).into_parts();
+        assert_eq!(prediction.slices.len(), annotations.len());
+    }
+}
@@ -1,8 +1,20 @@
-use crate::simulation::game::Game;
-use crate::simulation::geometry::Sphere;
+use alloc::vec::Vec;
+
+use crate::simulation::escape::EscapeEvent;
+use crate::simulation::flags;
+use crate::simulation::game::{Accuracy, Arena, ContactModel, Game};
+use crate::simulation::geometry::{Ray, Sphere};
+use crate::simulation::material::Material;
+use crate::simulation::mutators::DragModel;
+use crate::simulation::physics::{self, BounceParams, FlightParams};
+use crate::simulation::tuning::Tuning;
 use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ball {
     pub time: f32,
     pub location: Vec3A,
@@ -11,12 +23,24 @@ pub struct Ball {
     pub radius: f32,
     pub collision_radius: f32,
     pub moi: f32,
+    pub drag_model: DragModel,
+    pub angular_damping: f32,
+    pub magnus: f32,
+    // the fraction of the pull of gravity into the surface that slows down a rolling ball, see `Mutators::rolling_resistance`
+    pub rolling_resistance: f32,
+    // see `flags`
+    pub flags: u8,
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BallPrediction {
     pub num_slices: usize,
     pub slices: Vec<Ball>,
+    // every bounce along the way, in order, which predictions that don't simulate step by step leave empty
+    pub bounces: Vec<BounceEvent>,
+    // set when the ball got out of the field, in which case the slices stop just before it did
+    pub escape: Option<EscapeEvent>,
 }
 
 impl Default for BallPrediction {
@@ -24,19 +48,55 @@ impl Default for BallPrediction {
         Self {
             num_slices: 0,
             slices: Vec::new(),
+            bounces: Vec::new(),
+            escape: None,
         }
     }
 }
 
+// A step where the ball hit a surface hard enough to bounce off of it, instead of rolling along it
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BounceEvent {
+    // the time and location at the start of the step, when the surface pushed back on the ball
+    pub time: f32,
+    pub location: Vec3A,
+    pub normal: Vec3A,
+    pub velocity_before: Vec3A,
+    // at the end of the step, after the bounce
+    pub velocity_after: Vec3A,
+}
+
+impl BounceEvent {
+    // the bounce in the step that went from `before` to `after`, given what the ball was touching at the start of it
+    pub(crate) fn check(before: &Ball, after: &Ball, contact: Option<Ray>) -> Option<Self> {
+        let contact = contact?;
+
+        if before.velocity.dot(contact.direction) > 0. || before.is_rolling(&contact) {
+            return None;
+        }
+
+        Some(Self {
+            time: before.time,
+            location: before.location,
+            normal: before.surface_normal(&contact),
+            velocity_before: before.velocity,
+            velocity_after: after.velocity,
+        })
+    }
+}
+
 impl Ball {
-    const RESTITUTION: f32 = 0.6;
-    const DRAG: f32 = -0.0305;
-    const MU: f32 = 2.;
+    pub(crate) const RESTITUTION: f32 = 0.6;
+    pub(crate) const DRAG: f32 = -0.0305;
+    pub(crate) const MU: f32 = 2.;
+    // the game only slows a rolling ball down with drag
+    pub(crate) const ROLLING_RESISTANCE: f32 = 0.;
 
-    const V_MAX: f32 = 4000.;
-    const W_MAX: f32 = 6.;
+    pub(crate) const V_MAX: f32 = 4000.;
+    pub(crate) const W_MAX: f32 = 6.;
 
-    const M: f32 = 30.;
+    pub(crate) const M: f32 = 30.;
 
     const SOCCAR_RADIUS: f32 = 91.25;
     const HOOPS_RADIUS: f32 = 91.25;
@@ -45,16 +105,23 @@ impl Ball {
     const HOOPS_COLLISION_RADIUS: f32 = 93.15;
     const DROPSHOT_COLLISION_RADIUS: f32 = 103.6;
 
-    const INV_M: f32 = 1. / 30.;
-    const RESTITUTION_M: f32 = -(1. + Ball::RESTITUTION) * Ball::M;
+    // A ball is rolling once it's settled onto a surface, and is barely moving into or away from it
+    const ROLLING_MAX_NORMAL_SPEED: f32 = 50.;
+    // the spin is capped, so fast balls slide along instead, and only keep sliding the same amount when they're slowed down
+    const ROLLING_MAX_SLIP: f32 = 50.;
+
+    // with `ContactModel::SplitImpulse`, how much of the penetration is corrected each step, and how much is left alone
+    const BAUMGARTE: f32 = 0.8;
+    const PENETRATION_SLOP: f32 = 0.01;
 
-    const SIMULATION_DT: f32 = 1. / 120.;
-    const STANDARD_NUM_SLICES: usize = 720;
+    pub(crate) const SIMULATION_DT: f32 = 1. / 120.;
+    pub(crate) const STANDARD_NUM_SLICES: usize = 720;
 
     pub fn initialize_soccar() -> Self {
         let mut ball = Ball {
             radius: Ball::SOCCAR_RADIUS,
             collision_radius: Ball::SOCCAR_COLLISION_RADIUS,
+            rolling_resistance: Ball::ROLLING_RESISTANCE,
             ..Default::default()
         };
 
@@ -67,6 +134,7 @@ impl Ball {
         let mut ball = Ball {
             radius: Ball::HOOPS_RADIUS,
             collision_radius: Ball::HOOPS_COLLISION_RADIUS,
+            rolling_resistance: Ball::ROLLING_RESISTANCE,
             ..Default::default()
         };
 
@@ -79,6 +147,7 @@ impl Ball {
         let mut ball = Ball {
             radius: Ball::DROPSHOT_RADIUS,
             collision_radius: Ball::DROPSHOT_COLLISION_RADIUS,
+            rolling_resistance: Ball::ROLLING_RESISTANCE,
             ..Default::default()
         };
 
@@ -92,6 +161,24 @@ impl Ball {
         self.calculate_moi();
     }
 
+    // for ball size mutators, the collision radius is scaled along with the radius, like the game does
+    // a ball without a size yet (like `Ball::default()`) gets the soccar ball's proportions
+    pub fn set_radius(&mut self, radius: f32) {
+        let ratio = if self.radius > 0. {
+            self.collision_radius / self.radius
+        } else {
+            Ball::SOCCAR_COLLISION_RADIUS / Ball::SOCCAR_RADIUS
+        };
+
+        self.set_radii(radius, radius * ratio);
+    }
+
+    pub fn set_radii(&mut self, radius: f32, collision_radius: f32) {
+        self.radius = radius;
+        self.collision_radius = collision_radius;
+        self.calculate_moi();
+    }
+
     pub fn calculate_moi(&mut self) {
         self.moi = 0.4 * Ball::M * self.radius * self.radius;
     }
@@ -103,51 +190,174 @@ impl Ball {
         self.angular_velocity = angular_velocity;
     }
 
-    fn hitbox(&self) -> Sphere {
+    pub(crate) fn hitbox(&self) -> Sphere {
         Sphere {
             center: self.location,
             radius: self.collision_radius,
         }
     }
 
-    pub fn step(game: &mut Game, dt: f32) {
-        match game.collision_mesh.collide(&game.ball.hitbox()) {
-            Some(contact) => {
-                let p = contact.start;
-                let n = contact.direction;
+    // applies the impulse from a contact, then moves the ball out of the surface
+    pub(crate) fn collide(&mut self, contact: &Ray, tuning: &Tuning, contact_model: ContactModel, dt: f32) {
+        self.bounce_off(contact, &BounceParams::from_ball(self, tuning), tuning.drag(), contact_model, dt);
+    }
 
-                let loc = p - game.ball.location;
+    // the same as `collide`, but off of a surface that isn't the field's normal one
+    pub(crate) fn collide_with_material(&mut self, contact: &Ray, material: Material, tuning: &Tuning, contact_model: ContactModel, dt: f32) {
+        let mut params = BounceParams::from_ball(self, tuning);
+        material.apply(&mut params);
 
-                let m_reduced = 1. / (Ball::INV_M + loc.length_squared() / game.ball.moi);
+        self.bounce_off(contact, &params, tuning.drag(), contact_model, dt);
+    }
 
-                let v_perp = n * game.ball.velocity.dot(n).min(0.);
-                let v_para = game.ball.velocity - v_perp - loc.cross(game.ball.angular_velocity);
+    fn bounce_off(&mut self, contact: &Ray, params: &BounceParams, drag: f32, contact_model: ContactModel, dt: f32) {
+        let p = contact.start;
+        let n = contact.direction;
 
-                let ratio = v_perp.length() / v_para.length().max(0.0001);
+        let loc = p - self.location;
+        let j = physics::impulse(self.velocity, self.angular_velocity, loc, n, params);
 
-                let j_perp = v_perp * Ball::RESTITUTION_M;
-                let j_para = -(Ball::MU * ratio).min(1.) * m_reduced * v_para;
+        self.angular_velocity += loc.cross(j) / self.moi;
+        self.velocity += (j / Ball::M) + self.velocity * (self.drag_model.coefficient(self.velocity, drag) * dt);
+        self.location += self.velocity * dt;
 
-                let j = j_perp + j_para;
+        self.correct_penetration(contact, contact_model);
+    }
 
-                game.ball.angular_velocity += loc.cross(j) / game.ball.moi;
-                game.ball.velocity += (j / Ball::M) + game.ball.velocity * (Ball::DRAG * dt);
-                game.ball.location += game.ball.velocity * dt;
+    // the ball is moved back out of the surface without touching its velocity
+    pub(crate) fn correct_penetration(&mut self, contact: &Ray, contact_model: ContactModel) {
+        let penetration = self.collision_radius - (self.location - contact.start).dot(contact.direction);
 
-                let penetration = game.ball.collision_radius - (game.ball.location - p).dot(n);
+        match contact_model {
+            ContactModel::Game => {
                 if penetration > 0. {
-                    game.ball.location += n * (1.001 * penetration);
+                    self.location += contact.direction * (1.001 * penetration);
+                }
+            }
+            // Only part of the penetration past the slop is corrected each step, so a ball pinched between surfaces
+            // gets eased out over a few steps instead of being launched out of them
+            ContactModel::SplitImpulse => {
+                if penetration > Ball::PENETRATION_SLOP {
+                    self.location += contact.direction * (Ball::BAUMGARTE * (penetration - Ball::PENETRATION_SLOP));
+                }
+            }
+        }
+    }
+
+    // the contact's direction is averaged over the triangles, and is zero when the ball is exactly touching, so this uses the direction to the ball instead
+    fn surface_normal(&self, contact: &Ray) -> Vec3A {
+        (self.location - contact.start).normalize_or_zero()
+    }
+
+    // The surface holds the ball up against the part of gravity that pushes it into the surface, but the rest still pulls it along,
+    // so a ball resting against a wall slides down it instead of being held in place by the penetration correction
+    fn supported_gravity(&self, contact: &Ray, gravity: Vec3A) -> Vec3A {
+        let n = self.surface_normal(contact);
+        let into_surface = gravity.dot(n);
+
+        if into_surface < 0. {
+            gravity - n * into_surface
+        } else {
+            gravity
+        }
+    }
+
+    pub(crate) fn is_rolling(&self, contact: &Ray) -> bool {
+        self.velocity.dot(self.surface_normal(contact)).abs() < Ball::ROLLING_MAX_NORMAL_SPEED
+    }
+
+    // Rolling resistance slows the ball down in proportion to how hard the surface holds it up, until it stops
+    // A settled ball is held up by the steps that collide, which both skip gravity and bounce the ball back a little,
+    // so `support` is the change in speed those two make up, and on average it matches gravity's pull into the surface
+    fn roll(&mut self, contact: &Ray, support: f32, resistance: f32) {
+        if resistance <= 0. || support <= 0. {
+            return;
+        }
+
+        let n = self.surface_normal(contact);
+        let loc = contact.start - self.location;
+        let tangent_velocity = self.velocity - n * self.velocity.dot(n);
+        let speed = tangent_velocity.length();
+
+        if speed == 0. {
+            return;
+        }
+
+        let slip = tangent_velocity - loc.cross(self.angular_velocity);
+        let slowdown = tangent_velocity * ((resistance * support).min(speed) / speed);
+
+        self.velocity -= slowdown;
+
+        if slip.length_squared() < Ball::ROLLING_MAX_SLIP * Ball::ROLLING_MAX_SLIP {
+            self.angular_velocity -= n.cross(slowdown) / loc.length();
+        }
+    }
+
+    pub(crate) fn limit_velocities(&mut self) {
+        physics::limit_velocities(&mut self.velocity, &mut self.angular_velocity);
+    }
+
+    pub fn step(game: &mut Game, dt: f32) {
+        Ball::step_with_contact(game, dt);
+    }
+
+    // the same as `step`, but also gives back what the ball was touching at the start of the step
+    pub(crate) fn step_with_contact(game: &mut Game, dt: f32) -> Option<Ray> {
+        let contact = game.collide(&game.ball.hitbox());
+        Ball::step_from_contact(game, contact, dt);
+
+        contact
+    }
+
+    // a step where what the ball is touching is already known, like from the end of the step before
+    pub(crate) fn step_from_contact(game: &mut Game, contact: Option<Ray>, dt: f32) {
+        let mut ball = game.ball;
+        ball.step_in(&game.arena(), contact, dt);
+        game.ball = ball;
+    }
+
+    pub(crate) fn step_in(&mut self, arena: &Arena, contact: Option<Ray>, dt: f32) {
+        match contact {
+            // with split impulse, a ball that's already moving away from the surface isn't bounced off of it again
+            Some(contact) if arena.contact_model == ContactModel::Game || self.velocity.dot(contact.direction) <= 0. => {
+                let rolling = self.rolling_resistance > 0. && self.is_rolling(&contact);
+
+                if arena.contact_model == ContactModel::SplitImpulse {
+                    self.velocity += self.supported_gravity(&contact, arena.gravity) * dt;
+                }
+
+                let before = self.velocity;
+
+                match arena.field.material_at(&self.hitbox()) {
+                    Some(material) => self.collide_with_material(&contact, material, &arena.field.tuning, arena.contact_model, dt),
+                    None => self.collide(&contact, &arena.field.tuning, arena.contact_model, dt),
+                }
+
+                if rolling {
+                    let n = self.surface_normal(&contact);
+                    let support = (self.velocity - before).dot(n) - arena.gravity.dot(n) * dt;
+                    self.roll(&contact, support, self.rolling_resistance);
                 }
             }
-            None => {
-                game.ball.velocity += (game.ball.velocity * Ball::DRAG + game.gravity) * dt;
-                game.ball.location += game.ball.velocity * dt;
+            contact => {
+                let params = FlightParams::from_arena(arena, self);
+                physics::fly(&mut self.location, &mut self.velocity, self.angular_velocity, dt, &params);
+
+                // the ball is already moving away from the surface, so all that's left is to stop it from sinking in
+                if let Some(contact) = contact {
+                    self.correct_penetration(&contact, arena.contact_model);
+                }
             }
         }
 
-        game.ball.angular_velocity *= (Ball::W_MAX * game.ball.angular_velocity.length_recip()).min(1.);
-        game.ball.velocity *= (Ball::V_MAX * game.ball.velocity.length_recip()).min(1.);
-        game.ball.time += dt;
+        self.angular_velocity = physics::damped(self.angular_velocity, self.angular_damping, dt);
+        self.limit_velocities();
+        self.time += dt;
+
+        #[cfg(feature = "plugins")]
+        for plugin in arena.plugins {
+            plugin.step(self, contact, dt);
+        }
     }
 
     pub fn get_ball_prediction_struct_for_time(game: &mut Game, time: &f32) -> BallPrediction {
@@ -159,23 +369,120 @@ impl Ball {
     }
 
     pub fn get_ball_prediction_struct_for_slices(game: &mut Game, num_slices: usize) -> BallPrediction {
-        let mut slices = Vec::with_capacity(num_slices);
+        Ball::get_ball_prediction_struct_with_dt_for_slices(game, Ball::SIMULATION_DT, 1, num_slices)
+    }
 
-        for _ in 0..num_slices {
-            Ball::step(game, Ball::SIMULATION_DT);
-            slices.push(game.ball);
-        }
+    // A slice every `dt` seconds instead of every 1/120th, over the same amount of time as `get_ball_prediction_struct`
+    // Shorter steps are more accurate, but take longer to simulate
+    pub fn get_ball_prediction_struct_with_dt(game: &mut Game, dt: f32) -> BallPrediction {
+        assert!(dt > 0., "The time step has to be positive");
+
+        let time = Ball::STANDARD_NUM_SLICES as f32 * Ball::SIMULATION_DT;
+        Ball::get_ball_prediction_struct_with_dt_for_slices(game, dt, 1, (time / dt).round() as usize)
+    }
+
+    // `num_slices` slices `dt` seconds apart, each simulated in `steps_per_slice` shorter steps
+    // For example, 60 Hz slices for rendering that are simulated at 240 Hz for accuracy
+    pub fn get_ball_prediction_struct_with_dt_for_slices(game: &mut Game, dt: f32, steps_per_slice: usize, num_slices: usize) -> BallPrediction {
+        Ball::predict(game, dt, steps_per_slice, num_slices, |_| false)
+    }
+
+    // A prediction with a different accuracy than the game's, like `Accuracy::FloorOnly` for a quick estimate of a ball in the middle of the field
+    pub fn get_ball_prediction_struct_with_accuracy(game: &mut Game, accuracy: Accuracy, num_slices: usize) -> BallPrediction {
+        let previous = game.accuracy;
+        game.set_accuracy(accuracy);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(game, num_slices);
+        game.accuracy = previous;
+
+        prediction
+    }
+
+    pub fn get_ball_prediction_struct_until<F: FnMut(&Ball) -> bool>(game: &mut Game, stop: F) -> BallPrediction {
+        Ball::get_ball_prediction_struct_until_for_slices(game, Ball::STANDARD_NUM_SLICES, stop)
+    }
+
+    // Stops early at the first slice that `stop` returns true for, which is the last slice in the prediction,
+    // for when only the first time something happens matters, like the ball dropping below a certain height
+    pub fn get_ball_prediction_struct_until_for_slices<F: FnMut(&Ball) -> bool>(game: &mut Game, num_slices: usize, stop: F) -> BallPrediction {
+        Ball::predict(game, Ball::SIMULATION_DT, 1, num_slices, stop)
+    }
+
+    // The same as `get_ball_prediction_struct`, but into an existing prediction, which saves allocating the slices every time
+    // once the prediction has held as many as this needs
+    pub fn get_ball_prediction_into(game: &mut Game, prediction: &mut BallPrediction) {
+        Ball::get_ball_prediction_for_slices_into(game, Ball::STANDARD_NUM_SLICES, prediction);
+    }
+
+    pub fn get_ball_prediction_for_slices_into(game: &mut Game, num_slices: usize, prediction: &mut BallPrediction) {
+        Ball::predict_into(game, Ball::SIMULATION_DT, 1, num_slices, |_| false, prediction);
+    }
+
+    fn predict<F: FnMut(&Ball) -> bool>(game: &mut Game, dt: f32, steps_per_slice: usize, num_slices: usize, stop: F) -> BallPrediction {
+        let mut prediction = BallPrediction {
+            slices: Vec::with_capacity(num_slices),
+            ..BallPrediction::default()
+        };
+
+        Ball::predict_into(game, dt, steps_per_slice, num_slices, stop, &mut prediction);
+        prediction
+    }
+
+    fn predict_into<F: FnMut(&Ball) -> bool>(game: &mut Game, dt: f32, steps_per_slice: usize, num_slices: usize, stop: F, prediction: &mut BallPrediction) {
+        let mut ball = game.ball;
+        Ball::predict_in(&game.arena(), &mut ball, dt, steps_per_slice, num_slices, stop, prediction);
+        game.ball = ball;
+    }
+
+    // the prediction itself, which leaves `ball` at the end of it
+    pub(crate) fn predict_in<F: FnMut(&Ball) -> bool>(arena: &Arena, ball: &mut Ball, dt: f32, steps_per_slice: usize, num_slices: usize, mut stop: F, prediction: &mut BallPrediction) {
+        assert!(dt > 0., "The time step has to be positive");
+        assert!(steps_per_slice > 0, "Every slice needs at least one step");
 
-        BallPrediction {
-            num_slices: slices.len(),
-            slices,
+        let step_dt = dt / steps_per_slice as f32;
+        let slices = &mut prediction.slices;
+        let bounces = &mut prediction.bounces;
+        let mut escape = None;
+
+        slices.clear();
+        slices.reserve(num_slices);
+        bounces.clear();
+
+        // what the ball touches at the end of each step is both what the next step starts from and what the slice's flags are from
+        let mut contact = arena.collide(&ball.hitbox());
+
+        for slice in 0..num_slices {
+            let start = *ball;
+
+            for _ in 0..steps_per_slice {
+                let before = *ball;
+                ball.step_in(arena, contact, step_dt);
+                bounces.extend(BounceEvent::check(&before, ball, contact));
+                contact = arena.collide(&ball.hitbox());
+            }
+
+            escape = EscapeEvent::check(arena, slice, &start, ball);
+            if escape.is_some() {
+                break;
+            }
+
+            ball.flags = flags::slice_flags(arena, ball, contact);
+            slices.push(*ball);
+
+            if stop(ball) {
+                break;
+            }
         }
+
+        prediction.num_slices = prediction.slices.len();
+        prediction.escape = escape;
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "soccar"))]
 mod test {
     use crate::load_soccar;
+    use crate::simulation::game::Integrator;
 
     use super::*;
 
@@ -214,4 +521,228 @@ mod test {
         assert_eq!(prediction.num_slices, predicted_slices);
         assert_eq!(prediction.slices.len(), predicted_slices);
     }
+
+    #[test]
+    fn check_custom_dt() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(500., 1000., 0.), Vec3A::ZERO);
+        let start = game.ball;
+
+        let standard = Ball::get_ball_prediction_struct(&mut game);
+
+        game.ball = start;
+        let half_rate = Ball::get_ball_prediction_struct_with_dt(&mut game, 1. / 60.);
+        assert_eq!(half_rate.num_slices, Ball::STANDARD_NUM_SLICES / 2);
+
+        // 60 Hz slices made from 240 Hz steps land close to the 120 Hz prediction at the same times, while the ball is in the air
+        game.ball = start;
+        let substepped = Ball::get_ball_prediction_struct_with_dt_for_slices(&mut game, 1. / 60., 4, 30);
+        assert_eq!(substepped.num_slices, 30);
+
+        for (i, slice) in substepped.slices.iter().enumerate() {
+            let expected = standard.slices[i * 2 + 1];
+            assert!((slice.time - expected.time).abs() < 1e-4);
+            assert!(slice.location.distance(expected.location) < 1., "{} vs {}", slice.location, expected.location);
+        }
+
+        // one step per slice at the standard rate is the standard prediction
+        game.ball = start;
+        let same = Ball::get_ball_prediction_struct_with_dt_for_slices(&mut game, Ball::SIMULATION_DT, 1, Ball::STANDARD_NUM_SLICES);
+        assert!(same.slices.iter().zip(&standard.slices).all(|(a, b)| a.location == b.location && a.velocity == b.velocity));
+    }
+
+    #[test]
+    fn rk4_follows_the_exact_arc() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 200.), Vec3A::new(300., 0., 1200.), Vec3A::ZERO);
+        let start = game.ball;
+
+        // with linear drag, the ball's path through the air has a closed form
+        let time = 2.;
+        let drag = game.field.tuning.drag();
+        let v_inf = -game.gravity / drag;
+        let exact = start.location + v_inf * time + (start.velocity - v_inf) * (((drag * time).exp() - 1.) / drag);
+
+        let mut error = |integrator| {
+            game.integrator = integrator;
+            game.ball = start;
+            let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &time);
+            prediction.slices.last().unwrap().location.distance(exact)
+        };
+
+        let euler = error(Integrator::Euler);
+        let rk4 = error(Integrator::Rk4);
+
+        assert!(euler > 1., "{euler}");
+        assert!(rk4 < 0.1, "{rk4}");
+    }
+
+    #[test]
+    fn spin_changes_the_bounce() {
+        let mut game = load_soccar();
+
+        let mut bounce = |spin: f32| {
+            game.ball.update(0., Vec3A::new(0., 0., game.ball.collision_radius - 0.5), Vec3A::new(500., 0., -1000.), Vec3A::new(0., spin, 0.));
+            Ball::step(&mut game, Ball::SIMULATION_DT);
+            game.ball
+        };
+
+        let topspin = bounce(6.);
+        let none = bounce(0.);
+        let backspin = bounce(-6.);
+
+        // the friction at the contact point pushes a ball with topspin forward, and a ball with backspin back
+        assert!(topspin.velocity.x > none.velocity.x);
+        assert!(backspin.velocity.x < none.velocity.x);
+
+        // and the spin is pulled towards rolling along with the ball
+        assert!(topspin.angular_velocity.y < 6.);
+        assert!(none.angular_velocity.y > 0.);
+        assert!(backspin.angular_velocity.y > -6.);
+
+        // they all bounce up the same
+        assert!(topspin.velocity.z > 0.);
+        assert_eq!(topspin.velocity.z, backspin.velocity.z);
+    }
+
+    #[test]
+    fn ball_size_mutator() {
+        let mut game = load_soccar();
+        let normal_moi = game.ball.moi;
+
+        game.ball.set_radius(Ball::SOCCAR_RADIUS * 2.);
+        assert_eq!(game.ball.collision_radius, Ball::SOCCAR_COLLISION_RADIUS * 2.);
+        assert_eq!(game.ball.moi, normal_moi * 4.);
+
+        // a bigger ball comes to rest higher off of the floor
+        game.ball.update(0., glam::vec3a(0., 0., 500.), Vec3A::ZERO, Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let resting = prediction.slices.last().unwrap();
+
+        assert!((resting.location.z - game.ball.collision_radius).abs() < 2., "{}", resting.location.z);
+
+        // a ball that doesn't have a size yet can still be given one
+        let mut ball = Ball::default();
+        ball.set_radius(Ball::SOCCAR_RADIUS);
+        assert_eq!(ball.collision_radius, Ball::SOCCAR_COLLISION_RADIUS);
+        assert_eq!(ball.moi, normal_moi);
+        assert!(resting.velocity.length() < 5.);
+    }
+
+    #[test]
+    fn bounce_events() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(300., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &10.);
+        let first = prediction.bounces[0];
+
+        // the first bounce is off of the floor, on the way down, and sends the ball back up
+        assert!(first.normal.distance(Vec3A::Z) < 1e-3, "{}", first.normal);
+        assert!((first.location.z - game.ball.collision_radius).abs() < 20., "{}", first.location);
+        assert!(first.velocity_before.z < 0. && first.velocity_after.z > 0.);
+
+        // a slice just before and just after
+        let slice = prediction.slices.iter().position(|slice| slice.time > first.time).unwrap();
+        assert_eq!(prediction.slices[slice].velocity, first.velocity_after);
+        assert_eq!(prediction.slices[slice - 1].velocity, first.velocity_before);
+
+        // each bounce is lower than the last, and once the ball has settled onto the floor it's only rolling
+        assert!(prediction.bounces.len() > 2);
+        assert!(prediction.bounces.windows(2).all(|pair| pair[0].time < pair[1].time && pair[0].velocity_after.z > pair[1].velocity_after.z));
+        assert!(prediction.bounces.last().unwrap().time < prediction.slices.last().unwrap().time - 1.);
+    }
+
+    #[test]
+    fn bounce_events_with_substeps() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+
+        // the same first bounce, whether or not the steps are grouped into slices
+        let stepped = Ball::get_ball_prediction_struct_with_dt(&mut game.clone(), Ball::SIMULATION_DT / 2.);
+        let grouped = Ball::get_ball_prediction_struct_with_dt_for_slices(&mut game, Ball::SIMULATION_DT, 2, Ball::STANDARD_NUM_SLICES);
+
+        assert_eq!(stepped.bounces, grouped.bounces);
+        assert!(!grouped.bounces.is_empty());
+    }
+
+    #[test]
+    fn stopping_early() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(0., -500., 0.), Vec3A::ZERO);
+
+        let full = Ball::get_ball_prediction_struct(&mut game.clone());
+        let mut calls = 0;
+        let early = Ball::get_ball_prediction_struct_until(&mut game.clone(), |slice| {
+            calls += 1;
+            slice.location.z < 200. && slice.location.y < 0.
+        });
+
+        // it stops at the first slice that matches, which it keeps, and doesn't look at any after it
+        let first = full.slices.iter().position(|slice| slice.location.z < 200. && slice.location.y < 0.).unwrap();
+        assert_eq!(early.num_slices, first + 1);
+        assert_eq!(calls, first + 1);
+        assert_eq!(early.slices.last().unwrap().location, full.slices[first].location);
+
+        // and goes to the end when nothing matches
+        let never = Ball::get_ball_prediction_struct_until_for_slices(&mut game, 100, |_| false);
+        assert_eq!(never.num_slices, 100);
+    }
+
+    #[test]
+    fn reusing_a_prediction() {
+        let mut game = load_soccar();
+        game.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(1000., 500., 0.), Vec3A::ZERO);
+
+        let expected = Ball::get_ball_prediction_struct(&mut game.clone());
+
+        // a longer prediction first, so there's already room for the slices
+        let mut prediction = Ball::get_ball_prediction_struct_for_slices(&mut game.clone(), 1000);
+        let buffer = prediction.slices.as_ptr();
+
+        Ball::get_ball_prediction_into(&mut game, &mut prediction);
+
+        assert_eq!(prediction.slices.as_ptr(), buffer);
+        assert_eq!(prediction.num_slices, Ball::STANDARD_NUM_SLICES);
+        assert_eq!(prediction.checksum(), expected.checksum());
+        assert_eq!(prediction.bounces, expected.bounces);
+    }
+
+    #[test]
+    fn resting_against_a_wall() {
+        // touching the side wall without moving, it has to slide down it rather than hang there
+        let mut game = load_soccar();
+
+        for contact_model in [ContactModel::Game, ContactModel::SplitImpulse] {
+            game.contact_model = contact_model;
+            game.ball.update(0., Vec3A::new(4096. - game.ball.collision_radius - 1., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+
+            let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+            // about as fast as it would fall through the air
+            let half_second = prediction.slices[59].location.z;
+            assert!((half_second - (1000. - 650. * 0.5 * 0.5 / 2.)).abs() < 10., "{contact_model:?}: {half_second}");
+
+            // and ends up on the floor
+            assert!(prediction.slices.last().unwrap().location.z < game.ball.collision_radius + 1.);
+        }
+    }
+
+    #[test]
+    fn split_impulse_is_opt_in() {
+        let mut game = load_soccar();
+        assert_eq!(game.contact_model, ContactModel::Game);
+
+        // bouncing around the corner, where the two models push the ball out of the walls differently
+        game.ball.update(0., Vec3A::new(3000., 4000., 150.), Vec3A::new(2800., 2800., -200.), Vec3A::ZERO);
+        let start = game.ball;
+
+        let default = Ball::get_ball_prediction_struct(&mut game);
+
+        game.ball = start;
+        game.contact_model = ContactModel::SplitImpulse;
+        let split_impulse = Ball::get_ball_prediction_struct(&mut game);
+
+        assert_ne!(default.checksum(), split_impulse.checksum());
+    }
 }
@@ -0,0 +1,244 @@
+use alloc::vec::Vec;
+use core::f32::consts::FRAC_PI_2;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::Ball;
+use super::game::{ContactModel, Game};
+use super::geometry::Ray;
+use super::tuning::Tuning;
+
+// Evenly spaced samples of one of the table's inputs, including both ends
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Samples {
+    pub min: f32,
+    pub max: f32,
+    pub count: usize,
+}
+
+impl Samples {
+    fn step(&self) -> f32 {
+        (self.max - self.min) / (self.count - 1) as f32
+    }
+
+    fn value(&self, i: usize) -> f32 {
+        self.min + i as f32 * self.step()
+    }
+
+    // the sample just below `x` and how far it is towards the next one, clamped to the ends
+    fn locate(&self, x: f32) -> (usize, f32) {
+        let local = ((x - self.min) / self.step()).clamp(0., (self.count - 1) as f32);
+        let i = (local as usize).min(self.count - 2);
+
+        (i, local - i as f32)
+    }
+}
+
+// A bounce off of a flat surface, in the plane of the incoming velocity and the surface's normal
+//
+// The tangent is the direction the ball was moving along the surface, and the spin is around the normal crossed with the tangent,
+// so positive spin is topspin (the way a ball rolling along the tangent spins)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounce {
+    pub tangential_speed: f32,
+    pub normal_speed: f32,
+    pub spin: f32,
+}
+
+impl Bounce {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            tangential_speed: self.tangential_speed + (other.tangential_speed - self.tangential_speed) * t,
+            normal_speed: self.normal_speed + (other.normal_speed - self.normal_speed) * t,
+            spin: self.spin + (other.spin - self.spin) * t,
+        }
+    }
+}
+
+// Precomputed bounces over a grid of incoming speed, angle and spin, for planners that need a lot of rough bounces quickly
+// The angle is between the incoming velocity and the surface, from 0 (skimming along it) to pi/2 (straight into it)
+#[derive(Clone, Debug)]
+pub struct BounceTable {
+    pub speeds: Samples,
+    pub angles: Samples,
+    pub spins: Samples,
+    bounces: Vec<Bounce>,
+}
+
+impl BounceTable {
+    pub const STANDARD_SPEEDS: Samples = Samples {
+        min: 0.,
+        max: Ball::V_MAX,
+        count: 41,
+    };
+    pub const STANDARD_ANGLES: Samples = Samples {
+        min: 0.,
+        max: FRAC_PI_2,
+        count: 19,
+    };
+    pub const STANDARD_SPINS: Samples = Samples {
+        min: -Ball::W_MAX,
+        max: Ball::W_MAX,
+        count: 25,
+    };
+
+    // a table for the game's ball, covering every speed and spin the ball can have
+    pub fn standard(game: &Game) -> Self {
+        Self::new(&game.ball, &game.field.tuning, BounceTable::STANDARD_SPEEDS, BounceTable::STANDARD_ANGLES, BounceTable::STANDARD_SPINS)
+    }
+
+    pub fn new(ball: &Ball, tuning: &Tuning, speeds: Samples, angles: Samples, spins: Samples) -> Self {
+        assert!(speeds.count >= 2 && angles.count >= 2 && spins.count >= 2, "Each input needs at least 2 samples");
+
+        let mut bounces = Vec::with_capacity(speeds.count * angles.count * spins.count);
+
+        for i in 0..speeds.count {
+            for j in 0..angles.count {
+                for k in 0..spins.count {
+                    bounces.push(simulate(ball, tuning, speeds.value(i), angles.value(j), spins.value(k)));
+                }
+            }
+        }
+
+        Self {
+            speeds,
+            angles,
+            spins,
+            bounces,
+        }
+    }
+
+    fn get(&self, i: usize, j: usize, k: usize) -> Bounce {
+        self.bounces[(i * self.angles.count + j) * self.spins.count + k]
+    }
+
+    // trilinear interpolation between the nearest samples, inputs outside of the table are clamped to its edges
+    pub fn lookup(&self, speed: f32, angle: f32, spin: f32) -> Bounce {
+        let (i, u) = self.speeds.locate(speed);
+        let (j, v) = self.angles.locate(angle);
+        let (k, w) = self.spins.locate(spin);
+
+        let along_spin = |i, j| self.get(i, j, k).lerp(self.get(i, j, k + 1), w);
+        let along_angle = |i| along_spin(i, j).lerp(along_spin(i, j + 1), v);
+
+        along_angle(i).lerp(along_angle(i + 1), u)
+    }
+
+    // The velocity and angular velocity after bouncing off of a surface with this normal
+    // Spin around the tangent and the normal isn't part of the table, so it's passed through unchanged
+    pub fn bounce(&self, velocity: Vec3A, angular_velocity: Vec3A, normal: Vec3A) -> (Vec3A, Vec3A) {
+        let normal_speed = velocity.dot(normal);
+
+        // it's already moving away from the surface
+        if normal_speed >= 0. {
+            return (velocity, angular_velocity);
+        }
+
+        let along_surface = velocity - normal * normal_speed;
+        let tangent = if along_surface.length_squared() > 1e-6 {
+            along_surface.normalize()
+        } else {
+            // straight in, so use the spin to pick a direction, which makes any spin topspin
+            let spin_along_surface = angular_velocity - normal * angular_velocity.dot(normal);
+
+            if spin_along_surface.length_squared() > 1e-6 {
+                spin_along_surface.normalize().cross(normal)
+            } else {
+                normal.any_orthonormal_vector()
+            }
+        };
+
+        let spin_axis = normal.cross(tangent);
+        let spin = angular_velocity.dot(spin_axis);
+
+        let bounce = self.lookup(velocity.length(), (-normal_speed).atan2(along_surface.length()), spin);
+
+        (tangent * bounce.tangential_speed + normal * bounce.normal_speed, angular_velocity + spin_axis * (bounce.spin - spin))
+    }
+}
+
+// the same single step the simulation takes when the ball hits a flat floor
+fn simulate(ball: &Ball, tuning: &Tuning, speed: f32, angle: f32, spin: f32) -> Bounce {
+    let mut ball = Ball {
+        location: Vec3A::Z * ball.collision_radius,
+        velocity: Vec3A::new(speed * angle.cos(), 0., -speed * angle.sin()),
+        angular_velocity: Vec3A::Y * spin,
+        ..*ball
+    };
+
+    let contact = Ray {
+        start: Vec3A::ZERO,
+        direction: Vec3A::Z,
+    };
+
+    ball.collide(&contact, tuning, ContactModel::default(), Ball::SIMULATION_DT);
+    ball.limit_velocities();
+
+    Bounce {
+        tangential_speed: ball.velocity.x,
+        normal_speed: ball.velocity.z,
+        spin: ball.angular_velocity.y,
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn samples_match_simulation() {
+        let game = load_soccar();
+        let table = BounceTable::standard(&game);
+
+        let (speeds, angles, spins) = (table.speeds, table.angles, table.spins);
+        for (i, j, k) in [(0, 0, 0), (10, 5, 12), (40, 18, 24), (25, 9, 3)] {
+            assert_eq!(table.lookup(speeds.value(i), angles.value(j), spins.value(k)), simulate(&game.ball, &game.field.tuning, speeds.value(i), angles.value(j), spins.value(k)));
+        }
+    }
+
+    #[test]
+    fn interpolation_is_close() {
+        let game = load_soccar();
+        let table = BounceTable::standard(&game);
+
+        for (speed, angle, spin) in [(1234., 0.3, 1.7), (2500., 1.2, -4.2), (3900., 0.05, 0.), (600., 0.9, 5.5)] {
+            let exact = simulate(&game.ball, &game.field.tuning, speed, angle, spin);
+            let approximate = table.lookup(speed, angle, spin);
+
+            assert!((exact.tangential_speed - approximate.tangential_speed).abs() < 25., "{exact:?} {approximate:?}");
+            assert!((exact.normal_speed - approximate.normal_speed).abs() < 25., "{exact:?} {approximate:?}");
+            assert!((exact.spin - approximate.spin).abs() < 0.5, "{exact:?} {approximate:?}");
+        }
+    }
+
+    #[test]
+    fn bounce_off_of_any_surface() {
+        let mut game = load_soccar();
+        let table = BounceTable::standard(&game);
+
+        // a ball hitting the side wall, with topspin
+        let normal = vec3a(-1., 0., 0.);
+        let velocity = vec3a(1500., 800., 300.);
+        let angular_velocity = normal.cross((velocity - normal * velocity.dot(normal)).normalize()) * 2.;
+
+        let (bounced_velocity, bounced_spin) = table.bounce(velocity, angular_velocity, normal);
+
+        // against a real wall
+        game.ball.update(0., vec3a(4096. - game.ball.collision_radius, 0., 1000.), velocity, angular_velocity);
+        game.gravity = Vec3A::ZERO;
+        Ball::step(&mut game, Ball::SIMULATION_DT);
+
+        assert!(game.ball.velocity.x < 0.);
+        assert!(bounced_velocity.distance(game.ball.velocity) < 25., "{bounced_velocity} {}", game.ball.velocity);
+        assert!(bounced_spin.distance(game.ball.angular_velocity) < 0.25, "{bounced_spin} {}", game.ball.angular_velocity);
+
+        // and nothing changes when it's moving away from the surface
+        assert_eq!(table.bounce(-velocity, angular_velocity, normal), (-velocity, angular_velocity));
+    }
+}
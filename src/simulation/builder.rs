@@ -0,0 +1,250 @@
+use glam::Vec3A;
+
+use super::game::{Accuracy, ContactModel, Game, Integrator};
+use super::goal::GoalCriteria;
+use super::mutators::{DragModel, Mutators};
+#[cfg(feature = "dropshot")]
+use crate::load_dropshot;
+#[cfg(feature = "hoops")]
+use crate::load_hoops;
+#[cfg(feature = "throwback")]
+use crate::load_soccar_throwback;
+#[cfg(feature = "soccar")]
+use crate::{load_snowday, load_soccar};
+
+// Sets up a game in one go, instead of changing its fields one at a time
+// The settings can be given in any order, `build` applies them in the order they need to be in,
+// like the mutators before the ball's size, and the ball's size before where it starts
+//
+//   let mut game = GameBuilder::soccar().gravity(vec3a(0., 0., -325.)).ball_location(vec3a(0., 0., 1000.)).build();
+#[derive(Clone)]
+pub struct GameBuilder {
+    game: Game,
+    mutators: Option<Mutators>,
+    gravity: Option<Vec3A>,
+    ball_radius: Option<f32>,
+    // None to scale it along with the radius
+    ball_collision_radius: Option<f32>,
+    drag_model: Option<DragModel>,
+    accuracy: Option<Accuracy>,
+    integrator: Option<Integrator>,
+    contact_model: Option<ContactModel>,
+    goal_criteria: Option<GoalCriteria>,
+    time: f32,
+    location: Option<Vec3A>,
+    velocity: Vec3A,
+    angular_velocity: Vec3A,
+}
+
+impl From<Game> for GameBuilder {
+    // starts from a field that's already loaded, where anything that isn't set is left the way it is, except the ball starts at rest
+    fn from(game: Game) -> Self {
+        Self {
+            game,
+            mutators: None,
+            gravity: None,
+            ball_radius: None,
+            ball_collision_radius: None,
+            drag_model: None,
+            accuracy: None,
+            integrator: None,
+            contact_model: None,
+            goal_criteria: None,
+            time: 0.,
+            location: None,
+            velocity: Vec3A::ZERO,
+            angular_velocity: Vec3A::ZERO,
+        }
+    }
+}
+
+impl GameBuilder {
+    #[cfg(feature = "soccar")]
+    pub fn soccar() -> Self {
+        Self::from(load_soccar())
+    }
+
+    #[cfg(feature = "hoops")]
+    pub fn hoops() -> Self {
+        Self::from(load_hoops())
+    }
+
+    #[cfg(feature = "dropshot")]
+    pub fn dropshot() -> Self {
+        Self::from(load_dropshot())
+    }
+
+    #[cfg(feature = "throwback")]
+    pub fn throwback() -> Self {
+        Self::from(load_soccar_throwback())
+    }
+
+    #[cfg(feature = "soccar")]
+    pub fn snowday() -> Self {
+        Self::from(load_snowday())
+    }
+
+    // every mutator at once, which `gravity`, `ball_radius` and `drag_model` override
+    pub fn mutators(mut self, mutators: Mutators) -> Self {
+        self.mutators = Some(mutators);
+        self
+    }
+
+    pub fn gravity(mut self, gravity: Vec3A) -> Self {
+        self.gravity = Some(gravity);
+        self
+    }
+
+    // the collision radius is scaled along with it, like `Ball::set_radius`
+    pub fn ball_radius(mut self, radius: f32) -> Self {
+        self.ball_radius = Some(radius);
+        self.ball_collision_radius = None;
+        self
+    }
+
+    pub fn ball_radii(mut self, radius: f32, collision_radius: f32) -> Self {
+        self.ball_radius = Some(radius);
+        self.ball_collision_radius = Some(collision_radius);
+        self
+    }
+
+    pub fn drag_model(mut self, drag_model: DragModel) -> Self {
+        self.drag_model = Some(drag_model);
+        self
+    }
+
+    pub fn accuracy(mut self, accuracy: Accuracy) -> Self {
+        self.accuracy = Some(accuracy);
+        self
+    }
+
+    pub fn integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = Some(integrator);
+        self
+    }
+
+    pub fn contact_model(mut self, contact_model: ContactModel) -> Self {
+        self.contact_model = Some(contact_model);
+        self
+    }
+
+    pub fn goal_criteria(mut self, goal_criteria: GoalCriteria) -> Self {
+        self.goal_criteria = Some(goal_criteria);
+        self
+    }
+
+    pub fn time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    // without this, the ball rests on the floor in the middle of the field, like at kickoff
+    pub fn ball_location(mut self, location: Vec3A) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    pub fn ball_velocity(mut self, velocity: Vec3A) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn ball_angular_velocity(mut self, angular_velocity: Vec3A) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn build(self) -> Game {
+        let mut game = self.game;
+
+        if let Some(mutators) = self.mutators {
+            mutators.apply(&mut game);
+        }
+
+        if let Some(gravity) = self.gravity {
+            game.gravity = gravity;
+        }
+
+        match (self.ball_radius, self.ball_collision_radius) {
+            (Some(radius), Some(collision_radius)) => game.ball.set_radii(radius, collision_radius),
+            (Some(radius), None) => game.ball.set_radius(radius),
+            _ => {}
+        }
+
+        if let Some(drag_model) = self.drag_model {
+            game.ball.drag_model = drag_model;
+        }
+
+        if let Some(accuracy) = self.accuracy {
+            game.set_accuracy(accuracy);
+        }
+
+        if let Some(integrator) = self.integrator {
+            game.integrator = integrator;
+        }
+
+        if let Some(contact_model) = self.contact_model {
+            game.contact_model = contact_model;
+        }
+
+        if let Some(goal_criteria) = self.goal_criteria {
+            game.goal_criteria = goal_criteria;
+        }
+
+        // a resized ball is put back on the floor
+        game.ball.location = Vec3A::ZERO;
+        game.ball.initialize();
+        let location = self.location.unwrap_or(game.ball.location);
+        game.ball.update(self.time, location, self.velocity, self.angular_velocity);
+
+        game
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops", feature = "dropshot"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::ball::Ball;
+    use crate::simulation::game::GameMode;
+
+    #[test]
+    fn same_as_setting_the_fields() {
+        let mut built = GameBuilder::soccar().ball_velocity(vec3a(500., 0., 0.)).gravity(vec3a(0., 0., -325.)).ball_location(vec3a(0., 0., 1000.)).build();
+
+        let mut game = load_soccar();
+        game.gravity = vec3a(0., 0., -325.);
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(500., 0., 0.), Vec3A::ZERO);
+
+        assert_eq!(built.mode, GameMode::Soccar);
+        let built = Ball::get_ball_prediction_struct(&mut built);
+        let expected = Ball::get_ball_prediction_struct(&mut game);
+        assert!(built.slices.iter().zip(&expected.slices).all(|(a, b)| a.location == b.location));
+    }
+
+    #[test]
+    fn order_doesnt_matter() {
+        let location = vec3a(100., 0., 500.);
+        let a = GameBuilder::dropshot().ball_location(location).mutators(Mutators::default()).ball_radius(150.).build();
+        let b = GameBuilder::dropshot().ball_radius(150.).ball_location(location).mutators(Mutators::default()).build();
+
+        // the radius that was set wins over the mutators' either way, and the collision radius is scaled from the mutators' one
+        let soccar = Mutators::default();
+        for game in [&a, &b] {
+            assert_eq!(game.ball.radius, 150.);
+            assert_eq!(game.ball.collision_radius, 150. * (soccar.ball_collision_radius / soccar.ball_radius));
+            assert_eq!(game.ball.location, location);
+            assert_eq!(game.gravity, Mutators::default().gravity);
+        }
+    }
+
+    #[test]
+    fn resting_ball() {
+        let game = GameBuilder::hoops().ball_radii(200., 210.).build();
+
+        assert_eq!(game.ball.location, vec3a(0., 0., 1.1 * 210.));
+        assert_eq!(game.ball.moi, 0.4 * Ball::M * 200. * 200.);
+        assert_eq!(game.ball.velocity, Vec3A::ZERO);
+    }
+}
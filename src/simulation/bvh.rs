@@ -1,7 +1,25 @@
+#[cfg(feature = "std")]
+use alloc::format;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3A;
+#[cfg(feature = "std")]
+use std::io::{self, Cursor, ErrorKind, Read};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
 use super::geometry::{Aabb, Tri};
-use super::geometry::{Ray, Sphere};
+use super::geometry::{Contact, Obb, Ray, Sphere};
+use super::mesh::{decimate, holes, open_edges, TriangleId};
 use super::morton::Morton;
-use std::boxed::Box;
+#[cfg(feature = "brute-force-check")]
+use super::reference;
+use crate::linear_algebra::math;
+use alloc::boxed::Box;
 
 #[derive(Clone)]
 pub struct BvhNode {
@@ -10,6 +28,7 @@ pub struct BvhNode {
     pub right: Option<Box<BvhNode>>,
     pub left: Option<Box<BvhNode>>,
     pub primitive: Option<Tri>,
+    pub id: Option<TriangleId>,
     pub morton: Option<u64>,
 }
 
@@ -21,6 +40,7 @@ impl Default for BvhNode {
             right: None,
             left: None,
             primitive: None,
+            id: None,
             morton: None,
         }
     }
@@ -34,22 +54,32 @@ impl BvhNode {
             right: Some(right),
             left: Some(left),
             primitive: None,
+            id: None,
             morton: None,
         })
     }
 
-    pub fn leaf(primitive: Tri, box_: Aabb, morton_code: u64) -> Box<Self> {
-        Box::new(Self {
+    pub fn leaf(primitive: Tri, id: TriangleId, box_: Aabb, morton_code: u64) -> Box<Self> {
+        Box::new(Self::unboxed_leaf(primitive, id, box_, morton_code))
+    }
+
+    // for the build's sorted list of leaves, which only gets boxed when the leaves are copied into the tree
+    pub(crate) fn unboxed_leaf(primitive: Tri, id: TriangleId, box_: Aabb, morton_code: u64) -> Self {
+        Self {
             is_terminal: true,
             box_,
             right: None,
             left: None,
             primitive: Some(primitive),
+            id: Some(id),
             morton: Some(morton_code),
-        })
+        }
     }
 }
 
+// how much bigger the boxes are for raycasts, which is about the error of a triangle test at the size of the fields
+const RAYCAST_PADDING: f32 = 0.01;
+
 // BVH stands for "Bounding Volume Hierarchy"
 #[derive(Clone)]
 pub struct Bvh {
@@ -58,7 +88,7 @@ pub struct Bvh {
     pub root: Box<BvhNode>,
 }
 
-fn global_aabb(boxes: &[Aabb]) -> Aabb {
+pub(crate) fn global_aabb(boxes: &[Aabb]) -> Aabb {
     let mut global_box = boxes[0];
 
     for b in boxes {
@@ -78,27 +108,102 @@ impl Default for Bvh {
     }
 }
 
+// The lists that a build sorts the triangles with, which are kept for the next build instead of being allocated again
+#[derive(Default)]
+pub(crate) struct BvhScratch {
+    pub boxes: Vec<Aabb>,
+    pub order: Vec<(u64, usize)>,
+    pub leaves: Vec<BvhNode>,
+}
+
+// Every set of lists that isn't being used by a build right now
+// Builds running at the same time (like the fields from `load_all`) each take their own, so there are only ever as many as were needed at once
+// Without std there's nothing to lock them with, so every build makes its own
+pub(crate) struct ScratchPool {
+    #[cfg(feature = "std")]
+    pool: Mutex<Vec<BvhScratch>>,
+}
+
+// shared by every build
+pub(crate) static SCRATCH: ScratchPool = ScratchPool::new();
+
+impl ScratchPool {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            pool: Mutex::new(Vec::new()),
+        }
+    }
+
+    // lends `f` a set of lists that's empty, but might already have the room for the build
+    pub fn with<T>(&self, f: impl FnOnce(&mut BvhScratch) -> T) -> T {
+        #[cfg(feature = "std")]
+        let mut scratch = self.pool.lock().ok().and_then(|mut pool| pool.pop()).unwrap_or_default();
+        #[cfg(not(feature = "std"))]
+        let mut scratch = BvhScratch::default();
+
+        let result = f(&mut scratch);
+
+        #[cfg(feature = "std")]
+        {
+            scratch.boxes.clear();
+            scratch.order.clear();
+            scratch.leaves.clear();
+
+            if let Ok(mut pool) = self.pool.lock() {
+                pool.push(scratch);
+            }
+        }
+
+        result
+    }
+}
+
 impl Bvh {
+    // every triangle's id is just its index in `primitives`
     pub fn from(primitives: &[Tri]) -> Self {
-        let num_leaves = primitives.len();
+        let ids: Vec<TriangleId> = (0..primitives.len() as u32)
+            .map(|index| TriangleId {
+                mesh: 0,
+                index,
+            })
+            .collect();
 
-        let mut boxes: Vec<Aabb> = Vec::with_capacity(num_leaves);
-        for primitive in primitives {
-            boxes.push(primitive.into());
-        }
+        Bvh::from_with_ids(primitives, &ids)
+    }
+
+    pub fn from_with_ids(primitives: &[Tri], ids: &[TriangleId]) -> Self {
+        // with the rayon feature, the build is spread over all of the cores, which gives the same tree
+        #[cfg(feature = "rayon")]
+        return Bvh::par_from_with_ids(primitives, ids);
 
-        let global_box = global_aabb(&boxes);
+        #[cfg(not(feature = "rayon"))]
+        Bvh::serial_from_with_ids(primitives, ids)
+    }
 
+    #[cfg_attr(feature = "rayon", allow(dead_code))]
+    pub(crate) fn serial_from_with_ids(primitives: &[Tri], ids: &[TriangleId]) -> Self {
+        SCRATCH.with(|scratch| Bvh::serial_from_with_ids_in(primitives, ids, scratch))
+    }
+
+    #[cfg_attr(feature = "rayon", allow(dead_code))]
+    fn serial_from_with_ids_in(primitives: &[Tri], ids: &[TriangleId], scratch: &mut BvhScratch) -> Self {
+        assert_eq!(primitives.len(), ids.len(), "Every triangle needs an id");
+
+        let num_leaves = primitives.len();
+        let BvhScratch { boxes, order, leaves } = scratch;
+
+        boxes.extend(primitives.iter().map(Aabb::from));
+        let global_box = global_aabb(boxes);
         let morton = Morton::from(&global_box);
-        let mut sorted_leaves: Vec<Box<BvhNode>> = Vec::with_capacity(num_leaves);
 
-        for (i, box_) in boxes.iter().enumerate() {
-            sorted_leaves.push(BvhNode::leaf(primitives[i], *box_, morton.get_code(box_)));
-        }
+        // ties between morton codes go to the earlier triangle, so the tree is the same however the sort is done
+        order.extend(boxes.iter().enumerate().map(|(i, box_)| (morton.get_code(box_), i)));
+        order.sort_unstable();
 
-        sorted_leaves.sort_unstable_by_key(|leaf| leaf.morton);
+        leaves.extend(order.iter().map(|&(code, i)| BvhNode::unboxed_leaf(primitives[i], ids[i], boxes[i], code)));
 
-        let root = Bvh::generate_hierarchy(&sorted_leaves, 0, num_leaves - 1);
+        let root = Bvh::generate_hierarchy(leaves, 0, num_leaves - 1);
 
         Self {
             global_box,
@@ -107,10 +212,10 @@ impl Bvh {
         }
     }
 
-    fn generate_hierarchy(sorted_leaves: &[Box<BvhNode>], first: usize, last: usize) -> Box<BvhNode> {
+    pub(crate) fn generate_hierarchy(sorted_leaves: &[BvhNode], first: usize, last: usize) -> Box<BvhNode> {
         // If we're dealing with a single object, return the leaf node
         if first == last {
-            return sorted_leaves[first].clone();
+            return Box::new(sorted_leaves[first].clone());
         }
 
         // Determine where to split the range
@@ -126,9 +231,25 @@ impl Bvh {
     }
 
     pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
-        let query_box: Aabb = query_object.into();
+        let mut hits = Vec::with_capacity(16);
+        self.for_each_intersecting_leaf(query_object, |_, tri| hits.push(tri));
 
+        #[cfg(feature = "brute-force-check")]
+        reference::check_intersect(self, query_object, &hits);
+
+        hits
+    }
+
+    // the ids of the triangles that the sphere is touching
+    pub fn intersect_ids(&self, query_object: &Sphere) -> Vec<TriangleId> {
         let mut hits = Vec::with_capacity(16);
+        self.for_each_intersecting_leaf(query_object, |leaf, _| hits.extend(leaf.id));
+
+        hits
+    }
+
+    fn for_each_intersecting_leaf<'a>(&'a self, query_object: &Sphere, mut f: impl FnMut(&'a BvhNode, Tri)) {
+        let query_box: Aabb = query_object.into();
 
         // Allocate traversal stack from thread-local memory,
         // and push NULL to indicate that there are no postponed nodes.
@@ -149,7 +270,7 @@ impl Bvh {
                     match left.primitive {
                         Some(left_tri) => {
                             if left_tri.intersect_sphere(query_object) {
-                                hits.push(left_tri);
+                                f(left, left_tri);
                             }
                         }
                         None => {
@@ -166,7 +287,7 @@ impl Bvh {
                     match right.primitive {
                         Some(right_tri) => {
                             if right_tri.intersect_sphere(query_object) {
-                                hits.push(right_tri);
+                                f(right, right_tri);
                             }
                         }
                         None => {
@@ -189,37 +310,508 @@ impl Bvh {
                 }
             }
         }
+    }
+
+    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
+        contact_from_tris(s, self.intersect(s))
+    }
+
+    // the deepest contact between the box and any triangle in the tree
+    pub fn collide_obb(&self, obb: &Obb) -> Option<Contact> {
+        let contact = self.intersect_aabb(&obb.into()).iter().filter_map(|tri| obb.intersect_tri(tri)).max_by(|a, b| a.depth.total_cmp(&b.depth));
+
+        #[cfg(feature = "brute-force-check")]
+        reference::check_collide_obb(self, obb, &contact);
+
+        contact
+    }
+
+    // the shortest distance from the segment to any triangle in the tree
+    pub fn distance_to_segment(&self, segment: &Ray) -> f32 {
+        let best = self.distance_to_segment_where(segment, |_| true);
+
+        #[cfg(feature = "brute-force-check")]
+        reference::check_distance_to_segment(self, segment, best);
+
+        best
+    }
+
+    // the same as `distance_to_segment`, but only to the triangles that `keep` is true for
+    pub(crate) fn distance_to_segment_where(&self, segment: &Ray, keep: impl Fn(&Tri) -> bool) -> f32 {
+        let segment_box = Aabb {
+            min: segment.start.min(segment.start + segment.direction),
+            max: segment.start.max(segment.start + segment.direction),
+        };
+
+        let mut best = f32::INFINITY;
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            // the distance between the boxes can never be more than the distance to anything inside of them
+            if node.box_.distance_to(&segment_box) >= best {
+                continue;
+            }
+
+            if let Some(primitive) = node.primitive.filter(|primitive| keep(primitive)) {
+                best = best.min(primitive.distance_to_segment(segment));
+            }
+
+            // look at the closer child first, so the further one is more likely to be skipped
+            match (node.left.as_deref(), node.right.as_deref()) {
+                (Some(left), Some(right)) => {
+                    if left.box_.distance_to(&segment_box) < right.box_.distance_to(&segment_box) {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+                (Some(child), None) | (None, Some(child)) => stack.push(child),
+                (None, None) => {}
+            }
+        }
+
+        best
+    }
+
+    // the first triangle the segment crosses, and how far along the segment it is
+    pub fn raycast(&self, ray: &Ray) -> Option<(f32, Tri)> {
+        let mut best: Option<(f32, Tri)> = None;
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            // the box and triangle tests round differently, so the boxes are padded a bit,
+            // and boxes that start just past the closest hit are still checked
+            let padded = Aabb {
+                min: node.box_.min - RAYCAST_PADDING,
+                max: node.box_.max + RAYCAST_PADDING,
+            };
+
+            match padded.intersect_ray(ray) {
+                Some(enter) if best.is_none_or(|(t, _)| enter <= t + 1e-5) => {}
+                _ => continue,
+            }
+
+            if let Some(primitive) = node.primitive {
+                if let Some(t) = primitive.intersect_ray(ray) {
+                    if best.is_none_or(|(best_t, _)| t < best_t) {
+                        best = Some((t, primitive));
+                    }
+                }
+            }
+
+            if let Some(left) = node.left.as_deref() {
+                stack.push(left);
+            }
+
+            if let Some(right) = node.right.as_deref() {
+                stack.push(right);
+            }
+        }
+
+        #[cfg(feature = "brute-force-check")]
+        reference::check_raycast(self, ray, best.map(|(t, _)| t));
+
+        best
+    }
+
+    // The first time a sphere moving from `start` to `end` touches any triangle, as a fraction of the way there, and where it touches
+    // The contact is the same as from `collide`, the point on the triangle and the normal pointing back at the sphere
+    pub fn spherecast(&self, start: Vec3A, end: Vec3A, radius: f32) -> Option<(f32, Ray)> {
+        let ray = Ray {
+            start,
+            direction: end - start,
+        };
+
+        let mut best: Option<(f32, Tri)> = None;
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            // anything the sphere touches is within the radius of a box that the center passes through
+            let padded = Aabb {
+                min: node.box_.min - radius - RAYCAST_PADDING,
+                max: node.box_.max + radius + RAYCAST_PADDING,
+            };
+
+            match padded.intersect_ray(&ray) {
+                Some(enter) if best.is_none_or(|(t, _)| enter <= t + 1e-5) => {}
+                _ => continue,
+            }
+
+            if let Some(primitive) = node.primitive {
+                if let Some(t) = primitive.sweep_sphere(&ray, radius) {
+                    if best.is_none_or(|(best_t, _)| t < best_t) {
+                        best = Some((t, primitive));
+                    }
+                }
+            }
+
+            if let Some(left) = node.left.as_deref() {
+                stack.push(left);
+            }
+
+            if let Some(right) = node.right.as_deref() {
+                stack.push(right);
+            }
+        }
+
+        #[cfg(feature = "brute-force-check")]
+        reference::check_spherecast(self, &ray, radius, best.map(|(t, _)| t));
+
+        best.map(|(t, tri)| {
+            let center = ray.start + ray.direction * t;
+            let point = tri.closest_point(center);
+
+            // the center can only be on the triangle if it started out that way
+            let normal = (center - point).try_normalize().unwrap_or_else(|| {
+                let n = tri.unit_normal();
+                if n.dot(ray.direction) > 0. {
+                    -n
+                } else {
+                    n
+                }
+            });
+
+            (
+                t,
+                Ray {
+                    start: point,
+                    direction: normal,
+                },
+            )
+        })
+    }
+
+    // every triangle whose bounding box overlaps the query box
+    pub fn intersect_aabb(&self, query_box: &Aabb) -> Vec<Tri> {
+        let mut hits = Vec::new();
+        self.for_each_overlapping_leaf(query_box, |leaf| hits.extend(leaf.primitive));
+
+        #[cfg(feature = "brute-force-check")]
+        reference::check_intersect_aabb(self, query_box, &hits);
 
         hits
     }
 
-    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
-        let mut contact_point = Ray::default();
-        let mut count = 0;
+    // the same as `intersect_aabb`, along with each triangle's id
+    pub fn intersect_aabb_ids(&self, query_box: &Aabb) -> Vec<(TriangleId, Tri)> {
+        let mut hits = Vec::new();
+        self.for_each_overlapping_leaf(query_box, |leaf| {
+            if let (Some(id), Some(primitive)) = (leaf.id, leaf.primitive) {
+                hits.push((id, primitive));
+            }
+        });
 
-        let tris_hit = self.intersect(s);
+        hits
+    }
+
+    fn for_each_overlapping_leaf<'a>(&'a self, query_box: &Aabb, mut f: impl FnMut(&'a BvhNode)) {
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            if !node.box_.intersect_self(query_box) {
+                continue;
+            }
 
-        for tri in tris_hit {
-            let p = tri.center();
-            let n = tri.unit_normal();
+            if node.primitive.is_some() {
+                f(node);
+            }
+
+            if let Some(left) = node.left.as_deref() {
+                stack.push(left);
+            }
 
-            let separation = (s.center - p).dot(n);
-            if separation <= s.radius {
-                count += 1;
-                contact_point.start += s.center - n * separation;
-                contact_point.direction += n * (s.radius - separation);
+            if let Some(right) = node.right.as_deref() {
+                stack.push(right);
             }
         }
+    }
+
+    // Edges of the field that only belong to a single triangle, which the ball could slip through
+    pub fn find_boundary_edges(&self) -> Vec<[Vec3A; 2]> {
+        open_edges(&self.primitives())
+    }
+
+    // Edges that are open even after accounting for T-junctions, which are actual gaps in the field
+    pub fn find_holes(&self) -> Vec<[Vec3A; 2]> {
+        holes(&self.primitives())
+    }
+
+    // If the field fully encloses a volume, with no gaps for the ball to escape through
+    // The built-in fields aren't, since their pieces overlap and cut through each other instead of meeting at shared edges
+    pub fn is_watertight(&self) -> bool {
+        self.find_holes().is_empty()
+    }
 
-        if count == 0 {
-            return None;
+    // If the point is inside of the field, which is when rays from it in every direction hit the field
+    // Counting how many times a ray crosses the field doesn't work, because parts of the fields overlap (like the corners and side walls in soccar)
+    // The fields are closed and roughly box-shaped, so from outside of them, at least one of the rays escapes
+    pub fn contains(&self, point: Vec3A) -> bool {
+        if !self.global_box.contains(point) {
+            return false;
         }
 
-        contact_point.start /= count as f32;
-        contact_point.direction = contact_point.direction.normalize_or_zero();
+        // the fields are mirrored across the axes, so rays along them would run right through seams
+        let length = (self.global_box.max - self.global_box.min).length();
+        let directions = [Vec3A::new(1., 0.3719, 0.1241), Vec3A::new(-0.2113, 1., 0.4171), Vec3A::new(0.3301, -0.1877, 1.)];
 
-        Some(contact_point)
+        directions.iter().flat_map(|direction| [*direction, -*direction]).all(|direction| {
+            let ray = Ray {
+                start: point,
+                direction: direction.normalize() * length,
+            };
+            let ray_box = Aabb {
+                min: ray.start.min(ray.start + ray.direction),
+                max: ray.start.max(ray.start + ray.direction),
+            };
+
+            self.intersect_aabb(&ray_box).iter().any(|tri| tri.intersect_ray(&ray).is_some())
+        })
+    }
+
+    // A lower detail copy of the tree, see `decimate`
+    pub fn decimated(&self, cell_size: f32) -> Self {
+        let (ids, triangles): (Vec<TriangleId>, Vec<Tri>) = decimate(&self.primitives_with_ids(), cell_size).into_iter().unzip();
+        Bvh::from_with_ids(&triangles, &ids)
+    }
+
+    // all of the triangles in the tree and their ids, in leaf order
+    pub fn primitives_with_ids(&self) -> Vec<(TriangleId, Tri)> {
+        let mut primitives = Vec::with_capacity(self.num_leaves as usize);
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            if let (Some(id), Some(primitive)) = (node.id, node.primitive) {
+                primitives.push((id, primitive));
+            }
+
+            if let Some(left) = node.left.as_deref() {
+                stack.push(left);
+            }
+
+            if let Some(right) = node.right.as_deref() {
+                stack.push(right);
+            }
+        }
+
+        primitives
+    }
+
+    // the triangle with the given id, if it's in the tree
+    pub fn triangle(&self, id: TriangleId) -> Option<Tri> {
+        self.primitives_with_ids().into_iter().find(|(other, _)| *other == id).map(|(_, tri)| tri)
+    }
+
+    // all of the triangles in the tree, in leaf order
+    pub fn primitives(&self) -> Vec<Tri> {
+        let mut primitives = Vec::with_capacity(self.num_leaves as usize);
+        let mut stack: Vec<&BvhNode> = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            if let Some(primitive) = node.primitive {
+                primitives.push(primitive);
+            }
+
+            if let Some(left) = node.left.as_deref() {
+                stack.push(left);
+            }
+
+            if let Some(right) = node.right.as_deref() {
+                stack.push(right);
+            }
+        }
+
+        primitives
+    }
+}
+
+// A built BVH, saved so that it can be loaded again without sorting and splitting the triangles
+//
+// Everything is little endian:
+//   magic (4 bytes), format version (u16), crate version (u16 length then utf-8), tag (u16 length then utf-8), number of leaves (u64)
+// then every node, parents before their children and right children before left ones:
+//   a branch is a 0 byte, and a leaf is a 1 byte followed by its triangle (9 f32), id (2 u32) and morton code (u64)
+// The boxes aren't saved, since they're rebuilt exactly from the triangles
+// The tag says which field the BVH is of, so that a file saved for one field isn't loaded as another one
+
+#[cfg(feature = "std")]
+pub const BVH_MAGIC: [u8; 4] = *b"RLBV";
+#[cfg(feature = "std")]
+pub const BVH_FORMAT_VERSION: u16 = 2;
+
+#[cfg(feature = "std")]
+// the trees are balanced, so this is only reached by a broken file
+const MAX_SAVED_DEPTH: usize = 64;
+
+#[cfg(feature = "std")]
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(feature = "std")]
+impl Bvh {
+    // BVHs saved by a different version of the crate aren't loaded again, since the fields might've changed
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_tag("")
+    }
+
+    pub fn to_bytes_with_tag(&self, tag: &str) -> Vec<u8> {
+        let crate_version = env!("CARGO_PKG_VERSION");
+
+        let mut bytes = Vec::with_capacity(18 + crate_version.len() + tag.len() + self.num_leaves as usize * 54);
+
+        bytes.extend_from_slice(&BVH_MAGIC);
+        bytes.write_u16::<LittleEndian>(BVH_FORMAT_VERSION).unwrap();
+        bytes.write_u16::<LittleEndian>(crate_version.len() as u16).unwrap();
+        bytes.extend_from_slice(crate_version.as_bytes());
+        bytes.write_u16::<LittleEndian>(tag.len() as u16).unwrap();
+        bytes.extend_from_slice(tag.as_bytes());
+        bytes.write_u64::<LittleEndian>(self.num_leaves).unwrap();
+
+        let mut stack = vec![self.root.as_ref()];
+
+        while let Some(node) = stack.pop() {
+            match (node.primitive, node.id) {
+                (Some(primitive), Some(id)) if node.is_terminal => {
+                    bytes.push(1);
+
+                    for value in primitive.p.iter().flat_map(|p| p.to_array()) {
+                        bytes.write_f32::<LittleEndian>(value).unwrap();
+                    }
+
+                    bytes.write_u32::<LittleEndian>(id.mesh).unwrap();
+                    bytes.write_u32::<LittleEndian>(id.index).unwrap();
+                    bytes.write_u64::<LittleEndian>(node.morton.unwrap_or_default()).unwrap();
+                }
+                _ => {
+                    bytes.push(0);
+
+                    // popped in the opposite order
+                    stack.extend(node.left.as_deref());
+                    stack.extend(node.right.as_deref());
+                }
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        Bvh::from_bytes_with_tag(bytes, "")
+    }
+
+    // only loads a BVH that was saved with the same tag
+    pub fn from_bytes_with_tag(bytes: &[u8], tag: &str) -> io::Result<Self> {
+        let mut bytes = Cursor::new(bytes);
+
+        let mut magic = [0; 4];
+        bytes.read_exact(&mut magic)?;
+        if magic != BVH_MAGIC {
+            return Err(invalid_data("Not a saved BVH"));
+        }
+
+        let version = bytes.read_u16::<LittleEndian>()?;
+        if version != BVH_FORMAT_VERSION {
+            return Err(invalid_data(&format!("Unsupported BVH format version {version}, expected {BVH_FORMAT_VERSION}")));
+        }
+
+        let mut crate_version = vec![0; bytes.read_u16::<LittleEndian>()? as usize];
+        bytes.read_exact(&mut crate_version)?;
+        if crate_version != env!("CARGO_PKG_VERSION").as_bytes() {
+            return Err(invalid_data("The BVH was saved by a different version of the crate"));
+        }
+
+        let mut saved_tag = vec![0; bytes.read_u16::<LittleEndian>()? as usize];
+        bytes.read_exact(&mut saved_tag)?;
+        if saved_tag != tag.as_bytes() {
+            return Err(invalid_data(&format!("The BVH was saved as {:?}, not {tag:?}", String::from_utf8_lossy(&saved_tag))));
+        }
+
+        let num_leaves = bytes.read_u64::<LittleEndian>()?;
+        if num_leaves == 0 {
+            return Err(invalid_data("A BVH needs at least one triangle"));
+        }
+
+        let mut leaves_read = 0;
+        let root = Bvh::read_node(&mut bytes, 0, &mut leaves_read)?;
+
+        if leaves_read != num_leaves {
+            return Err(invalid_data("The number of leaves doesn't match the tree"));
+        }
+
+        if bytes.position() != bytes.get_ref().len() as u64 {
+            return Err(invalid_data("Extra data after the BVH"));
+        }
+
+        Ok(Self {
+            global_box: root.box_,
+            num_leaves,
+            root,
+        })
     }
+
+    fn read_node(bytes: &mut Cursor<&[u8]>, depth: usize, leaves_read: &mut u64) -> io::Result<Box<BvhNode>> {
+        if depth > MAX_SAVED_DEPTH {
+            return Err(invalid_data("The BVH is too deep"));
+        }
+
+        match bytes.read_u8()? {
+            0 => {
+                let right = Bvh::read_node(bytes, depth + 1, leaves_read)?;
+                let left = Bvh::read_node(bytes, depth + 1, leaves_read)?;
+
+                Ok(BvhNode::branch(right, left))
+            }
+            1 => {
+                let mut p = [Vec3A::ZERO; 3];
+                for vertex in &mut p {
+                    *vertex = Vec3A::new(bytes.read_f32::<LittleEndian>()?, bytes.read_f32::<LittleEndian>()?, bytes.read_f32::<LittleEndian>()?);
+                }
+
+                let primitive = Tri {
+                    p,
+                };
+                let id = TriangleId {
+                    mesh: bytes.read_u32::<LittleEndian>()?,
+                    index: bytes.read_u32::<LittleEndian>()?,
+                };
+                let morton = bytes.read_u64::<LittleEndian>()?;
+
+                *leaves_read += 1;
+
+                Ok(BvhNode::leaf(primitive, id, Aabb::from(&primitive), morton))
+            }
+            tag => Err(invalid_data(&format!("Unknown BVH node {tag}"))),
+        }
+    }
+}
+
+// turns the triangles that a sphere overlaps into a single averaged contact
+pub(crate) fn contact_from_tris(s: &Sphere, tris_hit: Vec<Tri>) -> Option<Ray> {
+    let mut contact_point = Ray::default();
+    let mut count = 0;
+
+    for tri in tris_hit {
+        let p = tri.center();
+        let n = tri.unit_normal();
+
+        let separation = (s.center - p).dot(n);
+        if separation <= s.radius {
+            count += 1;
+            contact_point.start += s.center - n * separation;
+            contact_point.direction += n * (s.radius - separation);
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    contact_point.start /= count as f32;
+    contact_point.direction = math::normalize_or_zero(contact_point.direction);
+
+    Some(contact_point)
 }
 
 #[cfg(test)]
@@ -229,6 +821,13 @@ mod test {
 
     use super::*;
 
+    // the normal is only approximately normalized with fast math
+    const DIRECTION_TOLERANCE: f32 = if cfg!(feature = "fast-math") {
+        1e-5
+    } else {
+        f32::EPSILON
+    };
+
     const MIN_X: f32 = -4107.33;
     const MIN_Y: f32 = -6000.0;
     const MIN_Z: f32 = -13.2678;
@@ -430,9 +1029,9 @@ mod test {
             assert!((ray.start.x - 2048.).abs() < f32::EPSILON);
             assert!((ray.start.y - 2560.).abs() < f32::EPSILON);
             assert!((ray.start.z - 0.).abs() < f32::EPSILON);
-            assert!((ray.direction.x - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.y - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.z - 1.0).abs() < f32::EPSILON);
+            assert!((ray.direction.x - 0.0).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.y - 0.0).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.z - 1.0).abs() < DIRECTION_TOLERANCE);
         }
         {
             // Middle of two Tris
@@ -448,9 +1047,9 @@ mod test {
             assert!((ray.start.x - 0.0).abs() < f32::EPSILON);
             assert!((ray.start.y - 0.0).abs() < f32::EPSILON);
             assert!((ray.start.z - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.x - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.y - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.z - 1.0).abs() < f32::EPSILON);
+            assert!((ray.direction.x - 0.0).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.y - 0.0).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.z - 1.0).abs() < DIRECTION_TOLERANCE);
         }
         {
             // Sphere is in a corner
@@ -466,9 +1065,9 @@ mod test {
             assert!((ray.start.x - 4096.).abs() < f32::EPSILON);
             assert!((ray.start.y - 5120.).abs() < f32::EPSILON);
             assert!((ray.start.z - 0.0).abs() < f32::EPSILON);
-            assert!((ray.direction.x - 0.6666667).abs() < f32::EPSILON);
-            assert!((ray.direction.y - 0.6666667).abs() < f32::EPSILON);
-            assert!((ray.direction.z - 0.33333334).abs() < f32::EPSILON);
+            assert!((ray.direction.x - 0.6666667).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.y - 0.6666667).abs() < DIRECTION_TOLERANCE);
+            assert!((ray.direction.z - 0.33333334).abs() < DIRECTION_TOLERANCE);
         }
     }
 
@@ -492,4 +1091,173 @@ mod test {
             assert!(ray.start.is_finite());
         }
     }
+
+    #[test]
+    fn ids_survive_rebuilds() {
+        let triangles = generate_tris();
+        let ids: Vec<TriangleId> = (0..triangles.len() as u32)
+            .map(|index| TriangleId {
+                mesh: 7,
+                index,
+            })
+            .collect();
+
+        // the same triangles in a different order end up in different leaves, but keep their ids
+        let mut reversed: Vec<(TriangleId, Tri)> = ids.iter().copied().zip(triangles.iter().copied()).collect();
+        reversed.reverse();
+        let (reversed_ids, reversed_triangles): (Vec<TriangleId>, Vec<Tri>) = reversed.into_iter().unzip();
+
+        let bvh = Bvh::from_with_ids(&triangles, &ids);
+        let reversed_bvh = Bvh::from_with_ids(&reversed_triangles, &reversed_ids);
+
+        for (i, id) in ids.iter().enumerate() {
+            assert_eq!(bvh.triangle(*id).unwrap().p, triangles[i].p);
+            assert_eq!(reversed_bvh.triangle(*id).unwrap().p, triangles[i].p);
+        }
+
+        let sphere = Sphere {
+            center: vec3a(0., 0., 93.15),
+            radius: 93.15,
+        };
+
+        let mut hit_ids = bvh.intersect_ids(&sphere);
+        let mut reversed_hit_ids = reversed_bvh.intersect_ids(&sphere);
+        hit_ids.sort_unstable();
+        reversed_hit_ids.sort_unstable();
+
+        assert!(!hit_ids.is_empty());
+        assert_eq!(hit_ids, reversed_hit_ids);
+        assert_eq!(hit_ids.len(), bvh.intersect(&sphere).len());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn scratch_is_reused() {
+        let triangles = generate_tris();
+        let ids: Vec<TriangleId> = (0..triangles.len() as u32)
+            .map(|index| TriangleId {
+                mesh: 0,
+                index,
+            })
+            .collect();
+
+        // a pool of its own, so builds from other tests can't take the lists in between
+        let pool = ScratchPool::new();
+        let mut boxes = None;
+
+        for _ in 0..2 {
+            let bvh = pool.with(|scratch| {
+                let bvh = Bvh::serial_from_with_ids_in(&triangles, &ids, scratch);
+
+                // the second build gets the lists the first one grew
+                let first_boxes = *boxes.get_or_insert(scratch.boxes.as_ptr());
+                assert_eq!(scratch.boxes.as_ptr(), first_boxes);
+
+                bvh
+            });
+
+            assert_eq!(bvh.to_bytes(), Bvh::from(&triangles).to_bytes());
+        }
+
+        // and the lists are empty when they go back
+        pool.with(|scratch| {
+            assert!(scratch.boxes.is_empty() && scratch.order.is_empty() && scratch.leaves.is_empty());
+            assert!(scratch.boxes.capacity() >= triangles.len());
+        });
+    }
+
+    #[test]
+    fn raycast() {
+        let bvh = Bvh::from(&generate_tris());
+
+        // straight down onto the floor
+        let down = Ray {
+            start: vec3a(100., 200., 1000.),
+            direction: vec3a(0., 0., -2000.),
+        };
+        let (t, tri) = bvh.raycast(&down).unwrap();
+        assert!((t - 0.5).abs() < f32::EPSILON);
+        assert!(tri.p.iter().all(|p| p.z == 0.));
+
+        // into the corner, along the side wall's face
+        let along_wall = Ray {
+            start: vec3a(-4096., 0., 1000.),
+            direction: vec3a(0., 10000., 0.),
+        };
+        assert!((bvh.raycast(&along_wall).unwrap().0 - 0.512).abs() < 1e-6);
+
+        // not long enough to reach anything
+        let short = Ray {
+            start: vec3a(0., 0., 1000.),
+            direction: vec3a(0., 0., 500.),
+        };
+        assert!(bvh.raycast(&short).is_none());
+    }
+
+    #[test]
+    fn spherecast() {
+        let bvh = Bvh::from(&generate_tris());
+
+        // falling onto the floor
+        let (t, contact) = bvh.spherecast(vec3a(100., 200., 1000.), vec3a(100., 200., -1000.), 100.).unwrap();
+        assert!((t - 0.45).abs() < 1e-6);
+        assert!((contact.start - vec3a(100., 200., 0.)).length() < 0.01);
+        assert!((contact.direction - Vec3A::Z).length() < 1e-5);
+
+        // into the edge between the floor and a side wall, where it has to touch at exactly the radius
+        let (start, end) = (vec3a(0., 0., 1000.), vec3a(-6000., 500., -1000.));
+        let (t, contact) = bvh.spherecast(start, end, 93.15).unwrap();
+        let center = start.lerp(end, t);
+        assert!(!bvh
+            .intersect(&Sphere {
+                center,
+                radius: 93.16
+            })
+            .is_empty());
+        assert!(bvh
+            .intersect(&Sphere {
+                center,
+                radius: 93.14
+            })
+            .is_empty());
+        assert!((center - contact.start).length() - 93.15 < 0.01);
+
+        // already touching, and not reaching anything
+        assert_eq!(bvh.spherecast(vec3a(0., 0., 50.), vec3a(0., 0., 500.), 93.15).unwrap().0, 0.);
+        assert!(bvh.spherecast(vec3a(0., 0., 1000.), vec3a(0., 0., 1100.), 93.15).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn saved_bvh() {
+        let bvh = Bvh::from(&generate_tris());
+        let bytes = bvh.to_bytes();
+        let loaded = Bvh::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.num_leaves, bvh.num_leaves);
+        assert_eq!(loaded.global_box.min, bvh.global_box.min);
+        assert_eq!(loaded.global_box.max, bvh.global_box.max);
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let ball = Sphere {
+            center: vec3a(-4000., 0., 100.),
+            radius: 100.,
+        };
+        assert_eq!(loaded.intersect(&ball).len(), bvh.intersect(&ball).len());
+        assert_eq!(loaded.collide(&ball).unwrap().direction, bvh.collide(&ball).unwrap().direction);
+
+        // truncated files, extra data and other versions are rejected
+        assert!(Bvh::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Bvh::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+
+        let mut other_version = bytes.clone();
+        other_version[4] = BVH_FORMAT_VERSION as u8 + 1;
+        assert!(Bvh::from_bytes(&other_version).is_err());
+
+        // and so are BVHs saved with a different tag
+        let tagged = bvh.to_bytes_with_tag("soccar");
+        assert_eq!(Bvh::from_bytes_with_tag(&tagged, "soccar").unwrap().to_bytes(), bytes);
+        assert!(Bvh::from_bytes_with_tag(&tagged, "hoops").is_err());
+        assert!(Bvh::from_bytes(&tagged).is_err());
+    }
 }
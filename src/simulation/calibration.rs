@@ -0,0 +1,167 @@
+use super::ball::Ball;
+use super::game::Game;
+use super::tuning::Tuning;
+
+// A stretch of recorded ball states (like from a replay) with nothing but the field touching the ball
+// The first state is where the simulation starts, and every state after it is compared to the simulation at the same time
+#[derive(Clone, Debug, Default)]
+pub struct RecordedSegment {
+    pub states: Vec<Ball>,
+}
+
+// How the search stops: once every step size is below its tolerance, or after this many rounds
+const MAX_ROUNDS: usize = 200;
+const STEP_TOLERANCE: f32 = 1e-4;
+
+// the mean squared distance between the recorded and simulated locations, over every recorded state after the first
+pub fn trajectory_error(game: &Game, segments: &[RecordedSegment], tuning: Tuning) -> f32 {
+    let mut game = game.clone();
+    game.field.tuning = tuning;
+
+    let mut total = 0.;
+    let mut count = 0;
+
+    for segment in segments {
+        let Some((start, recorded)) = segment.states.split_first() else {
+            continue;
+        };
+
+        game.ball = *start;
+        let mut steps = 0;
+
+        for state in recorded {
+            let target_steps = ((state.time - start.time) / Ball::SIMULATION_DT).round() as usize;
+
+            while steps < target_steps {
+                Ball::step(&mut game, Ball::SIMULATION_DT);
+                steps += 1;
+            }
+
+            total += game.ball.location.distance_squared(state.location);
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        0.
+    } else {
+        total / count as f32
+    }
+}
+
+fn clamp(tuning: Tuning) -> Tuning {
+    Tuning {
+        restitution: tuning.restitution.clamp(0., 1.),
+        friction: tuning.friction.max(0.),
+        drag: tuning.drag.min(0.),
+    }
+}
+
+// Finds the restitution, friction and drag that make the simulation follow the recordings most closely, starting from `game.tuning`
+// This is a pattern search: each constant is nudged up and down, and the nudges get smaller whenever none of them help
+pub fn fit_constants(game: &Game, segments: &[RecordedSegment]) -> Tuning {
+    let mut best = game.field.tuning;
+    let mut best_error = trajectory_error(game, segments, best);
+
+    // start with steps of about a tenth of each constant's usual value
+    let mut steps = [0.1, 0.2, 0.005];
+    let tolerances = [STEP_TOLERANCE, STEP_TOLERANCE, STEP_TOLERANCE / 20.];
+
+    for _ in 0..MAX_ROUNDS {
+        if steps.iter().zip(tolerances).all(|(step, tolerance)| *step < tolerance) {
+            break;
+        }
+
+        let mut improved = false;
+
+        for (axis, step) in steps.iter_mut().enumerate() {
+            for direction in [1., -1.] {
+                let mut candidate = best;
+
+                *match axis {
+                    0 => &mut candidate.restitution,
+                    1 => &mut candidate.friction,
+                    _ => &mut candidate.drag,
+                } += direction * *step;
+
+                let candidate = clamp(candidate);
+                let error = trajectory_error(game, segments, candidate);
+
+                if error < best_error {
+                    best = candidate;
+                    best_error = error;
+                    improved = true;
+                    // keep going in a direction that's working
+                    *step *= 1.5;
+                    break;
+                }
+            }
+        }
+
+        if !improved {
+            for step in &mut steps {
+                *step /= 2.;
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    fn record(game: &Game, tuning: Tuning, location: glam::Vec3A, velocity: glam::Vec3A, angular_velocity: glam::Vec3A) -> RecordedSegment {
+        let mut game = game.clone();
+        game.field.tuning = tuning;
+        game.ball.update(0., location, velocity, angular_velocity);
+
+        let mut states = vec![game.ball];
+
+        // like a replay, which has fewer frames than the simulation has steps
+        for _ in 0..20 {
+            for _ in 0..6 {
+                Ball::step(&mut game, Ball::SIMULATION_DT);
+            }
+
+            states.push(game.ball);
+        }
+
+        RecordedSegment {
+            states,
+        }
+    }
+
+    #[test]
+    fn recovers_constants() {
+        let game = load_soccar();
+
+        let actual = Tuning {
+            restitution: 0.5,
+            friction: 1.5,
+            drag: -0.04,
+        };
+
+        let segments = [record(&game, actual, vec3a(0., 0., 800.), vec3a(800., 0., -1200.), vec3a(0., 4., 0.)), record(&game, actual, vec3a(-1000., 2000., 200.), vec3a(0., 1500., -800.), vec3a(-5., 0., 0.)), record(&game, actual, vec3a(2000., -1000., 1000.), vec3a(1800., 0., 500.), vec3a(0., 0., 3.))];
+
+        assert!(trajectory_error(&game, &segments, game.field.tuning) > 100.);
+
+        let fitted = fit_constants(&game, &segments);
+        assert!(trajectory_error(&game, &segments, fitted) < 1., "{fitted:?}");
+
+        assert!((fitted.restitution - actual.restitution).abs() < 0.01, "{fitted:?}");
+        assert!((fitted.drag - actual.drag).abs() < 0.001, "{fitted:?}");
+        // friction is capped for all but the most glancing bounces, so these shots can't pin it down
+    }
+
+    #[test]
+    fn no_recordings() {
+        let game = load_soccar();
+        assert_eq!(trajectory_error(&game, &[], game.field.tuning), 0.);
+        assert_eq!(fit_constants(&game, &[RecordedSegment::default()]), game.field.tuning);
+    }
+}
@@ -0,0 +1,249 @@
+use glam::{const_vec3a, Mat3A, Vec3, Vec3A};
+
+use super::ball::Ball;
+use super::geometry::Obb;
+
+// The size of a car's hitbox, and where its center is relative to the car's origin (in the car's local space)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hitbox {
+    pub half_extents: Vec3A,
+    pub offset: Vec3A,
+}
+
+impl Hitbox {
+    pub const OCTANE: Self = Self {
+        half_extents: const_vec3a!([59.00369, 42.099705, 18.079536]),
+        offset: const_vec3a!([13.87566, 0., 20.75499]),
+    };
+
+    // the diagonal of the inertia tensor, around the car's forward, left and up axes, as if it were a solid box this size
+    // like the ball's, this is in uu, so it can be used right alongside `Ball::moi`
+    pub fn inertia(&self, mass: f32) -> Vec3A {
+        let size = self.half_extents * 2.;
+        let squared = size * size;
+
+        Vec3A::new(squared.y + squared.z, squared.x + squared.z, squared.x + squared.y) * (mass / 12.)
+    }
+}
+
+impl Default for Hitbox {
+    fn default() -> Self {
+        Self::OCTANE
+    }
+}
+
+// Where a car is and how it's moving, which is everything a hit on the ball depends on
+// The columns of `orientation` are the car's forward, left and up directions
+#[derive(Clone, Copy, Debug)]
+pub struct Car {
+    pub location: Vec3A,
+    pub orientation: Mat3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+    pub hitbox: Hitbox,
+}
+
+impl Default for Car {
+    fn default() -> Self {
+        Self {
+            location: Vec3A::ZERO,
+            orientation: Mat3A::IDENTITY,
+            velocity: Vec3A::ZERO,
+            angular_velocity: Vec3A::ZERO,
+            hitbox: Hitbox::OCTANE,
+        }
+    }
+}
+
+impl Car {
+    pub const MASS: f32 = 180.;
+
+    // how much of the relative speed the game's extra "psyonix" impulse adds to the ball, at a few speeds with straight lines in between
+    const PSYONIX_SCALE: [(f32, f32); 4] = [(0., 0.65), (500., 0.65), (2300., 0.55), (4600., 0.3)];
+    const PSYONIX_MAX_SPEED: f32 = 4600.;
+    // the psyonix impulse is flattened out, so it pushes the ball along the ground more than up or down,
+    // and less in the direction the car is facing than out to the sides
+    const PSYONIX_VERTICAL_SCALE: f32 = 0.35;
+    const PSYONIX_FORWARD_SCALE: f32 = 0.35;
+
+    const FRICTION: f32 = 2.;
+
+    pub fn hitbox(&self) -> Obb {
+        Obb {
+            center: self.location + self.orientation * self.hitbox.offset,
+            orientation: self.orientation,
+            half_extents: self.hitbox.half_extents,
+        }
+    }
+
+    pub fn forward(&self) -> Vec3A {
+        self.orientation.x_axis
+    }
+
+    fn psyonix_scale(speed: f32) -> f32 {
+        let points = Car::PSYONIX_SCALE;
+
+        for window in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (window[0], window[1]);
+
+            if speed <= x1 {
+                return y0 + (y1 - y0) * ((speed - x0) / (x1 - x0)).max(0.);
+            }
+        }
+
+        points[points.len() - 1].1
+    }
+
+    // The ball's velocity and angular velocity right after the car hits it, or None if they aren't touching
+    //
+    // This is the model from RLUtilities: an inelastic collision between the ball and the car's hitbox, with Coulomb friction,
+    // plus the extra impulse the game adds on top that depends on how fast they're moving towards each other
+    // (RLUtilities' car inertia isn't in uu, so the car is treated as a solid box instead, see `Hitbox::inertia`)
+    pub fn hit(&self, ball: &Ball) -> Option<(Vec3A, Vec3A)> {
+        let hitbox = self.hitbox();
+        let p = hitbox.closest_point(ball.location);
+        let sphere = ball.hitbox();
+
+        if p.distance_squared(sphere.center) > sphere.radius * sphere.radius {
+            return None;
+        }
+
+        // where they touch, from the ball's center and from the car's
+        let l_b = p - ball.location;
+        let l_c = p - self.location;
+
+        let inverse_inertia = self.orientation * Mat3A::from_diagonal(Vec3::from(self.hitbox.inertia(Car::MASS).recip())) * self.orientation.transpose();
+        let (skew_b, skew_c) = (skew(l_b), skew(l_c));
+        let k = Mat3A::from_diagonal(Vec3::splat(1. / Ball::M + 1. / Car::MASS)) - skew_b * skew_b * (1. / ball.moi) - skew_c * inverse_inertia * skew_c;
+
+        // how fast the car's side of the contact is moving compared to the ball's
+        let relative_velocity = (self.velocity + self.angular_velocity.cross(l_c)) - (ball.velocity + ball.angular_velocity.cross(l_b));
+
+        // the impulse that stops them from moving into each other, with the part along the surface limited by friction
+        let n1 = l_b.normalize_or_zero();
+        let j1 = k.inverse() * relative_velocity;
+        let j1_perp = n1 * j1.dot(n1).min(-1.);
+        let j1_para = j1 - j1_perp;
+        let ratio = j1_perp.length() / j1_para.length().max(0.001);
+        let j1 = j1_perp + j1_para * (Car::FRICTION * ratio).min(1.);
+
+        let forward = self.forward();
+        let mut n2 = ball.location - self.location;
+        n2.z *= Car::PSYONIX_VERTICAL_SCALE;
+        let n2 = (n2 - forward * (Car::PSYONIX_FORWARD_SCALE * n2.dot(forward))).normalize_or_zero();
+
+        let speed = (ball.velocity - self.velocity).length().min(Car::PSYONIX_MAX_SPEED);
+        let j2 = n2 * (Ball::M * speed * Car::psyonix_scale(speed));
+
+        Some((ball.velocity + (j1 + j2) / Ball::M, ball.angular_velocity + l_b.cross(j1) / ball.moi))
+    }
+}
+
+// the matrix that crosses `v` with whatever it's multiplied by
+fn skew(v: Vec3A) -> Mat3A {
+    Mat3A::from_cols(Vec3A::new(0., v.z, -v.y), Vec3A::new(-v.z, 0., v.x), Vec3A::new(v.y, -v.x, 0.))
+}
+
+impl Ball {
+    // hits the ball with the car, if they're touching, and returns whether they were
+    pub fn hit_by(&mut self, car: &Car) -> bool {
+        match car.hit(self) {
+            Some((velocity, angular_velocity)) => {
+                self.velocity = velocity;
+                self.angular_velocity = angular_velocity;
+                self.limit_velocities();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    // a car driving along +x, with the front of its hitbox just touching a ball that's sitting at `ball_z`
+    fn setup(speed: f32, ball_z: f32) -> (Car, Ball) {
+        let game = load_soccar();
+        let car = Car {
+            velocity: vec3a(speed, 0., 0.),
+            ..Car::default()
+        };
+
+        let front = car.hitbox().center.x + car.hitbox.half_extents.x;
+        let ball = Ball {
+            location: vec3a(front + game.ball.radius - 1., 0., ball_z),
+            ..game.ball
+        };
+
+        (car, ball)
+    }
+
+    #[test]
+    fn skew_is_a_cross_product() {
+        let (a, b) = (vec3a(1., -2., 3.), vec3a(-4., 5., 0.5));
+        assert_eq!(skew(a) * b, a.cross(b));
+    }
+
+    #[test]
+    fn psyonix_scale() {
+        assert_eq!(Car::psyonix_scale(0.), 0.65);
+        assert_eq!(Car::psyonix_scale(500.), 0.65);
+        assert!((Car::psyonix_scale(1400.) - 0.6).abs() < 1e-6);
+        assert_eq!(Car::psyonix_scale(4600.), 0.3);
+        assert_eq!(Car::psyonix_scale(6000.), 0.3);
+    }
+
+    #[test]
+    fn missing_the_ball() {
+        let (car, mut ball) = setup(1000., 30.);
+        ball.location.x += 100.;
+
+        assert_eq!(car.hit(&ball), None);
+        assert!(!ball.hit_by(&car));
+    }
+
+    #[test]
+    fn straight_hit() {
+        let (car, ball) = setup(1400., Hitbox::OCTANE.offset.z);
+        let (velocity, angular_velocity) = car.hit(&ball).unwrap();
+
+        // the ball goes off faster than the car, straight ahead and a little up, without much spin
+        assert!(velocity.x > 1400., "{velocity}");
+        assert!(velocity.y.abs() < 1. && velocity.z >= 0. && velocity.z < 0.2 * velocity.x, "{velocity}");
+        assert!(angular_velocity.length() < 2., "{velocity} {angular_velocity}");
+
+        // and a slower car doesn't hit it as hard
+        let (slower, _) = Car {
+            velocity: vec3a(700., 0., 0.),
+            ..car
+        }
+        .hit(&ball)
+        .unwrap();
+        assert!(slower.x < velocity.x);
+    }
+
+    #[test]
+    fn hitting_under_the_ball() {
+        // the ball is higher than the car's hitbox, so it's hit below its center, and gets backspin from the friction
+        let (car, ball) = setup(1400., Hitbox::OCTANE.offset.z + 40.);
+        let (velocity, angular_velocity) = car.hit(&ball).unwrap();
+
+        assert!(velocity.x > 0. && velocity.z > 0., "{velocity}");
+        assert!(angular_velocity.y < 0., "{angular_velocity}");
+    }
+
+    #[test]
+    fn hit_by_applies_the_hit() {
+        let (car, mut ball) = setup(2000., Hitbox::OCTANE.offset.z);
+        let (velocity, _) = car.hit(&ball).unwrap();
+
+        assert!(ball.hit_by(&car));
+        assert_eq!(ball.velocity, velocity);
+        assert!(ball.angular_velocity.length() <= Ball::W_MAX * 1.0001);
+    }
+}
@@ -0,0 +1,230 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::BallPrediction;
+use super::game::Game;
+
+// Everything is rounded to these before hashing, so tiny floating point differences don't change the checksum
+pub const TIME_QUANTUM: f32 = 1e-4;
+pub const LOCATION_QUANTUM: f32 = 0.01;
+pub const VELOCITY_QUANTUM: f32 = 0.01;
+pub const ANGULAR_VELOCITY_QUANTUM: f32 = 1e-4;
+
+// How a value is rounded to one of the quanta above
+pub fn quantize(value: f32, quantum: f32) -> i64 {
+    (value / quantum).round() as i64
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// 64-bit FNV-1a, which is simple enough to re-implement anywhere that wants to compare against it
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write_i64(&mut self, value: i64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_f32(&mut self, value: f32, quantum: f32) {
+        self.write_i64(quantize(value, quantum));
+    }
+
+    fn write_vec3a(&mut self, value: Vec3A, quantum: f32) {
+        for component in value.to_array() {
+            self.write_f32(component, quantum);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ball prediction checksum mismatch: expected {:016x}, got {:016x}", self.expected, self.actual)
+    }
+}
+
+impl Error for ChecksumMismatch {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumError {
+    // the expected checksum wasn't 16 hex digits, like `checksum_hex` gives
+    InvalidHex(String),
+    Mismatch(ChecksumMismatch),
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHex(hex) => write!(f, "Invalid ball prediction checksum {hex:?}, expected 16 hex digits"),
+            Self::Mismatch(mismatch) => mismatch.fmt(f),
+        }
+    }
+}
+
+impl Error for ChecksumError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidHex(_) => None,
+            Self::Mismatch(mismatch) => Some(mismatch),
+        }
+    }
+}
+
+impl From<ChecksumMismatch> for ChecksumError {
+    fn from(mismatch: ChecksumMismatch) -> Self {
+        Self::Mismatch(mismatch)
+    }
+}
+
+impl BallPrediction {
+    // A stable hash of all of the slices, for cheap determinism checks
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+
+        hasher.write_i64(self.slices.len() as i64);
+
+        for slice in &self.slices {
+            hasher.write_f32(slice.time, TIME_QUANTUM);
+            hasher.write_vec3a(slice.location, LOCATION_QUANTUM);
+            hasher.write_vec3a(slice.velocity, VELOCITY_QUANTUM);
+            hasher.write_vec3a(slice.angular_velocity, ANGULAR_VELOCITY_QUANTUM);
+        }
+
+        hasher.0
+    }
+
+    // The checksum as 16 hex digits, for storing in test fixtures
+    pub fn checksum_hex(&self) -> String {
+        format!("{:016x}", self.checksum())
+    }
+
+    pub fn verify_checksum(&self, expected: u64) -> Result<(), ChecksumMismatch> {
+        let actual = self.checksum();
+
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(ChecksumMismatch {
+                expected,
+                actual,
+            })
+        }
+    }
+
+    pub fn verify_checksum_hex(&self, expected: &str) -> Result<(), ChecksumError> {
+        let hex = expected.trim();
+
+        // `from_str_radix` also takes shorter numbers and a leading `+`, which `checksum_hex` never gives
+        let parsed = if hex.len() == 16 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            u64::from_str_radix(hex, 16).ok()
+        } else {
+            None
+        };
+
+        let expected = parsed.ok_or_else(|| ChecksumError::InvalidHex(expected.to_string()))?;
+        Ok(self.verify_checksum(expected)?)
+    }
+}
+
+impl Game {
+    // A stable hash of the field's triangles, which doesn't depend on how the BVH was built
+    // Predictions made in fields with different hashes can't be mixed
+    pub fn geometry_hash(&self) -> u64 {
+        let mut triangles: Vec<_> = self.field.collision_mesh.primitives_with_ids().into_iter().map(|(id, tri)| (id, tri.p.map(|p| p.to_array().map(f32::to_bits)))).collect();
+        triangles.sort_unstable();
+
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+
+        hasher.write_i64(triangles.len() as i64);
+
+        for (id, vertices) in triangles {
+            hasher.write_i64(id.mesh as i64);
+            hasher.write_i64(id.index as i64);
+
+            for bits in vertices.into_iter().flatten() {
+                hasher.write_i64(bits as i64);
+            }
+        }
+
+        hasher.0
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::ball::Ball;
+    use crate::{load_hoops, load_soccar};
+
+    #[test]
+    fn checksum_is_repeatable() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 500.), vec3a(500., 1000., 0.), Vec3A::ZERO);
+        let mut game_2 = game.clone();
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let prediction_2 = Ball::get_ball_prediction_struct(&mut game_2);
+
+        assert_eq!(prediction.checksum(), prediction_2.checksum());
+        assert!(prediction.verify_checksum_hex(&prediction_2.checksum_hex()).is_ok());
+    }
+
+    #[test]
+    fn checksum_detects_changes() {
+        let mut game = load_soccar();
+        let mut prediction = Ball::get_ball_prediction_struct(&mut game);
+        let expected = prediction.checksum();
+
+        // below the quantization, so it shouldn't matter
+        prediction.slices[100].location.x += LOCATION_QUANTUM * 0.1;
+        assert!(prediction.verify_checksum(expected).is_ok());
+
+        prediction.slices[100].location.x += 1.;
+        let error = prediction.verify_checksum(expected).unwrap_err();
+        assert_eq!(error.expected, expected);
+        assert_ne!(error.actual, expected);
+
+        assert_eq!(prediction.verify_checksum_hex(&format!("{expected:016x}")), Err(ChecksumError::Mismatch(error)));
+    }
+
+    #[test]
+    fn invalid_hex_checksums() {
+        let mut game = load_soccar();
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 10);
+
+        // none of these are a checksum, so they're errors rather than a mismatch against 0
+        for hex in ["", "not a checksum", "0", "+123456789abcdef", "0123456789abcdef0"] {
+            assert_eq!(prediction.verify_checksum_hex(hex), Err(ChecksumError::InvalidHex(hex.to_string())));
+        }
+
+        assert!(prediction.verify_checksum_hex(&format!(" {} ", prediction.checksum_hex())).is_ok());
+    }
+
+    #[test]
+    fn geometry_hash() {
+        let soccar = load_soccar();
+
+        assert_eq!(soccar.geometry_hash(), soccar.clone().geometry_hash());
+        assert_eq!(soccar.geometry_hash(), load_soccar().geometry_hash());
+        assert_ne!(soccar.geometry_hash(), load_hoops().geometry_hash());
+    }
+}
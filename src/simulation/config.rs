@@ -0,0 +1,473 @@
+#[cfg(feature = "std")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use glam::Vec3A;
+#[cfg(feature = "std")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use serde::Deserialize;
+#[cfg(feature = "std")]
+use toml::{Spanned, Value};
+
+use super::ball::{Ball, BallPrediction};
+use super::game::{Accuracy, Arena, Game};
+use super::goal::GoalCriteria;
+#[cfg(feature = "std")]
+use super::mutators::DragModel;
+use super::mutators::Mutators;
+
+// Settings for a prediction that can be read from a config file, so they don't have to be hardcoded in every bot
+//
+// Any valid TOML is understood, so the mutators can also be written as `mutators.magnus = 0.01` or `mutators = { magnus = 0.01 }`
+//
+//   num_slices = 720 # or `time = 6.0`, in seconds
+//   accuracy = "full" # or "coarse", or "floor_only"
+//   goal_criteria = "full_ball" # or "center"
+//
+//   [mutators]
+//   gravity = [0, 0, -650]
+//   ball_radius = 91.25
+//   ball_collision_radius = 93.15
+//   drag = "linear" # or "quadratic" or "none"
+//   angular_damping = 0 # how quickly the spin slows down, as a fraction of it per second
+//   magnus = 0 # how much the spin curves the ball in the air
+//   rolling_resistance = 0 # how much a rolling ball is slowed down, as a fraction of gravity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PredictionConfig {
+    pub num_slices: usize,
+    pub accuracy: Accuracy,
+    pub goal_criteria: GoalCriteria,
+    // `None` leaves the ball and the gravity of the game that's predicted alone, like a dropshot ball or a game with low gravity
+    // a [mutators] section in a config file starts from standard soccar, with the keys that are given changed
+    pub mutators: Option<Mutators>,
+}
+
+impl Default for PredictionConfig {
+    fn default() -> Self {
+        Self {
+            num_slices: Ball::STANDARD_NUM_SLICES,
+            accuracy: Accuracy::default(),
+            goal_criteria: GoalCriteria::default(),
+            mutators: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    // 1-based, 0 when the problem isn't on any one line
+    pub line: usize,
+    pub message: String,
+}
+
+#[cfg(feature = "std")]
+impl ConfigError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(feature = "std")]
+type Table = BTreeMap<String, Spanned<Value>>;
+
+#[cfg(feature = "std")]
+// everything is read as a plain toml value first, and checked key by key below, so the errors can say what's wrong with which key
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    num_slices: Option<Spanned<Value>>,
+    time: Option<Spanned<Value>>,
+    accuracy: Option<Spanned<Value>>,
+    goal_criteria: Option<Spanned<Value>>,
+    #[serde(default)]
+    mutators: Table,
+}
+
+#[cfg(feature = "std")]
+// 1-based
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+#[cfg(feature = "std")]
+fn deserialize<T: DeserializeOwned>(source: &str) -> Result<T, ConfigError> {
+    toml::from_str(source).map_err(|error| {
+        let line = error.span().map_or(0, |span| line_of(source, span.start));
+        ConfigError::new(line, error.message())
+    })
+}
+
+#[cfg(feature = "std")]
+struct Entry<'a> {
+    line: usize,
+    key: &'a str,
+    value: &'a Value,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Entry<'a> {
+    fn new(source: &str, key: &'a str, value: &'a Spanned<Value>) -> Self {
+        Self {
+            line: line_of(source, value.span().start),
+            key,
+            value: value.get_ref(),
+        }
+    }
+
+    fn number(&self) -> Result<f64, ConfigError> {
+        match *self.value {
+            Value::Integer(number) => Ok(number as f64),
+            Value::Float(number) => Ok(number),
+            _ => Err(self.error("expected a number")),
+        }
+    }
+
+    fn positive(&self) -> Result<f32, ConfigError> {
+        let number = self.number()?;
+
+        if number.is_finite() && number > 0. {
+            Ok(number as f32)
+        } else {
+            Err(self.error(format!("must be a positive number, got {number}")))
+        }
+    }
+
+    fn non_negative(&self) -> Result<f32, ConfigError> {
+        let number = self.number()?;
+
+        if number.is_finite() && number >= 0. {
+            Ok(number as f32)
+        } else {
+            Err(self.error(format!("must be a number that's at least 0, got {number}")))
+        }
+    }
+
+    fn finite(&self) -> Result<f32, ConfigError> {
+        let number = self.number()?;
+
+        if number.is_finite() {
+            Ok(number as f32)
+        } else {
+            Err(self.error(format!("must be a finite number, got {number}")))
+        }
+    }
+
+    fn string(&self) -> Result<&'a str, ConfigError> {
+        self.value.as_str().ok_or_else(|| self.error("expected a string"))
+    }
+
+    fn error(&self, message: impl fmt::Display) -> ConfigError {
+        ConfigError::new(self.line, format!("`{}`: {message}", self.key))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Mutators {
+    // keys that aren't given keep their standard soccar value
+    pub fn from_toml(source: &str) -> Result<Self, ConfigError> {
+        let mut mutators = Self::default();
+
+        for (key, value) in &deserialize::<Table>(source)? {
+            let entry = Entry::new(source, key, value);
+
+            if entry.value.is_table() {
+                return Err(ConfigError::new(entry.line, format!("unexpected section [{key}]")));
+            }
+
+            mutators.set(&entry)?;
+        }
+
+        mutators.validate()?;
+
+        Ok(mutators)
+    }
+
+    fn set(&mut self, entry: &Entry) -> Result<(), ConfigError> {
+        match entry.key {
+            "gravity" => {
+                let gravity = match entry.value.as_array() {
+                    Some(values) if values.len() == 3 => values
+                        .iter()
+                        .map(|value| Entry { value, ..*entry }.finite())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| entry.error("expected an array of 3 numbers"))?,
+                    _ => return Err(entry.error("expected an array of 3 numbers")),
+                };
+
+                self.gravity = Vec3A::from_slice(&gravity);
+            }
+            "ball_radius" => self.ball_radius = entry.positive()?,
+            "ball_collision_radius" => self.ball_collision_radius = entry.positive()?,
+            "drag" => {
+                self.drag_model = match entry.string()? {
+                    "linear" => DragModel::Linear,
+                    "quadratic" => DragModel::Quadratic,
+                    "none" => DragModel::None,
+                    other => return Err(entry.error(format!("expected \"linear\", \"quadratic\" or \"none\", got \"{other}\""))),
+                };
+            }
+            "angular_damping" => self.angular_damping = entry.non_negative()?,
+            "magnus" => self.magnus = entry.finite()?,
+            "rolling_resistance" => self.rolling_resistance = entry.non_negative()?,
+            key => return Err(ConfigError::new(entry.line, format!("unknown mutator `{key}`, expected one of `gravity`, `ball_radius`, `ball_collision_radius`, `drag`, `angular_damping`, `magnus`, `rolling_resistance`"))),
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.ball_collision_radius < self.ball_radius {
+            return Err(ConfigError::new(0, format!("`ball_collision_radius` ({}) can't be smaller than `ball_radius` ({})", self.ball_collision_radius, self.ball_radius)));
+        }
+
+        Ok(())
+    }
+}
+
+impl PredictionConfig {
+    // ten minutes, which is already far longer than any prediction stays accurate for
+    pub const MAX_NUM_SLICES: usize = 10 * 60 * 120;
+
+    #[cfg(feature = "std")]
+    // keys that aren't given keep their default value
+    pub fn from_toml(source: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let document: Document = deserialize(source)?;
+
+        match (&document.num_slices, &document.time) {
+            (Some(num_slices), Some(time)) => {
+                let (num_slices, time) = (Entry::new(source, "num_slices", num_slices), Entry::new(source, "time", time));
+                let (first, second) = if num_slices.line <= time.line { (num_slices, time) } else { (time, num_slices) };
+
+                return Err(second.error(format!("can't be set along with `{}` on line {}", first.key, first.line)));
+            }
+            (Some(num_slices), None) => {
+                let entry = Entry::new(source, "num_slices", num_slices);
+                let num_slices = entry.number()?;
+
+                if num_slices.fract() != 0. || num_slices < 1. {
+                    return Err(entry.error(format!("must be a positive whole number, got {num_slices}")));
+                }
+
+                if num_slices > Self::MAX_NUM_SLICES as f64 {
+                    return Err(entry.error(format!("can be at most {}, got {num_slices}", Self::MAX_NUM_SLICES)));
+                }
+
+                config.num_slices = num_slices as usize;
+            }
+            (None, Some(time)) => {
+                let entry = Entry::new(source, "time", time);
+                let time = entry.positive()?;
+                let max_time = (Self::MAX_NUM_SLICES as f32 * Ball::SIMULATION_DT).round();
+
+                if time > max_time {
+                    return Err(entry.error(format!("can be at most {max_time} seconds, got {time}")));
+                }
+
+                config.num_slices = (time / Ball::SIMULATION_DT).round() as usize;
+
+                if config.num_slices == 0 {
+                    return Err(entry.error(format!("is shorter than a single slice (1/120th of a second), got {time}")));
+                }
+            }
+            (None, None) => {}
+        }
+
+        if let Some(accuracy) = &document.accuracy {
+            let entry = Entry::new(source, "accuracy", accuracy);
+
+            config.accuracy = match entry.string()? {
+                "full" => Accuracy::Full,
+                "coarse" => Accuracy::Coarse,
+                "floor_only" => Accuracy::FloorOnly,
+                other => return Err(entry.error(format!("expected \"full\", \"coarse\" or \"floor_only\", got \"{other}\""))),
+            };
+        }
+
+        if let Some(goal_criteria) = &document.goal_criteria {
+            let entry = Entry::new(source, "goal_criteria", goal_criteria);
+
+            config.goal_criteria = match entry.string()? {
+                "full_ball" => GoalCriteria::FullBall,
+                "center" => GoalCriteria::Center,
+                other => return Err(entry.error(format!("expected \"full_ball\" or \"center\", got \"{other}\""))),
+            };
+        }
+
+        if !document.mutators.is_empty() {
+            let mut mutators = Mutators::default();
+
+            for (key, value) in &document.mutators {
+                mutators.set(&Entry::new(source, key, value))?;
+            }
+
+            mutators.validate()?;
+            config.mutators = Some(mutators);
+        }
+
+        Ok(config)
+    }
+
+    pub fn apply(&self, game: &mut Game) {
+        if let Some(mutators) = &self.mutators {
+            mutators.apply(game);
+        }
+
+
+        game.set_accuracy(self.accuracy);
+        game.goal_criteria = self.goal_criteria;
+    }
+
+    // applies the config to `game`, then predicts with it
+    pub fn predict(&self, game: &mut Game) -> BallPrediction {
+        self.apply(game);
+        Ball::get_ball_prediction_struct_for_slices(game, self.num_slices)
+    }
+
+    // a prediction from `ball` in an arena that the config was already applied to
+    pub(crate) fn predict_in(&self, arena: &Arena, mut ball: Ball) -> BallPrediction {
+        let mut prediction = BallPrediction {
+            slices: Vec::with_capacity(self.num_slices),
+            ..BallPrediction::default()
+        };
+
+        Ball::predict_in(arena, &mut ball, Ball::SIMULATION_DT, 1, self.num_slices, |_| false, &mut prediction);
+        prediction
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn full_config() {
+        use crate::load_soccar;
+
+        let config = PredictionConfig::from_toml(
+            r#"
+            # a low gravity tournament
+            time = 3.0
+            accuracy = "coarse"
+            goal_criteria = "center"
+
+            [mutators]
+            gravity = [0, 0, -325.5] # half of the usual
+            ball_radius = 100
+            ball_collision_radius = 1_05
+            drag = "none"
+            angular_damping = 0.25
+            magnus = 0.01
+            rolling_resistance = 0.02
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.num_slices, 360);
+        assert_eq!(config.accuracy, Accuracy::Coarse);
+        assert_eq!(config.goal_criteria, GoalCriteria::Center);
+
+        let mutators = config.mutators.unwrap();
+        assert_eq!(mutators.gravity, vec3a(0., 0., -325.5));
+        assert_eq!(mutators.ball_radius, 100.);
+        assert_eq!(mutators.ball_collision_radius, 105.);
+        assert_eq!(mutators.drag_model, DragModel::None);
+        assert_eq!(mutators.angular_damping, 0.25);
+        assert_eq!(mutators.magnus, 0.01);
+        assert_eq!(mutators.rolling_resistance, 0.02);
+
+        let mut game = load_soccar();
+        let prediction = config.predict(&mut game);
+        assert_eq!(prediction.num_slices, 360);
+        assert_eq!(game.gravity.z, -325.5);
+        assert_eq!(game.ball.radius, 100.);
+        assert_eq!(game.ball.drag_model, DragModel::None);
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(PredictionConfig::from_toml("").unwrap(), PredictionConfig::default());
+        assert_eq!(Mutators::from_toml("ball_radius = 91.25").unwrap(), Mutators::default());
+    }
+
+    #[test]
+    fn any_toml() {
+        let config = PredictionConfig::from_toml(
+            r#"
+            time = 2
+            mutators.magnus = 0.01
+            mutators.gravity = [
+                0,
+                0, # a multi-line array
+                -325,
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.num_slices, 240);
+
+        let mutators = config.mutators.unwrap();
+        assert_eq!(mutators.magnus, 0.01);
+        assert_eq!(mutators.gravity, vec3a(0., 0., -325.));
+
+        let config = PredictionConfig::from_toml(r#"mutators = { drag = "none", angular_damping = 0.5 } # not "quadratic""#).unwrap();
+        let mutators = config.mutators.unwrap();
+        assert_eq!(mutators.drag_model, DragModel::None);
+        assert_eq!(mutators.angular_damping, 0.5);
+
+        // a # inside a string isn't a comment
+        assert_eq!(PredictionConfig::from_toml("accuracy = \"#coarse\"").unwrap_err().to_string(), "line 1: `accuracy`: expected \"full\", \"coarse\" or \"floor_only\", got \"#coarse\"");
+    }
+
+    #[test]
+    fn descriptive_errors() {
+        let error = |source: &str| PredictionConfig::from_toml(source).unwrap_err().to_string();
+
+        assert_eq!(error("num_slices = 10\ntime = 2"), "line 2: `time`: can't be set along with `num_slices` on line 1");
+        assert_eq!(error("num_slices = -4"), "line 1: `num_slices`: must be a positive whole number, got -4");
+        assert_eq!(error("num_slices = 1e9"), "line 1: `num_slices`: can be at most 72000, got 1000000000");
+        assert_eq!(error("time = 0.001"), "line 1: `time`: is shorter than a single slice (1/120th of a second), got 0.001");
+        assert_eq!(error("time = 1e9"), "line 1: `time`: can be at most 600 seconds, got 1000000000");
+        assert_eq!(error("accuracy = \"exact\""), "line 1: `accuracy`: expected \"full\", \"coarse\" or \"floor_only\", got \"exact\"");
+        assert_eq!(error("accuracy = 1"), "line 1: `accuracy`: expected a string");
+        assert_eq!(error("\n[mutators]\ngravity = [0, -650]"), "line 3: `gravity`: expected an array of 3 numbers");
+        assert_eq!(error("[mutators]\nball_radius = 0"), "line 2: `ball_radius`: must be a positive number, got 0");
+        assert_eq!(error("[mutators]\nball_radius = 200"), "`ball_collision_radius` (93.15) can't be smaller than `ball_radius` (200)");
+        assert_eq!(error("[mutators]\ndrag = \"cubic\""), "line 2: `drag`: expected \"linear\", \"quadratic\" or \"none\", got \"cubic\"");
+        assert_eq!(error("[mutators]\nangular_damping = -1"), "line 2: `angular_damping`: must be a number that's at least 0, got -1");
+        assert_eq!(error("[mutators]\nmagnus = inf"), "line 2: `magnus`: must be a finite number, got inf");
+        assert!(error("[cars]\nboost = 1").starts_with("line 1: unknown field `cars`"));
+        assert!(error("[mutators]\nboost = 1").starts_with("line 2: unknown mutator `boost`"));
+        assert!(error("speed = 1").starts_with("line 1: unknown field `speed`"));
+        assert!(error("time = 1\ntime = 2").starts_with("line 2: duplicate key `time`"));
+        assert!(error("time 1").starts_with("line 1: "));
+
+        assert_eq!(Mutators::from_toml("[mutators]\nball_radius = 1").unwrap_err().to_string(), "line 1: unexpected section [mutators]");
+    }
+}
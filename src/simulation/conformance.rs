@@ -0,0 +1,233 @@
+use core::error::Error;
+use core::fmt;
+
+use glam::Vec3A;
+
+use super::ball::{Ball, BallPrediction};
+use super::checksum::{quantize, LOCATION_QUANTUM, VELOCITY_QUANTUM};
+use super::game::{Game, GameMode};
+
+// The physics conformance cases, which other implementations and language bindings can check themselves against
+// They're plain text, so they can also be read without this crate, see the top of the file for the format
+pub const CONFORMANCE_CASES: &str = include_str!("../../assets/conformance/cases.txt");
+
+// What a prediction has to come out as, quantized the same way as `BallPrediction::checksum`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConformanceOutput {
+    pub num_slices: usize,
+    pub checksum: u64,
+    // the last slice, in steps of `LOCATION_QUANTUM` and `VELOCITY_QUANTUM`
+    pub final_location: [i64; 3],
+    pub final_velocity: [i64; 3],
+}
+
+impl ConformanceOutput {
+    pub fn of(prediction: &BallPrediction) -> Self {
+        let last = prediction.slices.last().copied().unwrap_or_default();
+
+        Self {
+            num_slices: prediction.slices.len(),
+            checksum: prediction.checksum(),
+            final_location: last.location.to_array().map(|x| quantize(x, LOCATION_QUANTUM)),
+            final_velocity: last.velocity.to_array().map(|x| quantize(x, VELOCITY_QUANTUM)),
+        }
+    }
+}
+
+// A ball to predict from, and what the prediction should be
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConformanceCase {
+    pub name: String,
+    pub mode: GameMode,
+    pub time: f32,
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+    pub expected: ConformanceOutput,
+}
+
+fn parse_mode(name: &str) -> Option<GameMode> {
+    Some(match name {
+        "soccar" => GameMode::Soccar,
+        "hoops" => GameMode::Hoops,
+        "dropshot" => GameMode::Dropshot,
+        "throwback" => GameMode::Throwback,
+        "snowday" => GameMode::Snowday,
+        _ => return None,
+    })
+}
+
+impl ConformanceCase {
+    // One line of the cases file, None if it isn't a valid case
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let name = words.next()?.to_string();
+        let mode = parse_mode(words.next()?)?;
+
+        let mut floats = [0.; 10];
+        for float in &mut floats {
+            *float = words.next()?.parse().ok()?;
+        }
+
+        let num_slices = words.next()?.parse().ok()?;
+        let checksum = u64::from_str_radix(words.next()?, 16).ok()?;
+
+        let mut quantized = [0; 6];
+        for value in &mut quantized {
+            *value = words.next()?.parse().ok()?;
+        }
+
+        if words.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            mode,
+            time: floats[0],
+            location: Vec3A::from_slice(&floats[1..4]),
+            velocity: Vec3A::from_slice(&floats[4..7]),
+            angular_velocity: Vec3A::from_slice(&floats[7..10]),
+            expected: ConformanceOutput {
+                num_slices,
+                checksum,
+                final_location: [quantized[0], quantized[1], quantized[2]],
+                final_velocity: [quantized[3], quantized[4], quantized[5]],
+            },
+        })
+    }
+
+    // The case's field with its ball in it, ready to predict
+    // Panics if the field was left out with its feature
+    pub fn game(&self) -> Game {
+        let mut game = crate::load_mode(self.mode).unwrap_or_else(|| panic!("The {:?} field isn't built in, its feature has to be on to predict this case", self.mode));
+        game.ball.update(self.time, self.location, self.velocity, self.angular_velocity);
+        game
+    }
+
+    // What this crate predicts for the case
+    pub fn predict(&self) -> BallPrediction {
+        Ball::get_ball_prediction_struct_for_slices(&mut self.game(), self.expected.num_slices)
+    }
+}
+
+// All of the cases that come with the crate
+pub fn conformance_cases() -> Vec<ConformanceCase> {
+    CONFORMANCE_CASES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| ConformanceCase::parse(line).unwrap_or_else(|| panic!("Invalid conformance case: {line}")))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub name: String,
+    pub expected: ConformanceOutput,
+    pub actual: ConformanceOutput,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Conformance case {} failed: expected {} slices with checksum {:016x} ending at {:?}, got {} slices with checksum {:016x} ending at {:?}",
+            self.name, self.expected.num_slices, self.expected.checksum, self.expected.final_location, self.actual.num_slices, self.actual.checksum, self.actual.final_location
+        )
+    }
+}
+
+impl Error for ConformanceFailure {}
+
+// Runs every case through `predict`, which should give `expected.num_slices` slices at 120 per second from the case's ball
+// Pass `ConformanceCase::predict` to check this crate itself, or a binding's or another implementation's prediction to check that
+//
+//   run_conformance(|case| my_prediction(case.mode, case.location, case.velocity, case.angular_velocity)).unwrap();
+pub fn run_conformance(mut predict: impl FnMut(&ConformanceCase) -> BallPrediction) -> Result<(), Vec<ConformanceFailure>> {
+    let failures: Vec<_> = conformance_cases()
+        .into_iter()
+        .filter_map(|case| {
+            let actual = ConformanceOutput::of(&predict(&case));
+
+            (actual != case.expected).then_some(ConformanceFailure {
+                name: case.name,
+                expected: case.expected,
+                actual,
+            })
+        })
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cases_parse() {
+        let cases = conformance_cases();
+        assert!(cases.len() >= 6);
+
+        // every case has its own name, so failures can be told apart
+        let mut names: Vec<_> = cases.iter().map(|case| &case.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), cases.len());
+
+        assert!(ConformanceCase::parse("kickoff soccar 0 0 0 93.15 0 0 0 0 0 0 720 00000000 0 0 0 0 0 0").is_some());
+        assert!(ConformanceCase::parse("kickoff rumble 0 0 0 93.15 0 0 0 0 0 0 720 00000000 0 0 0 0 0 0").is_none());
+        assert!(ConformanceCase::parse("kickoff soccar 0 0 0 93.15 0 0 0 0 0 0 720 00000000 0 0 0 0").is_none());
+    }
+
+    #[test]
+    // the cases are spread over every built-in field
+    #[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+    #[cfg_attr(feature = "fast-math", ignore = "fast math doesn't give bit-stable results")]
+    fn crate_conforms() {
+        if let Err(failures) = run_conformance(ConformanceCase::predict) {
+            panic!("{}", failures.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+    fn cases_settle_on_the_floor() {
+        // a ball that's stopped moving has to be resting on something beneath it, not held up against a wall or the rim
+        for case in conformance_cases() {
+            let game = case.game();
+            let last = *case.predict().slices.last().unwrap();
+
+            if last.velocity.length() < 1. {
+                let contact = game.collide(&last.hitbox());
+                assert!(contact.is_some_and(|contact| contact.direction.z > 0.7), "{} ends at rest above the floor, at {}", case.name, last.location);
+            }
+        }
+    }
+
+    #[test]
+    fn failures_are_reported() {
+        // a ball that never moves doesn't match any of them
+        let failures = run_conformance(|case| {
+            let ball = Ball {
+                location: case.location,
+                ..Default::default()
+            };
+
+            BallPrediction {
+                num_slices: case.expected.num_slices,
+                slices: vec![ball; case.expected.num_slices],
+                ..Default::default()
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(failures.len(), conformance_cases().len());
+        assert!(failures.iter().all(|failure| failure.actual.num_slices == failure.expected.num_slices));
+    }
+}
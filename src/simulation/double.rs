@@ -0,0 +1,219 @@
+use alloc::vec::Vec;
+
+use glam::{DVec3, Vec3, Vec3A};
+
+use super::ball::Ball;
+use super::game::{Game, Integrator};
+use super::mutators::{DragModel, QUADRATIC_DRAG_REFERENCE_SPEED};
+use super::physics::FlightParams;
+
+// Double precision free flight, for analysis over long stretches of time in the air where the error adds up
+//
+// Only the flights are in f64: the field and the collisions are in f32, so a step that touches the field is done by the normal simulation,
+// and the state is rounded to f32 for it and starts over from its result. A ball rolling along the floor is no more precise than in f32,
+// but the flights between bounces, and the time, never lose any precision to the running totals
+
+// The ball at one slice of a double precision prediction
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DBallState {
+    pub time: f64,
+    pub location: DVec3,
+    pub velocity: DVec3,
+    pub angular_velocity: DVec3,
+}
+
+fn to_dvec3(v: Vec3A) -> DVec3 {
+    Vec3::from(v).as_dvec3()
+}
+
+fn to_vec3a(v: DVec3) -> Vec3A {
+    Vec3A::from(v.as_vec3())
+}
+
+impl DBallState {
+    pub fn from_ball(ball: &Ball) -> Self {
+        Self {
+            time: ball.time as f64,
+            location: to_dvec3(ball.location),
+            velocity: to_dvec3(ball.velocity),
+            angular_velocity: to_dvec3(ball.angular_velocity),
+        }
+    }
+
+    // the state rounded back to f32, with everything else about the ball taken from `template`
+    pub fn to_ball(&self, template: &Ball) -> Ball {
+        Ball {
+            time: self.time as f32,
+            location: to_vec3a(self.location),
+            velocity: to_vec3a(self.velocity),
+            angular_velocity: to_vec3a(self.angular_velocity),
+            ..*template
+        }
+    }
+}
+
+// the same as `FlightParams::acceleration`, in f64
+fn acceleration(params: &FlightParams, velocity: DVec3, angular_velocity: DVec3) -> DVec3 {
+    let drag = params.drag as f64;
+    let coefficient = match params.drag_model {
+        DragModel::Linear => drag,
+        DragModel::Quadratic => drag * velocity.length() / QUADRATIC_DRAG_REFERENCE_SPEED as f64,
+        DragModel::None => 0.,
+    };
+
+    let acceleration = velocity * coefficient + to_dvec3(params.gravity);
+
+    if params.magnus == 0. {
+        acceleration
+    } else {
+        acceleration + angular_velocity.cross(velocity) * params.magnus as f64
+    }
+}
+
+// the same as `physics::fly`, in f64
+fn fly(state: &mut DBallState, dt: f64, params: &FlightParams) {
+    let w = state.angular_velocity;
+
+    match params.integrator {
+        Integrator::Euler => {
+            state.velocity += acceleration(params, state.velocity, w) * dt;
+            state.location += state.velocity * dt;
+        }
+        Integrator::Rk4 => {
+            let v1 = state.velocity;
+            let a1 = acceleration(params, v1, w);
+            let v2 = v1 + a1 * (dt / 2.);
+            let a2 = acceleration(params, v2, w);
+            let v3 = v1 + a2 * (dt / 2.);
+            let a3 = acceleration(params, v3, w);
+            let v4 = v1 + a3 * dt;
+            let a4 = acceleration(params, v4, w);
+
+            state.location += (v1 + 2. * (v2 + v3) + v4) * (dt / 6.);
+            state.velocity += (a1 + 2. * (a2 + a3) + a4) * (dt / 6.);
+        }
+    }
+}
+
+impl Ball {
+    // One step of the simulation on a double precision state, with `game.ball` kept in sync as the f32 copy
+    // The step is only done in f64 when the ball isn't touching anything, otherwise it's rounded to f32 for it
+    pub fn step_f64_flight(game: &mut Game, state: &mut DBallState, dt: f64) {
+        game.ball = state.to_ball(&game.ball);
+        let time = state.time + dt;
+
+        match game.collide(&game.ball.hitbox()) {
+            None => {
+                fly(state, dt, &FlightParams::from_game(game));
+
+                state.angular_velocity *= (1. - game.ball.angular_damping as f64 * dt).max(0.);
+                state.angular_velocity *= (Ball::W_MAX as f64 / state.angular_velocity.length()).min(1.);
+                state.velocity *= (Ball::V_MAX as f64 / state.velocity.length()).min(1.);
+                state.time = time;
+
+                game.ball = state.to_ball(&game.ball);
+            }
+            contact => {
+                Ball::step_from_contact(game, contact, dt as f32);
+
+                *state = DBallState {
+                    time,
+                    ..DBallState::from_ball(&game.ball)
+                };
+                game.ball.time = time as f32;
+            }
+        }
+    }
+
+    pub fn get_ball_prediction_f64_flight(game: &mut Game) -> Vec<DBallState> {
+        Ball::get_ball_prediction_f64_flight_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+
+    // A prediction like `get_ball_prediction_struct_for_slices`, in double precision while the ball is in the air, see `step_f64_flight`
+    pub fn get_ball_prediction_f64_flight_for_slices(game: &mut Game, num_slices: usize) -> Vec<DBallState> {
+        let mut state = DBallState::from_ball(&game.ball);
+        let start = state.time;
+
+        (1..=num_slices)
+            .map(|slice| {
+                Ball::step_f64_flight(game, &mut state, Ball::SIMULATION_DT as f64);
+                // counting the slices instead of adding up the steps, so the time doesn't drift at all
+                state.time = start + slice as f64 * Ball::SIMULATION_DT as f64;
+                state
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::mesh::Mesh;
+    use crate::simulation::physics::{self, BallState};
+    use crate::load_custom_mesh;
+
+    // a field that's nowhere near the ball, so it's in the air the whole time
+    // with gravity low enough that the ball never gets to the speed limit, which the closed form doesn't have
+    fn open_sky() -> Game {
+        let far_away = Mesh {
+            ids: vec![0, 1, 2],
+            vertices: vec![0., 0., -1e6, 1., 0., -1e6, 0., 1., -1e6],
+        };
+
+        let mut game = load_custom_mesh(&far_away);
+        game.gravity = vec3a(0., 0., -100.);
+        game.ball.update(0., vec3a(0., 0., 0.), vec3a(1000., -300., 2000.), Vec3A::ZERO);
+        game
+    }
+
+    #[test]
+    fn long_flights_stay_exact() {
+        const STEPS: usize = 120 * 60;
+
+        let mut game = open_sky();
+        let params = FlightParams::from_game(&game);
+        let start = BallState::from_ball(&game.ball);
+
+        let double = Ball::get_ball_prediction_f64_flight_for_slices(&mut game, STEPS);
+        let single = (0..STEPS).fold(start, |state, _| physics::free_flight(state, Ball::SIMULATION_DT, params));
+
+        // the closed form of the linear drag steps, see `analytic`
+        let (dt, drag) = (Ball::SIMULATION_DT as f64, params.drag as f64);
+        let a = 1. + drag * dt;
+        let v_inf = -to_dvec3(params.gravity) / drag;
+        let dv = to_dvec3(start.velocity) - v_inf;
+        let a_n = a.powi(STEPS as i32);
+        let exact = to_dvec3(start.location) + dt * (dv * (a * (1. - a_n) / (1. - a)) + v_inf * STEPS as f64);
+
+        let end = double.last().unwrap();
+        let double_error = end.location.distance(exact);
+        let single_error = to_dvec3(single.location).distance(exact);
+
+        assert!(double_error < 1e-3, "{double_error}");
+        assert!(double_error * 100. < single_error, "{double_error} vs {single_error}");
+        assert_eq!(end.time, STEPS as f64 * dt);
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn bounces_match_the_simulation() {
+        use crate::load_soccar;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(500., 300., 0.), vec3a(1., 0., 0.));
+
+        let single = Ball::get_ball_prediction_struct(&mut game.clone());
+        let double = Ball::get_ball_prediction_f64_flight(&mut game);
+
+        assert_eq!(double.len(), single.num_slices);
+
+        for (single, double) in single.slices.iter().zip(&double) {
+            assert!(to_dvec3(single.location).distance(double.location) < 1., "{} vs {}", single.location, double.location);
+        }
+
+        // and the game's ball is left at the end, like any other prediction
+        assert_eq!(game.ball.location, to_vec3a(double.last().unwrap().location));
+    }
+}
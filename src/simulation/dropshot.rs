@@ -0,0 +1,328 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::geometry::{Aabb, Ray, Sphere, Tri};
+
+// How much a dropshot tile has been hit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileState {
+    #[default]
+    Intact,
+    Damaged,
+    // the ball falls through broken tiles
+    Broken,
+}
+
+// The hexagonal tiles that make up the floor of the dropshot field, with blue's half (negative y) first
+//
+// Each half has 7 rows of tiles, going from 13 tiles next to the center line down to 7 against the back wall,
+// and the tiles are sized to fit between the center line and the closest part of the walls.
+// The layout follows the game's, but the size and position of each tile haven't been checked against it
+#[derive(Clone, Debug)]
+pub struct DropshotTiles {
+    pub floor_height: f32,
+    // from the center of a tile to its corners, which point along y
+    pub tile_radius: f32,
+    centers: Vec<Vec3A>,
+    states: Vec<TileState>,
+}
+
+impl DropshotTiles {
+    pub const NUM_TILES: usize = 140;
+    pub const NUM_ROWS: usize = 7;
+    const CENTER_ROW_TILES: usize = 13;
+
+    // `wall_distance` is the closest the walls get to the center of the field
+    pub fn new(floor_height: f32, wall_distance: f32) -> Self {
+        // the first row is one tile radius from the center line, the rows are 1.5 tile radii apart, and the last row's corners reach the walls
+        let tile_radius = wall_distance / (1. + 1.5 * (DropshotTiles::NUM_ROWS - 1) as f32 + 1.);
+        let spacing = 3f32.sqrt() * tile_radius;
+
+        let mut centers = Vec::with_capacity(DropshotTiles::NUM_TILES);
+
+        for side in [-1., 1.] {
+            for row in 0..DropshotTiles::NUM_ROWS {
+                let num_tiles = DropshotTiles::CENTER_ROW_TILES - row;
+                let y = side * tile_radius * (1. + 1.5 * row as f32);
+
+                for i in 0..num_tiles {
+                    let x = (i as f32 - (num_tiles - 1) as f32 / 2.) * spacing;
+                    centers.push(Vec3A::new(x, y, floor_height));
+                }
+            }
+        }
+
+        Self {
+            floor_height,
+            tile_radius,
+            states: vec![TileState::Intact; centers.len()],
+            centers,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.centers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centers.is_empty()
+    }
+
+    pub fn center(&self, tile: usize) -> Vec3A {
+        self.centers[tile]
+    }
+
+    pub fn state(&self, tile: usize) -> TileState {
+        self.states[tile]
+    }
+
+    pub fn states(&self) -> &[TileState] {
+        &self.states
+    }
+
+    pub fn set_state(&mut self, tile: usize, state: TileState) {
+        self.states[tile] = state;
+    }
+
+    pub fn break_tile(&mut self, tile: usize) {
+        self.set_state(tile, TileState::Broken);
+    }
+
+    // an intact tile gets damaged, and a damaged tile breaks
+    pub fn damage(&mut self, tile: usize) {
+        self.states[tile] = match self.states[tile] {
+            TileState::Intact => TileState::Damaged,
+            TileState::Damaged | TileState::Broken => TileState::Broken,
+        };
+    }
+
+    // puts every tile back, like at kickoff
+    pub fn reset(&mut self) {
+        self.states.fill(TileState::Intact);
+    }
+
+    // the tile that's under this point, ignoring height
+    pub fn tile_at(&self, point: Vec3A) -> Option<usize> {
+        let apothem = 3f32.sqrt() / 2. * self.tile_radius;
+        // the normals of the sides of a hexagon with corners along y
+        let sides = [Vec3A::X, Vec3A::new(0.5, 3f32.sqrt() / 2., 0.), Vec3A::new(-0.5, 3f32.sqrt() / 2., 0.)];
+
+        self.centers.iter().position(|center| {
+            let offset = Vec3A::new(point.x - center.x, point.y - center.y, 0.);
+            offset.length_squared() <= self.tile_radius * self.tile_radius && sides.iter().all(|side| side.dot(offset).abs() <= apothem)
+        })
+    }
+
+    // if there's a broken tile under this point
+    pub fn is_hole(&self, point: Vec3A) -> bool {
+        self.tile_at(point).is_some_and(|tile| self.states[tile] == TileState::Broken)
+    }
+
+    // if any part of a broken tile is inside of the box, ignoring height
+    pub(crate) fn has_hole_in(&self, area: &Aabb) -> bool {
+        self.centers.iter().zip(&self.states).any(|(center, state)| {
+            *state == TileState::Broken
+                && center.x + self.tile_radius >= area.min.x
+                && center.x - self.tile_radius <= area.max.x
+                && center.y + self.tile_radius >= area.min.y
+                && center.y - self.tile_radius <= area.max.y
+        })
+    }
+
+    // The floor is flat, so the ball only ever touches it right underneath its center
+    // Once that's over a broken tile, the floor isn't there for the ball anymore, even though the edges of the hole would still catch it in the game
+    pub(crate) fn removes_floor_under(&self, s: &Sphere) -> bool {
+        s.center.z - s.radius <= self.floor_height && self.is_hole(s.center)
+    }
+
+    pub(crate) fn is_floor(&self, tri: &Tri) -> bool {
+        tri.p.iter().all(|p| (p.z - self.floor_height).abs() < 0.01)
+    }
+
+    // How close the segment gets to the floor, leaving out the parts of it that are over broken tiles
+    // Like for the ball, the floor is only there right underneath the segment, so this is its height above (or below) it,
+    // checked where it crosses the floor and every so often along the way
+    pub(crate) fn floor_distance(&self, segment: &Ray) -> f32 {
+        let height = |point: Vec3A| if self.is_hole(point) { f32::INFINITY } else { (point.z - self.floor_height).abs() };
+        let end = segment.start + segment.direction;
+
+        if (segment.start.z - self.floor_height) * (end.z - self.floor_height) <= 0. && segment.direction.z != 0. {
+            let crossing = segment.start + segment.direction * ((self.floor_height - segment.start.z) / segment.direction.z);
+
+            if !self.is_hole(crossing) {
+                return 0.;
+            }
+        }
+
+        let steps = (segment.direction.length() / (self.tile_radius / 8.)).ceil().max(1.) as usize;
+        (0..=steps).map(|i| height(segment.start + segment.direction * (i as f32 / steps as f32))).fold(f32::INFINITY, f32::min)
+    }
+}
+
+#[cfg(all(test, feature = "dropshot"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_dropshot;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn layout() {
+        let game = load_dropshot();
+        let tiles = game.dropshot_tiles.as_ref().unwrap();
+
+        assert_eq!(tiles.len(), DropshotTiles::NUM_TILES);
+
+        for tile in 0..tiles.len() {
+            let center = tiles.center(tile);
+            assert_eq!(tiles.tile_at(center), Some(tile));
+            assert_eq!(center.y < 0., tile < DropshotTiles::NUM_TILES / 2);
+
+            // every tile is inside of the field, above the floor
+            let above = Sphere {
+                center: center + Vec3A::Z * 200.,
+                radius: 100.,
+            };
+            assert!(game.collide(&above).is_none(), "{center}");
+        }
+
+        // the center line is only touched by the tiles' corners
+        assert_eq!(tiles.tile_at(vec3a(3f32.sqrt() / 2. * tiles.tile_radius, 0., 0.)), None);
+    }
+
+    #[test]
+    fn damage_breaks_tiles() {
+        let mut tiles = load_dropshot().dropshot_tiles.unwrap();
+
+        tiles.damage(3);
+        assert_eq!(tiles.state(3), TileState::Damaged);
+        assert!(!tiles.is_hole(tiles.center(3)));
+
+        tiles.damage(3);
+        assert_eq!(tiles.state(3), TileState::Broken);
+        assert!(tiles.is_hole(tiles.center(3)));
+        assert_eq!(tiles.states().iter().filter(|state| **state != TileState::Intact).count(), 1);
+
+        tiles.reset();
+        assert_eq!(tiles.state(3), TileState::Intact);
+    }
+
+    #[test]
+    fn falls_through_broken_tiles() {
+        let mut game = load_dropshot();
+        let tiles = game.dropshot_tiles.as_mut().unwrap();
+        let center = tiles.center(100);
+        tiles.break_tile(100);
+
+        game.ball.update(0., center + Vec3A::Z * 500., Vec3A::ZERO, Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+        assert!(prediction.slices.last().unwrap().location.z < 0.);
+
+        // the tile next to it is still there
+        let next = game.dropshot_tiles.as_ref().unwrap().center(101);
+        game.ball.update(0., next + Vec3A::Z * 500., Vec3A::ZERO, Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_time(&mut game, &2.);
+        assert!(prediction.slices.iter().all(|ball| ball.location.z > 0.));
+    }
+
+    #[test]
+    fn boxes_fall_into_broken_tiles() {
+        use glam::Mat3A;
+
+        use crate::simulation::geometry::Obb;
+
+        let mut game = load_dropshot();
+        let center = game.dropshot_tiles.as_ref().unwrap().center(100);
+
+        // a car sitting on the tile, slightly sunk into it
+        let car = Obb {
+            center: center + Vec3A::Z * 17.,
+            orientation: Mat3A::IDENTITY,
+            half_extents: vec3a(59.0, 42.1, 18.1),
+        };
+
+        let contact = game.obb_collides(&car).unwrap();
+        assert!((contact.normal - Vec3A::Z).length() < 1e-4);
+        assert_eq!(contact.depth, game.field.obb_collides(&car).unwrap().depth);
+
+        // the field still has the tile, but the game doesn't
+        game.dropshot_tiles.as_mut().unwrap().break_tile(100);
+        assert!(game.field.obb_collides(&car).is_some());
+        assert!(game.obb_collides(&car).is_none());
+    }
+
+    #[test]
+    fn seeing_through_broken_tiles() {
+        let mut game = load_dropshot();
+        let tiles = game.dropshot_tiles.as_ref().unwrap();
+        let (center, next) = (tiles.center(100), tiles.center(101));
+
+        let through = (center + vec3a(0., 0., 500.), center - vec3a(0., 0., 500.));
+        let beside = (next + vec3a(0., 0., 500.), next - vec3a(0., 0., 500.));
+        let above = (center + vec3a(0., 0., 500.), next + vec3a(0., 0., 500.));
+
+        assert_eq!(game.lines_of_sight(&[through, beside, above]), [false, false, true]);
+
+        // only the line down through the broken tile can see under the floor, which the field alone doesn't know about
+        game.dropshot_tiles.as_mut().unwrap().break_tile(100);
+        assert_eq!(game.lines_of_sight(&[through, beside, above]), [true, false, true]);
+        assert!(!game.field.line_of_sight(through.0, through.1));
+    }
+
+    #[test]
+    fn clearance_over_broken_tiles() {
+        let mut game = load_dropshot();
+        let radius = game.ball.collision_radius;
+        let tiles = game.dropshot_tiles.as_ref().unwrap();
+        let (center, next) = (tiles.center(100), tiles.center(101));
+
+        let through = [center + Vec3A::Z * 500., center + Vec3A::Z * 50., center - Vec3A::Z * 200.];
+        let beside = [next + Vec3A::Z * 500., next + Vec3A::Z * 50.];
+
+        // without any broken tiles, it's the same as the field's
+        game.dropshot_tiles.as_mut().unwrap().damage(100);
+        assert_eq!(game.clearance_along(&through, radius), game.field.clearance_along(&through, radius));
+
+        game.dropshot_tiles.as_mut().unwrap().break_tile(100);
+
+        // straight down through the hole, where there's nothing to hit
+        let clearance = game.clearance_along(&through, radius);
+        assert_eq!(clearance.len(), 2);
+        assert!(clearance.iter().all(|clearance| *clearance > 0.), "{clearance:?}");
+        assert!(game.field.clearance_along(&through, radius)[0] < 0.);
+
+        // the tile next to it is still there
+        let clearance = game.clearance_along(&beside, radius);
+        assert!((clearance[0] - (50. - radius)).abs() < 0.01, "{clearance:?}");
+        assert!((game.clearance_along(&beside[..1], radius)[0] - (500. - radius)).abs() < 0.01);
+    }
+
+    #[test]
+    fn shortcuts_see_broken_tiles() {
+        use crate::simulation::analytic::reachable_floor_height;
+        use crate::simulation::game::Accuracy;
+
+        let mut game = load_dropshot();
+        let center = game.dropshot_tiles.as_ref().unwrap().center(100);
+        game.ball.update(0., center + Vec3A::Z * 500., Vec3A::ZERO, Vec3A::ZERO);
+
+        // over an intact tile, the floor is all that's in reach
+        assert!(reachable_floor_height(&game, 240).is_some());
+
+        game.dropshot_tiles.as_mut().unwrap().break_tile(100);
+        assert!(reachable_floor_height(&game, 240).is_none());
+
+        // so the analytic prediction falls back to stepping, and falls through the hole like the floor only one does
+        let analytic = Ball::get_ball_prediction_struct_analytic_for_slices(&mut game.clone(), 240);
+        let floor_only = Ball::get_ball_prediction_struct_with_accuracy(&mut game, Accuracy::FloorOnly, 240);
+        assert!(analytic.slices.last().unwrap().location.z < 0.);
+        assert!(floor_only.slices.last().unwrap().location.z < 0.);
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+
+use super::ball::{Ball, BallPrediction};
+
+// Total mechanical energy per unit of mass: kinetic, rotational, and potential
+pub fn specific_energy(ball: &Ball, gravity: Vec3A) -> f32 {
+    0.5 * ball.velocity.length_squared() + 0.5 * ball.moi / Ball::M * ball.angular_velocity.length_squared() - gravity.dot(ball.location)
+}
+
+// A step where the ball gained energy, which nothing in the simulation should be able to do on its own
+#[derive(Clone, Copy, Debug)]
+pub struct EnergyViolation {
+    // the index of the slice at the end of the step
+    pub slice: usize,
+    pub before: f32,
+    pub after: f32,
+}
+
+impl EnergyViolation {
+    pub fn gain(&self) -> f32 {
+        self.after - self.before
+    }
+}
+
+impl BallPrediction {
+    // Every step (starting from `start`, the ball the prediction was made from) where the energy went up by more than `tolerance`
+    pub fn energy_violations(&self, start: &Ball, gravity: Vec3A, tolerance: f32) -> Vec<EnergyViolation> {
+        let mut before = specific_energy(start, gravity);
+        let mut violations = Vec::new();
+
+        for (slice, ball) in self.slices.iter().enumerate() {
+            let after = specific_energy(ball, gravity);
+
+            if after - before > tolerance {
+                violations.push(EnergyViolation {
+                    slice,
+                    before,
+                    after,
+                });
+            }
+
+            before = after;
+        }
+
+        violations
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn flight_loses_energy() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(1000., -500., 800.), vec3a(1., 2., 3.));
+        let start = game.ball;
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 60);
+
+        assert!(prediction.energy_violations(&start, game.gravity, 0.).is_empty());
+    }
+
+    #[test]
+    fn bounces_lose_energy() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(300., 200., -500.), Vec3A::ZERO);
+        let start = game.ball;
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let violations = prediction.energy_violations(&start, game.gravity, 1000.);
+
+        assert!(violations.is_empty(), "{violations:?}");
+        assert!(specific_energy(prediction.slices.last().unwrap(), game.gravity) < specific_energy(&start, game.gravity));
+    }
+
+    #[test]
+    fn catches_gains() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+        let start = game.ball;
+
+        let mut prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 60);
+        prediction.slices[30].velocity.z += 500.;
+
+        let violations = prediction.energy_violations(&start, game.gravity, 1.);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].slice, 30);
+        assert!(violations[0].gain() > 0.);
+    }
+}
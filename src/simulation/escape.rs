@@ -0,0 +1,118 @@
+use core::fmt;
+
+use glam::Vec3A;
+
+use super::ball::Ball;
+use super::game::Arena;
+
+// The ball got out of the field, by tunneling through it or through a hole in a custom mesh
+// The prediction stops at the last slice inside, instead of going on with a ball falling forever,
+// and this has what's needed to reproduce it
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscapeEvent {
+    // the index the first slice outside would have had, which is also how many slices were kept
+    pub slice: usize,
+    // the ball at the start and end of the step it got out in
+    pub before: Ball,
+    pub after: Ball,
+}
+
+impl EscapeEvent {
+    // If the step from `before` to `after` took the ball out of the field's bounding box on a side that gravity won't bring it back from,
+    // like through the floor or a wall, which a ball that's turned into NaNs always counts as
+    // Fields without a ceiling can be flat, so flying up out of the box isn't an escape
+    pub(crate) fn check(arena: &Arena, slice: usize, before: &Ball, after: &Ball) -> Option<Self> {
+        let field = arena.field.collision_mesh.global_box;
+        let (p, radius) = (after.location, after.collision_radius);
+
+        // how far past the box the ball is along each axis, positive past the max side and negative past the min side
+        let outside = (p - (field.max + radius)).max(Vec3A::ZERO) - ((field.min - radius) - p).max(Vec3A::ZERO);
+        let escaped = (outside.cmpne(Vec3A::ZERO) & (outside * arena.gravity).cmpge(Vec3A::ZERO)).any();
+
+        if p.is_finite() && !escaped {
+            return None;
+        }
+
+        Some(Self {
+            slice,
+            before: *before,
+            after: *after,
+        })
+    }
+
+    pub fn time(&self) -> f32 {
+        self.after.time
+    }
+}
+
+impl fmt::Display for EscapeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = (&self.before, &self.after);
+
+        write!(f, "The ball left the arena at {:.3}s (slice {}), going from {} moving {} spinning {} to {}", after.time, self.slice, before.location, before.velocity, before.angular_velocity, after.location)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::simulation::game::Game;
+    use crate::simulation::mesh::Mesh;
+    use crate::load_custom_mesh;
+
+    // a square floor with one wall at -x, so the ball can roll off of the other edges
+    fn ledge() -> Game {
+        let mesh = Mesh {
+            ids: vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7],
+            vertices: vec![
+                -1000., -1000., 0., 1000., -1000., 0., 1000., 1000., 0., -1000., 1000., 0., //
+                -1000., -1000., 0., -1000., 1000., 0., -1000., 1000., 1000., -1000., -1000., 1000.,
+            ],
+        };
+
+        let mut game = load_custom_mesh(&mesh);
+        game.ball.update(0., vec3a(0., 0., 200.), vec3a(1000., 0., 0.), Vec3A::ZERO);
+        game
+    }
+
+    #[test]
+    fn rolling_off_of_the_edge() {
+        let prediction = Ball::get_ball_prediction_struct(&mut ledge());
+        let escape = prediction.escape.unwrap();
+
+        // the prediction stops at the last slice that's still in the field
+        assert_eq!(prediction.slices.len(), escape.slice);
+        assert_eq!(prediction.num_slices, escape.slice);
+        assert_eq!(prediction.slices.last().unwrap().location, escape.before.location);
+        let edge = 1000. + escape.after.collision_radius;
+        assert!(escape.before.location.x <= edge && escape.after.location.x > edge);
+        assert!(escape.time() > 0.5 && escape.time() < 2., "{}", escape.time());
+        assert!(escape.to_string().starts_with("The ball left the arena at "), "{escape}");
+
+        let summary = prediction.summary(&ledge());
+        assert_eq!(summary.escape_time, Some(escape.time()));
+        assert!(summary.to_string().ends_with(&format!("no goal, escaped the arena at {:.2}s", escape.time())), "{summary}");
+
+        // and the goal prediction stops at the same place
+        let (with_goal, goal) = Ball::get_ball_prediction_struct_with_goal(&mut ledge());
+        assert!(goal.is_none());
+        assert_eq!(with_goal.slices.len(), escape.slice);
+        assert_eq!(with_goal.escape.unwrap().after.location, escape.after.location);
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn staying_in_the_field() {
+        use crate::load_soccar;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(2000., 1000., 500.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        assert!(prediction.escape.is_none());
+        assert_eq!(prediction.num_slices, Ball::STANDARD_NUM_SLICES);
+    }
+}
@@ -0,0 +1,115 @@
+use core::fmt::Write;
+
+use super::ball::BallPrediction;
+
+// The names of the values in each row of `to_csv` and `to_json`, the same as `PredictionTable`'s minus the rollout id
+pub const EXPORT_COLUMNS: [&str; 10] = ["t", "x", "y", "z", "vx", "vy", "vz", "wx", "wy", "wz"];
+
+// JSON doesn't have NaN or infinity, a ball that blew up is better as a null than as a file that doesn't parse
+fn write_json_number(out: &mut String, value: f32) {
+    if value.is_finite() {
+        write!(out, "{value}").unwrap();
+    } else {
+        out.push_str("null");
+    }
+}
+
+// Quick exports for plotting and looking at a prediction by hand, with a row per slice of its time, location, velocity and angular velocity
+// Numbers are written as the shortest text that reads back to the same f32
+impl BallPrediction {
+    // a header row with the column names, then one row per slice
+    pub fn to_csv(&self) -> String {
+        let (data, [_, row_len]) = self.to_row_major();
+        let mut csv = EXPORT_COLUMNS.join(",");
+        csv.push('\n');
+
+        for row in data.chunks_exact(row_len) {
+            for (i, value) in row.iter().enumerate() {
+                if i > 0 {
+                    csv.push(',');
+                }
+
+                write!(csv, "{value}").unwrap();
+            }
+
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    // an array with an object per slice, like [{"t":0.008333334,"x":0,...},...]
+    pub fn to_json(&self) -> String {
+        let (data, [_, row_len]) = self.to_row_major();
+        let mut json = String::from("[");
+
+        for (i, row) in data.chunks_exact(row_len).enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            json.push('{');
+
+            for (j, (name, value)) in EXPORT_COLUMNS.iter().zip(row).enumerate() {
+                if j > 0 {
+                    json.push(',');
+                }
+
+                write!(json, "\"{name}\":").unwrap();
+                write_json_number(&mut json, *value);
+            }
+
+            json.push('}');
+        }
+
+        json.push(']');
+        json
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    fn prediction() -> BallPrediction {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 300.), vec3a(100., 200., 0.), vec3a(1., 0., 0.));
+        Ball::get_ball_prediction_struct_for_slices(&mut game, 30)
+    }
+
+    #[test]
+    fn csv() {
+        let prediction = prediction();
+        let csv = prediction.to_csv();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), "t,x,y,z,vx,vy,vz,wx,wy,wz");
+
+        let rows: Vec<Vec<f32>> = lines.map(|line| line.split(',').map(|value| value.parse().unwrap()).collect()).collect();
+        assert_eq!(rows.len(), 30);
+        assert_eq!(rows[12][0], prediction.slices[12].time);
+        assert_eq!(rows[12][3], prediction.slices[12].location.z);
+        assert_eq!(rows[12][7], prediction.slices[12].angular_velocity.x);
+    }
+
+    #[test]
+    fn json() {
+        let prediction = prediction();
+        let json = prediction.to_json();
+
+        assert!(json.starts_with("[{\"t\":") && json.ends_with("}]"));
+        assert_eq!(json.matches('{').count(), 30);
+        assert!(json.contains(&format!("\"vy\":{}", prediction.slices[0].velocity.y)));
+
+        assert_eq!(BallPrediction::default().to_json(), "[]");
+
+        let mut broken = prediction;
+        broken.slices.truncate(1);
+        broken.slices[0].location.x = f32::NAN;
+        assert!(broken.to_json().contains("\"x\":null"));
+    }
+}
@@ -0,0 +1,189 @@
+use core::error::Error;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use super::ball::{Ball, BallPrediction};
+use super::game::Game;
+
+// Why a prediction was given up on, instead of returning slices full of NaNs or a ball flying off into nothing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PredictionError {
+    // the ball's location, velocity or spin had a NaN or an infinity in it to begin with
+    NonFiniteInput,
+    // the ball left the field's bounding box, or its state stopped being finite, at this time
+    EscapedArena {
+        time: f32,
+    },
+    Cancelled,
+    BudgetExceeded,
+}
+
+impl fmt::Display for PredictionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredictionError::NonFiniteInput => write!(f, "The ball's starting state isn't finite"),
+            PredictionError::EscapedArena {
+                time,
+            } => write!(f, "The ball left the arena at {time:.3}s"),
+            PredictionError::Cancelled => write!(f, "The prediction was cancelled"),
+            PredictionError::BudgetExceeded => write!(f, "The prediction ran past its deadline"),
+        }
+    }
+}
+
+impl Error for PredictionError {}
+
+// When a fallible prediction should stop early, both checked once per slice
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PredictionLimits<'a> {
+    // set from another thread to stop the prediction
+    pub cancel: Option<&'a AtomicBool>,
+    #[cfg(feature = "std")]
+    // `Instant` isn't available in the browser, so leave this unset there, and there's no clock at all without std
+    pub deadline: Option<Instant>,
+}
+
+impl PredictionLimits<'_> {
+    fn check(&self) -> Result<(), PredictionError> {
+        if self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            return Err(PredictionError::Cancelled);
+        }
+
+        #[cfg(feature = "std")]
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(PredictionError::BudgetExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+impl Ball {
+    fn is_finite(&self) -> bool {
+        self.location.is_finite() && self.velocity.is_finite() && self.angular_velocity.is_finite()
+    }
+
+    pub fn try_get_ball_prediction_struct(game: &mut Game, limits: &PredictionLimits) -> Result<BallPrediction, PredictionError> {
+        Ball::try_get_ball_prediction_struct_for_slices(game, Ball::STANDARD_NUM_SLICES, limits)
+    }
+
+    // The same slices as `get_ball_prediction_struct_for_slices`, or the reason it couldn't finish them
+    pub fn try_get_ball_prediction_struct_for_slices(game: &mut Game, num_slices: usize, limits: &PredictionLimits) -> Result<BallPrediction, PredictionError> {
+        if !game.ball.is_finite() {
+            return Err(PredictionError::NonFiniteInput);
+        }
+
+        limits.check()?;
+
+        // the limits are checked before every slice after the first, so a prediction that's already finished isn't thrown away
+        let mut error = None;
+        let mut remaining = num_slices;
+        let prediction = Ball::get_ball_prediction_struct_until_for_slices(game, num_slices, |ball| {
+            remaining -= 1;
+
+            error = if !ball.is_finite() {
+                Some(PredictionError::EscapedArena {
+                    time: ball.time,
+                })
+            } else if remaining > 0 {
+                limits.check().err()
+            } else {
+                None
+            };
+
+            error.is_some()
+        });
+
+        if let Some(escape) = prediction.escape {
+            return Err(PredictionError::EscapedArena {
+                time: escape.time(),
+            });
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(prediction),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use std::time::Duration;
+
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn same_as_the_normal_prediction() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(1000., 500., 0.), vec3a(1., 2., 3.));
+
+        let expected = Ball::get_ball_prediction_struct(&mut game.clone());
+        let prediction = Ball::try_get_ball_prediction_struct(&mut game, &PredictionLimits::default()).unwrap();
+
+        assert_eq!(prediction.num_slices, expected.num_slices);
+        assert_eq!(prediction.checksum(), expected.checksum());
+        assert_eq!(prediction.bounces, expected.bounces);
+
+        // the flags too, which the ball touching the field sets on some of the slices
+        assert!(expected.slices.iter().any(|slice| slice.flags != 0));
+        for (slice, expected) in prediction.slices.iter().zip(&expected.slices) {
+            assert_eq!(slice.flags, expected.flags);
+        }
+    }
+
+    #[test]
+    fn non_finite_input() {
+        let mut game = load_soccar();
+        game.ball.velocity.x = f32::NAN;
+
+        let result = Ball::try_get_ball_prediction_struct(&mut game, &PredictionLimits::default());
+        assert_eq!(result.err(), Some(PredictionError::NonFiniteInput));
+    }
+
+    #[test]
+    fn escaped_arena() {
+        let mut game = load_soccar();
+        // under the floor, and falling
+        let bottom = game.field.collision_mesh.global_box.min.z;
+        game.ball.update(0., vec3a(0., 0., bottom - 200.), vec3a(0., 0., -1000.), Vec3A::ZERO);
+
+        match Ball::try_get_ball_prediction_struct(&mut game, &PredictionLimits::default()) {
+            Err(PredictionError::EscapedArena {
+                time,
+            }) => assert_eq!(time, Ball::SIMULATION_DT),
+            result => panic!("{:?}", result.map(|prediction| prediction.num_slices)),
+        }
+    }
+
+    #[test]
+    fn cancelled_and_over_budget() {
+        let mut game = load_soccar();
+
+        let cancel = AtomicBool::new(true);
+        let cancelled = PredictionLimits {
+            cancel: Some(&cancel),
+            ..PredictionLimits::default()
+        };
+        assert_eq!(Ball::try_get_ball_prediction_struct(&mut game.clone(), &cancelled).err(), Some(PredictionError::Cancelled));
+
+        let over_budget = PredictionLimits {
+            deadline: Some(Instant::now()),
+            ..PredictionLimits::default()
+        };
+        assert_eq!(Ball::try_get_ball_prediction_struct(&mut game.clone(), &over_budget).err(), Some(PredictionError::BudgetExceeded));
+
+        // and with plenty of time, and nothing cancelling it
+        cancel.store(false, Ordering::Relaxed);
+        let limits = PredictionLimits {
+            cancel: Some(&cancel),
+            deadline: Some(Instant::now() + Duration::from_secs(60)),
+        };
+        assert!(Ball::try_get_ball_prediction_struct(&mut game, &limits).is_ok());
+    }
+}
@@ -1,9 +1,17 @@
-use std::f32::consts::{FRAC_PI_3, FRAC_PI_6};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::{FRAC_PI_3, FRAC_PI_6};
 
-use glam::{const_mat3a, vec3a, Mat3A, Vec3, Vec3A};
+use glam::const_mat3a;
+use glam::Vec3;
+use glam::{vec3a, Mat3A, Vec3A};
 
 use super::bvh::Bvh;
-use super::mesh::Mesh;
+use super::dropshot::DropshotTiles;
+use super::instanced::Part;
+use super::instanced::{BvhCache, InstancedBvh};
+use super::material::MaterialId;
+use super::mesh::{triangle_ids, Mesh};
 use crate::linear_algebra::mat::MatrixExt;
 use crate::linear_algebra::math::{axis_to_rotation, dot};
 
@@ -11,6 +19,35 @@ const FLIP_X: Mat3A = const_mat3a!([-1., 0., 0.], [0., 1., 0.], [0., 0., 1.]);
 
 const FLIP_Y: Mat3A = const_mat3a!([1., 0., 0.], [0., -1., 0.], [0., 0., 1.]);
 
+// Every triangle gets a stable id from the mesh it came from
+fn build_field(meshes: Vec<&Mesh>) -> Bvh {
+    let ids = triangle_ids(&meshes);
+    let triangles = Mesh::from(meshes).to_triangles();
+    Bvh::from_with_ids(&triangles, &ids)
+}
+
+// The flat field, with its own copy of every placement of each mesh
+fn build_parts(parts: &[Part]) -> Bvh {
+    let meshes: Vec<Mesh> = parts.iter().flat_map(Part::meshes).collect();
+    build_field(meshes.iter().collect())
+}
+
+// The material of every mesh that `build_parts` puts together, in the same order as the meshes in their triangle ids
+pub fn material_tags(parts: &[Part]) -> Vec<MaterialId> {
+    parts.iter().flat_map(|part| part.transforms.iter().map(|_| part.material)).collect()
+}
+
+// A field made from any mesh, like one loaded from a file
+// Nothing checks that it's watertight, see `Bvh::is_watertight`
+pub fn initialize_custom(mesh: &Mesh) -> Bvh {
+    initialize_custom_meshes(&[mesh])
+}
+
+// A field made from several meshes, which keep their own ids so that each one can be made of something different
+pub fn initialize_custom_meshes(meshes: &[&Mesh]) -> Bvh {
+    Bvh::from_with_ids(&Mesh::from(meshes.to_vec()).to_triangles(), &triangle_ids(meshes))
+}
+
 fn quad(p: Vec3A, e1: Vec3A, e2: Vec3A) -> Mesh {
     let vertices = [p + e1 + e2, p - e1 + e2, p - e1 - e2, p + e1 - e2].iter().flat_map(|vertex| vertex.to_array()).collect();
 
@@ -20,79 +57,79 @@ fn quad(p: Vec3A, e1: Vec3A, e2: Vec3A) -> Mesh {
     }
 }
 
-pub fn initialize_soccar(soccar_corner: &Mesh, soccar_goal: &Mesh, soccar_ramps_0: &Mesh, soccar_ramps_1: &Mesh) -> Bvh {
-    let floor = quad(Vec3A::default(), vec3a(4096., 0., 0.), vec3a(0., 5120., 0.));
+// The meshes of the soccar field, and where each of them goes
+fn soccar_parts(soccar_corner: &Mesh, soccar_goal: &Mesh, soccar_ramps_0: &Mesh, soccar_ramps_1: &Mesh) -> Vec<Part> {
+    // the floor and ceiling go past the goal line (y = 5120) because the goal's own floor only starts at y = 5354,
+    // so stopping at the goal line leaves a gap that the ball falls through in the goal mouth
+    // the part behind the back wall is outside of the field, so nothing can touch it
+    let floor = quad(Vec3A::default(), vec3a(4096., 0., 0.), vec3a(0., 5500., 0.));
 
-    let ceiling = quad(vec3a(0., 0., 2048.), vec3a(-4096., 0., 0.), vec3a(0., 5120., 0.));
+    let ceiling = quad(vec3a(0., 0., 2048.), vec3a(-4096., 0., 0.), vec3a(0., 5500., 0.));
 
-    let side_walls = [quad(vec3a(4096., 0., 1024.), vec3a(0., -5120., 0.), vec3a(0., 0., 1024.)), quad(vec3a(-4096., 0., 1024.), vec3a(0., 5120., 0.), vec3a(0., 0., 1024.))];
+    let side_wall_0 = quad(vec3a(4096., 0., 1024.), vec3a(0., -5120., 0.), vec3a(0., 0., 1024.));
+    let side_wall_1 = quad(vec3a(-4096., 0., 1024.), vec3a(0., 5120., 0.), vec3a(0., 0., 1024.));
 
-    let field_mesh = Mesh::from(vec![
-        soccar_corner,
-        &soccar_corner.transform(FLIP_X),
-        &soccar_corner.transform(FLIP_Y),
-        &soccar_corner.transform(FLIP_X.dot(FLIP_Y)),
-        &soccar_goal.translate(vec3a(0., -5120., 0.)),
-        &soccar_goal.translate(vec3a(0., -5120., 0.)).transform(FLIP_Y),
-        soccar_ramps_0,
-        &soccar_ramps_0.transform(FLIP_X),
-        soccar_ramps_1,
-        &soccar_ramps_1.transform(FLIP_X),
-        &floor,
-        &ceiling,
-        &side_walls[0],
-        &side_walls[1],
-    ]);
+    vec![Part::mirrored(soccar_corner.clone(), &[FLIP_X, FLIP_Y, FLIP_X.dot(FLIP_Y)]), Part::mirrored(soccar_goal.translate(vec3a(0., -5120., 0.)), &[FLIP_Y]), Part::mirrored(soccar_ramps_0.clone(), &[FLIP_X]), Part::mirrored(soccar_ramps_1.clone(), &[FLIP_X]), Part::single(floor), Part::single(ceiling), Part::single(side_wall_0), Part::single(side_wall_1)]
+}
 
-    let triangles = field_mesh.to_triangles();
-    Bvh::from(&triangles)
+pub fn initialize_soccar(soccar_corner: &Mesh, soccar_goal: &Mesh, soccar_ramps_0: &Mesh, soccar_ramps_1: &Mesh) -> Bvh {
+    build_parts(&soccar_parts(soccar_corner, soccar_goal, soccar_ramps_0, soccar_ramps_1))
 }
 
-pub fn initialize_hoops(hoops_corner: &Mesh, hoops_net: &Mesh, hoops_rim: &Mesh, hoops_ramps_0: &Mesh, hoops_ramps_1: &Mesh) -> Bvh {
-    let scale = 0.9;
-    let y_offset = 431.664;
+pub fn initialize_soccar_instanced(soccar_corner: &Mesh, soccar_goal: &Mesh, soccar_ramps_0: &Mesh, soccar_ramps_1: &Mesh, cache: &mut BvhCache) -> InstancedBvh {
+    InstancedBvh::from_parts_cached(&soccar_parts(soccar_corner, soccar_goal, soccar_ramps_0, soccar_ramps_1), cache)
+}
 
-    let s = Mat3A::from_diagonal(Vec3::splat(scale));
+const HOOP_SCALE: f32 = 0.9;
+const HOOP_Y_OFFSET: f32 = 431.664;
 
-    let dy = vec3a(0., y_offset, 0.);
+// the orange side's net or rim, where it is in the field
+pub(crate) fn place_hoop(mesh: &Mesh) -> Mesh {
+    mesh.transform(Mat3A::from_diagonal(Vec3::splat(HOOP_SCALE))).translate(vec3a(0., HOOP_Y_OFFSET, 0.))
+}
 
-    let transformed_hoops_net = hoops_net.transform(s).translate(dy);
-    let transformed_hoops_rim = hoops_rim.transform(s).translate(dy);
+fn hoops_parts(hoops_corner: &Mesh, hoops_net: &Mesh, hoops_rim: &Mesh, hoops_ramps_0: &Mesh, hoops_ramps_1: &Mesh) -> Vec<Part> {
+    let transformed_hoops_net = place_hoop(hoops_net);
+    let transformed_hoops_rim = place_hoop(hoops_rim);
 
     let floor = quad(Vec3A::default(), vec3a(2966., 0., 0.), vec3a(0., 3581., 0.));
 
     let ceiling = quad(vec3a(0., 0., 1820.), vec3a(-2966., 0., 0.), vec3a(0., 3581., 0.));
 
-    let side_walls = [quad(vec3a(2966., 0., 910.), vec3a(0., -3581., 0.), vec3a(0., 0., 910.)), quad(vec3a(-2966., 0., 910.), vec3a(0., 3581., 0.), vec3a(0., 0., 910.))];
-
-    let back_walls = [quad(vec3a(0., 0., 1024.), vec3a(0., -5120., 0.), vec3a(0., 0., 1024.)), quad(vec3a(0., 0., 1024.), vec3a(0., 5120., 0.), vec3a(0., 0., 1024.))];
-
-    let field_mesh = Mesh::from(vec![
-        hoops_corner,
-        &hoops_corner.transform(FLIP_X),
-        &hoops_corner.transform(FLIP_Y),
-        &hoops_corner.transform(FLIP_X.dot(FLIP_Y)),
-        &transformed_hoops_net,
-        &transformed_hoops_net.transform(FLIP_Y),
-        &transformed_hoops_rim,
-        &transformed_hoops_rim.transform(FLIP_Y),
-        hoops_ramps_0,
-        &hoops_ramps_0.transform(FLIP_X),
-        hoops_ramps_1,
-        &hoops_ramps_1.transform(FLIP_Y),
-        &floor,
-        &ceiling,
-        &side_walls[0],
-        &side_walls[1],
-        &back_walls[0],
-        &back_walls[1],
-    ]);
+    let side_wall_0 = quad(vec3a(2966., 0., 910.), vec3a(0., -3581., 0.), vec3a(0., 0., 910.));
+    let side_wall_1 = quad(vec3a(-2966., 0., 910.), vec3a(0., 3581., 0.), vec3a(0., 0., 910.));
+
+    let back_wall_0 = quad(vec3a(0., 0., 1024.), vec3a(0., -5120., 0.), vec3a(0., 0., 1024.));
+    let back_wall_1 = quad(vec3a(0., 0., 1024.), vec3a(0., 5120., 0.), vec3a(0., 0., 1024.));
+
+    vec![
+        Part::mirrored(hoops_corner.clone(), &[FLIP_X, FLIP_Y, FLIP_X.dot(FLIP_Y)]),
+        Part::mirrored(transformed_hoops_net, &[FLIP_Y]),
+        Part::mirrored(transformed_hoops_rim, &[FLIP_Y]),
+        Part::mirrored(hoops_ramps_0.clone(), &[FLIP_X]),
+        Part::mirrored(hoops_ramps_1.clone(), &[FLIP_Y]),
+        Part::single(floor),
+        Part::single(ceiling),
+        Part::single(side_wall_0),
+        Part::single(side_wall_1),
+        Part::single(back_wall_0),
+        Part::single(back_wall_1),
+    ]
+}
 
-    let triangles = field_mesh.to_triangles();
+pub fn initialize_hoops(hoops_corner: &Mesh, hoops_net: &Mesh, hoops_rim: &Mesh, hoops_ramps_0: &Mesh, hoops_ramps_1: &Mesh) -> Bvh {
+    build_parts(&hoops_parts(hoops_corner, hoops_net, hoops_rim, hoops_ramps_0, hoops_ramps_1))
+}
 
-    Bvh::from(&triangles)
+pub fn initialize_hoops_instanced(hoops_corner: &Mesh, hoops_net: &Mesh, hoops_rim: &Mesh, hoops_ramps_0: &Mesh, hoops_ramps_1: &Mesh, cache: &mut BvhCache) -> InstancedBvh {
+    InstancedBvh::from_parts_cached(&hoops_parts(hoops_corner, hoops_net, hoops_rim, hoops_ramps_0, hoops_ramps_1), cache)
 }
 
+// the floor that the tiles are on
+const DROPSHOT_FLOOR_HEIGHT: f32 = 2.;
+// the closest the field's inner walls get to the center at floor level, measured from the mesh
+const DROPSHOT_INNER_WALL_DISTANCE: f32 = 4016.;
+
 #[allow(clippy::many_single_char_names)]
 pub fn initialize_dropshot(dropshot: &Mesh) -> Bvh {
     let scale = 0.393;
@@ -104,7 +141,7 @@ pub fn initialize_dropshot(dropshot: &Mesh) -> Bvh {
 
     let dz = vec3a(0., 0., z_offset);
 
-    let floor = quad(vec3a(0., 0., 2.), vec3a(10000., 0., 0.), vec3a(0., 7000., 0.));
+    let floor = quad(vec3a(0., 0., DROPSHOT_FLOOR_HEIGHT), vec3a(10000., 0., 0.), vec3a(0., 7000., 0.));
     let ceiling = quad(vec3a(0., 0., 2020.), vec3a(-10000., 0., 0.), vec3a(0., 7000., 0.));
     let mut walls: Vec<Mesh> = Vec::with_capacity(6);
 
@@ -119,13 +156,15 @@ pub fn initialize_dropshot(dropshot: &Mesh) -> Bvh {
         x = dot(r, x);
     }
 
-    let field_mesh = Mesh::from(vec![&dropshot.transform(q.dot(s)).translate(dz), &floor, &ceiling, &walls[0], &walls[1], &walls[2], &walls[3], &walls[4], &walls[5]]);
-
-    let triangles = field_mesh.to_triangles();
+    build_field(vec![&dropshot.transform(q.dot(s)).translate(dz), &floor, &ceiling, &walls[0], &walls[1], &walls[2], &walls[3], &walls[4], &walls[5]])
+}
 
-    Bvh::from(&triangles)
+// the tiles that cover the floor of the field from `initialize_dropshot`, which all start out intact
+pub fn initialize_dropshot_tiles() -> DropshotTiles {
+    DropshotTiles::new(DROPSHOT_FLOOR_HEIGHT, DROPSHOT_INNER_WALL_DISTANCE)
 }
 
+#[derive(Clone, Copy)]
 pub struct InitializeThrowbackParams<'a> {
     pub back_ramps_lower: &'a Mesh,
     pub back_ramps_upper: &'a Mesh,
@@ -139,7 +178,10 @@ pub struct InitializeThrowbackParams<'a> {
     pub side_ramps_upper: &'a Mesh,
 }
 
-pub fn initialize_throwback(
+// Throwback's ramps are wooden, so they can be given their own material
+pub const THROWBACK_RAMPS: MaterialId = 1;
+
+fn throwback_parts(
     InitializeThrowbackParams {
         back_ramps_lower,
         back_ramps_upper,
@@ -152,68 +194,56 @@ pub fn initialize_throwback(
         side_ramps_lower,
         side_ramps_upper,
     }: InitializeThrowbackParams<'_>,
-) -> Bvh {
+) -> Vec<Part> {
     let scale = 100.;
 
     let s = Mat3A::from_diagonal(Vec3::splat(scale));
 
     let floor = quad(Vec3A::default(), vec3a(4096.6, 0., 0.), vec3a(0., 6910., 0.));
     let ceiling = quad(vec3a(0., 0., 2048.), vec3a(-4096.6, 0., 0.), vec3a(0., 6910., 0.));
-    let side_walls: [Mesh; 2] = [quad(vec3a(4096.6, 0., 1024.), vec3a(0., -6910., 0.), vec3a(0., 0., 1024.)), quad(vec3a(-4096.6, 0., 1024.), vec3a(0., 6910., 0.), vec3a(0., 0., 1024.))];
-
-    let back_walls: [Mesh; 2] = [quad(vec3a(0., 6910., 1024.), vec3a(4096., 0., 0.), vec3a(0., 0., 1024.)), quad(vec3a(0., -6910., 1024.), vec3a(-4096., 0., 0.), vec3a(0., 0., 1024.))];
-
-    let throwback_goal = goal.transform(s);
-    let throwback_side_ramps_lower = side_ramps_lower.transform(s);
-    let throwback_side_ramps_upper = side_ramps_upper.transform(s);
-    let throwback_back_ramps_lower = back_ramps_lower.transform(s);
-    let throwback_back_ramps_upper = back_ramps_upper.transform(s);
-    let throwback_corner_ramps_lower = corner_ramps_lower.transform(s);
-    let throwback_corner_ramps_upper = corner_ramps_upper.transform(s);
-    let throwback_corner_wall_0 = corner_wall_0.transform(s);
-    let throwback_corner_wall_1 = corner_wall_1.transform(s);
-    let throwback_corner_wall_2 = corner_wall_2.transform(s);
-
-    let field_mesh = Mesh::from(vec![
-        &throwback_corner_ramps_lower,
-        &throwback_corner_ramps_lower.transform(FLIP_X),
-        &throwback_corner_ramps_lower.transform(FLIP_Y),
-        &throwback_corner_ramps_lower.transform(FLIP_Y).transform(FLIP_X),
-        &throwback_corner_ramps_upper,
-        &throwback_corner_ramps_upper.transform(FLIP_X),
-        &throwback_corner_ramps_upper.transform(FLIP_Y),
-        &throwback_corner_ramps_upper.transform(FLIP_Y).transform(FLIP_X),
-        &throwback_goal,
-        &throwback_goal.transform(FLIP_Y),
-        &throwback_side_ramps_lower,
-        &throwback_side_ramps_lower.transform(FLIP_X),
-        &throwback_side_ramps_upper,
-        &throwback_side_ramps_upper.transform(FLIP_X),
-        &throwback_back_ramps_lower,
-        &throwback_back_ramps_lower.transform(FLIP_Y),
-        &throwback_back_ramps_upper,
-        &throwback_back_ramps_upper.transform(FLIP_Y),
-        &throwback_corner_wall_0,
-        &throwback_corner_wall_0.transform(FLIP_X),
-        &throwback_corner_wall_0.transform(FLIP_Y),
-        &throwback_corner_wall_0.transform(FLIP_Y).transform(FLIP_X),
-        &throwback_corner_wall_1,
-        &throwback_corner_wall_1.transform(FLIP_X),
-        &throwback_corner_wall_1.transform(FLIP_Y),
-        &throwback_corner_wall_1.transform(FLIP_Y).transform(FLIP_X),
-        &throwback_corner_wall_2,
-        &throwback_corner_wall_2.transform(FLIP_X),
-        &throwback_corner_wall_2.transform(FLIP_Y),
-        &throwback_corner_wall_2.transform(FLIP_Y).transform(FLIP_X),
-        &floor,
-        &ceiling,
-        &side_walls[0],
-        &side_walls[1],
-        &back_walls[0],
-        &back_walls[1],
-    ]);
-
-    let triangles = field_mesh.to_triangles();
-
-    Bvh::from(&triangles)
+    let side_wall_0 = quad(vec3a(4096.6, 0., 1024.), vec3a(0., -6910., 0.), vec3a(0., 0., 1024.));
+    let side_wall_1 = quad(vec3a(-4096.6, 0., 1024.), vec3a(0., 6910., 0.), vec3a(0., 0., 1024.));
+
+    let back_wall_0 = quad(vec3a(0., 6910., 1024.), vec3a(4096., 0., 0.), vec3a(0., 0., 1024.));
+    let back_wall_1 = quad(vec3a(0., -6910., 1024.), vec3a(-4096., 0., 0.), vec3a(0., 0., 1024.));
+
+    let corners = [FLIP_X, FLIP_Y, FLIP_X.dot(FLIP_Y)];
+
+    vec![
+        Part::mirrored(corner_ramps_lower.transform(s), &corners).with_material(THROWBACK_RAMPS),
+        Part::mirrored(corner_ramps_upper.transform(s), &corners).with_material(THROWBACK_RAMPS),
+        Part::mirrored(goal.transform(s), &[FLIP_Y]),
+        Part::mirrored(side_ramps_lower.transform(s), &[FLIP_X]).with_material(THROWBACK_RAMPS),
+        Part::mirrored(side_ramps_upper.transform(s), &[FLIP_X]).with_material(THROWBACK_RAMPS),
+        Part::mirrored(back_ramps_lower.transform(s), &[FLIP_Y]).with_material(THROWBACK_RAMPS),
+        Part::mirrored(back_ramps_upper.transform(s), &[FLIP_Y]).with_material(THROWBACK_RAMPS),
+        Part::mirrored(corner_wall_0.transform(s), &corners),
+        Part::mirrored(corner_wall_1.transform(s), &corners),
+        Part::mirrored(corner_wall_2.transform(s), &corners),
+        Part::single(floor),
+        Part::single(ceiling),
+        Part::single(side_wall_0),
+        Part::single(side_wall_1),
+        Part::single(back_wall_0),
+        Part::single(back_wall_1),
+    ]
+}
+
+pub fn initialize_throwback(params: InitializeThrowbackParams<'_>) -> Bvh {
+    build_parts(&throwback_parts(params))
+}
+
+// which of Throwback's triangles are the ramps
+pub fn throwback_material_tags(params: InitializeThrowbackParams<'_>) -> Vec<MaterialId> {
+    material_tags(&throwback_parts(params))
+}
+
+// `initialize_throwback` and `throwback_material_tags` from the same parts, so the meshes are only placed once
+pub fn initialize_throwback_with_material_tags(params: InitializeThrowbackParams<'_>) -> (Bvh, Vec<MaterialId>) {
+    let parts = throwback_parts(params);
+    (build_parts(&parts), material_tags(&parts))
+}
+
+pub fn initialize_throwback_instanced(params: InitializeThrowbackParams<'_>, cache: &mut BvhCache) -> InstancedBvh {
+    InstancedBvh::from_parts_cached(&throwback_parts(params), cache)
 }
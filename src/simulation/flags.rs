@@ -0,0 +1,112 @@
+use super::ball::Ball;
+use super::game::Arena;
+use super::geometry::Ray;
+
+// What a slice of a prediction was touching, worked out while it was simulated, so filtering slices doesn't need any more collision checks
+// Only the slices of predictions have these set, the ball in a `Game` keeps whatever its last slice had
+pub const ON_GROUND: u8 = 1 << 0;
+pub const TOUCHING_WALL: u8 = 1 << 1;
+pub const TOUCHING_CEILING: u8 = 1 << 2;
+pub const IN_GOAL: u8 = 1 << 3;
+// the ball was pushed deep into a surface, like after a fast hit into a wall or when it's pinched between two,
+// so its bounce is rougher than usual
+pub const LOW_CONFIDENCE: u8 = 1 << 4;
+
+// surfaces with a normal at least this far up are the ground, and this far down are the ceiling, and everything in between is a wall
+const GROUND_NORMAL_Z: f32 = 0.7;
+// how far into a surface the ball has to be for the slice to be low confidence
+const LOW_CONFIDENCE_PENETRATION: f32 = 10.;
+
+// the flags for the ball, given what it's touching
+pub(crate) fn slice_flags(arena: &Arena, ball: &Ball, contact: Option<Ray>) -> u8 {
+    let mut flags = 0;
+
+    if let Some(contact) = contact {
+        let offset = ball.location - contact.start;
+        let normal = offset.normalize_or_zero();
+
+        flags |= match normal.z {
+            z if z >= GROUND_NORMAL_Z => ON_GROUND,
+            z if z <= -GROUND_NORMAL_Z => TOUCHING_CEILING,
+            _ => TOUCHING_WALL,
+        };
+
+        if ball.collision_radius - offset.length() > LOW_CONFIDENCE_PENETRATION {
+            flags |= LOW_CONFIDENCE;
+        }
+    }
+
+    if arena.goal_scored(ball).is_some() {
+        flags |= IN_GOAL;
+    }
+
+    flags
+}
+
+impl Ball {
+    pub fn on_ground(&self) -> bool {
+        self.flags & ON_GROUND != 0
+    }
+
+    pub fn touching_wall(&self) -> bool {
+        self.flags & TOUCHING_WALL != 0
+    }
+
+    pub fn touching_ceiling(&self) -> bool {
+        self.flags & TOUCHING_CEILING != 0
+    }
+
+    pub fn in_goal(&self) -> bool {
+        self.flags & IN_GOAL != 0
+    }
+
+    pub fn low_confidence(&self) -> bool {
+        self.flags & LOW_CONFIDENCE != 0
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn flags_match_the_field() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(0., 0., 2000.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+        // every flag agrees with a collision check on the slice
+        for slice in &prediction.slices {
+            let contact = game.collide(&slice.hitbox());
+            assert_eq!(slice.flags, slice_flags(&game.arena(), slice, contact), "{}", slice.time);
+        }
+
+        // it hits the ceiling, then bounces on the floor
+        let ceiling = prediction.slices.iter().position(Ball::touching_ceiling).unwrap();
+        let ground = prediction.slices.iter().position(Ball::on_ground).unwrap();
+        assert!(ceiling < ground);
+        assert!(prediction.slices.iter().filter(|slice| slice.on_ground()).all(|slice| slice.location.z < 2. * slice.collision_radius));
+        assert!(!prediction.slices.iter().any(|slice| slice.touching_wall() || slice.in_goal()));
+    }
+
+    #[test]
+    fn walls_goals_and_confidence() {
+        let mut game = load_soccar();
+
+        // fast into the side wall, which it gets pushed deep into
+        game.ball.update(0., vec3a(3500., 0., 500.), vec3a(4000., 0., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 60);
+        let wall = prediction.slices.iter().find(|slice| slice.touching_wall()).unwrap();
+        assert!(wall.low_confidence());
+
+        // straight into the orange goal
+        game.ball.update(0., vec3a(0., 4500., 300.), vec3a(0., 2000., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 120);
+        assert!(prediction.slices.iter().any(Ball::in_goal));
+        assert!(!prediction.slices[0].in_goal());
+    }
+}
@@ -0,0 +1,100 @@
+use glam::Vec3A;
+
+use super::dropshot::DropshotTiles;
+use super::game::Field;
+use super::geometry::{Ray, Sphere};
+
+impl Field {
+    // What `Accuracy::FloorOnly` collides with: the floor, as a flat plane at z = 0 (or the height of dropshot's tiles, with holes where they're broken),
+    // and the plane of the back wall around each of the field's goals, which the ball goes straight through inside of the goal's opening
+    // Nothing behind the goal line is there
+    pub fn collide_floor_only(&self, s: &Sphere, tiles: Option<&DropshotTiles>) -> Option<Ray> {
+        let floor_height = tiles.map_or(0., |tiles| tiles.floor_height);
+        let over_hole = tiles.is_some_and(|tiles| tiles.is_hole(s.center));
+
+        let floor = (s.center.z - floor_height <= s.radius && !over_hole).then_some(Ray {
+            start: Vec3A::new(s.center.x, s.center.y, floor_height),
+            direction: Vec3A::Z,
+        });
+
+        let back_wall = self.goals.iter().find_map(|goal| {
+            // how far the center is from the plane of the back wall, on the field's side of it
+            let distance = -(s.center - goal.location).dot(goal.direction);
+            if distance < 0. || distance > s.radius {
+                return None;
+            }
+
+            let offset = s.center - goal.location;
+            let along_line = goal.direction.cross(Vec3A::Z).normalize_or_zero();
+            if offset.dot(along_line).abs() <= goal.width / 2. && offset.z <= goal.height {
+                return None;
+            }
+
+            Some(Ray {
+                start: s.center + goal.direction * distance,
+                direction: -goal.direction,
+            })
+        });
+
+        // in the corner where the floor meets the back wall, the ball is pushed out of both at once
+        match (floor, back_wall) {
+            (Some(floor), Some(wall)) => Some(Ray {
+                start: (floor.start + wall.start) / 2.,
+                direction: (floor.direction + wall.direction).normalize(),
+            }),
+            (floor, wall) => floor.or(wall),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+    use crate::simulation::game::Accuracy;
+
+    #[test]
+    fn same_as_full_in_the_middle() {
+        // bouncing around the middle of the field, where the floor is all the ball touches
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 800.), vec3a(500., 300., 200.), vec3a(1., 0., 2.));
+
+        let full = Ball::get_ball_prediction_struct_for_slices(&mut game.clone(), 240);
+        let floor_only = Ball::get_ball_prediction_struct_with_accuracy(&mut game, Accuracy::FloorOnly, 240);
+
+        // the game keeps its own accuracy
+        assert_eq!(game.accuracy, Accuracy::Full);
+
+        for (full, floor_only) in full.slices.iter().zip(&floor_only.slices) {
+            assert!(full.location.distance(floor_only.location) < 1., "{} and {}", full.location, floor_only.location);
+        }
+    }
+
+    #[test]
+    fn walls_are_ignored() {
+        // straight at the side wall, which isn't there
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(3500., 0., 500.), vec3a(2000., 0., 0.), vec3a(0., 0., 0.));
+
+        let prediction = Ball::get_ball_prediction_struct_with_accuracy(&mut game, Accuracy::FloorOnly, 120);
+        assert!(prediction.slices.last().unwrap().location.x > 4096.);
+    }
+
+    #[test]
+    fn back_walls_and_goals() {
+        let mut game = load_soccar();
+
+        // beside the goal, the back wall is in the way
+        game.ball.update(0., vec3a(2000., 4500., 300.), vec3a(0., 2000., 0.), vec3a(0., 0., 0.));
+        let prediction = Ball::get_ball_prediction_struct_with_accuracy(&mut game, Accuracy::FloorOnly, 120);
+        assert!(prediction.slices.iter().all(|slice| slice.location.y < 5120.));
+        assert!(prediction.slices.last().unwrap().velocity.y < 0.);
+
+        // but in front of it, the ball goes right in
+        game.ball.update(0., vec3a(0., 4500., 300.), vec3a(0., 2000., 0.), vec3a(0., 0., 0.));
+        let prediction = Ball::get_ball_prediction_struct_with_accuracy(&mut game, Accuracy::FloorOnly, 120);
+        assert!(prediction.slices.last().unwrap().location.y > 5120. + game.ball.radius);
+    }
+}
@@ -1,21 +1,390 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
 use glam::Vec3A;
 
 use super::ball::Ball;
-use super::bvh::Bvh;
+use super::bvh::{contact_from_tris, Bvh};
+use super::dropshot::{DropshotTiles, TileState};
+use super::geometry::{Contact, Obb, Ray, Sphere};
+use super::goal::{Goal, GoalCriteria};
+use super::grid::Grid;
+use super::instanced::InstancedBvh;
+use super::heuristics::FieldHeuristics;
+use super::material::{Material, MaterialId, SurfaceMaterials};
+#[cfg(feature = "plugins")]
+use super::plugin::Plugin;
+use super::puck::Puck;
+use super::sdf::DistanceField;
+use super::throwback::ThrowbackLayout;
+use super::tuning::Tuning;
+
+// Which of the built-in fields is being used
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMode {
+    // a field that was put together by hand
+    #[default]
+    Custom,
+    Soccar,
+    Hoops,
+    Dropshot,
+    Throwback,
+    // 5 was heatseeker, whose steering was never checked against the game, so it isn't simulated
+    Snowday = 6,
+}
+
+impl TryFrom<u8> for GameMode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        Ok(match value {
+            0 => GameMode::Custom,
+            1 => GameMode::Soccar,
+            2 => GameMode::Hoops,
+            3 => GameMode::Dropshot,
+            4 => GameMode::Throwback,
+            6 => GameMode::Snowday,
+            _ => return Err(value),
+        })
+    }
+}
+
+// Which structure is used to find the triangles the ball is touching
+#[derive(Clone, Default)]
+pub enum Broadphase {
+    #[default]
+    Bvh,
+    Grid(Arc<Grid>),
+    // the same triangles as the field's BVH, with the mirrored meshes only stored once, see `InstancedBvh`
+    Instanced(Arc<InstancedBvh>),
+}
 
-#[derive(Clone)]
+// How much of the field's detail is used for collisions
+// Coarse collisions are cheaper, but bounces off of curved surfaces (ramps, corners) are less accurate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Accuracy {
+    #[default]
+    Full,
+    Coarse,
+    // only the floor and the back walls around the goals, see `Game::collide_floor_only`
+    // much faster than either, but only right while the ball stays away from the side walls, corners and ceiling
+    FloorOnly,
+}
+
+// How the ball is moved through the air between contacts
+// Contacts always use the same single step, so these only differ in flight
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Integrator {
+    // the game's, which updates the velocity and then moves with the new one
+    #[default]
+    Euler,
+    // fourth order Runge-Kutta, which stays much closer to the exact arc, for a few times the math per step in the air
+    Rk4,
+}
+
+// How the ball is kept out of the surfaces it's touching
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContactModel {
+    // the game's, where every step that touches a surface is a bounce without gravity, and the ball is pushed all the way back out of it
+    #[default]
+    Game,
+    // Split impulse: gravity along the surface still pulls the ball during a contact, a ball already moving away from the surface flies like it's in the air,
+    // and only part of the penetration is corrected each step, so balls pinched between surfaces don't gain energy and balls resting against walls slide down them
+    // This hasn't been checked against recorded game trajectories, so it's opt-in
+    SplitImpulse,
+}
+
+// The grid size used to simplify the field for `Accuracy::Coarse`, which is about the size of the ball
+pub const COARSE_CELL_SIZE: f32 = 100.;
+
+// The parts of a game that stay the same while it's played, which a prediction only ever reads
+// Everything that's expensive to build is behind an `Arc`, so cloning a field (or a game) doesn't rebuild or copy any of it
+#[derive(Clone, Default)]
+pub struct Field {
+    pub collision_mesh: Arc<Bvh>,
+    pub broadphase: Broadphase,
+    // only built once it's needed
+    pub coarse_collision_mesh: Option<Arc<Bvh>>,
+    // lets most collision checks skip the BVH entirely
+    pub distance_field: Option<Arc<DistanceField>>,
+    pub goals: Vec<Goal>,
+    // measured from the field when it's loaded
+    pub heuristics: Option<FieldHeuristics>,
+    // only in throwback, whose goals and ramps aren't where they are in soccar
+    pub throwback: Option<ThrowbackLayout>,
+    pub tuning: Tuning,
+    // what each part of the field is made of, when the field was built with any of them tagged
+    pub materials: Option<Arc<SurfaceMaterials>>,
+}
+
+#[derive(Clone, Default)]
 pub struct Game {
+    pub mode: GameMode,
     pub gravity: Vec3A,
-    pub collision_mesh: Bvh,
+    // shared between clones of the game, so that cloning one to try things out is cheap
+    pub field: Field,
     pub ball: Ball,
+    // only used in Snow Day, by the puck simulation
+    pub puck: Puck,
+    pub accuracy: Accuracy,
+    pub integrator: Integrator,
+    pub contact_model: ContactModel,
+    pub goal_criteria: GoalCriteria,
+    // only in dropshot, where the ball falls through broken tiles
+    pub dropshot_tiles: Option<DropshotTiles>,
+    // run after every step of the ball, in the order they were added
+    #[cfg(feature = "plugins")]
+    pub plugins: Vec<Arc<Plugin>>,
+}
+
+// Everything a step of the ball reads, borrowed from a game or put together for a prediction on its own field
+#[derive(Clone, Copy)]
+pub(crate) struct Arena<'a> {
+    pub field: &'a Field,
+    pub gravity: Vec3A,
+    pub accuracy: Accuracy,
+    pub integrator: Integrator,
+    pub contact_model: ContactModel,
+    pub goal_criteria: GoalCriteria,
+    pub dropshot_tiles: Option<&'a DropshotTiles>,
+    #[cfg(feature = "plugins")]
+    pub plugins: &'a [Arc<Plugin>],
+}
+
+impl Arena<'_> {
+    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
+        self.field.collide(s, self.accuracy, self.dropshot_tiles)
+    }
+
+    // the index of the goal that the ball is in
+    pub fn goal_scored(&self, ball: &Ball) -> Option<usize> {
+        self.field.goals.iter().position(|goal| goal.is_scored(ball, self.goal_criteria))
+    }
+}
+
+impl Field {
+    // makes every triangle tagged with `id` bounce the ball like `material`
+    pub fn set_material(&mut self, id: MaterialId, material: Material) {
+        Arc::make_mut(self.materials.get_or_insert_with(Default::default)).set(id, material);
+    }
+
+    // the material of the triangles the sphere is touching, or None if it's only touching the field's normal surface
+    pub fn material_at(&self, s: &Sphere) -> Option<Material> {
+        let materials = self.materials.as_ref().filter(|materials| !materials.materials.is_empty())?;
+        materials.blend(&self.collision_mesh.intersect_ids(s), &self.tuning)
+    }
+
+    // switch this field over to a uniform grid built from the same triangles as the BVH
+    pub fn use_grid_broadphase(&mut self, cell_size: f32) {
+        self.broadphase = Broadphase::Grid(Arc::new(Grid::from(&self.collision_mesh.primitives(), cell_size)));
+    }
+
+    // `instanced` has to be built from the same parts as this field, like the one from `load_soccar_instanced` for soccar
+    pub fn use_instanced_broadphase(&mut self, instanced: InstancedBvh) {
+        assert_eq!(instanced.num_leaves(), self.collision_mesh.num_leaves, "The instanced field has to have the same triangles as this one");
+        self.broadphase = Broadphase::Instanced(Arc::new(instanced));
+    }
+
+    pub fn use_bvh_broadphase(&mut self) {
+        self.broadphase = Broadphase::Bvh;
+    }
+
+    // `point` has to be inside of the field
+    pub fn build_distance_field(&mut self, cell_size: f32, point: Vec3A) {
+        self.distance_field = Some(Arc::new(DistanceField::from(&self.collision_mesh, cell_size, point)));
+    }
+
+    // makes `Accuracy::Coarse` available, which falls back to the full field until this is called
+    pub fn build_coarse_collision_mesh(&mut self) {
+        if self.coarse_collision_mesh.is_none() {
+            self.coarse_collision_mesh = Some(Arc::new(self.collision_mesh.decimated(COARSE_CELL_SIZE)));
+        }
+    }
+
+    // if a box (like a car's hitbox) would be clipping into the field
+    pub fn obb_collides(&self, obb: &Obb) -> Option<Contact> {
+        self.collision_mesh.collide_obb(obb)
+    }
+
+    // if nothing in the field is in the way of a straight line between the two points
+    pub fn line_of_sight(&self, a: Vec3A, b: Vec3A) -> bool {
+        self.collision_mesh
+            .raycast(&Ray {
+                start: a,
+                direction: b - a,
+            })
+            .is_none()
+    }
+
+    // `line_of_sight` for each pair of points, in the same order
+    pub fn lines_of_sight(&self, segments: &[(Vec3A, Vec3A)]) -> Vec<bool> {
+        segments.iter().map(|(a, b)| self.line_of_sight(*a, *b)).collect()
+    }
+
+    // how far a sphere moving along each segment of the path stays away from the field
+    // this is negative when the sphere would be clipping into the field
+    pub fn clearance_along(&self, path: &[Vec3A], radius: f32) -> Vec<f32> {
+        if let [point] = path {
+            return vec![
+                self.collision_mesh.distance_to_segment(&Ray {
+                    start: *point,
+                    direction: Vec3A::ZERO,
+                }) - radius,
+            ];
+        }
+
+        path.windows(2)
+            .map(|pair| {
+                let segment = Ray {
+                    start: pair[0],
+                    direction: pair[1] - pair[0],
+                };
+
+                self.collision_mesh.distance_to_segment(&segment) - radius
+            })
+            .collect()
+    }
+
+    // what the sphere is touching, with the floor taken out from under any broken dropshot `tiles`
+    // if the coarse field hasn't been built, this falls back to the full one
+    pub fn collide(&self, s: &Sphere, accuracy: Accuracy, tiles: Option<&DropshotTiles>) -> Option<Ray> {
+        if accuracy == Accuracy::FloorOnly {
+            return self.collide_floor_only(s, tiles);
+        }
+
+        // holes in the floor always use the full field
+        if let Some(tiles) = tiles.filter(|tiles| tiles.removes_floor_under(s)) {
+            return contact_from_tris(s, self.collision_mesh.intersect(s).into_iter().filter(|tri| !tiles.is_floor(tri)).collect());
+        }
+
+        if let (Accuracy::Coarse, Some(coarse)) = (accuracy, &self.coarse_collision_mesh) {
+            return coarse.collide(s);
+        }
+
+        if self.distance_field.as_ref().is_some_and(|field| !field.may_collide(s)) {
+            return None;
+        }
+
+        match &self.broadphase {
+            Broadphase::Bvh => self.collision_mesh.collide(s),
+            Broadphase::Grid(grid) => grid.collide(s),
+            Broadphase::Instanced(instanced) => instanced.collide(s),
+        }
+    }
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Self {
-            gravity: Vec3A::default(),
-            collision_mesh: Bvh::default(),
-            ball: Ball::default(),
+impl Game {
+    pub(crate) fn arena(&self) -> Arena<'_> {
+        Arena {
+            field: &self.field,
+            gravity: self.gravity,
+            accuracy: self.accuracy,
+            integrator: self.integrator,
+            contact_model: self.contact_model,
+            goal_criteria: self.goal_criteria,
+            dropshot_tiles: self.dropshot_tiles.as_ref(),
+            #[cfg(feature = "plugins")]
+            plugins: &self.plugins,
+        }
+    }
+
+    // the ball has to be inside of the field when this is called
+    pub fn build_distance_field(&mut self, cell_size: f32) {
+        self.field.build_distance_field(cell_size, self.ball.location);
+    }
+
+    // the accuracy used by the ball simulation
+    pub fn set_accuracy(&mut self, accuracy: Accuracy) {
+        if accuracy == Accuracy::Coarse {
+            self.field.build_coarse_collision_mesh();
         }
+
+        self.accuracy = accuracy;
+    }
+
+    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
+        self.collide_with_accuracy(s, self.accuracy)
+    }
+
+    // if the coarse field hasn't been built, this falls back to the full one
+    pub fn collide_with_accuracy(&self, s: &Sphere, accuracy: Accuracy) -> Option<Ray> {
+        self.field.collide(s, accuracy, self.dropshot_tiles.as_ref())
+    }
+
+    // `Field::obb_collides`, with the floor taken out from under any broken dropshot tiles
+    pub fn obb_collides(&self, obb: &Obb) -> Option<Contact> {
+        let Some(tiles) = &self.dropshot_tiles else {
+            return self.field.obb_collides(obb);
+        };
+
+        self.field
+            .collision_mesh
+            .intersect_aabb(&obb.into())
+            .iter()
+            .filter_map(|tri| obb.intersect_tri(tri).filter(|contact| !(tiles.is_floor(tri) && tiles.is_hole(contact.point))))
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+
+    // `Field::line_of_sight`, where the line can go through the holes left by broken dropshot tiles
+    pub fn line_of_sight(&self, a: Vec3A, b: Vec3A) -> bool {
+        let Some(tiles) = &self.dropshot_tiles else {
+            return self.field.line_of_sight(a, b);
+        };
+
+        let mut start = a;
+
+        while let Some((t, tri)) = self.field.collision_mesh.raycast(&Ray { start, direction: b - start }) {
+            let hit = start + (b - start) * t;
+            if !tiles.is_floor(&tri) || !tiles.is_hole(hit) {
+                return false;
+            }
+
+            // carry on from just past the floor
+            start = hit + (b - a).normalize() * 0.1;
+            if (b - start).dot(b - a) <= 0. {
+                break;
+            }
+        }
+
+        true
+    }
+
+    // `line_of_sight` for each pair of points, in the same order
+    pub fn lines_of_sight(&self, segments: &[(Vec3A, Vec3A)]) -> Vec<bool> {
+        segments.iter().map(|(a, b)| self.line_of_sight(*a, *b)).collect()
+    }
+
+    // `Field::clearance_along`, where the floor isn't there over broken dropshot tiles
+    pub fn clearance_along(&self, path: &[Vec3A], radius: f32) -> Vec<f32> {
+        let Some(tiles) = self.dropshot_tiles.as_ref().filter(|tiles| tiles.states().contains(&TileState::Broken)) else {
+            return self.field.clearance_along(path, radius);
+        };
+
+        let clearance = |segment: Ray| {
+            let rest = self.field.collision_mesh.distance_to_segment_where(&segment, |tri| !tiles.is_floor(tri));
+            rest.min(tiles.floor_distance(&segment)) - radius
+        };
+
+        if let [point] = path {
+            return vec![clearance(Ray {
+                start: *point,
+                direction: Vec3A::ZERO,
+            })];
+        }
+
+        path.windows(2)
+            .map(|pair| {
+                clearance(Ray {
+                    start: pair[0],
+                    direction: pair[1] - pair[0],
+                })
+            })
+            .collect()
     }
 }
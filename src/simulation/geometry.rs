@@ -1,25 +1,40 @@
+//! The geometric primitives used by the ball simulation.
+//!
+//! These are the exact same types and intersection tests that the collision code uses,
+//! so anything built on top of them (visualizers, car collision, etc.) agrees with the simulation.
+
+use alloc::vec::Vec;
+
 use crate::linear_algebra::math::dot;
 use glam::{Mat3A, Vec3A};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
 
+/// Distance from `p` to the line segment that goes from `start` to `start + dir`
 pub fn distance_between(start: Vec3A, dir: Vec3A, p: Vec3A) -> f32 {
     let u = ((p - start).dot(dir) / dir.length_squared()).clamp(0., 1.);
     (start + dir * u - p).length()
 }
 
+/// A triangle, wound counter-clockwise when looking at the side its normal points to
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Tri {
     pub p: [Vec3A; 3],
 }
 
 impl Tri {
+    /// The centroid of the triangle
     pub fn center(&self) -> Vec3A {
         self.p.iter().sum::<Vec3A>() / 3.
     }
 
+    /// The normal of the triangle's plane, which is what the ball bounces off of
     pub fn unit_normal(&self) -> Vec3A {
         (self.p[1] - self.p[0]).cross(self.p[2] - self.p[0]).normalize()
     }
 
+    /// If any part of the sphere is touching the triangle, including its edges and corners
     #[allow(clippy::many_single_char_names)]
     pub fn intersect_sphere(&self, b: &Sphere) -> bool {
         let e1 = self.p[1] - self.p[0];
@@ -27,6 +42,12 @@ impl Tri {
         let e3 = self.p[0] - self.p[2];
         let n = e3.cross(e1).normalize();
 
+        // nothing on the triangle can be closer than its plane, which skips the expensive inverse for most triangles
+        #[cfg(feature = "fast-math")]
+        if n.dot(b.center - self.p[0]).abs() > b.radius {
+            return false;
+        }
+
         let a = Mat3A::from_cols_array_2d(&[[e1.x, -e3.x, n.x], [e1.y, -e3.y, n.y], [e1.z, -e3.z, n.z]]);
         let x = dot(a.inverse(), b.center - self.p[0]);
 
@@ -49,10 +70,221 @@ impl Tri {
 
         dist <= b.radius
     }
+
+    /// The point on the triangle that's the closest to `p`
+    pub fn closest_point(&self, p: Vec3A) -> Vec3A {
+        // Real-Time Collision Detection, Christer Ericson, section 5.1.5
+        let [a, b, c] = self.p;
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0. && d2 <= 0. {
+            return a;
+        }
+
+        let bp = p - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0. && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0. && d1 >= 0. && d3 <= 0. {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = p - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0. && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0. && d2 >= 0. && d6 <= 0. {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1. / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+
+    /// Where the segment crosses the triangle, as a fraction of the way along the ray
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        // Möller–Trumbore, hitting the triangle from either side
+        let e1 = self.p[1] - self.p[0];
+        let e2 = self.p[2] - self.p[0];
+
+        let h = ray.direction.cross(e2);
+        let det = e1.dot(h);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_det = 1. / det;
+        let s = ray.start - self.p[0];
+
+        let u = s.dot(h) * inv_det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(e1);
+        let v = ray.direction.dot(q) * inv_det;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+
+        let t = e2.dot(q) * inv_det;
+        (0. ..=1.).contains(&t).then_some(t)
+    }
+
+    /// Where a sphere moving along the segment first touches the triangle, as a fraction of the way along the ray, or 0 if it starts out touching it
+    pub fn sweep_sphere(&self, ray: &Ray, radius: f32) -> Option<f32> {
+        if (self.closest_point(ray.start) - ray.start).length_squared() <= radius * radius {
+            return Some(0.);
+        }
+
+        let mut first: Option<f32> = None;
+        let mut consider = |t: f32| {
+            if (0. ..=1.).contains(&t) && first.is_none_or(|first| t < first) {
+                first = Some(t);
+            }
+        };
+
+        // the face, from whichever side the sphere is on
+        let n = self.unit_normal();
+        let distance = n.dot(ray.start - self.p[0]);
+        let approach = n.dot(ray.direction);
+
+        if approach * distance < 0. {
+            let side = distance.signum();
+            let t = (side * radius - distance) / approach;
+            let touch = ray.start + ray.direction * t - n * side * radius;
+
+            if (self.closest_point(touch) - touch).length_squared() < 1e-4 {
+                consider(t);
+            }
+        }
+
+        // the edges, as the infinite cylinders around them, and only where the touch is between the corners
+        for i in 0..3 {
+            let a = self.p[i];
+            let e = self.p[(i + 1) % 3] - a;
+            let e_sq = e.length_squared();
+
+            let m = ray.start - a;
+            let m_perp = m - e * (m.dot(e) / e_sq);
+            let d_perp = ray.direction - e * (ray.direction.dot(e) / e_sq);
+
+            if let Some(t) = first_root(d_perp.length_squared(), 2. * m_perp.dot(d_perp), m_perp.length_squared() - radius * radius) {
+                let along = (m + ray.direction * t).dot(e) / e_sq;
+
+                if (0. ..=1.).contains(&along) {
+                    consider(t);
+                }
+            }
+        }
+
+        // the corners
+        for p in self.p {
+            let m = ray.start - p;
+
+            if let Some(t) = first_root(ray.direction.length_squared(), 2. * m.dot(ray.direction), m.length_squared() - radius * radius) {
+                consider(t);
+            }
+        }
+
+        first
+    }
+
+    /// The shortest distance between the line segment and the triangle
+    pub fn distance_to_segment(&self, segment: &Ray) -> f32 {
+        if self.intersect_ray(segment).is_some() {
+            return 0.;
+        }
+
+        let end = segment.start + segment.direction;
+
+        let mut dist = (self.closest_point(segment.start) - segment.start).length().min((self.closest_point(end) - end).length());
+
+        for i in 0..3 {
+            let edge_start = self.p[i];
+            let edge = self.p[(i + 1) % 3] - edge_start;
+            dist = dist.min(segment_distance(segment.start, segment.direction, edge_start, edge));
+        }
+
+        dist
+    }
+}
+
+/// Distance between the segments that go from `p1` to `p1 + d1` and `p2` to `p2 + d2`
+pub fn segment_distance(p1: Vec3A, d1: Vec3A, p2: Vec3A, d2: Vec3A) -> f32 {
+    // Real-Time Collision Detection, Christer Ericson, section 5.1.9
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+        (0., 0.)
+    } else if a <= f32::EPSILON {
+        (0., (f / e).clamp(0., 1.))
+    } else {
+        let c = d1.dot(r);
+
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0., 1.), 0.)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+
+            let s = if denom != 0. {
+                ((b * f - c * e) / denom).clamp(0., 1.)
+            } else {
+                0.
+            };
+            let t = (b * s + f) / e;
+
+            if t < 0. {
+                ((-c / a).clamp(0., 1.), 0.)
+            } else if t > 1. {
+                (((b - c) / a).clamp(0., 1.), 1.)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    ((p1 + d1 * s) - (p2 + d2 * t)).length()
+}
+
+// the smaller root of a * t^2 + b * t + c = 0, which is when something moving along a line starts to touch a round shape
+fn first_root(a: f32, b: f32, c: f32) -> Option<f32> {
+    if a <= f32::EPSILON {
+        return None;
+    }
+
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return None;
+    }
+
+    Some((-b - discriminant.sqrt()) / (2. * a))
 }
 
-// AABB stands for "Axis-Aligned Bounding Boxes"
-// Learn more here: https://developer.nvidia.com/blog/thinking-parallel-part-i-collision-detection-gpu/
+/// AABB stands for "Axis-Aligned Bounding Boxes"
+///
+/// Learn more here: <https://developer.nvidia.com/blog/thinking-parallel-part-i-collision-detection-gpu/>
 #[derive(Clone, Copy, Debug)]
 pub struct Aabb {
     pub min: Vec3A,
@@ -69,6 +301,7 @@ impl Default for Aabb {
 }
 
 impl Aabb {
+    /// The smallest box that contains both boxes
     pub fn add(&self, b: &Aabb) -> Self {
         Self {
             min: self.min.min(b.min),
@@ -76,6 +309,7 @@ impl Aabb {
         }
     }
 
+    /// The smallest box that contains the triangle
     pub fn from_tri(t: &Tri) -> Self {
         let min = t.p.into_iter().reduce(Vec3A::min).expect("Tri points array empty?");
 
@@ -87,6 +321,7 @@ impl Aabb {
         }
     }
 
+    /// The smallest box that contains the sphere
     pub fn from_sphere(s: &Sphere) -> Self {
         Self {
             min: s.center - s.radius,
@@ -94,14 +329,54 @@ impl Aabb {
         }
     }
 
+    /// If the two boxes overlap, including just touching
     pub fn intersect_self(&self, b: &Aabb) -> bool {
         self.min.cmple(b.max).all() && self.max.cmpge(b.min).all()
     }
 
+    /// If any part of the sphere is inside of the box
     pub fn intersect_sphere(&self, b: &Sphere) -> bool {
         let nearest = b.center.clamp(self.min, self.max);
 
-        (b.center - nearest).length() <= b.radius
+        if cfg!(feature = "fast-math") {
+            (b.center - nearest).length_squared() <= b.radius * b.radius
+        } else {
+            (b.center - nearest).length() <= b.radius
+        }
+    }
+
+    /// The total area of the box's 6 faces
+    pub fn surface_area(&self) -> f32 {
+        let size = (self.max - self.min).max(Vec3A::ZERO);
+        2. * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// If the point is inside of the box, including on its surface
+    pub fn contains(&self, p: Vec3A) -> bool {
+        self.min.cmple(p).all() && self.max.cmpge(p).all()
+    }
+
+    /// The shortest distance between the two boxes, 0 if they overlap
+    pub fn distance_to(&self, b: &Aabb) -> f32 {
+        (self.min - b.max).max(b.min - self.max).max(Vec3A::ZERO).length()
+    }
+
+    /// Where the segment enters the box, as a fraction of the way along the ray, or 0 if it starts inside
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        // the slab method, where dividing by 0 gives infinities that still compare correctly
+        let inv = ray.direction.recip();
+        let t1 = (self.min - ray.start) * inv;
+        let t2 = (self.max - ray.start) * inv;
+
+        // a ray along one of the faces gives 0 * infinity, and it's inside of the box along that axis
+        let along_face = t1.is_nan_mask() | t2.is_nan_mask();
+        let near = Vec3A::select(along_face, Vec3A::splat(f32::NEG_INFINITY), t1.min(t2));
+        let far = Vec3A::select(along_face, Vec3A::splat(f32::INFINITY), t1.max(t2));
+
+        let enter = near.max_element().max(0.);
+        let exit = far.min_element().min(1.);
+
+        (enter <= exit).then_some(enter)
     }
 }
 
@@ -117,6 +392,7 @@ impl From<&'_ Sphere> for Aabb {
     }
 }
 
+/// A pair of integers
 #[derive(Clone, Copy, Debug)]
 pub struct Int2 {
     pub x: i32,
@@ -132,19 +408,130 @@ impl Default for Int2 {
     }
 }
 
-// endpoint is start + direction
+/// A line segment, the endpoint is `start + direction`
+///
+/// Collisions also use this to describe a contact, with `start` being the contact point and `direction` the unit normal
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Ray {
     pub start: Vec3A,
     pub direction: Vec3A,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// A sphere, which is how the ball's hitbox is represented
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Sphere {
     pub center: Vec3A,
     pub radius: f32,
 }
 
+/// An oriented bounding box, like a car's hitbox
+///
+/// The columns of `orientation` are the box's local axes, in world space
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Obb {
+    pub center: Vec3A,
+    pub orientation: Mat3A,
+    pub half_extents: Vec3A,
+}
+
+/// Where two shapes are touching
+///
+/// `normal` points away from the field geometry, and `depth` is how far the shapes overlap along it
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Contact {
+    pub point: Vec3A,
+    pub normal: Vec3A,
+    pub depth: f32,
+}
+
+impl Obb {
+    fn axes(&self) -> [Vec3A; 3] {
+        [self.orientation.x_axis, self.orientation.y_axis, self.orientation.z_axis]
+    }
+
+    /// The corner of the box that's the furthest along `direction`
+    pub fn support(&self, direction: Vec3A) -> Vec3A {
+        self.axes().iter().zip(self.half_extents.to_array()).fold(self.center, |p, (axis, half_extent)| p + *axis * half_extent.copysign(axis.dot(direction)))
+    }
+
+    /// The point in (or on) the box that's closest to `p`
+    pub fn closest_point(&self, p: Vec3A) -> Vec3A {
+        let d = p - self.center;
+        self.axes().iter().zip(self.half_extents.to_array()).fold(self.center, |closest, (axis, half_extent)| closest + *axis * axis.dot(d).clamp(-half_extent, half_extent))
+    }
+
+    /// Separating axis test between the box and a triangle, returning the axis of least penetration
+    pub fn intersect_tri(&self, tri: &Tri) -> Option<Contact> {
+        let axes = self.axes();
+
+        // work in the box's local space, where the box is centered at the origin
+        let v = tri.p.map(|p| {
+            let d = p - self.center;
+            Vec3A::new(d.dot(axes[0]), d.dot(axes[1]), d.dot(axes[2]))
+        });
+        let e = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+
+        let mut candidates = Vec::with_capacity(13);
+        candidates.extend_from_slice(&[Vec3A::X, Vec3A::Y, Vec3A::Z, e[0].cross(e[1])]);
+        for box_axis in [Vec3A::X, Vec3A::Y, Vec3A::Z] {
+            for edge in e {
+                candidates.push(box_axis.cross(edge));
+            }
+        }
+
+        let mut best: Option<(f32, Vec3A)> = None;
+
+        for axis in candidates {
+            // parallel edges don't give a usable axis
+            if axis.length_squared() < 1e-6 {
+                continue;
+            }
+
+            let axis = axis.normalize();
+
+            let projections = v.map(|p| p.dot(axis));
+            let tri_min = projections[0].min(projections[1]).min(projections[2]);
+            let tri_max = projections[0].max(projections[1]).max(projections[2]);
+            let r = self.half_extents.dot(axis.abs());
+
+            if tri_min > r || tri_max < -r {
+                return None;
+            }
+
+            // how far the box would have to move along the axis (either way) to stop touching the triangle
+            let (overlap, direction) = if tri_max + r < r - tri_min {
+                (tri_max + r, axis)
+            } else {
+                (r - tri_min, -axis)
+            };
+
+            if best.is_none_or(|(depth, _)| overlap < depth) {
+                best = Some((overlap, direction));
+            }
+        }
+
+        let (depth, local_normal) = best?;
+        let normal = axes[0] * local_normal.x + axes[1] * local_normal.y + axes[2] * local_normal.z;
+
+        Some(Contact {
+            point: self.support(-normal),
+            normal,
+            depth,
+        })
+    }
+}
+
+impl From<&'_ Obb> for Aabb {
+    fn from(value: &'_ Obb) -> Self {
+        let extent = value.axes().iter().zip(value.half_extents.to_array()).fold(Vec3A::ZERO, |extent, (axis, half_extent)| extent + axis.abs() * half_extent);
+
+        Self {
+            min: value.center - extent,
+            max: value.center + extent,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use glam::{const_vec3a, vec3a};
@@ -257,6 +644,98 @@ mod test {
         assert!(!aabb.intersect_sphere(&SPHERE));
     }
 
+    #[test]
+    fn obb_tri_intersect() {
+        let floor = Tri {
+            p: [vec3a(-1000., -1000., 0.), vec3a(1000., -1000., 0.), vec3a(0., 1000., 0.)],
+        };
+
+        let obb = Obb {
+            center: vec3a(0., 0., 15.),
+            orientation: Mat3A::from_rotation_z(0.5),
+            half_extents: vec3a(60., 40., 20.),
+        };
+
+        let contact = obb.intersect_tri(&floor).unwrap();
+        assert!((contact.depth - 5.).abs() < 1e-4);
+        assert!((contact.normal - Vec3A::Z).length() < 1e-6);
+        assert!((contact.point.z - -5.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn obb_tri_not_intersect() {
+        let obb = Obb {
+            center: vec3a(0., 0., 25.),
+            orientation: Mat3A::from_rotation_x(0.1),
+            half_extents: vec3a(60., 40., 20.),
+        };
+
+        assert!(obb.intersect_tri(&TRI).is_none());
+
+        // tilted enough that only a corner pokes through
+        let floor = Tri {
+            p: [vec3a(-1000., -1000., 0.), vec3a(1000., -1000., 0.), vec3a(0., 1000., 0.)],
+        };
+
+        let tilted = Obb {
+            center: vec3a(0., 0., 30.),
+            orientation: Mat3A::from_rotation_x(0.6),
+            half_extents: vec3a(60., 40., 20.),
+        };
+
+        assert!(tilted.intersect_tri(&floor).is_some());
+        assert!(Obb {
+            center: vec3a(0., 0., 60.),
+            ..tilted
+        }
+        .intersect_tri(&floor)
+        .is_none());
+    }
+
+    #[test]
+    fn tri_closest_point() {
+        // inside, over an edge, and past a corner
+        assert_eq!(TRI.closest_point(TRI.center() + TRI.unit_normal() * 3.), TRI.center());
+        assert_eq!(TRI.closest_point(vec3a(2., 10., 0.)), vec3a(2., 5., 0.));
+        assert_eq!(TRI.closest_point(vec3a(-5., 5., 0.)), TRI.p[0]);
+    }
+
+    #[test]
+    fn tri_segment_distance() {
+        let floor = Tri {
+            p: [vec3a(-1000., -1000., 0.), vec3a(1000., -1000., 0.), vec3a(0., 1000., 0.)],
+        };
+
+        // crosses the triangle
+        let ray = Ray {
+            start: vec3a(0., 0., 100.),
+            direction: vec3a(0., 0., -200.),
+        };
+        assert_eq!(floor.intersect_ray(&ray), Some(0.5));
+        assert_eq!(floor.distance_to_segment(&ray), 0.);
+
+        // parallel to the triangle
+        let ray = Ray {
+            start: vec3a(-100., 0., 50.),
+            direction: vec3a(200., 0., 0.),
+        };
+        assert_eq!(floor.intersect_ray(&ray), None);
+        assert!((floor.distance_to_segment(&ray) - 50.).abs() < 1e-4);
+
+        // passes beyond a corner
+        let ray = Ray {
+            start: vec3a(-100., 1100., 0.),
+            direction: vec3a(200., 0., 0.),
+        };
+        assert!((floor.distance_to_segment(&ray) - 100.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn aabb_aabb_distance() {
+        assert_eq!(BOUNDING_BOXES[0].distance_to(&BOUNDING_BOXES[1]), 0.);
+        assert!((BOUNDING_BOXES[1].distance_to(&BOUNDING_BOXES[3]) - 12f32.sqrt()).abs() < 1e-6);
+    }
+
     #[test]
     fn aabb_aabb_intersect() {
         // Test for intersection with itself
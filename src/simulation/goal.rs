@@ -0,0 +1,357 @@
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+
+use super::ball::{Ball, BallPrediction};
+use super::dropshot::TileState;
+use super::game::{Game, GameMode};
+use super::hoops::Funnel;
+
+// When the ball counts as being in the goal
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GoalCriteria {
+    // the whole ball has to be past the goal line, which is how the game scores goals
+    #[default]
+    FullBall,
+    // just the center of the ball has to be past the goal line
+    Center,
+}
+
+// Blue defends the goal on the negative y side, and orange the one on the positive side
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Team {
+    #[default]
+    Blue,
+    Orange,
+}
+
+impl Team {
+    // the sign of y on this team's half of the field
+    pub fn side(self) -> f32 {
+        match self {
+            Team::Blue => -1.,
+            Team::Orange => 1.,
+        }
+    }
+
+    // the index of this team's goal in `Game::goals`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn opponent(self) -> Team {
+        match self {
+            Team::Blue => Team::Orange,
+            Team::Orange => Team::Blue,
+        }
+    }
+}
+
+// The opening of a goal, as a rectangle standing on the goal line
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Goal {
+    // the middle of the goal line, on the floor
+    pub location: Vec3A,
+    // horizontal, pointing from the field into the goal
+    pub direction: Vec3A,
+    pub width: f32,
+    pub height: f32,
+}
+
+// When and where a prediction scores
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GoalEvent {
+    pub slice: usize,
+    pub time: f32,
+    // the index into `Game::goals`, for soccar and throwback 0 is blue's goal and 1 is orange's
+    pub goal: usize,
+}
+
+impl Goal {
+    const SOCCAR_GOAL_LINE: f32 = 5120.;
+    const SOCCAR_WIDTH: f32 = 1785.51;
+    const SOCCAR_HEIGHT: f32 = 642.775;
+
+    // measured from the field's mesh, the goals sit out in front of the back walls
+    const THROWBACK_GOAL_LINE: f32 = 4900.;
+    const THROWBACK_WIDTH: f32 = 1887.4;
+    const THROWBACK_HEIGHT: f32 = 640.;
+
+    // blue's goal is on the negative y side, orange's is on the positive side
+    fn pair(goal_line: f32, width: f32, height: f32) -> [Goal; 2] {
+        [-1., 1.].map(|side| Goal {
+            location: Vec3A::new(0., side * goal_line, 0.),
+            direction: Vec3A::new(0., side, 0.),
+            width,
+            height,
+        })
+    }
+
+    pub fn soccar() -> [Goal; 2] {
+        Goal::pair(Goal::SOCCAR_GOAL_LINE, Goal::SOCCAR_WIDTH, Goal::SOCCAR_HEIGHT)
+    }
+
+    pub fn throwback() -> [Goal; 2] {
+        Goal::pair(Goal::THROWBACK_GOAL_LINE, Goal::THROWBACK_WIDTH, Goal::THROWBACK_HEIGHT)
+    }
+
+    // how far the ball's center is past the goal line
+    pub fn depth(&self, ball: &Ball) -> f32 {
+        (ball.location - self.location).dot(self.direction)
+    }
+
+    // the point in the opening that's closest to `point`
+    pub fn closest_point(&self, point: Vec3A) -> Vec3A {
+        let offset = point - self.location;
+        let along_line = self.direction.cross(Vec3A::Z).normalize_or_zero();
+
+        let across = offset.dot(along_line).clamp(-self.width / 2., self.width / 2.);
+        let up = offset.z.clamp(0., self.height);
+
+        self.location + along_line * across + Vec3A::Z * up
+    }
+
+    pub fn is_scored(&self, ball: &Ball, criteria: GoalCriteria) -> bool {
+        let required_depth = match criteria {
+            GoalCriteria::FullBall => ball.radius,
+            GoalCriteria::Center => 0.,
+        };
+
+        if self.depth(ball) <= required_depth {
+            return false;
+        }
+
+        // the ball has to be inside of the goal, not just past the line somewhere else
+        let offset = ball.location - self.location;
+        let along_line = self.direction.cross(Vec3A::Z).normalize_or_zero();
+
+        offset.dot(along_line).abs() <= self.width / 2. && offset.z <= self.height
+    }
+}
+
+impl Game {
+    // the index of the goal that the ball is in, using this game's goal criteria
+    pub fn goal_scored(&self, ball: &Ball) -> Option<usize> {
+        self.arena().goal_scored(ball)
+    }
+
+    // The closest point to `point` that the ball can score through on `team`'s side, in any mode
+    // In hoops that's the opening of the hoop, and in dropshot it's the middle of a tile, where broken tiles come first since the ball can only fall through those
+    // None if the field doesn't have anywhere to score, like most custom fields
+    pub fn nearest_goal(&self, point: Vec3A, team: Team) -> Option<Vec3A> {
+        match self.mode {
+            GameMode::Hoops => {
+                let hoop = Funnel::hoops()[team.index()];
+                let offset = (point - hoop.center) * Vec3A::new(1., 1., 0.);
+
+                Some(hoop.center + offset.clamp_length_max(hoop.radius) + Vec3A::Z * hoop.height)
+            }
+            GameMode::Dropshot => {
+                let tiles = self.dropshot_tiles.as_ref()?;
+                let on_side = (0..tiles.len()).filter(|&tile| tiles.center(tile).y * team.side() > 0.);
+                let broken = on_side.clone().filter(|&tile| tiles.state(tile) == TileState::Broken);
+
+                closest(point, broken.map(|tile| tiles.center(tile))).or_else(|| closest(point, on_side.map(|tile| tiles.center(tile))))
+            }
+            _ => self.field.goals.get(team.index()).map(|goal| goal.closest_point(point)),
+        }
+    }
+
+    // if the point is in the half of the field that `team` attacks, which is the other team's half in every mode
+    pub fn in_offensive_half(&self, point: Vec3A, team: Team) -> bool {
+        point.y * team.side() < 0.
+    }
+}
+
+fn closest(point: Vec3A, candidates: impl Iterator<Item = Vec3A>) -> Option<Vec3A> {
+    candidates.min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+}
+
+impl BallPrediction {
+    // the first slice where the ball is in a goal, and which goal it's in
+    pub fn first_goal(&self, game: &Game) -> Option<GoalEvent> {
+        self.slices.iter().enumerate().find_map(|(slice, ball)| GoalEvent::check(game, slice, ball))
+    }
+}
+
+impl GoalEvent {
+    fn check(game: &Game, slice: usize, ball: &Ball) -> Option<Self> {
+        Some(Self {
+            slice,
+            time: ball.time,
+            goal: game.goal_scored(ball)?,
+        })
+    }
+}
+
+impl Ball {
+    pub fn get_ball_prediction_struct_with_goal(game: &mut Game) -> (BallPrediction, Option<GoalEvent>) {
+        Ball::get_ball_prediction_struct_with_goal_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+
+    // Like a normal prediction, but checks each slice against the game's goals as it goes
+    // The ball explodes once it's scored, so the prediction stops at the slice it goes in
+    pub fn get_ball_prediction_struct_with_goal_for_slices(game: &mut Game, num_slices: usize) -> (BallPrediction, Option<GoalEvent>) {
+        let mut prediction = BallPrediction {
+            slices: Vec::with_capacity(num_slices),
+            ..BallPrediction::default()
+        };
+        let mut event = None;
+
+        let arena = game.arena();
+        let mut ball = game.ball;
+        let mut slice = 0;
+
+        let scored = |ball: &Ball| {
+            event = arena.goal_scored(ball).map(|goal| GoalEvent {
+                slice,
+                time: ball.time,
+                goal,
+            });
+            slice += 1;
+
+            event.is_some()
+        };
+
+        Ball::predict_in(&arena, &mut ball, Ball::SIMULATION_DT, 1, num_slices, scored, &mut prediction);
+        game.ball = ball;
+        (prediction, event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+
+    #[test]
+    fn criteria() {
+        let mut ball = Ball::initialize_soccar();
+        let goal = Goal::soccar()[1];
+
+        ball.location = vec3a(0., 5120. + 50., 100.);
+        assert!(goal.is_scored(&ball, GoalCriteria::Center));
+        assert!(!goal.is_scored(&ball, GoalCriteria::FullBall));
+
+        ball.location.y = 5120. + ball.radius + 1.;
+        assert!(goal.is_scored(&ball, GoalCriteria::FullBall));
+
+        // past the line, but over the crossbar
+        ball.location.z = 800.;
+        assert!(!goal.is_scored(&ball, GoalCriteria::Center));
+
+        // and outside of the posts
+        ball.location = vec3a(1000., 5300., 100.);
+        assert!(!goal.is_scored(&ball, GoalCriteria::Center));
+
+        assert!(!Goal::soccar()[0].is_scored(&Ball::initialize_soccar(), GoalCriteria::Center));
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn predicted_goal() {
+        use crate::load_soccar;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 3000., 300.), vec3a(0., 2000., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+        let full = prediction.first_goal(&game).unwrap();
+        assert_eq!(full.goal, 1);
+        assert_eq!(full.time, prediction.slices[full.slice].time);
+
+        game.goal_criteria = GoalCriteria::Center;
+        let center = prediction.first_goal(&game).unwrap();
+        assert_eq!(center.goal, 1);
+
+        // the center of the ball crosses the line first
+        assert!(center.slice < full.slice);
+    }
+
+    #[test]
+    #[cfg(all(feature = "soccar", feature = "throwback"))]
+    fn prediction_stops_at_goal() {
+        use crate::{load_soccar, load_soccar_throwback};
+
+        for mut game in [load_soccar(), load_soccar_throwback()] {
+            game.ball.update(0., vec3a(500., -2500., 200.), vec3a(-300., -2500., 300.), Vec3A::ZERO);
+            let mut full_game = game.clone();
+
+            let (prediction, event) = Ball::get_ball_prediction_struct_with_goal(&mut game);
+            let event = event.unwrap();
+
+            assert_eq!(event.goal, 0);
+            assert_eq!(prediction.num_slices, event.slice + 1);
+            assert_eq!(prediction.slices.len(), prediction.num_slices);
+            assert_eq!(prediction.slices.last().unwrap().time, event.time);
+
+            // the same as checking a full prediction afterwards
+            let full = Ball::get_ball_prediction_struct(&mut full_game);
+            assert_eq!(full.first_goal(&full_game), Some(event));
+        }
+
+        // shots that miss don't stop the prediction
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 100.), vec3a(2000., 0., 0.), Vec3A::ZERO);
+        let (prediction, event) = Ball::get_ball_prediction_struct_with_goal(&mut game);
+        assert_eq!(event, None);
+        assert_eq!(prediction.num_slices, Ball::STANDARD_NUM_SLICES);
+    }
+
+    #[test]
+    #[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+    fn nearest_goal() {
+        use crate::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
+
+        let point = vec3a(3000., 2000., 1500.);
+
+        // inside of the posts and under the crossbar
+        let soccar = load_soccar().nearest_goal(point, Team::Orange).unwrap();
+        assert_eq!(soccar, vec3a(Goal::SOCCAR_WIDTH / 2., Goal::SOCCAR_GOAL_LINE, Goal::SOCCAR_HEIGHT));
+        let throwback = load_soccar_throwback().nearest_goal(point, Team::Blue).unwrap();
+        assert_eq!(throwback.y, -Goal::THROWBACK_GOAL_LINE);
+
+        // on the rim of the hoop
+        let hoop = Funnel::hoops()[Team::Orange.index()];
+        let hoops = load_hoops().nearest_goal(point, Team::Orange).unwrap();
+        assert!((((hoops - hoop.center) * vec3a(1., 1., 0.)).length() - hoop.radius).abs() < 0.01);
+        assert_eq!(hoops.z, hoop.height);
+
+        // a tile on orange's half, and the broken one once there is one
+        let mut dropshot = load_dropshot();
+        let tile = dropshot.nearest_goal(point, Team::Orange).unwrap();
+        assert!(tile.y > 0.);
+
+        let tiles = dropshot.dropshot_tiles.as_mut().unwrap();
+        let far_tile = (0..tiles.len()).rev().find(|&tile| tiles.center(tile).x < 0.).unwrap();
+        tiles.break_tile(far_tile);
+        assert_eq!(dropshot.nearest_goal(point, Team::Orange), Some(dropshot.dropshot_tiles.as_ref().unwrap().center(far_tile)));
+
+        let mut custom = load_soccar();
+        custom.mode = GameMode::Custom;
+        custom.field.goals.clear();
+        assert_eq!(custom.nearest_goal(point, Team::Blue), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+    fn offensive_half() {
+        use crate::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
+
+        for game in [load_soccar(), load_hoops(), load_dropshot(), load_soccar_throwback()] {
+            let near_orange = vec3a(0., 1000., 100.);
+
+            assert!(game.in_offensive_half(near_orange, Team::Blue));
+            assert!(!game.in_offensive_half(near_orange, Team::Orange));
+            assert!(game.in_offensive_half(-near_orange, Team::Blue.opponent()));
+
+            // each team's own goal is in the other team's offensive half
+            if let Some(goal) = game.nearest_goal(Vec3A::ZERO, Team::Orange) {
+                assert!(game.in_offensive_half(goal, Team::Blue), "{:?}", game.mode);
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+
+use super::bvh::contact_from_tris;
+use super::geometry::{Aabb, Ray, Sphere, Tri};
+
+// A uniform grid (spatial hash) broadphase
+// Most of the standard fields are made out of big axis-aligned pieces,
+// so for ball-sized queries looking up a handful of cells can be cheaper than walking the BVH
+#[derive(Clone, Debug, Default)]
+pub struct Grid {
+    pub global_box: Aabb,
+    pub cell_size: f32,
+    pub dims: [usize; 3],
+    // cell i owns items[cell_start[i]..cell_start[i + 1]]
+    cell_start: Vec<u32>,
+    items: Vec<u32>,
+    primitives: Vec<Tri>,
+}
+
+impl Grid {
+    pub const DEFAULT_CELL_SIZE: f32 = 256.;
+
+    pub fn from(primitives: &[Tri], cell_size: f32) -> Self {
+        assert!(cell_size > 0., "Grid cell size must be positive");
+
+        let boxes: Vec<Aabb> = primitives.iter().map(Aabb::from).collect();
+        let global_box = boxes.iter().skip(1).fold(boxes.first().copied().unwrap_or_default(), |global, b| global.add(b));
+
+        let dims = ((global_box.max - global_box.min) / cell_size).floor().as_uvec3() + 1;
+        let dims = [dims.x as usize, dims.y as usize, dims.z as usize];
+        let num_cells = dims[0] * dims[1] * dims[2];
+
+        let mut grid = Self {
+            global_box,
+            cell_size,
+            dims,
+            cell_start: Vec::with_capacity(num_cells + 1),
+            items: Vec::new(),
+            primitives: primitives.to_vec(),
+        };
+
+        // count how many triangles land in each cell, then fill the cells in a second pass
+        let mut counts = vec![0u32; num_cells];
+
+        for box_ in &boxes {
+            grid.for_each_cell(box_, |cell| counts[cell] += 1);
+        }
+
+        let mut total = 0;
+        for count in &counts {
+            grid.cell_start.push(total);
+            total += count;
+        }
+        grid.cell_start.push(total);
+
+        let mut next = grid.cell_start.clone();
+        grid.items = vec![0; total as usize];
+
+        for (i, box_) in boxes.iter().enumerate() {
+            let mut cells = Vec::new();
+            grid.for_each_cell(box_, |cell| cells.push(cell));
+
+            for cell in cells {
+                grid.items[next[cell] as usize] = i as u32;
+                next[cell] += 1;
+            }
+        }
+
+        grid
+    }
+
+    fn cell_coords(&self, p: Vec3A) -> [usize; 3] {
+        let c = ((p - self.global_box.min) / self.cell_size).floor().max(Vec3A::ZERO);
+
+        [(c.x as usize).min(self.dims[0] - 1), (c.y as usize).min(self.dims[1] - 1), (c.z as usize).min(self.dims[2] - 1)]
+    }
+
+    fn for_each_cell<F: FnMut(usize)>(&self, box_: &Aabb, mut f: F) {
+        if !box_.intersect_self(&self.global_box) {
+            return;
+        }
+
+        let lo = self.cell_coords(box_.min);
+        let hi = self.cell_coords(box_.max);
+
+        for z in lo[2]..=hi[2] {
+            for y in lo[1]..=hi[1] {
+                for x in lo[0]..=hi[0] {
+                    f((z * self.dims[1] + y) * self.dims[0] + x);
+                }
+            }
+        }
+    }
+
+    pub fn num_primitives(&self) -> usize {
+        self.primitives.len()
+    }
+
+    pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
+        let query_box: Aabb = query_object.into();
+
+        let mut candidates: Vec<u32> = Vec::with_capacity(32);
+        self.for_each_cell(&query_box, |cell| candidates.extend_from_slice(&self.items[self.cell_start[cell] as usize..self.cell_start[cell + 1] as usize]));
+
+        // big triangles span multiple cells, so only check each one once
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates.into_iter().map(|i| self.primitives[i as usize]).filter(|tri| Aabb::from(tri).intersect_self(&query_box) && tri.intersect_sphere(query_object)).collect()
+    }
+
+    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
+        contact_from_tris(s, self.intersect(s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::bvh::Bvh;
+
+    static VERT_MAP: &[[usize; 3]; 12] = &[[1, 0, 2], [3, 1, 2], [7, 5, 6], [4, 6, 5], [2, 0, 4], [6, 2, 4], [7, 3, 5], [1, 5, 3], [4, 0, 1], [5, 4, 1], [7, 6, 3], [2, 3, 6]];
+
+    fn generate_tris() -> Vec<Tri> {
+        let verts = &[vec3a(-4096.0, -5120.0, 0.0), vec3a(-4096.0, -5120.0, 2044.0), vec3a(-4096.0, 5120.0, 0.0), vec3a(-4096.0, 5120.0, 2044.0), vec3a(4096.0, -5120.0, 0.0), vec3a(4096.0, -5120.0, 2044.0), vec3a(4096.0, 5120.0, 0.0), vec3a(4096.0, 5120.0, 2044.0)];
+        VERT_MAP
+            .iter()
+            .map(|map| Tri {
+                p: [verts[map[0]], verts[map[1]], verts[map[2]]],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_grid_build() {
+        let triangles = generate_tris();
+
+        let grid = Grid::from(&triangles, Grid::DEFAULT_CELL_SIZE);
+
+        assert_eq!(grid.num_primitives(), triangles.len());
+        assert_eq!(grid.dims, [33, 41, 8]);
+    }
+
+    #[test]
+    fn test_grid_intersect() {
+        let triangles = generate_tris();
+
+        let grid = Grid::from(&triangles, Grid::DEFAULT_CELL_SIZE);
+
+        let cases = [
+            // Sphere hits nothing
+            (vec3a(0., 0., 1022.), 0),
+            // Sphere hits one Tri
+            (vec3a(4096. / 2., 5120. / 2., 100.), 1),
+            // Middle of two Tris
+            (Vec3A::ZERO, 2),
+            // Sphere is in a corner
+            (vec3a(4096., 5120., 0.), 5),
+        ];
+
+        for (center, num_hits) in cases {
+            let sphere = Sphere {
+                center,
+                radius: 100.,
+            };
+
+            assert_eq!(grid.intersect(&sphere).len(), num_hits);
+        }
+    }
+
+    #[test]
+    fn test_grid_matches_bvh() {
+        let triangles = generate_tris();
+
+        let grid = Grid::from(&triangles, Grid::DEFAULT_CELL_SIZE);
+        let bvh = Bvh::from(&triangles);
+
+        for center in [vec3a(0., 0., 1022.), vec3a(4096. / 2., 5120. / 2., 99.), Vec3A::ZERO, vec3a(4096., 5120., 0.), vec3a(-4000., 5000., 2000.)] {
+            let sphere = Sphere {
+                center,
+                radius: 100.,
+            };
+
+            let grid_contact = grid.collide(&sphere);
+            let bvh_contact = bvh.collide(&sphere);
+
+            assert_eq!(grid_contact.is_some(), bvh_contact.is_some());
+
+            if let (Some(a), Some(b)) = (grid_contact, bvh_contact) {
+                assert!((a.start - b.start).length() < 0.01);
+                assert!((a.direction - b.direction).length() < 0.0001);
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+
+use glam::{Vec2, Vec3A};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::Ball;
+use super::game::Game;
+use super::mutators::DragModel;
+use super::tuning::Tuning;
+
+// A ball that only moves along the ground plane
+// This is meant for rolling balls, where the vertical motion doesn't matter,
+// so there's no BVH query at all - only drag and the side/back walls (as a box) are simulated
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GroundBall {
+    pub time: f32,
+    pub location: Vec2,
+    pub velocity: Vec2,
+    pub drag_model: DragModel,
+}
+
+#[derive(Clone, Default)]
+pub struct GroundPrediction {
+    pub num_slices: usize,
+    pub slices: Vec<GroundBall>,
+}
+
+// The walls of the field, shrunk by the ball's radius
+#[derive(Clone, Copy, Debug)]
+pub struct GroundBounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl GroundBounds {
+    pub fn from_game(game: &Game) -> Self {
+        let global_box = game.field.collision_mesh.global_box;
+        let radius = Vec2::splat(game.ball.collision_radius);
+
+        Self {
+            min: Vec2::new(global_box.min.x, global_box.min.y) + radius,
+            max: Vec2::new(global_box.max.x, global_box.max.y) - radius,
+        }
+    }
+}
+
+impl GroundBall {
+    pub fn from_ball(ball: &Ball) -> Self {
+        Self {
+            time: ball.time,
+            location: Vec2::new(ball.location.x, ball.location.y),
+            velocity: Vec2::new(ball.velocity.x, ball.velocity.y),
+            drag_model: ball.drag_model,
+        }
+    }
+
+    // puts the ball back in 3D, resting on the floor
+    pub fn to_ball(&self, template: &Ball) -> Ball {
+        Ball {
+            time: self.time,
+            location: Vec3A::new(self.location.x, self.location.y, template.collision_radius),
+            velocity: Vec3A::new(self.velocity.x, self.velocity.y, 0.),
+            angular_velocity: Vec3A::ZERO,
+            drag_model: self.drag_model,
+            ..*template
+        }
+    }
+
+    pub fn step(&mut self, bounds: &GroundBounds, tuning: &Tuning, dt: f32) {
+        self.velocity += self.velocity * (self.drag_model.coefficient(self.velocity.extend(0.).into(), tuning.drag()) * dt);
+        self.velocity *= (Ball::V_MAX * self.velocity.length_recip()).min(1.);
+        self.location += self.velocity * dt;
+
+        // bounce off of the walls, losing the same amount of speed as a full collision would
+        for axis in 0..2 {
+            if self.location[axis] < bounds.min[axis] {
+                self.location[axis] = bounds.min[axis];
+                self.velocity[axis] = -self.velocity[axis].min(0.) * tuning.restitution();
+            } else if self.location[axis] > bounds.max[axis] {
+                self.location[axis] = bounds.max[axis];
+                self.velocity[axis] = -self.velocity[axis].max(0.) * tuning.restitution();
+            }
+        }
+
+        self.time += dt;
+    }
+
+    pub fn get_ground_prediction_struct_for_time(game: &Game, time: &f32) -> GroundPrediction {
+        GroundBall::get_ground_prediction_struct_for_slices(game, (time / Ball::SIMULATION_DT).round() as usize)
+    }
+
+    pub fn get_ground_prediction_struct(game: &Game) -> GroundPrediction {
+        GroundBall::get_ground_prediction_struct_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+
+    pub fn get_ground_prediction_struct_for_slices(game: &Game, num_slices: usize) -> GroundPrediction {
+        let bounds = GroundBounds::from_game(game);
+        let mut ball = GroundBall::from_ball(&game.ball);
+        let mut slices = Vec::with_capacity(num_slices);
+
+        for _ in 0..num_slices {
+            ball.step(&bounds, &game.field.tuning, Ball::SIMULATION_DT);
+            slices.push(ball);
+        }
+
+        GroundPrediction {
+            num_slices: slices.len(),
+            slices,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+
+    #[test]
+    fn check_num_slices() {
+        let game = load_soccar();
+
+        let prediction = GroundBall::get_ground_prediction_struct(&game);
+
+        assert_eq!(prediction.num_slices, Ball::STANDARD_NUM_SLICES);
+        assert_eq!(prediction.slices.len(), Ball::STANDARD_NUM_SLICES);
+    }
+
+    #[test]
+    fn rolling_ball_matches_full_simulation() {
+        let mut game = load_soccar();
+
+        // a ball that's already rolling without slipping along the floor
+        let velocity = vec3a(1000., 500., 0.);
+        let angular_velocity = Vec3A::Z.cross(velocity) / game.ball.collision_radius;
+        game.ball.update(0., vec3a(0., 0., game.ball.collision_radius), velocity, angular_velocity);
+
+        let ground = GroundBall::get_ground_prediction_struct_for_slices(&game, 120);
+        let full = Ball::get_ball_prediction_struct_for_slices(&mut game, 120);
+
+        for (ground_slice, full_slice) in ground.slices.iter().zip(&full.slices) {
+            let full_location = Vec2::new(full_slice.location.x, full_slice.location.y);
+            assert!((ground_slice.location - full_location).length() < 5.);
+        }
+    }
+
+    #[test]
+    fn bounces_off_the_side_wall() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(3500., 0., game.ball.collision_radius), vec3a(2000., 0., 0.), Vec3A::ZERO);
+
+        let prediction = GroundBall::get_ground_prediction_struct(&game);
+        let bounds = GroundBounds::from_game(&game);
+
+        assert!(prediction.slices.iter().all(|slice| slice.location.x <= bounds.max.x));
+        assert!(prediction.slices.last().unwrap().velocity.x < 0.);
+    }
+}
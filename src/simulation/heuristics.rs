@@ -0,0 +1,310 @@
+use alloc::vec::Vec;
+use core::f32::consts::SQRT_2;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::analysis::Wall;
+use super::bvh::Bvh;
+use super::geometry::{Aabb, Ray};
+use super::goal::Team;
+
+// how far into a goal the inside of it is measured, and how deep it has to be to count
+const OPENING_DEPTH: f32 = 10.;
+// how precisely the edges of the ramps and the goals are found
+pub(super) const EDGE_PRECISION: f32 = 0.5;
+const SCAN_STEP: f32 = 64.;
+// the height the goals are looked for at, so the ray doesn't skim the floor
+const MOUTH_HEIGHT: f32 = 50.;
+// far enough to cross any of the fields
+const MAX_DISTANCE: f32 = 20000.;
+// the fields are mirrored across x = 0 and y = 0, so rays right on those planes can slip between the two halves
+pub(super) const PROBE_OFFSET: f32 = 0.5;
+// how far up between the floor and the ceiling the back walls are measured, which is above the goals
+const BACKBOARD_HEIGHT: f32 = 0.7;
+// how far out from the backboard the rays that find its edges start, and how close they have to land to its plane
+const BACKBOARD_PROBE_DISTANCE: f32 = 200.;
+const PLANE_TOLERANCE: f32 = 1.;
+
+// Geometry that bots tend to need, measured from a field's collision mesh instead of hardcoded for each mode
+// The walls are found by looking out from the center of the field, so they're the walls facing the center
+#[derive(Clone, Debug, Default)]
+pub struct FieldHeuristics {
+    pub floor_height: f32,
+    pub ceiling_height: f32,
+    // from the center of the field, along x and along y
+    pub side_wall_distance: f32,
+    pub back_wall_distance: f32,
+    // the radius of the curved ramp between the floor and the side walls
+    pub ramp_radius: f32,
+    // the radius of a rounded corner that cuts the corners between the side and back walls off as much as the field does
+    pub corner_radius: f32,
+    // the inside of the goals, from the goal line to the back of the goal, with negative y first
+    pub goal_mouths: Vec<Aabb>,
+    // the back walls, measured high enough to be above the goals, in the order of `Team::index`
+    pub backboards: [Backboard; 2],
+}
+
+// The flat part of a back wall, above a team's goal
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Backboard {
+    pub team: Team,
+    // the normal points into the field
+    pub plane: Wall,
+    // the part of the plane that's actually flat wall, before it curves into the corners, the ceiling or the goal
+    pub extents: Aabb,
+}
+
+impl Backboard {
+    // (a, b, c, d) for the plane a * x + b * y + c * z + d = 0, where (a, b, c) points into the field
+    pub fn equation(&self) -> [f32; 4] {
+        let n = self.plane.normal;
+        [n.x, n.y, n.z, -n.dot(self.plane.point)]
+    }
+
+    // how far the point is in front of the plane
+    pub fn distance(&self, point: Vec3A) -> f32 {
+        self.plane.normal.dot(point - self.plane.point)
+    }
+}
+
+impl FieldHeuristics {
+    // None if the mesh isn't closed around the center of the field, like most custom fields
+    pub fn measure(mesh: &Bvh) -> Option<Self> {
+        let center = Vec3A::new(PROBE_OFFSET, PROBE_OFFSET, 500.);
+        let floor_height = center.z - cast(mesh, center, -Vec3A::Z)?.0;
+        let ceiling_height = floor_height + 1. + cast(mesh, Vec3A::new(center.x, center.y, floor_height + 1.), Vec3A::Z)?.0;
+        let middle = Vec3A::new(center.x, center.y, (floor_height + ceiling_height) / 2.);
+        let upper = Vec3A::new(center.x, center.y, floor_height + (ceiling_height - floor_height) * BACKBOARD_HEIGHT);
+
+        let side_wall_distance = PROBE_OFFSET + cast(mesh, middle, Vec3A::X)?.0;
+        let back_wall_distance = PROBE_OFFSET + cast(mesh, upper, Vec3A::Y)?.0;
+
+        // where the floor starts curving up into the side wall
+        let is_flat = |x: f32| cast(mesh, Vec3A::new(x, middle.y, middle.z), -Vec3A::Z).is_some_and(|(distance, _)| (middle.z - distance - floor_height).abs() < EDGE_PRECISION);
+        let ramp_radius = side_wall_distance - scan(0., side_wall_distance, is_flat);
+
+        // along the diagonal through the corner, a rounded corner with radius r is r * (sqrt(2) - 1) in from the corner
+        let inset = side_wall_distance.min(back_wall_distance);
+        let corner = Vec3A::new(side_wall_distance, back_wall_distance, middle.z);
+        let diagonal = Vec3A::new(1., 1., 0.).normalize();
+        let start = corner - diagonal * inset * SQRT_2;
+        let corner_gap = inset * SQRT_2 - cast(mesh, start, diagonal)?.0;
+        let corner_radius = corner_gap.max(0.) / (SQRT_2 - 1.);
+
+        let mut goal_mouths = Vec::new();
+        let mut backboards = [Backboard::default(); 2];
+
+        for team in [Team::Blue, Team::Orange] {
+            let side = team.side();
+            let direction = Vec3A::Y * side;
+
+            let (distance, normal) = cast(mesh, upper, direction)?;
+            let mut backboard = Backboard {
+                team,
+                plane: Wall {
+                    point: upper + direction * distance,
+                    normal,
+                },
+                extents: Aabb::default(),
+            };
+
+            backboard.extents = backboard_extents(mesh, &backboard, floor_height, ceiling_height, side_wall_distance);
+            backboards[team.index()] = backboard;
+
+            if let Some(mouth) = goal_mouth(mesh, floor_height, middle.z, side) {
+                goal_mouths.push(mouth);
+            }
+        }
+
+        Some(Self {
+            floor_height,
+            ceiling_height,
+            side_wall_distance,
+            back_wall_distance,
+            ramp_radius,
+            corner_radius,
+            goal_mouths,
+            backboards,
+        })
+    }
+}
+
+// walks out from the middle of the backboard for as long as rays towards it still land on its plane
+fn backboard_extents(mesh: &Bvh, backboard: &Backboard, floor_height: f32, ceiling_height: f32, side_wall_distance: f32) -> Aabb {
+    let plane = backboard.plane;
+
+    let is_on_plane = |x: f32, z: f32| {
+        let target = Vec3A::new(x, plane.point.y, z);
+        // along the normal, so the ray hits the plane where it's tilted too
+        let start = target - plane.normal * (backboard.distance(target) - BACKBOARD_PROBE_DISTANCE);
+
+        cast(mesh, start, -plane.normal).is_some_and(|(distance, normal)| (distance - BACKBOARD_PROBE_DISTANCE).abs() < PLANE_TOLERANCE && normal.dot(plane.normal) > 0.999)
+    };
+
+    let (x, z) = (plane.point.x, plane.point.z);
+    let min_x = scan(x, -side_wall_distance, |x| is_on_plane(x, z));
+    let max_x = scan(x, side_wall_distance, |x| is_on_plane(x, z));
+    let min_z = scan(z, floor_height, |z| is_on_plane(x, z));
+    let max_z = scan(z, ceiling_height, |z| is_on_plane(x, z));
+
+    // moved along y onto the plane, which matters when it's tilted
+    let corners = [(min_x, min_z), (min_x, max_z), (max_x, min_z), (max_x, max_z)].map(|(x, z)| {
+        let point = Vec3A::new(x, plane.point.y, z);
+        Vec3A::new(x, point.y - backboard.distance(point) / plane.normal.y, z)
+    });
+
+    Aabb {
+        min: corners.iter().fold(Vec3A::splat(f32::MAX), |min, corner| min.min(*corner)),
+        max: corners.iter().fold(Vec3A::splat(f32::MIN), |max, corner| max.max(*corner)),
+    }
+}
+
+// the inside of the goal behind the back wall, if there's one in the middle of it
+fn goal_mouth(mesh: &Bvh, floor_height: f32, max_height: f32, side: f32) -> Option<Aabb> {
+    let low = floor_height + MOUTH_HEIGHT;
+    let back = cast(mesh, Vec3A::new(PROBE_OFFSET, PROBE_OFFSET * side, low), Vec3A::Y * side)?.0 + PROBE_OFFSET;
+
+    // the goal line is where the ceiling drops down to the roof of the goal
+    let roof_at = |y: f32| cast(mesh, Vec3A::new(PROBE_OFFSET, y * side, low), Vec3A::Z).map_or(f32::INFINITY, |(distance, _)| low + distance);
+    let goal_line = scan(0., back, |y| roof_at(y) > max_height);
+
+    if back - goal_line < OPENING_DEPTH {
+        return None;
+    }
+
+    let height = roof_at(goal_line + OPENING_DEPTH);
+    if height > max_height {
+        return None;
+    }
+
+    let inside = Vec3A::new(PROBE_OFFSET, (goal_line + OPENING_DEPTH) * side, (floor_height + height) / 2.);
+    let half_width = PROBE_OFFSET + cast(mesh, inside, Vec3A::X)?.0;
+
+    let (near, far) = (goal_line * side, back * side);
+
+    Some(Aabb {
+        min: Vec3A::new(-half_width, near.min(far), floor_height),
+        max: Vec3A::new(half_width, near.max(far), height),
+    })
+}
+
+// how far the ray goes before it hits the mesh, and the normal of what it hits, facing back towards the ray
+pub(super) fn cast(mesh: &Bvh, start: Vec3A, direction: Vec3A) -> Option<(f32, Vec3A)> {
+    let ray = Ray {
+        start,
+        direction: direction * MAX_DISTANCE,
+    };
+
+    let (t, tri) = mesh.raycast(&ray)?;
+
+    let normal = tri.unit_normal();
+    let normal = if normal.dot(direction) > 0. {
+        -normal
+    } else {
+        normal
+    };
+
+    Some((t * MAX_DISTANCE, normal))
+}
+
+// the last value between `start` and `end` where `is_inside` is still true, assuming it's true at `start`
+// this steps along before bisecting, so a ray slipping through a crack in the mesh doesn't throw it off
+pub(super) fn scan(start: f32, end: f32, is_inside: impl Fn(f32) -> bool) -> f32 {
+    let num_steps = ((end - start).abs() / SCAN_STEP).ceil() as usize;
+    let step = (end - start) / num_steps.max(1) as f32;
+
+    (1..=num_steps).map(|i| start + step * i as f32).find(|&x| !is_inside(x)).map_or(end, |outside| bisect(outside - step, outside, is_inside))
+}
+
+// the same as `scan`, but without the steps, so it assumes `is_inside` flips exactly once
+fn bisect(mut start: f32, mut end: f32, is_inside: impl Fn(f32) -> bool) -> f32 {
+    while (end - start).abs() > EDGE_PRECISION {
+        let mid = (start + end) / 2.;
+
+        if is_inside(mid) {
+            start = mid;
+        } else {
+            end = mid;
+        }
+    }
+
+    start
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops", feature = "dropshot", feature = "throwback"))]
+mod test {
+    use super::*;
+    use crate::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
+
+    #[test]
+    fn soccar() {
+        let game = load_soccar();
+        let heuristics = game.field.heuristics.unwrap();
+
+        assert!(heuristics.floor_height.abs() < 0.01);
+        assert!((heuristics.ceiling_height - 2048.).abs() < 1.);
+        assert!((heuristics.side_wall_distance - 4096.).abs() < 1.);
+        assert!((heuristics.back_wall_distance - 5120.).abs() < 1.);
+        assert!(heuristics.ramp_radius > 100. && heuristics.ramp_radius < 500., "{}", heuristics.ramp_radius);
+        assert!(heuristics.corner_radius > 500., "{}", heuristics.corner_radius);
+
+        // the same goals as the hardcoded ones
+        assert_eq!(heuristics.goal_mouths.len(), 2, "{heuristics:?}");
+        for (mouth, goal) in heuristics.goal_mouths.iter().zip(&game.field.goals) {
+            assert!((mouth.max.x - goal.width / 2.).abs() < 5., "{mouth:?}");
+            assert!((mouth.max.z - goal.height).abs() < 5., "{mouth:?}");
+            assert!((mouth.min.y.abs().min(mouth.max.y.abs()) - goal.location.y.abs()).abs() < 1., "{mouth:?}");
+        }
+
+        assert!(heuristics.goal_mouths[0].max.y < 0. && heuristics.goal_mouths[1].min.y > 0.);
+        assert_eq!(heuristics.backboards[0].plane.normal, Vec3A::Y);
+        assert_eq!(heuristics.backboards[1].plane.normal, -Vec3A::Y);
+    }
+
+    #[test]
+    fn soccar_backboards() {
+        let heuristics = load_soccar().field.heuristics.unwrap();
+
+        for team in [Team::Blue, Team::Orange] {
+            let backboard = heuristics.backboards[team.index()];
+            assert_eq!(backboard.team, team);
+            let equation = backboard.equation();
+            assert!(equation.iter().zip([0., -team.side(), 0., 5120.]).all(|(a, b)| (a - b).abs() < 0.01), "{equation:?}");
+
+            // it's flat from the crossbar up to where it curves into the ceiling, and until it curves into the corners
+            let extents = backboard.extents;
+            assert!(extents.min.z > 600. && extents.min.z < 700., "{extents:?}");
+            assert!(extents.max.z > 1500. && extents.max.z < 1600., "{extents:?}");
+            assert!(extents.max.x > 2500. && extents.max.x < 3000., "{extents:?}");
+            assert!((extents.min.x + extents.max.x).abs() < 1., "{extents:?}");
+            assert!((extents.min.y.abs() - 5120.).abs() < 0.01 && (extents.max.y.abs() - 5120.).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn throwback_goals() {
+        let game = load_soccar_throwback();
+        let heuristics = game.field.heuristics.unwrap();
+
+        assert_eq!(heuristics.goal_mouths.len(), 2);
+        for (mouth, goal) in heuristics.goal_mouths.iter().zip(&game.field.goals) {
+            assert!((mouth.max.x - goal.width / 2.).abs() < 10., "{mouth:?}");
+            assert!((mouth.max.z - goal.height).abs() < 10., "{mouth:?}");
+        }
+    }
+
+    #[test]
+    fn other_modes() {
+        // hoops has its goals above the floor, and dropshot doesn't have any
+        let hoops = load_hoops().field.heuristics.unwrap();
+        assert!(hoops.goal_mouths.is_empty());
+        assert!(hoops.backboards[Team::Orange.index()].distance(Vec3A::new(0., 3000., 1000.)) > 500.);
+
+        let dropshot = load_dropshot().field.heuristics.unwrap();
+        assert!(dropshot.goal_mouths.is_empty());
+        assert!((dropshot.floor_height - 2.).abs() < 0.01);
+    }
+}
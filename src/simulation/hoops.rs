@@ -0,0 +1,143 @@
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+
+use super::ball::{Ball, BallPrediction};
+use super::game::{Game, GameMode};
+use super::goal::{GoalEvent, Team};
+
+// The inside of a hoop, from the rim down to the floor, as a cylinder the size of the inside of the rim
+// Once the center of the ball is in here it can only get out by going back up through the rim, which it doesn't,
+// so the ball is committed to the hoop before it's actually scored
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Funnel {
+    // the middle of the hoop, on the floor
+    pub center: Vec3A,
+    pub radius: f32,
+    // the top of the rim
+    pub height: f32,
+}
+
+impl Funnel {
+    // measured from the rim's mesh, where `initialize_hoops` puts it, which `matches_the_rim` checks
+    // the rim isn't quite round, so the radius is to the closest point on the inside of it
+    const RIM_Y: f32 = 2982.3135;
+    const RIM_INNER_RADIUS: f32 = 661.611;
+    const RIM_TOP: f32 = 397.5903;
+
+    // in the same order as the goals, blue's hoop is on the negative y side and orange's is on the positive side
+    pub fn hoops() -> [Funnel; 2] {
+        [Team::Blue, Team::Orange].map(|team| Funnel {
+            center: Vec3A::new(0., team.side() * Funnel::RIM_Y, 0.),
+            radius: Funnel::RIM_INNER_RADIUS,
+            height: Funnel::RIM_TOP,
+        })
+    }
+
+    pub fn contains(&self, ball: &Ball) -> bool {
+        let offset = ball.location - self.center;
+
+        offset.z <= self.height && offset.x * offset.x + offset.y * offset.y <= self.radius * self.radius
+    }
+}
+
+impl BallPrediction {
+    // Which team's hoop the ball is in at each slice, in the same order as the slices
+    // These are all None outside of hoops
+    pub fn in_funnel(&self, game: &Game) -> Vec<Option<Team>> {
+        self.slices.iter().map(|slice| funnel_containing(game, slice)).collect()
+    }
+
+    // the first slice where the ball is committed to a hoop, with `GoalEvent::goal` as the index of the hoop's team
+    pub fn first_in_funnel(&self, game: &Game) -> Option<GoalEvent> {
+        self.slices.iter().enumerate().find_map(|(slice, ball)| {
+            funnel_containing(game, ball).map(|team| GoalEvent {
+                slice,
+                time: ball.time,
+                goal: team.index(),
+            })
+        })
+    }
+}
+
+fn funnel_containing(game: &Game, ball: &Ball) -> Option<Team> {
+    if game.mode != GameMode::Hoops {
+        return None;
+    }
+
+    let [blue, orange] = Funnel::hoops();
+
+    if blue.contains(ball) {
+        Some(Team::Blue)
+    } else if orange.contains(ball) {
+        Some(Team::Orange)
+    } else {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::field::place_hoop;
+    use crate::{hoops_meshes, load_hoops, load_soccar, Assets};
+
+    #[test]
+    fn matches_the_rim() {
+        let [_, _, rim, _, _] = hoops_meshes(Assets::Embedded).unwrap();
+        let rim = place_hoop(&rim);
+        let vertices: Vec<_> = rim.vertices.chunks(3).map(|p| vec3a(p[0], p[1], p[2])).collect();
+
+        let min = vertices.iter().copied().fold(Vec3A::splat(f32::MAX), Vec3A::min);
+        let max = vertices.iter().copied().fold(Vec3A::splat(f32::MIN), Vec3A::max);
+        let center = (min + max) / 2.;
+        let inner_radius = vertices.iter().map(|p| (*p - center).truncate().length()).fold(f32::MAX, f32::min);
+
+        assert!(center.x.abs() < 0.01, "{center}");
+        assert!((center.y - Funnel::RIM_Y).abs() < 0.01, "{center}");
+        assert!((inner_radius - Funnel::RIM_INNER_RADIUS).abs() < 0.01, "{inner_radius}");
+        assert!((max.z - Funnel::RIM_TOP).abs() < 0.01, "{max}");
+    }
+
+    #[test]
+    fn dropped_into_a_hoop() {
+        let mut game = load_hoops();
+        // above orange's hoop, off to the side of the plane that splits the field down the middle, then it falls through the rim
+        game.ball.update(0., vec3a(200., Funnel::RIM_Y, 800.), Vec3A::ZERO, Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+        let event = prediction.first_in_funnel(&game).unwrap();
+
+        assert_eq!(event.goal, Team::Orange.index());
+        assert!(prediction.slices[event.slice].location.z <= Funnel::RIM_TOP);
+        assert!(prediction.slices[event.slice - 1].location.z > Funnel::RIM_TOP);
+
+        // and it stays in the hoop from then on
+        let in_funnel = prediction.in_funnel(&game);
+        assert!(in_funnel[..event.slice].iter().all(Option::is_none));
+        assert!(in_funnel[event.slice..].iter().all(|team| *team == Some(Team::Orange)));
+    }
+
+    #[test]
+    fn bouncing_off_of_the_rim() {
+        let mut game = load_hoops();
+        // falling onto the rim of blue's hoop, from outside of it
+        game.ball.update(0., vec3a(Funnel::RIM_INNER_RADIUS + 150., -Funnel::RIM_Y, 800.), vec3a(300., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 120);
+        assert_eq!(prediction.first_in_funnel(&game), None);
+    }
+
+    #[test]
+    fn only_in_hoops() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., Funnel::RIM_Y, 300.), Vec3A::ZERO, Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 10);
+        assert!(Funnel::hoops()[Team::Orange.index()].contains(&prediction.slices[0]));
+        assert_eq!(prediction.first_in_funnel(&game), None);
+        assert!(prediction.in_funnel(&game).iter().all(Option::is_none));
+    }
+}
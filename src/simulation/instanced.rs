@@ -0,0 +1,426 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use glam::{Mat3A, Vec3A};
+
+use super::bvh::{contact_from_tris, Bvh};
+use super::geometry::{Aabb, Ray, Sphere, Tri};
+use super::material::{MaterialId, DEFAULT_MATERIAL};
+use super::mesh::{Mesh, TriangleId};
+use crate::linear_algebra::math::dot;
+
+// A mesh, and every way that it's placed in a field
+// The fields are mirrored across the axes, so most of their meshes (like the corners and ramps) are used more than once
+#[derive(Clone, Debug)]
+pub struct Part {
+    pub mesh: Mesh,
+    pub transforms: Vec<Mat3A>,
+    // what every placement of the mesh is made of
+    pub material: MaterialId,
+}
+
+impl Part {
+    pub fn single(mesh: Mesh) -> Self {
+        Self {
+            mesh,
+            transforms: vec![Mat3A::IDENTITY],
+            material: DEFAULT_MATERIAL,
+        }
+    }
+
+    pub fn mirrored(mesh: Mesh, transforms: &[Mat3A]) -> Self {
+        Self {
+            mesh,
+            transforms: [Mat3A::IDENTITY].into_iter().chain(transforms.iter().copied()).collect(),
+            material: DEFAULT_MATERIAL,
+        }
+    }
+
+    pub fn with_material(self, material: MaterialId) -> Self {
+        Self {
+            material,
+            ..self
+        }
+    }
+
+    // every placement of the mesh, in the same order as the transforms
+    pub fn meshes(&self) -> impl Iterator<Item = Mesh> + '_ {
+        self.transforms.iter().map(|transform| {
+            if *transform == Mat3A::IDENTITY {
+                self.mesh.clone()
+            } else {
+                self.mesh.transform(*transform)
+            }
+        })
+    }
+}
+
+// The BVHs of meshes that have already been built, so that loading another field with the same meshes can share them
+// Meshes are matched by their contents, and the cache keeps its own copy of each one to make sure that two meshes with the same hash really are the same
+#[derive(Clone, Default)]
+pub struct BvhCache {
+    bvhs: BTreeMap<u64, Vec<(Mesh, Arc<Bvh>)>>,
+}
+
+// FNV-1a, since std's hasher isn't there without std and the cache checks the contents of matching meshes anyway
+struct ContentHasher(u64);
+
+impl Hasher for ContentHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl BvhCache {
+    fn content_hash(mesh: &Mesh) -> u64 {
+        let mut hasher = ContentHasher(0xcbf2_9ce4_8422_2325);
+        mesh.ids.hash(&mut hasher);
+        mesh.vertices.iter().for_each(|v| v.to_bits().hash(&mut hasher));
+        hasher.finish()
+    }
+
+    fn same_contents(a: &Mesh, b: &Mesh) -> bool {
+        a.ids == b.ids && a.vertices.len() == b.vertices.len() && a.vertices.iter().zip(&b.vertices).all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+
+    // the BVH of the mesh, only building it if no mesh with the same contents has been built before
+    pub fn get_or_build(&mut self, mesh: &Mesh) -> Arc<Bvh> {
+        let matches = self.bvhs.entry(BvhCache::content_hash(mesh)).or_default();
+
+        if let Some((_, bvh)) = matches.iter().find(|(other, _)| BvhCache::same_contents(mesh, other)) {
+            return bvh.clone();
+        }
+
+        let bvh = Arc::new(Bvh::from(&mesh.to_triangles()));
+        matches.push((mesh.clone(), bvh.clone()));
+        bvh
+    }
+
+    // how many different meshes have been built
+    pub fn len(&self) -> usize {
+        self.bvhs.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bvhs.is_empty()
+    }
+}
+
+// One placement of a shared BVH
+#[derive(Clone, Debug)]
+pub struct Instance {
+    pub prototype: usize,
+    // the mesh index that this instance's triangles get in their ids, same as in the flat field
+    pub mesh: u32,
+    // only rotations and reflections, so the inverse is the transpose
+    pub transform: Mat3A,
+    pub box_: Aabb,
+}
+
+impl Instance {
+    fn to_local(&self, v: Vec3A) -> Vec3A {
+        dot(self.transform.transpose(), v)
+    }
+
+    // the triangle in world space, with the same vertices and winding that `Mesh::transform` would give it
+    fn to_world(&self, tri: Tri) -> Tri {
+        if self.transform == Mat3A::IDENTITY {
+            return tri;
+        }
+
+        let p = tri.p.map(|p| dot(self.transform, p));
+
+        Tri {
+            p: if self.transform.determinant() < 0. {
+                [p[1], p[0], p[2]]
+            } else {
+                p
+            },
+        }
+    }
+}
+
+// A field where each mesh only has one BVH, no matter how many times it's used
+// Every query is moved into the space of each instance that it could touch, so the triangles of mirrored meshes are never stored more than once,
+// and the results (including the triangle ids) are the same as from the flat `Bvh` of the same parts
+#[derive(Clone, Default)]
+pub struct InstancedBvh {
+    pub global_box: Aabb,
+    // meshes that are used more than once, even across fields built with the same `BvhCache`, share the same BVH
+    pub prototypes: Vec<Arc<Bvh>>,
+    pub instances: Vec<Instance>,
+}
+
+impl InstancedBvh {
+    pub fn from_parts(parts: &[Part]) -> Self {
+        InstancedBvh::from_parts_cached(parts, &mut BvhCache::default())
+    }
+
+    pub fn from_parts_cached(parts: &[Part], cache: &mut BvhCache) -> Self {
+        let mut prototypes = Vec::with_capacity(parts.len());
+        let mut instances = Vec::new();
+
+        for part in parts {
+            let prototype = cache.get_or_build(&part.mesh);
+
+            for transform in &part.transforms {
+                let corners = (0..8).map(|i| {
+                    let corner = Vec3A::select(glam::BVec3A::new(i & 1 != 0, i & 2 != 0, i & 4 != 0), prototype.global_box.max, prototype.global_box.min);
+                    dot(*transform, corner)
+                });
+
+                let box_ = corners.fold(
+                    Aabb {
+                        min: Vec3A::splat(f32::INFINITY),
+                        max: Vec3A::splat(f32::NEG_INFINITY),
+                    },
+                    |box_, corner| Aabb {
+                        min: box_.min.min(corner),
+                        max: box_.max.max(corner),
+                    },
+                );
+
+                instances.push(Instance {
+                    prototype: prototypes.len(),
+                    mesh: instances.len() as u32,
+                    transform: *transform,
+                    box_,
+                });
+            }
+
+            prototypes.push(prototype);
+        }
+
+        let global_box = instances.iter().skip(1).fold(instances.first().map(|instance| instance.box_).unwrap_or_default(), |global, instance| global.add(&instance.box_));
+
+        Self {
+            global_box,
+            prototypes,
+            instances,
+        }
+    }
+
+    // how many triangles the field has, counting every instance
+    pub fn num_leaves(&self) -> u64 {
+        self.instances.iter().map(|instance| self.prototypes[instance.prototype].num_leaves).sum()
+    }
+
+    // how many triangles are actually stored, counting each shared BVH once
+    pub fn num_stored_leaves(&self) -> u64 {
+        let mut seen: Vec<&Arc<Bvh>> = Vec::with_capacity(self.prototypes.len());
+
+        self.prototypes
+            .iter()
+            .filter(|prototype| {
+                let new = !seen.iter().any(|other| Arc::ptr_eq(other, prototype));
+                seen.push(prototype);
+                new
+            })
+            .map(|prototype| prototype.num_leaves)
+            .sum()
+    }
+
+    fn for_each_intersecting(&self, query_object: &Sphere, mut f: impl FnMut(TriangleId, Tri)) {
+        let query_box: Aabb = query_object.into();
+
+        for instance in self.instances.iter().filter(|instance| instance.box_.intersect_self(&query_box)) {
+            // reflecting the box is exact, so the same leaves overlap it as in the flat field
+            let local = Sphere {
+                center: instance.to_local(query_object.center),
+                radius: query_object.radius,
+            };
+
+            // and the triangles are tested where they are in the world, just like in the flat field
+            for (id, tri) in self.prototypes[instance.prototype].intersect_aabb_ids(&(&local).into()) {
+                let tri = instance.to_world(tri);
+
+                if tri.intersect_sphere(query_object) {
+                    f(
+                        TriangleId {
+                            mesh: instance.mesh,
+                            index: id.index,
+                        },
+                        tri,
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn intersect(&self, query_object: &Sphere) -> Vec<Tri> {
+        let mut hits = Vec::with_capacity(16);
+        self.for_each_intersecting(query_object, |_, tri| hits.push(tri));
+        hits
+    }
+
+    pub fn intersect_ids(&self, query_object: &Sphere) -> Vec<TriangleId> {
+        let mut hits = Vec::with_capacity(16);
+        self.for_each_intersecting(query_object, |id, _| hits.push(id));
+        hits
+    }
+
+    pub fn collide(&self, s: &Sphere) -> Option<Ray> {
+        contact_from_tris(s, self.intersect(s))
+    }
+
+    // the closest triangle along the ray, like `Bvh::raycast`
+    pub fn raycast(&self, ray: &Ray) -> Option<(f32, Tri)> {
+        let mut best: Option<(f32, Tri)> = None;
+
+        for instance in &self.instances {
+            // padded like the boxes in `Bvh::raycast`, so rays that graze an instance aren't missed
+            let padded = Aabb {
+                min: instance.box_.min - 0.01,
+                max: instance.box_.max + 0.01,
+            };
+
+            match padded.intersect_ray(ray) {
+                Some(enter) if best.is_none_or(|(t, _)| enter <= t) => {}
+                _ => continue,
+            }
+
+            let local = Ray {
+                start: instance.to_local(ray.start),
+                direction: instance.to_local(ray.direction),
+            };
+
+            if let Some((t, tri)) = self.prototypes[instance.prototype].raycast(&local) {
+                if best.is_none_or(|(best_t, _)| t < best_t) {
+                    best = Some((t, instance.to_world(tri)));
+                }
+            }
+        }
+
+        best
+    }
+
+    // all of the triangles in the field and their ids, instance by instance
+    pub fn primitives_with_ids(&self) -> Vec<(TriangleId, Tri)> {
+        self.instances
+            .iter()
+            .flat_map(|instance| {
+                self.prototypes[instance.prototype].primitives_with_ids().into_iter().map(|(id, tri)| {
+                    (
+                        TriangleId {
+                            mesh: instance.mesh,
+                            index: id.index,
+                        },
+                        instance.to_world(tri),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops", feature = "throwback"))]
+mod test {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{load_hoops, load_hoops_instanced, load_hoops_instanced_cached, load_soccar, load_soccar_instanced, load_soccar_instanced_cached, load_soccar_throwback, load_throwback_instanced};
+
+    fn same_triangles(instanced: &InstancedBvh, flat: &Bvh) {
+        let mut a = instanced.primitives_with_ids();
+        let mut b = flat.primitives_with_ids();
+        a.sort_unstable_by_key(|(id, _)| *id);
+        b.sort_unstable_by_key(|(id, _)| *id);
+
+        assert_eq!(a.len(), b.len());
+        for ((id_a, tri_a), (id_b, tri_b)) in a.iter().zip(&b) {
+            assert_eq!(id_a, id_b);
+            assert_eq!(tri_a.p, tri_b.p, "{id_a:?}");
+        }
+    }
+
+    fn same_hits(instanced: &InstancedBvh, flat: &Bvh) {
+        let mut rng = rand::thread_rng();
+        let size = flat.global_box.max;
+
+        for _ in 0..2000 {
+            let s = Sphere {
+                center: Vec3A::new(rng.gen_range(-size.x..size.x), rng.gen_range(-size.y..size.y), rng.gen_range(0. ..size.z)),
+                radius: rng.gen_range(50. ..300.),
+            };
+
+            let mut a = instanced.intersect_ids(&s);
+            let mut b = flat.intersect_ids(&s);
+            a.sort_unstable();
+            b.sort_unstable();
+            assert_eq!(a, b, "{s:?}");
+
+            let ray = Ray {
+                start: s.center,
+                direction: Vec3A::new(rng.gen_range(-1. ..1.), rng.gen_range(-1. ..1.), rng.gen_range(-1. ..1.)) * 20000.,
+            };
+            let (a, b) = (instanced.raycast(&ray).map(|(t, _)| t), flat.raycast(&ray).map(|(t, _)| t));
+            assert_eq!(a.is_some(), b.is_some(), "{ray:?}");
+            if let (Some(a), Some(b)) = (a, b) {
+                assert!((a - b).abs() < 1e-4, "{ray:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_flat_fields() {
+        for (instanced, flat) in [(load_soccar_instanced(), load_soccar().field.collision_mesh), (load_hoops_instanced(), load_hoops().field.collision_mesh), (load_throwback_instanced(), load_soccar_throwback().field.collision_mesh)] {
+            assert_eq!(instanced.num_leaves(), flat.num_leaves);
+            same_triangles(&instanced, &flat);
+            same_hits(&instanced, &flat);
+        }
+    }
+
+    #[test]
+    fn stores_less() {
+        for instanced in [load_soccar_instanced(), load_hoops_instanced(), load_throwback_instanced()] {
+            let ratio = instanced.num_stored_leaves() as f32 / instanced.num_leaves() as f32;
+            assert!(ratio < 0.6, "{ratio}");
+        }
+    }
+
+    #[test]
+    fn cache_shares_meshes() {
+        let mut cache = BvhCache::default();
+        let soccar = load_soccar_instanced_cached(&mut cache);
+        let built = cache.len();
+        assert_eq!(built, soccar.prototypes.len());
+
+        // loading the same field again doesn't build anything
+        let again = load_soccar_instanced_cached(&mut cache);
+        assert_eq!(cache.len(), built);
+        assert!(soccar.prototypes.iter().zip(&again.prototypes).all(|(a, b)| Arc::ptr_eq(a, b)));
+
+        // and a different field only builds what it doesn't have in common with it
+        let hoops = load_hoops_instanced_cached(&mut cache);
+        assert!(cache.len() > built);
+        assert_eq!(hoops.num_leaves(), load_hoops_instanced().num_leaves());
+    }
+
+    #[test]
+    fn cache_matches_contents() {
+        let mesh = Mesh {
+            ids: vec![0, 1, 2],
+            vertices: vec![0., 0., 0., 1., 0., 0., 0., 1., 0.],
+        };
+        let moved = mesh.translate(Vec3A::Z);
+
+        let mut cache = BvhCache::default();
+        let a = InstancedBvh::from_parts_cached(&[Part::single(mesh.clone()), Part::single(moved)], &mut cache);
+        let b = InstancedBvh::from_parts_cached(&[Part::mirrored(mesh, &[Mat3A::from_diagonal(glam::Vec3::new(-1., 1., 1.))])], &mut cache);
+
+        assert_eq!(cache.len(), 2);
+        assert!(!Arc::ptr_eq(&a.prototypes[0], &a.prototypes[1]));
+        assert!(Arc::ptr_eq(&a.prototypes[0], &b.prototypes[0]));
+        assert_eq!(b.num_stored_leaves(), 1);
+        assert_eq!(b.num_leaves(), 2);
+    }
+}
@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+
+use super::ball::BallPrediction;
+
+// Changes in vertical speed smaller than this are micro-bounces, rather than the ball actually bouncing
+pub const MICRO_BOUNCE_SPEED: f32 = 20.;
+// How many micro-bounces in a row it takes to count as jitter, rather than the ball settling down
+pub const MAX_MICRO_BOUNCE_RUN: usize = 6;
+// How many slices the high frequency energy is measured over
+pub const WINDOW: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JitterReport {
+    // how many times the ball went from falling to rising, by less than MICRO_BOUNCE_SPEED
+    pub micro_bounces: usize,
+    pub longest_run: usize,
+    pub first_micro_bounce: Option<usize>,
+    // The strongest slice-to-slice alternation in the ball's vertical acceleration, in uu/s per slice
+    // Gravity, drag, and single bounces have almost none of this, while a ball vibrating against the floor is all this
+    pub high_frequency_energy: f32,
+}
+
+impl JitterReport {
+    pub fn is_smooth(&self) -> bool {
+        self.longest_run < MAX_MICRO_BOUNCE_RUN
+    }
+}
+
+impl BallPrediction {
+    pub fn jitter_report(&self) -> JitterReport {
+        let mut report = JitterReport::default();
+        let mut run = 0;
+
+        for (i, pair) in self.slices.windows(2).enumerate() {
+            let (before, after) = (pair[0].velocity.z, pair[1].velocity.z);
+
+            // going from rising to falling is just gravity, so it doesn't break up a run
+            if before < 0. && after > 0. && after - before < MICRO_BOUNCE_SPEED {
+                report.micro_bounces += 1;
+                report.first_micro_bounce.get_or_insert(i + 1);
+                run += 1;
+                report.longest_run = report.longest_run.max(run);
+            } else if before * after > 0. {
+                run = 0;
+            }
+        }
+
+        // the Nyquist frequency bin of the change in vertical velocity
+        let accelerations: Vec<f32> = self.slices.windows(2).map(|pair| pair[1].velocity.z - pair[0].velocity.z).collect();
+
+        for window in accelerations.windows(WINDOW) {
+            let alternating_sum: f32 = window
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    if i % 2 == 0 {
+                        *a
+                    } else {
+                        -*a
+                    }
+                })
+                .sum();
+            report.high_frequency_energy = report.high_frequency_energy.max(alternating_sum.abs() / WINDOW as f32);
+        }
+
+        report
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn flight_is_smooth() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1500.), vec3a(300., 600., 200.), Vec3A::ZERO);
+
+        let report = Ball::get_ball_prediction_struct_for_slices(&mut game, 200).jitter_report();
+
+        assert!(report.is_smooth());
+        assert_eq!(report.micro_bounces, 0);
+        assert!(report.high_frequency_energy < 0.01, "{}", report.high_frequency_energy);
+    }
+
+    #[test]
+    fn rolling_is_smooth() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 93.15), vec3a(800., 300., 0.), Vec3A::ZERO);
+
+        assert!(Ball::get_ball_prediction_struct(&mut game).jitter_report().is_smooth());
+    }
+
+    #[test]
+    fn resting_jitter() {
+        // Once the ball settles on the floor, it vibrates between two heights forever instead of coming to rest
+        // This test documents that, and should be flipped around once it's fixed
+        let mut game = load_soccar();
+
+        let report = Ball::get_ball_prediction_struct(&mut game).jitter_report();
+
+        assert!(!report.is_smooth());
+        assert!(report.first_micro_bounce.unwrap() < 120);
+        assert!(report.high_frequency_energy > 1.);
+    }
+}
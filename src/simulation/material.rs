@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+
+use super::mesh::TriangleId;
+use super::physics::BounceParams;
+use super::tuning::Tuning;
+
+// Which material a triangle is made of, as tagged when the field was put together
+// 0 is the field's normal surface, which bounces the ball with the game's `Tuning`
+pub type MaterialId = u16;
+
+pub const DEFAULT_MATERIAL: MaterialId = 0;
+
+// How a surface bounces the ball back, in place of the game's restitution and friction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl Material {
+    // the field's normal surface
+    pub fn from_tuning(tuning: &Tuning) -> Self {
+        Self {
+            restitution: tuning.restitution(),
+            friction: tuning.friction(),
+        }
+    }
+
+    pub fn apply(&self, params: &mut BounceParams) {
+        params.restitution = self.restitution;
+        params.friction = self.friction;
+    }
+}
+
+// The materials of a field's triangles, looked up by the mesh each triangle came from (`TriangleId::mesh`)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SurfaceMaterials {
+    // the material of each mesh the field was assembled from, any that are missing are the default
+    pub tags: Vec<MaterialId>,
+    // the material with id `i` is `materials[i - 1]`, and ids without one are the default
+    pub materials: Vec<Option<Material>>,
+}
+
+impl SurfaceMaterials {
+    pub fn from_tags(tags: Vec<MaterialId>) -> Self {
+        Self {
+            tags,
+            materials: Vec::new(),
+        }
+    }
+
+    pub fn tag(&self, id: TriangleId) -> MaterialId {
+        self.tags.get(id.mesh as usize).copied().unwrap_or(DEFAULT_MATERIAL)
+    }
+
+    pub fn set(&mut self, id: MaterialId, material: Material) {
+        assert_ne!(id, DEFAULT_MATERIAL, "The default material always comes from the tuning");
+
+        let index = id as usize - 1;
+        if self.materials.len() <= index {
+            self.materials.resize(index + 1, None);
+        }
+
+        self.materials[index] = Some(material);
+    }
+
+    pub fn material(&self, id: TriangleId) -> Option<Material> {
+        match self.tag(id) {
+            DEFAULT_MATERIAL => None,
+            tag => self.materials.get(tag as usize - 1).copied().flatten(),
+        }
+    }
+
+    // The average material of the triangles the ball is touching, the same way their normals are averaged into one contact
+    // None when they're all the normal surface, so the usual bounce is used unchanged
+    pub fn blend(&self, ids: &[TriangleId], tuning: &Tuning) -> Option<Material> {
+        let materials: Vec<Option<Material>> = ids.iter().map(|&id| self.material(id)).collect();
+
+        if materials.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let default = Material::from_tuning(tuning);
+        let n = materials.len() as f32;
+        let (restitution, friction) = materials.into_iter().map(|material| material.unwrap_or(default)).fold((0., 0.), |(restitution, friction), material| (restitution + material.restitution, friction + material.friction));
+
+        Some(Material {
+            restitution: restitution / n,
+            friction: friction / n,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "throwback"))]
+mod test {
+    use super::*;
+    use crate::simulation::field::THROWBACK_RAMPS;
+    use crate::{load_soccar, load_soccar_throwback};
+
+    #[test]
+    fn blending() {
+        let tuning = load_soccar().field.tuning;
+        let mut materials = SurfaceMaterials::from_tags(vec![DEFAULT_MATERIAL, 2]);
+        let (normal, tagged, untagged) = (
+            TriangleId {
+                mesh: 0,
+                index: 0,
+            },
+            TriangleId {
+                mesh: 1,
+                index: 5,
+            },
+            TriangleId {
+                mesh: 7,
+                index: 0,
+            },
+        );
+
+        // a tag without a material is the normal surface
+        assert_eq!(materials.blend(&[normal, tagged, untagged], &tuning), None);
+
+        let ice = Material {
+            restitution: 0.2,
+            friction: 0.,
+        };
+        materials.set(2, ice);
+        assert_eq!(materials.material(tagged), Some(ice));
+        assert_eq!(materials.material(untagged), None);
+        assert_eq!(materials.blend(&[tagged], &tuning), Some(ice));
+
+        let default = Material::from_tuning(&tuning);
+        let half = materials.blend(&[normal, tagged], &tuning).unwrap();
+        assert_eq!(half.restitution, (default.restitution + ice.restitution) / 2.);
+        assert_eq!(half.friction, (default.friction + ice.friction) / 2.);
+    }
+
+    #[test]
+    fn throwback_ramps() {
+        let game = load_soccar_throwback();
+        let materials = game.field.materials.as_ref().unwrap();
+
+        let (ramps, rest): (Vec<_>, Vec<_>) = game.field.collision_mesh.primitives_with_ids().into_iter().partition(|(id, _)| materials.tag(*id) == THROWBACK_RAMPS);
+        assert!(!ramps.is_empty());
+
+        // the floor and the ceiling are never the ramps
+        assert!(rest.iter().any(|(_, tri)| tri.p.iter().all(|p| p.z == 0.)));
+        assert!(ramps.iter().all(|(_, tri)| tri.p.iter().any(|p| p.z != 0.) && tri.p.iter().any(|p| p.z != 2048.)));
+    }
+}
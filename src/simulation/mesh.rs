@@ -1,9 +1,174 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+#[cfg(feature = "std")]
+use std::io::{self, ErrorKind};
+
 use glam::{Mat3A, Vec3A};
 
 use super::geometry::Tri;
 
 use crate::linear_algebra::math::dot;
 
+// Vertices closer than this are treated as the same point when matching up edges,
+// since mirrored and scaled pieces rarely line up bit-for-bit
+pub const WELD_DISTANCE: f32 = 0.01;
+
+// standard base64, with padding, for embedding binary data in text formats like glTF
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = u32::from(chunk[0]) << 16 | u32::from(*chunk.get(1).unwrap_or(&0)) << 8 | u32::from(*chunk.get(2).unwrap_or(&0));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+// The edges (in either direction) that only show up once
+fn unshared(edges: impl Iterator<Item = [Vec3A; 2]>) -> Vec<[Vec3A; 2]> {
+    let key = |p: Vec3A| (p / WELD_DISTANCE).round().to_array().map(|x| x as i64);
+
+    let mut counts: BTreeMap<_, (usize, [Vec3A; 2])> = BTreeMap::new();
+
+    for [a, b] in edges {
+        let (key_a, key_b) = (key(a), key(b));
+
+        // degenerate edges don't separate anything
+        if key_a == key_b {
+            continue;
+        }
+
+        let edge_key = if key_a < key_b {
+            (key_a, key_b)
+        } else {
+            (key_b, key_a)
+        };
+        counts.entry(edge_key).or_insert((0, [a, b])).0 += 1;
+    }
+
+    counts.into_values().filter(|(count, _)| *count == 1).map(|(_, edge)| edge).collect()
+}
+
+// Edges (in either direction) that only belong to a single triangle
+// In a closed field, every edge is shared, so these are holes the ball could escape through
+pub(crate) fn open_edges(triangles: &[Tri]) -> Vec<[Vec3A; 2]> {
+    unshared(triangles.iter().flat_map(|tri| (0..3).map(|i| [tri.p[i], tri.p[(i + 1) % 3]])))
+}
+
+// Open edges that aren't covered by other open edges
+// A T-junction (where the edge of one big triangle runs along the edges of several small ones) leaves open edges,
+// but no gap, so the long edge is split at every vertex that lies on it before matching them up again
+pub(crate) fn holes(triangles: &[Tri]) -> Vec<[Vec3A; 2]> {
+    let open = open_edges(triangles);
+    let points: Vec<Vec3A> = open.iter().flatten().copied().collect();
+
+    let mut pieces = Vec::with_capacity(open.len());
+
+    for [a, b] in &open {
+        let ab = *b - *a;
+        let length_squared = ab.length_squared();
+
+        let mut splits: Vec<f32> = points
+            .iter()
+            .filter_map(|p| {
+                let t = (*p - *a).dot(ab) / length_squared;
+                let on_edge = t > 0. && t < 1. && (*a + ab * t).distance_squared(*p) < WELD_DISTANCE * WELD_DISTANCE;
+                on_edge.then_some(t)
+            })
+            .collect();
+
+        splits.push(0.);
+        splits.push(1.);
+        splits.sort_unstable_by(|x, y| x.total_cmp(y));
+
+        pieces.extend(splits.windows(2).map(|pair| [*a + ab * pair[0], *a + ab * pair[1]]));
+    }
+
+    unshared(pieces.into_iter())
+}
+
+// Simplifies a triangle soup by snapping every vertex to the average of the vertices in its cell of a grid
+// Flat surfaces made of big triangles are left alone, while finely tessellated curves (ramps, corners) lose detail
+// Triangles that collapse to a line or a point are removed, and the survivors keep their ids
+pub fn decimate(triangles: &[(TriangleId, Tri)], cell_size: f32) -> Vec<(TriangleId, Tri)> {
+    let key = |p: Vec3A| (p / cell_size).floor().to_array().map(|x| x as i64);
+
+    let mut cells: BTreeMap<[i64; 3], (Vec3A, f32)> = BTreeMap::new();
+    for (_, tri) in triangles {
+        for p in tri.p {
+            let cell = cells.entry(key(p)).or_default();
+            cell.0 += p;
+            cell.1 += 1.;
+        }
+    }
+
+    let snap = |p: Vec3A| {
+        let (sum, count) = cells[&key(p)];
+        sum / count
+    };
+
+    let mut seen = BTreeMap::new();
+
+    triangles
+        .iter()
+        .filter_map(|(id, tri)| {
+            let keys = tri.p.map(key);
+            if keys[0] == keys[1] || keys[1] == keys[2] || keys[2] == keys[0] {
+                return None;
+            }
+
+            // neighboring triangles can collapse onto the exact same one
+            let mut sorted_keys = keys;
+            sorted_keys.sort_unstable();
+            if seen.insert(sorted_keys, ()).is_some() {
+                return None;
+            }
+
+            Some((
+                *id,
+                Tri {
+                    p: tri.p.map(snap),
+                },
+            ))
+        })
+        .collect()
+}
+
+// Identifies a triangle by where it came from: the index of its mesh in the list a field was assembled from, and its index in that mesh
+// Neither depends on how the BVH orders its leaves, so they stay the same across rebuilds and reloads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TriangleId {
+    pub mesh: u32,
+    pub index: u32,
+}
+
+// The ids of every triangle in `Mesh::from(meshes)`, in the same order as its triangles
+pub fn triangle_ids(meshes: &[&Mesh]) -> Vec<TriangleId> {
+    meshes
+        .iter()
+        .enumerate()
+        .flat_map(|(mesh, m)| {
+            (0..m.ids.len() / 3).map(move |index| TriangleId {
+                mesh: mesh as u32,
+                index: index as u32,
+            })
+        })
+        .collect()
+}
+
 #[derive(Clone, Debug)]
 pub struct Mesh {
     pub ids: Vec<i32>,
@@ -87,6 +252,135 @@ impl Mesh {
         }
     }
 
+    #[cfg(feature = "std")]
+    // Reads the vertices and faces of a Wavefront OBJ file, ignoring everything else (normals, texture coordinates, materials, groups)
+    // Coordinates are used as they are, so they need to be in game units with z pointing up
+    // Faces with more than 3 vertices are split into a fan of triangles
+    pub fn from_obj(source: &str) -> io::Result<Self> {
+        let mut ids = Vec::new();
+        let mut vertices = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let error = |message: String| io::Error::new(ErrorKind::InvalidData, format!("line {}: {message}", i + 1));
+
+            let mut words = line.split('#').next().unwrap().split_whitespace();
+
+            match words.next() {
+                Some("v") => {
+                    let coords = words.take(3).map(|word| word.parse::<f32>().map_err(|_| error(format!("`{word}` isn't a number")))).collect::<io::Result<Vec<_>>>()?;
+
+                    if coords.len() != 3 {
+                        return Err(error("vertices need 3 coordinates".to_string()));
+                    }
+
+                    vertices.extend(coords);
+                }
+                Some("f") => {
+                    let num_vertices = (vertices.len() / 3) as i64;
+
+                    let face = words
+                        .map(|word| {
+                            // only the vertex index matters, not the texture or normal ones after it
+                            let index = word.split('/').next().unwrap();
+                            let index: i64 = index.parse().map_err(|_| error(format!("`{word}` isn't a vertex index")))?;
+
+                            // indices start at 1, and negative ones count back from the latest vertex
+                            let id = if index < 0 {
+                                num_vertices + index
+                            } else {
+                                index - 1
+                            };
+
+                            if (0..num_vertices).contains(&id) {
+                                Ok(id as i32)
+                            } else {
+                                Err(error(format!("vertex {index} doesn't exist, there are {num_vertices} so far")))
+                            }
+                        })
+                        .collect::<io::Result<Vec<_>>>()?;
+
+                    if face.len() < 3 {
+                        return Err(error("faces need at least 3 vertices".to_string()));
+                    }
+
+                    for pair in face[1..].windows(2) {
+                        ids.extend([face[0], pair[0], pair[1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if ids.is_empty() {
+            return Err(io::Error::new(ErrorKind::InvalidData, "The file doesn't have any faces"));
+        }
+
+        Ok(Self {
+            ids,
+            vertices,
+        })
+    }
+
+    // The vertices and faces as a Wavefront OBJ file, which `from_obj` reads back to the same mesh
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+
+        for vertex in self.vertices.chunks_exact(3) {
+            writeln!(obj, "v {} {} {}", vertex[0], vertex[1], vertex[2]).unwrap();
+        }
+
+        for tri in self.ids.chunks_exact(3) {
+            writeln!(obj, "f {} {} {}", tri[0] + 1, tri[1] + 1, tri[2] + 1).unwrap();
+        }
+
+        obj
+    }
+
+    // A glTF 2.0 file with the mesh in it, and its data embedded so it's the only file that's needed
+    // glTF is y up, so the mesh is turned to stand the same way it does in the game
+    pub fn to_gltf(&self) -> String {
+        let mut buffer = Vec::with_capacity((self.ids.len() + self.vertices.len()) * 4);
+        buffer.extend(self.ids.iter().flat_map(|id| (*id as u32).to_le_bytes()));
+        buffer.extend(self.vertices.iter().flat_map(|x| x.to_le_bytes()));
+
+        let ids_len = self.ids.len() * 4;
+        let vertices_len = self.vertices.len() * 4;
+        let num_vertices = self.vertices.len() / 3;
+
+        // positions have to come with their bounds
+        let (min, max) = self.vertices.chunks_exact(3).map(Vec3A::from_slice).fold((Vec3A::splat(f32::INFINITY), Vec3A::splat(f32::NEG_INFINITY)), |(min, max), v| (min.min(v), max.max(v)));
+        let (min, max) = if num_vertices == 0 {
+            (Vec3A::ZERO, Vec3A::ZERO)
+        } else {
+            (min, max)
+        };
+
+        format!(
+            concat!(
+                r#"{{"asset":{{"version":"2.0","generator":"rl_ball_sym"}},"scene":0,"scenes":[{{"nodes":[0]}}],"#,
+                // a quarter turn around x, from z up to y up
+                r#""nodes":[{{"mesh":0,"rotation":[-0.70710677,0,0,0.70710677]}}],"#,
+                r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":1}},"indices":0}}]}}],"#,
+                r#""buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}],"#,
+                r#""bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":{},"target":34963}},{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}}],"#,
+                r#""accessors":[{{"bufferView":0,"componentType":5125,"count":{},"type":"SCALAR"}},{{"bufferView":1,"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}]}}"#,
+            ),
+            buffer.len(),
+            base64(&buffer),
+            ids_len,
+            ids_len,
+            vertices_len,
+            self.ids.len(),
+            num_vertices,
+            min.x,
+            min.y,
+            min.z,
+            max.x,
+            max.y,
+            max.z,
+        )
+    }
+
     pub fn transform(&self, a: Mat3A) -> Self {
         debug_assert_eq!(self.vertices.len() % 3, 0);
         debug_assert_eq!(self.ids.len() % 3, 0);
@@ -125,7 +419,12 @@ impl Mesh {
         }
     }
 
+    pub fn find_open_edges(&self) -> Vec<[Vec3A; 2]> {
+        open_edges(&self.to_triangles())
+    }
+
     #[rustfmt::skip]
+    #[allow(clippy::unnecessary_cast)]
     pub fn to_triangles(&self) -> Vec<Tri> {
         let n = self.ids.len() / 3;
         let mut triangles: Vec<Tri> = Vec::with_capacity(n);
@@ -143,3 +442,111 @@ impl Mesh {
         triangles
     }
 }
+
+#[cfg(test)]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+
+    fn mesh(vertices: &[Vec3A], ids: &[i32]) -> Mesh {
+        Mesh {
+            ids: ids.to_vec(),
+            vertices: vertices.iter().flat_map(|v| v.to_array()).collect(),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn obj() {
+        let quad = Mesh::from_obj(
+            "# a unit square
+            o square
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vn 0 0 1
+            f 1//1 2//1 3//1 -1//1
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(quad.ids, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(quad.vertices.len(), 12);
+        assert_eq!(quad.to_triangles().len(), 2);
+
+        let error = |source: &str| Mesh::from_obj(source).unwrap_err().to_string();
+        assert_eq!(error("v 0 0 0\nf 1 2 3"), "line 2: vertex 2 doesn't exist, there are 1 so far");
+        assert_eq!(error("v 0 zero 0"), "line 1: `zero` isn't a number");
+        assert_eq!(error("v 0 0"), "line 1: vertices need 3 coordinates");
+        assert_eq!(error("v 0 0 0\nv 1 0 0\nf 1 2"), "line 3: faces need at least 3 vertices");
+        assert_eq!(error("v 0 0 0"), "The file doesn't have any faces");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn obj_round_trip() {
+        let quad = mesh(&[vec3a(0., 0., 0.), vec3a(1.5, 0., 0.), vec3a(1.5, 1., -2.), vec3a(0., 1., 0.)], &[0, 1, 2, 0, 2, 3]);
+
+        let read = Mesh::from_obj(&quad.to_obj()).unwrap();
+        assert_eq!(read.ids, quad.ids);
+        assert_eq!(read.vertices, quad.vertices);
+    }
+
+    #[test]
+    fn gltf() {
+        assert_eq!(base64(b"rl"), "cmw=");
+        assert_eq!(base64(b"rlbs"), "cmxicw==");
+        assert_eq!(base64(b"ball"), "YmFsbA==");
+        assert_eq!(base64(b"sim"), "c2lt");
+
+        let quad = mesh(&[vec3a(0., 0., 0.), vec3a(1., 0., 0.), vec3a(1., 1., 2.), vec3a(0., 1., 0.)], &[0, 1, 2, 0, 2, 3]);
+        let gltf = quad.to_gltf();
+
+        // 6 ids and 12 coordinates, 4 bytes each
+        assert!(gltf.contains(r#""byteLength":72,"#));
+        assert!(gltf.contains(r#""count":6,"type":"SCALAR""#));
+        assert!(gltf.contains(r#""count":4,"type":"VEC3","min":[0,0,0],"max":[1,1,2]"#));
+        assert_eq!(gltf.matches('{').count(), gltf.matches('}').count());
+    }
+
+    #[test]
+    fn open_quad() {
+        let quad = mesh(&[vec3a(0., 0., 0.), vec3a(1., 0., 0.), vec3a(1., 1., 0.), vec3a(0., 1., 0.)], &[0, 1, 2, 0, 2, 3]);
+
+        // the diagonal is shared, the outside isn't
+        assert_eq!(quad.find_open_edges().len(), 4);
+
+        // and nothing covers the outside edges, so they're all holes
+        assert_eq!(holes(&quad.to_triangles()).len(), 4);
+    }
+
+    #[test]
+    fn t_junction() {
+        // one big triangle along the edge of two small ones, with a vertex in the middle of its edge
+        let vertices = [vec3a(0., 0., 0.), vec3a(2., 0., 0.), vec3a(1., 0., 0.), vec3a(1., 1., 0.), vec3a(1., -1., 0.)];
+        let mesh = mesh(&vertices, &[0, 2, 3, 2, 1, 3, 0, 4, 1]);
+
+        // the long edge and the two short ones aren't shared with each other, but they cover each other
+        let holes = holes(&mesh.to_triangles());
+        assert_eq!(holes.len(), 4);
+        assert!(holes.iter().all(|[a, b]| a.y != 0. || b.y != 0.));
+    }
+
+    #[test]
+    fn closed_tetrahedron() {
+        let vertices = [vec3a(0., 0., 0.), vec3a(1., 0., 0.), vec3a(0., 1., 0.), vec3a(0., 0., 1.)];
+        let tetrahedron = mesh(&vertices, &[0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2]);
+
+        assert!(tetrahedron.find_open_edges().is_empty());
+
+        // the same faces, but with none of the vertices shared (and a tiny bit of error) are still closed
+        let split = Mesh::from(vec![&mesh(&vertices, &[0, 2, 1, 0, 1, 3]), &mesh(&vertices, &[1, 2, 3]).translate(Vec3A::splat(WELD_DISTANCE * 0.1)), &mesh(&vertices, &[0, 3, 2])]);
+        assert!(split.find_open_edges().is_empty());
+
+        // removing a face opens up its 3 edges
+        let open = mesh(&vertices, &[0, 2, 1, 0, 1, 3, 1, 2, 3]);
+        assert_eq!(open.find_open_edges().len(), 3);
+    }
+}
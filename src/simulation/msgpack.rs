@@ -0,0 +1,60 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use super::ball::BallPrediction;
+
+// MessagePack, with the names of the fields kept in, so anything can read it without knowing the layout,
+// while still being a lot smaller than JSON, for sending predictions to websocket clients and such
+impl BallPrediction {
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // writing into memory can't fail
+        self.write_msgpack(&mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn write_msgpack<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        rmp_serde::encode::write_named(writer, self).map_err(io::Error::other)
+    }
+
+    pub fn from_msgpack(bytes: &[u8]) -> io::Result<Self> {
+        BallPrediction::read_msgpack(bytes)
+    }
+
+    pub fn read_msgpack<R: Read>(reader: R) -> io::Result<Self> {
+        rmp_serde::from_read(reader).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn round_trip() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(1000., 2000., 0.), Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+        let bytes = prediction.to_msgpack();
+        let decoded = BallPrediction::from_msgpack(&bytes).unwrap();
+
+        assert_eq!(decoded.num_slices, prediction.num_slices);
+        assert_eq!(decoded.bounces, prediction.bounces);
+        assert!(decoded.slices.iter().zip(&prediction.slices).all(|(a, b)| a.time == b.time && a.location == b.location && a.velocity == b.velocity && a.flags == b.flags));
+    }
+
+    #[test]
+    fn self_describing() {
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut load_soccar(), 10);
+        let bytes = prediction.to_msgpack();
+
+        // the names of the fields are in there
+        assert!(bytes.windows(b"angular_velocity".len()).any(|window| window == b"angular_velocity"));
+
+        assert!(matches!(BallPrediction::from_msgpack(&bytes[..bytes.len() / 2]), Err(error) if error.kind() == ErrorKind::InvalidData));
+    }
+}
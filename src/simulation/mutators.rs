@@ -0,0 +1,263 @@
+use glam::Vec3A;
+
+use super::ball::Ball;
+use super::game::Game;
+
+// How the air slows the ball down
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum DragModel {
+    // the game's, in proportion to the ball's speed
+    #[default]
+    Linear,
+    // in proportion to the square of the ball's speed, like real air resistance
+    // it's the same as the game's at `QUADRATIC_DRAG_REFERENCE_SPEED`, weaker when slower and stronger when faster
+    Quadratic,
+    None,
+}
+
+// the speed where quadratic drag slows the ball down as much as the game's drag does
+pub const QUADRATIC_DRAG_REFERENCE_SPEED: f32 = 2000.;
+
+impl TryFrom<u8> for DragModel {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, u8> {
+        Ok(match value {
+            0 => DragModel::Linear,
+            1 => DragModel::Quadratic,
+            2 => DragModel::None,
+            _ => return Err(value),
+        })
+    }
+}
+
+impl DragModel {
+    // the linear drag coefficient that has the same effect at this velocity, where `drag` is the game's (from `Tuning`)
+    #[inline]
+    pub fn coefficient(self, velocity: Vec3A, drag: f32) -> f32 {
+        match self {
+            DragModel::Linear => drag,
+            DragModel::Quadratic => drag * velocity.length() / QUADRATIC_DRAG_REFERENCE_SPEED,
+            DragModel::None => 0.,
+        }
+    }
+}
+
+// The match settings that change how the ball moves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mutators {
+    pub gravity: Vec3A,
+    pub ball_radius: f32,
+    pub ball_collision_radius: f32,
+    pub drag_model: DragModel,
+    // how quickly the ball's spin slows down on its own, in the air and on the ground, as a fraction of it per second
+    // the game doesn't slow the spin down on its own, so this is normally 0
+    pub angular_damping: f32,
+    // how much the ball's spin curves it in the air, as the acceleration from the spin crossed with the velocity
+    // the game doesn't have a Magnus effect, so this is normally 0, and it's only here to experiment with
+    pub magnus: f32,
+    // the fraction of the pull of gravity into the surface that slows down a ball that's rolling along it
+    // the game only slows a rolling ball down with drag, so this is normally 0
+    pub rolling_resistance: f32,
+}
+
+impl Default for Mutators {
+    // a standard soccar match
+    fn default() -> Self {
+        Self::from(&Ball::initialize_soccar(), Vec3A::new(0., 0., -650.))
+    }
+}
+
+impl Mutators {
+    fn from(ball: &Ball, gravity: Vec3A) -> Self {
+        Self {
+            gravity,
+            ball_radius: ball.radius,
+            ball_collision_radius: ball.collision_radius,
+            drag_model: ball.drag_model,
+            angular_damping: ball.angular_damping,
+            magnus: ball.magnus,
+            rolling_resistance: ball.rolling_resistance,
+        }
+    }
+
+    // the settings that `game` is currently using
+    pub fn from_game(game: &Game) -> Self {
+        Self::from(&game.ball, game.gravity)
+    }
+
+    pub fn apply(&self, game: &mut Game) {
+        game.gravity = self.gravity;
+        self.apply_to_ball(&mut game.ball);
+    }
+
+    pub fn apply_to_ball(&self, ball: &mut Ball) {
+        ball.set_radii(self.ball_radius, self.ball_collision_radius);
+        ball.drag_model = self.drag_model;
+        ball.angular_damping = self.angular_damping;
+        ball.magnus = self.magnus;
+        ball.rolling_resistance = self.rolling_resistance;
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "dropshot"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::simulation::game::Game;
+    use crate::{load_dropshot, load_soccar};
+
+    #[test]
+    fn round_trip() {
+        let mut game = load_soccar();
+        assert_eq!(Mutators::from_game(&game), Mutators::default());
+
+        let dropshot = Mutators::from_game(&load_dropshot());
+        let low_gravity = Mutators {
+            gravity: vec3a(0., 0., -325.),
+            ..dropshot
+        };
+
+        low_gravity.apply(&mut game);
+        assert_eq!(Mutators::from_game(&game), low_gravity);
+        assert_eq!(game.ball.moi, Ball::initialize_dropshot().moi);
+    }
+
+    #[test]
+    fn drag_models() {
+        let mut game = load_soccar();
+        let start = Ball {
+            location: vec3a(0., 0., 1000.),
+            velocity: vec3a(3000., 0., 0.),
+            ..game.ball
+        };
+
+        let mut speed_after = |drag_model| {
+            Mutators {
+                drag_model,
+                ..Mutators::default()
+            }
+            .apply(&mut game);
+            game.ball = Ball {
+                drag_model,
+                ..start
+            };
+
+            Ball::get_ball_prediction_struct_for_slices(&mut game, 30).slices.last().unwrap().velocity.x
+        };
+
+        let linear = speed_after(DragModel::Linear);
+        let quadratic = speed_after(DragModel::Quadratic);
+        let none = speed_after(DragModel::None);
+
+        // above the reference speed, quadratic drag is the strongest
+        assert_eq!(none, 3000.);
+        assert!(linear < none);
+        assert!(quadratic < linear);
+
+        assert_eq!(Mutators::from_game(&game).drag_model, DragModel::None);
+        assert_eq!(DragModel::try_from(DragModel::Quadratic as u8), Ok(DragModel::Quadratic));
+        assert_eq!(DragModel::try_from(3), Err(3));
+    }
+
+    #[test]
+    fn angular_damping() {
+        let mut game = load_soccar();
+        let start = Ball {
+            location: vec3a(0., 0., 1000.),
+            angular_velocity: vec3a(0., 0., 4.),
+            ..game.ball
+        };
+
+        game.ball = start;
+        let spin = Ball::get_ball_prediction_struct_for_slices(&mut game, 120).slices.last().unwrap().angular_velocity.z;
+        assert_eq!(spin, 4.);
+
+        Mutators {
+            angular_damping: 0.5,
+            ..Mutators::default()
+        }
+        .apply(&mut game);
+        game.ball = Ball {
+            angular_damping: 0.5,
+            ..start
+        };
+
+        // the spin falls off exponentially
+        let spin = Ball::get_ball_prediction_struct_for_slices(&mut game, 120).slices.last().unwrap().angular_velocity.z;
+        assert!((spin - 4. * 0.5f32.exp().recip()).abs() < 0.01, "{spin}");
+    }
+
+    #[test]
+    fn magnus() {
+        let mut game = load_soccar();
+        let fly = |game: &mut Game, angular_velocity: Vec3A| {
+            game.ball.update(0., vec3a(0., 0., 1000.), vec3a(1000., 0., 0.), angular_velocity);
+            Ball::get_ball_prediction_struct_for_slices(game, 60).slices.last().unwrap().location
+        };
+
+        let sidespin = vec3a(0., 0., 4.);
+        let topspin = vec3a(0., 4., 0.);
+        let (straight, dropped) = (fly(&mut game, sidespin), fly(&mut game, topspin));
+        assert_eq!(straight.y, 0.);
+
+        Mutators {
+            magnus: 0.05,
+            ..Mutators::default()
+        }
+        .apply(&mut game);
+
+        // sidespin curves the ball to the side, and topspin makes it drop faster
+        assert!(fly(&mut game, sidespin).y > 20.);
+        assert!(fly(&mut game, topspin).z < dropped.z - 20.);
+    }
+
+    #[test]
+    fn rolling_resistance() {
+        let mut game = load_soccar();
+        let r = game.ball.collision_radius;
+        let g = 650.;
+        // drag doesn't slow the spin down, so friction with the floor drags the ball along with its spin, and it slows down less than a ball in the air
+        let drag = Ball::DRAG / (1. + game.ball.moi / (Ball::M * r * r));
+
+        // slow enough to roll without the spin being capped, and a little into the floor so it's settled instead of exactly touching
+        let roll = |game: &mut Game, speed: f32| {
+            game.ball.update(0., vec3a(0., -3000., r - 0.1), vec3a(0., speed, 0.), vec3a(-speed / r, 0., 0.));
+            Ball::get_ball_prediction_struct_for_time(game, &2.).slices.iter().map(|slice| (slice.time, slice.velocity.y)).collect::<Vec<_>>()
+        };
+
+        // a rolling ball is only slowed down by drag, like in the game, so it follows v0 * e^(drag * t)
+        assert_eq!(Mutators::default().rolling_resistance, 0.);
+        for (t, speed) in roll(&mut game, 500.) {
+            let expected = 500. * (drag * t).exp();
+            assert!((speed - expected).abs() < 0.5, "{t}: {speed} {expected}");
+        }
+
+        // rolling resistance takes away a constant share of gravity on top of that, which gives dv/dt = drag * v - resistance * g,
+        // or v0 * e^(drag * t) - (resistance * g / -drag) * (1 - e^(drag * t))
+        let resistance = 0.05;
+        Mutators {
+            rolling_resistance: resistance,
+            ..Mutators::default()
+        }
+        .apply(&mut game);
+
+        for (t, speed) in roll(&mut game, 500.) {
+            let decay = (drag * t).exp();
+            let expected = 500. * decay - resistance * g / -drag * (1. - decay);
+            assert!((speed - expected).abs() < 0.5, "{t}: {speed} {expected}");
+        }
+
+        // a slow ball stops, and doesn't start rolling backwards
+        Mutators {
+            rolling_resistance: 0.5,
+            ..Mutators::default()
+        }
+        .apply(&mut game);
+        let (_, stopped) = *roll(&mut game, 50.).last().unwrap();
+        assert!(stopped.abs() < 1., "{stopped}");
+    }
+}
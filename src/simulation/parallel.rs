@@ -0,0 +1,152 @@
+use rayon::prelude::*;
+
+use glam::Vec3A;
+
+use super::analysis::is_occluded;
+use super::ball::{Ball, BallPrediction};
+use super::bvh::{global_aabb, Bvh, BvhNode, BvhScratch, SCRATCH};
+use super::game::Game;
+use super::geometry::{Aabb, Tri};
+use super::mesh::TriangleId;
+use super::morton::Morton;
+
+// Helpers for running expensive per-slice functions across all cores
+impl BallPrediction {
+    // the first slice (in time order) that matches the predicate
+    pub fn par_find<F: Fn(&Ball) -> bool + Sync + Send>(&self, predicate: F) -> Option<&Ball> {
+        self.slices.par_iter().find_first(|slice| predicate(slice))
+    }
+
+    // the index of the first slice (in time order) that matches the predicate
+    pub fn par_position<F: Fn(&Ball) -> bool + Sync + Send>(&self, predicate: F) -> Option<usize> {
+        self.slices.par_iter().position_first(predicate)
+    }
+
+    // the score of every slice, in the same order as the slices
+    pub fn par_score<F: Fn(&Ball) -> f32 + Sync + Send>(&self, score: F) -> Vec<f32> {
+        self.slices.par_iter().map(score).collect()
+    }
+
+    // the index and score of the highest scoring slice, ties go to the earliest slice
+    pub fn par_best<F: Fn(&Ball) -> f32 + Sync + Send>(&self, score: F) -> Option<(usize, f32)> {
+        self.slices.par_iter().enumerate().map(|(i, slice)| (i, score(slice))).reduce_with(|a, b| {
+            if b.1 > a.1 || (b.1 == a.1 && b.0 < a.0) {
+                b
+            } else {
+                a
+            }
+        })
+    }
+
+    // the same as `occluded_from`, with the raycasts spread over all of the cores
+    pub fn par_occluded_from(&self, game: &Game, camera: Vec3A) -> Vec<bool> {
+        self.slices.par_iter().map(|slice| is_occluded(game, camera, slice)).collect()
+    }
+}
+
+// below this many leaves, splitting the work up again costs more than it saves
+const PAR_HIERARCHY_THRESHOLD: usize = 1024;
+
+impl Bvh {
+    // The same tree as `Bvh::from_with_ids` without rayon, built on all of the cores
+    pub fn par_from_with_ids(primitives: &[Tri], ids: &[TriangleId]) -> Self {
+        SCRATCH.with(|scratch| Bvh::par_from_with_ids_in(primitives, ids, scratch))
+    }
+
+    fn par_from_with_ids_in(primitives: &[Tri], ids: &[TriangleId], scratch: &mut BvhScratch) -> Self {
+        assert_eq!(primitives.len(), ids.len(), "Every triangle needs an id");
+
+        let num_leaves = primitives.len();
+        let BvhScratch { boxes, order, leaves } = scratch;
+
+        boxes.par_extend(primitives.par_iter().map(Aabb::from));
+        let global_box = global_aabb(boxes);
+        let morton = Morton::from(&global_box);
+
+        order.par_extend(boxes.par_iter().enumerate().map(|(i, box_)| (morton.get_code(box_), i)));
+        order.par_sort_unstable();
+
+        leaves.par_extend(order.par_iter().map(|&(code, i)| BvhNode::unboxed_leaf(primitives[i], ids[i], boxes[i], code)));
+
+        let root = par_generate_hierarchy(leaves, 0, num_leaves - 1);
+
+        Self {
+            global_box,
+            num_leaves: num_leaves as u64,
+            root,
+        }
+    }
+}
+
+// splits the leaves the same way as `Bvh::generate_hierarchy`, building both halves at once
+fn par_generate_hierarchy(sorted_leaves: &[BvhNode], first: usize, last: usize) -> Box<BvhNode> {
+    if last - first < PAR_HIERARCHY_THRESHOLD {
+        return Bvh::generate_hierarchy(sorted_leaves, first, last);
+    }
+
+    let split = first + ((last - first) / 2);
+
+    let (right, left) = rayon::join(|| par_generate_hierarchy(sorted_leaves, first, split), || par_generate_hierarchy(sorted_leaves, split + 1, last));
+
+    BvhNode::branch(right, left)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn parallel_matches_serial() {
+        use glam::vec3a;
+
+        use crate::load_soccar;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1500.), vec3a(1000., 1000., 0.), vec3a(0., 0., 0.));
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+        let low = |slice: &Ball| slice.location.z < 200.;
+        assert_eq!(prediction.par_find(low).map(|slice| slice.time), prediction.slices.iter().find(|slice| low(slice)).map(|slice| slice.time));
+        assert_eq!(prediction.par_position(low), prediction.slices.iter().position(low));
+
+        let height = |slice: &Ball| slice.location.z;
+        let scores = prediction.par_score(height);
+        assert_eq!(scores, prediction.slices.iter().map(height).collect::<Vec<_>>());
+
+        let (best, score) = prediction.par_best(|slice| -slice.location.z).unwrap();
+        assert_eq!(score, -scores[best]);
+        assert!(scores.iter().all(|z| *z >= scores[best]));
+
+        let camera = vec3a(0., -6500., 300.);
+        assert_eq!(prediction.par_occluded_from(&game, camera), prediction.occluded_from(&game, camera));
+    }
+
+    #[test]
+    fn empty_prediction() {
+        let prediction = BallPrediction::default();
+
+        assert!(prediction.par_find(|_| true).is_none());
+        assert!(prediction.par_best(|_| 0.).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "throwback")]
+    fn parallel_bvh_matches_serial() {
+        use crate::load_soccar_throwback;
+
+        let (ids, triangles): (Vec<TriangleId>, Vec<Tri>) = load_soccar_throwback().field.collision_mesh.primitives_with_ids().into_iter().unzip();
+
+        let serial = Bvh::serial_from_with_ids(&triangles, &ids);
+        let parallel = Bvh::par_from_with_ids(&triangles, &ids);
+
+        assert_eq!(parallel.num_leaves, serial.num_leaves);
+        assert_eq!(parallel.global_box.min, serial.global_box.min);
+        assert_eq!(parallel.global_box.max, serial.global_box.max);
+
+        // the leaves come out in the same order, so the trees are the same
+        let leaves = |bvh: &Bvh| bvh.primitives_with_ids().into_iter().map(|(id, tri)| (id, tri.p)).collect::<Vec<_>>();
+        assert_eq!(leaves(&parallel), leaves(&serial));
+    }
+}
@@ -0,0 +1,432 @@
+use glam::Vec3A;
+
+use super::ball::Ball;
+use super::game::{Arena, Game, Integrator};
+use super::mutators::{DragModel, Mutators};
+use super::tuning::Tuning;
+use crate::linear_algebra::math;
+
+// The exact bounce the simulation uses, as a pure function, so ports of the physics can be checked against this crate
+//
+// A bounce is a single impulse j at the point where the ball touches the surface, at `loc` from the ball's center:
+//   v_perp = n * min(v . n, 0)                               the speed into the surface
+//   v_para = v - v_perp - loc x w                            how fast the contact point slides along the surface
+//   j_perp = -(1 + restitution) * mass * v_perp
+//   j_para = -min(friction * |v_perp| / |v_para|, 1) * m_reduced * v_para
+//   m_reduced = 1 / (1 / mass + |loc|^2 / moi)
+// and then
+//   v' = v + j / mass
+//   w' = w + (loc x j) / moi
+// where j = j_perp + j_para
+// A ball that's already moving away from the surface gets no impulse at all
+
+// Between bounces, a step through the air is
+//   v' = v + a(v) * dt
+//   x' = x + v' * dt
+// with a(v) = drag(v) + gravity + magnus * (w x v), or fourth order Runge-Kutta on the same acceleration with `Integrator::Rk4`
+// Then the spin slows down by `angular_damping`, and both speeds are capped at `Ball::V_MAX` and `Ball::W_MAX`
+
+// Everything about the ball and the surface a bounce depends on, other than how the ball is moving
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BounceParams {
+    // the distance from the ball's center to the surface
+    pub radius: f32,
+    pub mass: f32,
+    pub moi: f32,
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl Default for BounceParams {
+    // the game's soccar ball
+    fn default() -> Self {
+        let ball = Ball::initialize_soccar();
+
+        Self {
+            radius: ball.collision_radius,
+            mass: Ball::M,
+            moi: ball.moi,
+            restitution: Ball::RESTITUTION,
+            friction: Ball::MU,
+        }
+    }
+}
+
+impl BounceParams {
+    pub fn from_ball(ball: &Ball, tuning: &Tuning) -> Self {
+        Self {
+            radius: ball.collision_radius,
+            mass: Ball::M,
+            moi: ball.moi,
+            restitution: tuning.restitution(),
+            friction: tuning.friction(),
+        }
+    }
+}
+
+// A ball's motion, without anything about the ball itself
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BallState {
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+}
+
+impl BallState {
+    pub fn from_ball(ball: &Ball) -> Self {
+        Self {
+            location: ball.location,
+            velocity: ball.velocity,
+            angular_velocity: ball.angular_velocity,
+        }
+    }
+}
+
+// Everything a step through the air depends on, other than how the ball is moving
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlightParams {
+    pub gravity: Vec3A,
+    // the game's drag, from `Tuning`, which `drag_model` turns into the drag at the ball's speed
+    pub drag: f32,
+    pub drag_model: DragModel,
+    pub angular_damping: f32,
+    pub magnus: f32,
+    pub integrator: Integrator,
+}
+
+impl Default for FlightParams {
+    // a standard soccar match
+    fn default() -> Self {
+        let mutators = Mutators::default();
+
+        Self {
+            gravity: mutators.gravity,
+            drag: Ball::DRAG,
+            drag_model: mutators.drag_model,
+            angular_damping: mutators.angular_damping,
+            magnus: mutators.magnus,
+            integrator: Integrator::default(),
+        }
+    }
+}
+
+impl FlightParams {
+    pub fn from_game(game: &Game) -> Self {
+        Self::from_arena(&game.arena(), &game.ball)
+    }
+
+    pub(crate) fn from_arena(arena: &Arena, ball: &Ball) -> Self {
+        Self {
+            gravity: arena.gravity,
+            drag: arena.field.tuning.drag(),
+            drag_model: ball.drag_model,
+            angular_damping: ball.angular_damping,
+            magnus: ball.magnus,
+            integrator: arena.integrator,
+        }
+    }
+
+    // how the ball speeds up in the air, while it has this velocity
+    #[inline]
+    fn acceleration(&self, velocity: Vec3A, angular_velocity: Vec3A) -> Vec3A {
+        let acceleration = velocity * self.drag_model.coefficient(velocity, self.drag) + self.gravity;
+
+        if self.magnus == 0. {
+            acceleration
+        } else {
+            acceleration + angular_velocity.cross(velocity) * self.magnus
+        }
+    }
+}
+
+// moves the ball through the air, without touching its spin
+#[inline]
+pub(crate) fn fly(location: &mut Vec3A, velocity: &mut Vec3A, angular_velocity: Vec3A, dt: f32, params: &FlightParams) {
+    match params.integrator {
+        Integrator::Euler => {
+            *velocity += params.acceleration(*velocity, angular_velocity) * dt;
+            *location += *velocity * dt;
+        }
+        Integrator::Rk4 => {
+            // the acceleration sampled at the start, twice in the middle, and at the end of the step
+            let acceleration = |velocity: Vec3A| params.acceleration(velocity, angular_velocity);
+
+            let v1 = *velocity;
+            let a1 = acceleration(v1);
+            let v2 = v1 + a1 * (dt / 2.);
+            let a2 = acceleration(v2);
+            let v3 = v1 + a2 * (dt / 2.);
+            let a3 = acceleration(v3);
+            let v4 = v1 + a3 * dt;
+            let a4 = acceleration(v4);
+
+            *location += (v1 + 2. * (v2 + v3) + v4) * (dt / 6.);
+            *velocity += (a1 + 2. * (a2 + a3) + a4) * (dt / 6.);
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn damped(angular_velocity: Vec3A, damping: f32, dt: f32) -> Vec3A {
+    angular_velocity * (1. - damping * dt).max(0.)
+}
+
+#[inline]
+pub(crate) fn limit_velocities(velocity: &mut Vec3A, angular_velocity: &mut Vec3A) {
+    *angular_velocity *= (Ball::W_MAX * math::length_recip(*angular_velocity)).min(1.);
+    *velocity *= (Ball::V_MAX * math::length_recip(*velocity)).min(1.);
+}
+
+// One step of `dt` seconds through the air, exactly like the simulation's when the ball isn't touching anything
+// Nothing here checks for the field, so this is only for cheap extrapolations over stretches where the ball is known to be in the air
+pub fn free_flight(state: BallState, dt: f32, params: FlightParams) -> BallState {
+    let BallState {
+        mut location,
+        mut velocity,
+        mut angular_velocity,
+    } = state;
+
+    fly(&mut location, &mut velocity, angular_velocity, dt, &params);
+    angular_velocity = damped(angular_velocity, params.angular_damping, dt);
+    limit_velocities(&mut velocity, &mut angular_velocity);
+
+    BallState {
+        location,
+        velocity,
+        angular_velocity,
+    }
+}
+
+// the impulse from a surface with the normal `n`, touching the ball at `loc` from its center
+#[inline]
+pub(crate) fn impulse(velocity: Vec3A, angular_velocity: Vec3A, loc: Vec3A, n: Vec3A, params: &BounceParams) -> Vec3A {
+    let m_reduced = 1. / (1. / params.mass + loc.length_squared() / params.moi);
+
+    let v_perp = n * velocity.dot(n).min(0.);
+    let v_para = velocity - v_perp - loc.cross(angular_velocity);
+
+    let ratio = math::length(v_perp) / math::length(v_para).max(0.0001);
+
+    let j_perp = v_perp * (-(1. + params.restitution) * params.mass);
+    let j_para = -(params.friction * ratio).min(1.) * m_reduced * v_para;
+
+    j_perp + j_para
+}
+
+// The velocity and angular velocity right after bouncing off of a surface with this (unit length) normal
+// This is only the impulse: drag, the speed limits and moving the ball are all separate steps in the simulation
+pub fn bounce(velocity: Vec3A, angular_velocity: Vec3A, normal: Vec3A, params: BounceParams) -> (Vec3A, Vec3A) {
+    let loc = normal * -params.radius;
+    let j = impulse(velocity, angular_velocity, loc, normal, &params);
+
+    (velocity + j / params.mass, angular_velocity + loc.cross(j) / params.moi)
+}
+
+#[cfg(test)]
+mod test {
+    use glam::{vec3a, Quat};
+
+    use super::*;
+
+    fn close(a: Vec3A, b: Vec3A, tolerance: f32) -> bool {
+        a.distance(b) <= tolerance
+    }
+
+    #[test]
+    fn straight_down() {
+        let params = BounceParams::default();
+        let (velocity, angular_velocity) = bounce(vec3a(0., 0., -1000.), Vec3A::ZERO, Vec3A::Z, params);
+
+        assert_eq!(velocity, vec3a(0., 0., 1000. * params.restitution));
+        assert_eq!(angular_velocity, Vec3A::ZERO);
+    }
+
+    #[test]
+    fn moving_away_is_unchanged() {
+        let params = BounceParams::default();
+
+        for (velocity, angular_velocity) in [(vec3a(300., -200., 500.), vec3a(1., 2., 3.)), (vec3a(1000., 0., 0.), Vec3A::ZERO), (Vec3A::ZERO, Vec3A::ZERO)] {
+            assert_eq!(bounce(velocity, angular_velocity, Vec3A::Z, params), (velocity, angular_velocity));
+        }
+    }
+
+    #[test]
+    fn no_restitution_stops_at_the_surface() {
+        let params = BounceParams {
+            restitution: 0.,
+            ..BounceParams::default()
+        };
+
+        let (velocity, _) = bounce(vec3a(500., 0., -800.), Vec3A::ZERO, Vec3A::Z, params);
+        assert_eq!(velocity.z, 0.);
+    }
+
+    #[test]
+    fn steep_bounces_roll() {
+        // friction is strong enough to stop the contact point from sliding at all
+        let params = BounceParams::default();
+        let (velocity, angular_velocity) = bounce(vec3a(500., 200., -1500.), vec3a(0., 0., 1.), Vec3A::Z, params);
+
+        let loc = Vec3A::Z * -params.radius;
+        let slide = velocity + angular_velocity.cross(loc);
+        assert!(close(slide * vec3a(1., 1., 0.), Vec3A::ZERO, 0.01), "{slide}");
+    }
+
+    #[test]
+    fn glancing_bounces_slide() {
+        // friction only takes away some of the sliding, in proportion to the speed into the surface
+        let params = BounceParams::default();
+        let (normal_speed, tangential_speed) = (100., 2000.);
+        let (velocity, angular_velocity) = bounce(vec3a(tangential_speed, 0., -normal_speed), Vec3A::ZERO, Vec3A::Z, params);
+
+        let m_reduced = 1. / (1. / params.mass + params.radius * params.radius / params.moi);
+        let expected = tangential_speed - params.friction * normal_speed * m_reduced / params.mass;
+        assert!((velocity.x - expected).abs() < 0.01, "{} {expected}", velocity.x);
+        assert!(velocity.x > 0. && angular_velocity.y > 0.);
+    }
+
+    #[test]
+    fn spin_kicks_the_ball() {
+        // backspin on a ball coming straight down sends it backwards
+        let params = BounceParams::default();
+        let (velocity, _) = bounce(vec3a(0., 0., -1000.), vec3a(0., -5., 0.), Vec3A::Z, params);
+
+        assert!(velocity.x < -100., "{velocity}");
+    }
+
+    #[test]
+    fn frictionless_keeps_the_spin() {
+        let params = BounceParams {
+            friction: 0.,
+            ..BounceParams::default()
+        };
+
+        let angular_velocity = vec3a(1., -2., 3.);
+        let (velocity, bounced_spin) = bounce(vec3a(700., -300., -900.), angular_velocity, Vec3A::Z, params);
+        assert_eq!(bounced_spin, angular_velocity);
+        assert_eq!((velocity.x, velocity.y), (700., -300.));
+    }
+
+    #[test]
+    fn angular_momentum_around_the_contact_is_kept() {
+        // the impulse goes through the contact point, so it can't change the angular momentum around it
+        let params = BounceParams::default();
+        let loc = Vec3A::Z * -params.radius;
+        let momentum = |velocity: Vec3A, angular_velocity: Vec3A| angular_velocity * params.moi - loc.cross(velocity) * params.mass;
+
+        for (velocity, angular_velocity) in [(vec3a(500., 200., -1500.), vec3a(0., 0., 1.)), (vec3a(2000., 0., -100.), vec3a(3., 0., 0.))] {
+            let (bounced_velocity, bounced_spin) = bounce(velocity, angular_velocity, Vec3A::Z, params);
+            let (before, after) = (momentum(velocity, angular_velocity), momentum(bounced_velocity, bounced_spin));
+
+            assert!(close(before, after, before.length() * 1e-5), "{before} {after}");
+        }
+    }
+
+    #[test]
+    fn energy_never_increases() {
+        let params = BounceParams::default();
+        let energy = |velocity: Vec3A, angular_velocity: Vec3A| 0.5 * params.mass * velocity.length_squared() + 0.5 * params.moi * angular_velocity.length_squared();
+
+        for velocity in [vec3a(0., 0., -1000.), vec3a(2000., 0., -100.), vec3a(-300., 800., -2500.)] {
+            for angular_velocity in [Vec3A::ZERO, vec3a(0., 6., 0.), vec3a(-4., 0., 2.)] {
+                let (bounced_velocity, bounced_spin) = bounce(velocity, angular_velocity, Vec3A::Z, params);
+                assert!(energy(bounced_velocity, bounced_spin) <= energy(velocity, angular_velocity) * (1. + 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn any_surface() {
+        // turning the surface and everything with it turns the bounce the same way
+        let params = BounceParams::default();
+        let (velocity, angular_velocity) = (vec3a(800., 300., -1200.), vec3a(2., -1., 0.5));
+        let (flat_velocity, flat_spin) = bounce(velocity, angular_velocity, Vec3A::Z, params);
+
+        let rotation = Quat::from_euler(glam::EulerRot::XYZ, 0.7, -1.2, 2.5);
+        let turn = |v: Vec3A| Vec3A::from(rotation * glam::Vec3::from(v));
+        let (turned_velocity, turned_spin) = bounce(turn(velocity), turn(angular_velocity), turn(Vec3A::Z), params);
+
+        assert!(close(turned_velocity, turn(flat_velocity), 0.05), "{turned_velocity} {}", turn(flat_velocity));
+        assert!(close(turned_spin, turn(flat_spin), 1e-4), "{turned_spin} {}", turn(flat_spin));
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn default_flight_is_soccar() {
+        use crate::load_soccar;
+
+        assert_eq!(FlightParams::default(), FlightParams::from_game(&load_soccar()));
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn free_flight_matches_the_simulation() {
+        use crate::load_soccar;
+
+        for integrator in [Integrator::Euler, Integrator::Rk4] {
+            let mut game = load_soccar();
+            game.integrator = integrator;
+            game.ball.magnus = 0.01;
+            game.ball.angular_damping = 0.5;
+            game.ball.update(0., vec3a(-500., 300., 1000.), vec3a(800., -600., 700.), vec3a(1., 2., -3.));
+
+            let params = FlightParams::from_game(&game);
+            let mut state = BallState::from_ball(&game.ball);
+
+            // a second in the middle of the field, without touching anything
+            for _ in 0..120 {
+                Ball::step(&mut game, Ball::SIMULATION_DT);
+                state = free_flight(state, Ball::SIMULATION_DT, params);
+
+                assert_eq!(state, BallState::from_ball(&game.ball));
+            }
+        }
+    }
+
+    #[test]
+    fn free_flight_limits_the_speed() {
+        let state = BallState {
+            location: Vec3A::ZERO,
+            velocity: vec3a(Ball::V_MAX, Ball::V_MAX, 0.),
+            angular_velocity: vec3a(0., 0., Ball::W_MAX * 2.),
+        };
+
+        let after = free_flight(state, Ball::SIMULATION_DT, FlightParams::default());
+        assert!((after.velocity.length() - Ball::V_MAX).abs() < 0.01);
+        assert!((after.angular_velocity.length() - Ball::W_MAX).abs() < 1e-5);
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn matches_the_simulation() {
+        use crate::geometry::Ray;
+        use crate::load_soccar;
+        use crate::simulation::game::ContactModel;
+
+        let game = load_soccar();
+        let tuning = game.field.tuning;
+        let ball = Ball {
+            location: vec3a(0., 0., game.ball.collision_radius),
+            velocity: vec3a(600., -400., -1300.),
+            angular_velocity: vec3a(-1., 3., 2.),
+            ..game.ball
+        };
+
+        let (velocity, angular_velocity) = bounce(ball.velocity, ball.angular_velocity, Vec3A::Z, BounceParams::from_ball(&ball, &tuning));
+
+        // without any time passing, a step on the floor is only the bounce
+        let mut stepped = ball;
+        stepped.collide(
+            &Ray {
+                start: Vec3A::ZERO,
+                direction: Vec3A::Z,
+            },
+            &tuning,
+            ContactModel::default(),
+            0.,
+        );
+
+        assert_eq!(stepped.velocity, velocity);
+        assert_eq!(stepped.angular_velocity, angular_velocity);
+    }
+}
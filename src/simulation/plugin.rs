@@ -0,0 +1,214 @@
+use std::error::Error;
+use std::ffi::c_void;
+use std::fmt;
+use std::path::Path;
+use std::ptr;
+use std::sync::Arc;
+
+use libloading::Library;
+
+use super::ball::Ball;
+use super::game::Game;
+use super::geometry::Ray;
+use crate::ffi::{RlbsBallSlice, RlbsVec3};
+
+// Bumped whenever `PluginTable` changes in a way that old plugins can't keep up with
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+// What a plugin's `rlbs_plugin` function gives back, laid out like `RlbsPlugin` in include/rl_ball_sym_plugin.h
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct PluginTable {
+    pub abi_version: u32,
+    // given to every hook, the plugin can keep whatever it wants behind it
+    pub state: *mut c_void,
+    // run after every step of the ball, with the normal of what it was touching at the start of the step (or null),
+    // and can change its location, velocity and angular velocity
+    pub step: Option<unsafe extern "C" fn(state: *mut c_void, ball: *mut RlbsBallSlice, contact_normal: *const RlbsVec3, dt: f32)>,
+    // run once when the plugin is dropped
+    pub free: Option<unsafe extern "C" fn(state: *mut c_void)>,
+}
+
+// Custom physics that can be added to a game without rebuilding anything that uses it, like a community game mode
+// Every clone of the game shares the plugin, which is freed once the last one is dropped
+pub struct Plugin {
+    table: PluginTable,
+    // kept open for as long as its functions can be called, None for plugins that were linked in
+    _library: Option<Library>,
+}
+
+// The plugin interface requires every hook to be safe to call from any thread, at the same time
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugin").field("table", &self.table).field("dynamic", &self._library.is_some()).finish()
+    }
+}
+
+impl Plugin {
+    /// Loads a shared library that exports `rlbs_plugin`
+    ///
+    /// # Safety
+    ///
+    /// The library runs its own code as it's loaded, and has to follow include/rl_ball_sym_plugin.h
+    pub unsafe fn load<P: AsRef<Path>>(path: P) -> Result<Self, PluginError> {
+        let library = Library::new(path.as_ref()).map_err(|error| PluginError::Load(error.to_string()))?;
+        let entry = library.get::<unsafe extern "C" fn() -> PluginTable>(b"rlbs_plugin\0").map_err(|_| PluginError::MissingEntryPoint)?;
+        let table = entry();
+
+        Plugin::new(table, Some(library))
+    }
+
+    /// A plugin whose table was made some other way, like from a plugin that's linked into the program
+    ///
+    /// # Safety
+    ///
+    /// The table's hooks have to follow include/rl_ball_sym_plugin.h for as long as the plugin is around
+    pub unsafe fn from_table(table: PluginTable) -> Result<Self, PluginError> {
+        Plugin::new(table, None)
+    }
+
+    fn new(table: PluginTable, library: Option<Library>) -> Result<Self, PluginError> {
+        // a table from a different version might not even be laid out the same way, so none of it can be trusted, not even `free`
+        if table.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                expected: PLUGIN_ABI_VERSION,
+                actual: table.abi_version,
+            });
+        }
+
+        Ok(Self {
+            table,
+            _library: library,
+        })
+    }
+
+    pub(crate) fn step(&self, ball: &mut Ball, contact: Option<Ray>, dt: f32) {
+        let Some(step) = self.table.step else {
+            return;
+        };
+
+        let mut slice = RlbsBallSlice::from(&*ball);
+        let normal = contact.map(|contact| RlbsVec3::from(contact.direction));
+        let normal = normal.as_ref().map_or(ptr::null(), ptr::from_ref);
+
+        unsafe { step(self.table.state, &mut slice, normal, dt) };
+
+        ball.location = slice.location.into();
+        ball.velocity = slice.velocity.into();
+        ball.angular_velocity = slice.angular_velocity.into();
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        if let Some(free) = self.table.free {
+            unsafe { free(self.table.state) };
+        }
+    }
+}
+
+impl Game {
+    // Runs `plugin` after every step of the ball, after any plugins that were added before it
+    pub fn add_plugin(&mut self, plugin: Plugin) {
+        self.plugins.push(Arc::new(plugin));
+    }
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Load(String),
+    // the library doesn't export `rlbs_plugin`
+    MissingEntryPoint,
+    AbiMismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Load(message) => write!(f, "Couldn't load the plugin: {message}"),
+            PluginError::MissingEntryPoint => write!(f, "The plugin doesn't export rlbs_plugin"),
+            PluginError::AbiMismatch {
+                expected,
+                actual,
+            } => write!(f, "The plugin was built for version {actual} of the plugin interface, but this is version {expected}"),
+        }
+    }
+}
+
+impl Error for PluginError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn plugins_run_every_step() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use glam::{vec3a, Vec3A};
+
+        use crate::load_soccar;
+
+        // cancels out gravity, and counts its steps in the state
+        unsafe extern "C" fn float_step(state: *mut c_void, ball: *mut RlbsBallSlice, _contact_normal: *const RlbsVec3, dt: f32) {
+            (*state.cast::<AtomicUsize>()).fetch_add(1, Ordering::Relaxed);
+            (*ball).velocity.z += 650. * dt;
+        }
+
+        static FREED: AtomicUsize = AtomicUsize::new(0);
+
+        unsafe extern "C" fn free_state(state: *mut c_void) {
+            drop(Box::from_raw(state.cast::<AtomicUsize>()));
+            FREED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let steps = Box::into_raw(Box::new(AtomicUsize::new(0)));
+        let table = PluginTable {
+            abi_version: PLUGIN_ABI_VERSION,
+            state: steps.cast(),
+            step: Some(float_step),
+            free: Some(free_state),
+        };
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+        game.add_plugin(unsafe { Plugin::from_table(table) }.unwrap());
+        assert!(!game.is_pure());
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+
+        assert_eq!(unsafe { (*steps).load(Ordering::Relaxed) }, 240);
+        // it would have hit the floor by now without the plugin, and only sinks by as much as gravity moves it in the step before the plugin runs
+        assert!(prediction.slices.iter().all(|slice| slice.location.z > 980.));
+
+        // clones share it, and it's only freed once
+        let freed = FREED.load(Ordering::Relaxed);
+        let clone = game.clone();
+        drop(game);
+        assert_eq!(FREED.load(Ordering::Relaxed), freed);
+        drop(clone);
+        assert_eq!(FREED.load(Ordering::Relaxed), freed + 1);
+    }
+
+    #[test]
+    fn mismatched_plugins() {
+        let table = PluginTable {
+            abi_version: PLUGIN_ABI_VERSION + 1,
+            state: ptr::null_mut(),
+            step: None,
+            free: None,
+        };
+
+        let error = unsafe { Plugin::from_table(table) }.unwrap_err();
+        assert!(matches!(error, PluginError::AbiMismatch { actual, .. } if actual == PLUGIN_ABI_VERSION + 1));
+
+        assert!(matches!(unsafe { Plugin::load("this_plugin_does_not_exist.so") }, Err(PluginError::Load(_))));
+    }
+}
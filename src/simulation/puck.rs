@@ -0,0 +1,357 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use glam::{Mat3A, Quat, Vec3, Vec3A};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::Ball;
+use super::game::Game;
+use super::geometry::Sphere;
+
+// The Snow Day puck, a flat cylinder that's simulated as a rigid body instead of a sphere
+// The dimensions and material values approximate the game's, they haven't been checked against it tick by tick
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Puck {
+    pub time: f32,
+    pub location: Vec3A,
+    pub velocity: Vec3A,
+    pub angular_velocity: Vec3A,
+    // rotates the puck's local axes (z along the cylinder's axis) into world space
+    pub orientation: Quat,
+    pub radius: f32,
+    pub height: f32,
+    pub mass: f32,
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+#[derive(Clone, Default)]
+pub struct PuckPrediction {
+    pub num_slices: usize,
+    pub slices: Vec<Puck>,
+}
+
+// a point of the puck that's inside of the field
+#[derive(Clone, Copy, Debug)]
+struct PuckContact {
+    // relative to the puck's center
+    offset: Vec3A,
+    // pointing out of the field, towards the puck
+    normal: Vec3A,
+    depth: f32,
+}
+
+impl Puck {
+    const SNOWDAY_RADIUS: f32 = 114.25;
+    const SNOWDAY_HEIGHT: f32 = 62.5;
+    const SNOWDAY_MASS: f32 = 50.;
+    const SNOWDAY_RESTITUTION: f32 = 0.1;
+    const SNOWDAY_FRICTION: f32 = 0.1;
+
+    pub(crate) const V_MAX: f32 = 6000.;
+
+    // the number of points sampled around each of the puck's rims
+    const RIM_SAMPLES: usize = 24;
+    const SOLVER_ITERATIONS: usize = 8;
+    // slower impacts than this don't bounce, so a resting puck doesn't jitter
+    const BOUNCE_THRESHOLD: f32 = 50.;
+
+    // same as the ball's
+    const BAUMGARTE: f32 = 0.8;
+    const PENETRATION_SLOP: f32 = 0.01;
+
+    pub fn initialize_snowday() -> Self {
+        Puck {
+            location: Vec3A::Z * Puck::SNOWDAY_HEIGHT / 2.,
+            radius: Puck::SNOWDAY_RADIUS,
+            height: Puck::SNOWDAY_HEIGHT,
+            mass: Puck::SNOWDAY_MASS,
+            restitution: Puck::SNOWDAY_RESTITUTION,
+            friction: Puck::SNOWDAY_FRICTION,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(&mut self, time: f32, location: Vec3A, velocity: Vec3A, angular_velocity: Vec3A) {
+        self.time = time;
+        self.location = location;
+        self.velocity = velocity;
+        self.angular_velocity = angular_velocity;
+    }
+
+    // the direction the flat faces point in
+    pub fn up(&self) -> Vec3A {
+        self.rotation() * Vec3A::Z
+    }
+
+    fn rotation(&self) -> Mat3A {
+        Mat3A::from_quat(self.orientation)
+    }
+
+    // the inertia tensor of a solid cylinder, around its own axes
+    pub fn local_inertia(&self) -> Vec3A {
+        let sides = self.mass * (3. * self.radius * self.radius + self.height * self.height) / 12.;
+
+        Vec3A::new(sides, sides, self.mass * self.radius * self.radius / 2.)
+    }
+
+    fn world_inverse_inertia(&self) -> Mat3A {
+        let inverse = self.local_inertia().recip();
+        let r = self.rotation();
+
+        r * Mat3A::from_cols(Vec3A::X * inverse.x, Vec3A::Y * inverse.y, Vec3A::Z * inverse.z) * r.transpose()
+    }
+
+    // the smallest sphere around the puck
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere {
+            center: self.location,
+            radius: self.radius.hypot(self.height / 2.),
+        }
+    }
+
+    // the points of the puck that can touch the field, which is anywhere on its two rims
+    fn rim_points(&self) -> impl Iterator<Item = Vec3A> + '_ {
+        let r = self.rotation();
+
+        (0..Puck::RIM_SAMPLES).flat_map(move |i| {
+            let angle = i as f32 * core::f32::consts::TAU / Puck::RIM_SAMPLES as f32;
+            let around = Vec3A::new(angle.cos(), angle.sin(), 0.) * self.radius;
+
+            [-1., 1.].map(|side| r * (around + Vec3A::Z * side * self.height / 2.))
+        })
+    }
+
+    fn contacts(&self, game: &Game) -> Vec<PuckContact> {
+        let mut query = self.bounding_sphere();
+        query.radius += Puck::PENETRATION_SLOP;
+
+        let tris = game.field.collision_mesh.intersect(&query);
+        if tris.is_empty() {
+            return Vec::new();
+        }
+
+        let mut contacts = Vec::new();
+
+        for offset in self.rim_points() {
+            let point = self.location + offset;
+            let mut deepest: Option<PuckContact> = None;
+
+            for tri in &tris {
+                // the winding isn't consistent across the field, so the normal is flipped to face the puck's center
+                let mut normal = tri.unit_normal();
+                if normal.dot(self.location - tri.p[0]) < 0. {
+                    normal = -normal;
+                }
+
+                let depth = -normal.dot(point - tri.p[0]);
+                // anything deeper than the puck is thick is on the other side of a thin part of the field
+                if depth <= 0. || depth > self.radius {
+                    continue;
+                }
+
+                // the point has to be over the triangle, not just past its plane
+                let projected = point + normal * depth;
+                if tri.closest_point(projected).distance_squared(projected) > 0.01 {
+                    continue;
+                }
+
+                if deepest.is_none_or(|contact| depth > contact.depth) {
+                    deepest = Some(PuckContact {
+                        offset,
+                        normal,
+                        depth,
+                    });
+                }
+            }
+
+            contacts.extend(deepest);
+        }
+
+        contacts
+    }
+
+    // sequential impulses, with the normal impulse clamped to only push and the friction to inside of the Coulomb cone
+    fn resolve(&mut self, contacts: &[PuckContact]) {
+        let inv_mass = 1. / self.mass;
+        let inv_inertia = self.world_inverse_inertia();
+
+        let point_velocity = |puck: &Puck, offset: Vec3A| puck.velocity + puck.angular_velocity.cross(offset);
+        let apply = |puck: &mut Puck, offset: Vec3A, impulse: Vec3A| {
+            puck.velocity += impulse * inv_mass;
+            puck.angular_velocity += inv_inertia * offset.cross(impulse);
+        };
+        let effective_mass = |offset: Vec3A, direction: Vec3A| 1. / (inv_mass + direction.dot((inv_inertia * offset.cross(direction)).cross(offset)));
+
+        // the speed each contact should separate at
+        let targets: Vec<f32> = contacts
+            .iter()
+            .map(|contact| {
+                let approach = -point_velocity(self, contact.offset).dot(contact.normal);
+                if approach > Puck::BOUNCE_THRESHOLD {
+                    approach * self.restitution
+                } else {
+                    0.
+                }
+            })
+            .collect();
+
+        let mut normal_impulses = vec![0.; contacts.len()];
+        let mut friction_impulses = vec![Vec3A::ZERO; contacts.len()];
+
+        for _ in 0..Puck::SOLVER_ITERATIONS {
+            for (i, contact) in contacts.iter().enumerate() {
+                let n = contact.normal;
+
+                let v_n = point_velocity(self, contact.offset).dot(n);
+                let total = (normal_impulses[i] + (targets[i] - v_n) * effective_mass(contact.offset, n)).max(0.);
+                apply(self, contact.offset, n * (total - normal_impulses[i]));
+                normal_impulses[i] = total;
+
+                let v = point_velocity(self, contact.offset);
+                let v_t = v - n * v.dot(n);
+                let speed = v_t.length();
+                if speed < 1e-4 {
+                    continue;
+                }
+
+                let t = v_t / speed;
+                let max_friction = self.friction * normal_impulses[i];
+                let total = friction_impulses[i] - t * speed * effective_mass(contact.offset, t);
+                let total = if total.length() > max_friction {
+                    total.normalize() * max_friction
+                } else {
+                    total
+                };
+                apply(self, contact.offset, total - friction_impulses[i]);
+                friction_impulses[i] = total;
+            }
+        }
+    }
+
+    // moves the puck out of the field, without pushing it out twice for contacts with the same surface
+    fn correct_penetration(&mut self, contacts: &[PuckContact]) {
+        let mut correction = Vec3A::ZERO;
+
+        for contact in contacts {
+            let remaining = (contact.depth - Puck::PENETRATION_SLOP) * Puck::BAUMGARTE - correction.dot(contact.normal);
+            if remaining > 0. {
+                correction += contact.normal * remaining;
+            }
+        }
+
+        self.location += correction;
+    }
+
+    fn limit_velocities(&mut self) {
+        let speed = self.velocity.length();
+        if speed > Puck::V_MAX {
+            self.velocity *= Puck::V_MAX / speed;
+        }
+    }
+
+    pub fn step(game: &mut Game, dt: f32) {
+        let mut puck = game.puck;
+
+        puck.velocity += (game.gravity + puck.velocity * game.field.tuning.drag()) * dt;
+
+        let contacts = puck.contacts(game);
+        if !contacts.is_empty() {
+            puck.resolve(&contacts);
+            puck.correct_penetration(&contacts);
+        }
+
+        puck.limit_velocities();
+
+        puck.location += puck.velocity * dt;
+        puck.orientation = (Quat::from_scaled_axis(Vec3::from(puck.angular_velocity * dt)) * puck.orientation).normalize();
+        puck.time += dt;
+
+        game.puck = puck;
+    }
+
+    pub fn get_puck_prediction_struct_for_time(game: &mut Game, time: &f32) -> PuckPrediction {
+        Puck::get_puck_prediction_struct_for_slices(game, (time / Ball::SIMULATION_DT).round() as usize)
+    }
+
+    pub fn get_puck_prediction_struct(game: &mut Game) -> PuckPrediction {
+        Puck::get_puck_prediction_struct_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+
+    pub fn get_puck_prediction_struct_for_slices(game: &mut Game, num_slices: usize) -> PuckPrediction {
+        let mut slices = Vec::with_capacity(num_slices);
+
+        for _ in 0..num_slices {
+            Puck::step(game, Ball::SIMULATION_DT);
+            slices.push(game.puck);
+        }
+
+        PuckPrediction {
+            num_slices,
+            slices,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_snowday;
+
+    #[test]
+    fn settles_on_the_floor() {
+        let mut game = load_snowday();
+        game.puck.update(0., vec3a(0., 0., 300.), Vec3A::ZERO, Vec3A::ZERO);
+
+        let prediction = Puck::get_puck_prediction_struct_for_time(&mut game, &3.);
+        let last = prediction.slices.last().unwrap();
+
+        assert!((last.location.z - last.height / 2.).abs() < 1., "{}", last.location);
+        assert!(last.velocity.length() < 1., "{}", last.velocity);
+        assert!(last.up().z > 0.999);
+    }
+
+    #[test]
+    fn slides_along_the_floor() {
+        let mut game = load_snowday();
+        game.puck.update(0., game.puck.location, vec3a(1500., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Puck::get_puck_prediction_struct_for_time(&mut game, &1.);
+        let last = prediction.slices.last().unwrap();
+
+        // only a little bit of friction from the floor
+        assert!(last.velocity.x > 1300. && last.velocity.x < 1500., "{}", last.velocity);
+        assert!(last.location.z < last.height / 2. + 1.);
+        assert!(last.up().z > 0.999);
+    }
+
+    #[test]
+    fn barely_bounces_off_of_walls() {
+        let mut game = load_snowday();
+        game.puck.update(0., vec3a(3500., 0., game.puck.height / 2.), vec3a(2000., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Puck::get_puck_prediction_struct_for_time(&mut game, &1.);
+        let last = prediction.slices.last().unwrap();
+
+        assert!(last.velocity.x < 0. && last.velocity.x > -400., "{}", last.velocity);
+        assert!(prediction.slices.iter().all(|puck| puck.location.x < 4096.));
+    }
+
+    #[test]
+    fn tilted_drop_lands_flat() {
+        let mut game = load_snowday();
+        game.puck.update(0., vec3a(0., 0., 400.), vec3a(200., 0., 0.), vec3a(1., 2., 0.));
+        game.puck.orientation = Quat::from_rotation_x(0.6);
+
+        let prediction = Puck::get_puck_prediction_struct_for_time(&mut game, &4.);
+        let last = prediction.slices.last().unwrap();
+
+        assert!(last.up().z.abs() > 0.999, "{}", last.up());
+        assert!((last.location.z - last.height / 2.).abs() < 1., "{}", last.location);
+    }
+}
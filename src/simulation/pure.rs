@@ -0,0 +1,144 @@
+use super::ball::{Ball, BallPrediction};
+use super::config::PredictionConfig;
+use super::game::{Arena, ContactModel, Field, Game, Integrator};
+
+// Predictions don't use any randomness, the clock, or anything else outside of the field they're given,
+// so the same field, ball and settings always give the same prediction, on any thread and in any order
+//
+// The `Ball::get_ball_prediction_*` functions still take `&mut Game` and leave the game's ball at the end of the prediction,
+// `Field::predict` and `Game::predict` are the same thing as pure functions, which makes them safe to cache or to run from many threads at once
+impl Field {
+    // A prediction from `ball` with the settings in `config`, on this field alone
+    // There's nothing but the field here, so every dropshot tile is intact, the integrator and contact model are the default ones, and no plugins run
+    // Without any mutators in `config`, the ball is predicted as it is, with standard gravity
+    // `Accuracy::Coarse` falls back to the full field unless `build_coarse_collision_mesh` was called on it first
+    pub fn predict(&self, mut ball: Ball, config: &PredictionConfig) -> BallPrediction {
+        if let Some(mutators) = &config.mutators {
+            mutators.apply_to_ball(&mut ball);
+        }
+
+        let arena = Arena {
+            field: self,
+            gravity: config.mutators.unwrap_or_default().gravity,
+            accuracy: config.accuracy,
+            integrator: Integrator::default(),
+            contact_model: ContactModel::default(),
+            goal_criteria: config.goal_criteria,
+            dropshot_tiles: None,
+            #[cfg(feature = "plugins")]
+            plugins: &[],
+        };
+
+        config.predict_in(&arena, ball)
+    }
+}
+
+impl Game {
+    // `Field::predict` with the rest of the game, its dropshot tiles, integrator, contact model and plugins, without changing the game
+    // Without any mutators in `config`, the ball is predicted as it is, with the game's gravity
+    // Like there, `Accuracy::Coarse` falls back to the full field unless the coarse one was built ahead of time
+    pub fn predict(&self, mut ball: Ball, config: &PredictionConfig) -> BallPrediction {
+        if let Some(mutators) = &config.mutators {
+            mutators.apply_to_ball(&mut ball);
+        }
+
+        let arena = Arena {
+            gravity: config.mutators.map_or(self.gravity, |mutators| mutators.gravity),
+            accuracy: config.accuracy,
+            goal_criteria: config.goal_criteria,
+            ..self.arena()
+        };
+
+        config.predict_in(&arena, ball)
+    }
+
+    // If predictions from this game are only a function of their inputs, which is always true unless a plugin was added,
+    // since a plugin is free to keep its own state between steps
+    pub fn is_pure(&self) -> bool {
+        #[cfg(feature = "plugins")]
+        if !self.plugins.is_empty() {
+            return false;
+        }
+
+        true
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use std::thread;
+
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::game::Accuracy;
+
+    #[test]
+    fn predict_is_pure() {
+        let mut game = load_soccar();
+        let before = game.ball;
+
+        let mut ball = game.ball;
+        ball.update(0., vec3a(0., 0., 500.), vec3a(800., -300., 400.), vec3a(1., 2., 3.));
+
+        let config = PredictionConfig::default();
+        let prediction = game.predict(ball, &config);
+
+        // the game's ball is left where it was
+        assert_eq!(game.ball.location, before.location);
+        assert_eq!(game.ball.time, before.time);
+        assert!(game.is_pure());
+
+        // and it's the same as the prediction that moves the game's ball
+        game.ball = ball;
+        let expected = config.predict(&mut game);
+        assert_eq!(prediction.checksum(), expected.checksum());
+
+        // the same inputs give the same prediction, even from other threads
+        let game = &game;
+        let checksums: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(move || game.predict(ball, &config).checksum())).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        assert!(checksums.iter().all(|&checksum| checksum == expected.checksum()));
+
+        // the field is all a prediction needs, and predicting from it builds nothing
+        let field = &game.field;
+        assert_eq!(field.predict(ball, &config).checksum(), expected.checksum());
+
+        let coarse = PredictionConfig {
+            accuracy: Accuracy::Coarse,
+            ..config
+        };
+
+        assert_eq!(field.predict(ball, &coarse).checksum(), expected.checksum());
+        assert!(field.coarse_collision_mesh.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "dropshot")]
+    fn predict_keeps_the_dropshot_ball() {
+        let mut game = crate::load_dropshot();
+        game.ball.update(0., vec3a(0., 0., 500.), vec3a(800., -300., 400.), vec3a(1., 2., 3.));
+
+        let prediction = game.predict(game.ball, &PredictionConfig::default());
+        assert_eq!(prediction.slices[0].radius, Ball::initialize_dropshot().radius);
+
+        let expected = Ball::get_ball_prediction_struct(&mut game);
+        assert_eq!(prediction.checksum(), expected.checksum());
+    }
+
+    #[test]
+    fn predict_keeps_the_game_gravity() {
+        let mut game = load_soccar();
+        game.gravity = vec3a(0., 0., -325.);
+        game.ball.update(0., vec3a(0., 0., 500.), vec3a(800., -300., 1000.), vec3a(1., 2., 3.));
+
+        let prediction = game.predict(game.ball, &PredictionConfig::default());
+        let expected = Ball::get_ball_prediction_struct(&mut game);
+        assert_eq!(prediction.checksum(), expected.checksum());
+        assert_eq!(prediction.slices[59].location, expected.slices[59].location);
+    }
+}
@@ -0,0 +1,173 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::bvh::{Bvh, BvhNode};
+use super::geometry::Aabb;
+
+// Relative costs used for the surface area heuristic
+pub const TRAVERSAL_COST: f32 = 1.;
+pub const INTERSECTION_COST: f32 = 1.;
+
+// Statistics about how well a BVH fits its geometry
+// A good tree is shallow, has little overlap between siblings, and has a low SAH cost
+#[derive(Clone, Debug, Default)]
+pub struct BvhQualityReport {
+    pub num_nodes: usize,
+    pub num_leaves: usize,
+    pub max_depth: usize,
+    // how many leaves are at each depth
+    pub depth_histogram: Vec<usize>,
+    pub max_primitives_per_leaf: usize,
+    pub mean_leaf_area: f32,
+    pub max_leaf_area: f32,
+    // how much of a node's surface area is shared by both of its children, averaged over every branch
+    pub mean_sibling_overlap: f32,
+    pub max_sibling_overlap: f32,
+    // the expected cost of a random ray query, relative to intersecting one primitive
+    pub sah_cost: f32,
+}
+
+impl fmt::Display for BvhQualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes: {}, leaves: {}, max depth: {}", self.num_nodes, self.num_leaves, self.max_depth)?;
+        writeln!(f, "primitives per leaf: at most {}", self.max_primitives_per_leaf)?;
+        writeln!(f, "leaf area: mean {:.1}, max {:.1}", self.mean_leaf_area, self.max_leaf_area)?;
+        writeln!(f, "sibling overlap: mean {:.1}%, max {:.1}%", self.mean_sibling_overlap * 100., self.max_sibling_overlap * 100.)?;
+        writeln!(f, "SAH cost: {:.2}", self.sah_cost)?;
+        write!(f, "leaves per depth: {:?}", self.depth_histogram)
+    }
+}
+
+fn overlap(a: &Aabb, b: &Aabb) -> Option<Aabb> {
+    a.intersect_self(b).then(|| Aabb {
+        min: a.min.max(b.min),
+        max: a.max.min(b.max),
+    })
+}
+
+impl Bvh {
+    pub fn quality_report(&self) -> BvhQualityReport {
+        let mut report = BvhQualityReport::default();
+
+        if self.num_leaves == 0 {
+            return report;
+        }
+
+        let root_area = self.root.box_.surface_area();
+        let mut num_branches = 0;
+        let mut total_leaf_area = 0.;
+        let mut total_overlap = 0.;
+
+        let mut stack: Vec<(&BvhNode, usize)> = vec![(&self.root, 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            report.num_nodes += 1;
+            report.max_depth = report.max_depth.max(depth);
+
+            let area = node.box_.surface_area();
+            let relative_area = if root_area > 0. {
+                area / root_area
+            } else {
+                1.
+            };
+
+            if node.is_terminal {
+                let num_primitives = node.primitive.iter().count();
+
+                report.num_leaves += 1;
+                report.max_primitives_per_leaf = report.max_primitives_per_leaf.max(num_primitives);
+                report.max_leaf_area = report.max_leaf_area.max(area);
+                report.sah_cost += relative_area * INTERSECTION_COST * num_primitives as f32;
+                total_leaf_area += area;
+
+                if report.depth_histogram.len() <= depth {
+                    report.depth_histogram.resize(depth + 1, 0);
+                }
+                report.depth_histogram[depth] += 1;
+
+                continue;
+            }
+
+            report.sah_cost += relative_area * TRAVERSAL_COST;
+
+            if let (Some(left), Some(right)) = (&node.left, &node.right) {
+                let shared = overlap(&left.box_, &right.box_).map_or(0., |b| b.surface_area());
+                let ratio = if area > 0. {
+                    shared / area
+                } else {
+                    0.
+                };
+
+                num_branches += 1;
+                total_overlap += ratio;
+                report.max_sibling_overlap = report.max_sibling_overlap.max(ratio);
+            }
+
+            stack.extend(node.left.as_deref().map(|child| (child, depth + 1)));
+            stack.extend(node.right.as_deref().map(|child| (child, depth + 1)));
+        }
+
+        report.mean_leaf_area = total_leaf_area / report.num_leaves as f32;
+        if num_branches > 0 {
+            report.mean_sibling_overlap = total_overlap / num_branches as f32;
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::Vec3A;
+
+    use super::*;
+    use crate::simulation::geometry::Tri;
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn soccar_report() {
+        use crate::load_soccar;
+
+        let game = load_soccar();
+        let report = game.field.collision_mesh.quality_report();
+
+        assert_eq!(report.num_leaves, game.field.collision_mesh.num_leaves as usize);
+        assert_eq!(report.num_nodes, report.num_leaves * 2 - 1);
+        assert_eq!(report.depth_histogram.iter().sum::<usize>(), report.num_leaves);
+        assert_eq!(report.depth_histogram.len(), report.max_depth + 1);
+        assert_eq!(report.max_primitives_per_leaf, 1);
+
+        // the root is always traversed
+        assert!(report.sah_cost > TRAVERSAL_COST);
+        assert!((0. ..=1.).contains(&report.mean_sibling_overlap));
+        assert!(report.mean_leaf_area <= report.max_leaf_area);
+
+        assert!(report.to_string().contains("SAH cost"));
+    }
+
+    #[test]
+    fn overlapping_tree_is_worse() {
+        let tri = |offset: Vec3A| Tri {
+            p: [offset, offset + Vec3A::X * 100., offset + Vec3A::Y * 100.],
+        };
+
+        // the same triangles, either spread out or stacked on top of each other
+        let spread: Vec<Tri> = (0..64).map(|i| tri(Vec3A::new((i % 8) as f32 * 200., (i / 8) as f32 * 200., 0.))).collect();
+        let stacked: Vec<Tri> = (0..64).map(|i| tri(Vec3A::new(i as f32 * 0.01, 0., 0.))).collect();
+
+        let spread = Bvh::from(&spread).quality_report();
+        let stacked = Bvh::from(&stacked).quality_report();
+
+        assert!(stacked.mean_sibling_overlap > spread.mean_sibling_overlap);
+        assert!(stacked.sah_cost > spread.sah_cost);
+    }
+
+    #[test]
+    fn empty_tree() {
+        let report = Bvh::default().quality_report();
+
+        assert_eq!(report.num_leaves, 0);
+        assert!(report.depth_histogram.is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+// Brute-force versions of every BVH query, that the real ones are checked against
+// Going through every triangle is slow, but it's simple enough to be obviously correct
+
+use alloc::vec::Vec;
+
+use super::bvh::Bvh;
+use super::geometry::{Aabb, Contact, Obb, Ray, Sphere, Tri};
+
+// triangles don't have a total order, so they're compared by their exact bits
+fn sorted(tris: &[Tri]) -> Vec<[u32; 9]> {
+    let mut keys: Vec<[u32; 9]> = tris
+        .iter()
+        .map(|tri| {
+            let mut key = [0; 9];
+            for (i, p) in tri.p.iter().enumerate() {
+                key[i * 3..i * 3 + 3].copy_from_slice(&p.to_array().map(f32::to_bits));
+            }
+            key
+        })
+        .collect();
+
+    keys.sort_unstable();
+    keys
+}
+
+pub(crate) fn check_intersect(bvh: &Bvh, query_object: &Sphere, hits: &[Tri]) {
+    let expected: Vec<Tri> = bvh.primitives().into_iter().filter(|tri| tri.intersect_sphere(query_object)).collect();
+
+    assert_eq!(sorted(hits), sorted(&expected), "BVH sphere query disagrees with brute force for {query_object:?}");
+}
+
+pub(crate) fn check_intersect_aabb(bvh: &Bvh, query_box: &Aabb, hits: &[Tri]) {
+    let expected: Vec<Tri> = bvh.primitives().into_iter().filter(|tri| Aabb::from_tri(tri).intersect_self(query_box)).collect();
+
+    assert_eq!(sorted(hits), sorted(&expected), "BVH box query disagrees with brute force for {query_box:?}");
+}
+
+pub(crate) fn check_collide_obb(bvh: &Bvh, obb: &Obb, contact: &Option<Contact>) {
+    let expected = bvh.primitives().iter().filter_map(|tri| obb.intersect_tri(tri)).map(|contact| contact.depth).reduce(f32::max);
+
+    // different triangles can be tied for the deepest, so only the depth has to match
+    assert_eq!(contact.map(|contact| contact.depth), expected, "BVH box collision disagrees with brute force for {obb:?}");
+}
+
+pub(crate) fn check_distance_to_segment(bvh: &Bvh, segment: &Ray, distance: f32) {
+    let expected = bvh.primitives().iter().map(|tri| tri.distance_to_segment(segment)).fold(f32::INFINITY, f32::min);
+
+    assert_eq!(distance, expected, "BVH segment distance disagrees with brute force for {segment:?}");
+}
+
+pub(crate) fn check_raycast(bvh: &Bvh, ray: &Ray, t: Option<f32>) {
+    let expected = bvh.primitives().iter().filter_map(|tri| tri.intersect_ray(ray)).reduce(f32::min);
+
+    // different triangles can be tied for the closest, so only the distance has to match
+    assert_eq!(t, expected, "BVH raycast disagrees with brute force for {ray:?}");
+}
+
+pub(crate) fn check_spherecast(bvh: &Bvh, ray: &Ray, radius: f32, t: Option<f32>) {
+    let expected = bvh.primitives().iter().filter_map(|tri| tri.sweep_sphere(ray, radius)).reduce(f32::min);
+
+    assert_eq!(t, expected, "BVH spherecast disagrees with brute force for {ray:?} with a radius of {radius}");
+}
@@ -0,0 +1,121 @@
+use glam::Vec3A;
+
+use super::game::Game;
+
+// A ball's physics as RLBot sends it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PacketPhysics {
+    pub location: [f32; 3],
+    pub velocity: [f32; 3],
+    pub angular_velocity: [f32; 3],
+}
+
+// The parts of RLBot's GameTickPacket that the simulation needs
+// This doesn't depend on any of RLBot's crates, so implement it for whichever packet type a bot gets:
+//
+//   impl TickPacket for GameTickPacket {
+//       fn seconds_elapsed(&self) -> f32 {
+//           self.game_info.seconds_elapsed
+//       }
+//
+//       fn world_gravity_z(&self) -> f32 {
+//           self.game_info.world_gravity_z
+//       }
+//
+//       fn ball(&self) -> Option<PacketPhysics> {
+//           let physics = &self.game_ball.physics;
+//
+//           Some(PacketPhysics {
+//               location: [physics.location.x, physics.location.y, physics.location.z],
+//               velocity: [physics.velocity.x, physics.velocity.y, physics.velocity.z],
+//               angular_velocity: [physics.angular_velocity.x, physics.angular_velocity.y, physics.angular_velocity.z],
+//           })
+//       }
+//   }
+pub trait TickPacket {
+    fn seconds_elapsed(&self) -> f32;
+    fn world_gravity_z(&self) -> f32;
+    // None when there isn't a ball on the field, like in newer packets between goals
+    fn ball(&self) -> Option<PacketPhysics>;
+}
+
+impl Game {
+    // Brings the game's time, gravity and ball up to date with a packet, which is usually the first thing a bot does every tick
+    // Returns false (and leaves the ball where it was) if the packet doesn't have a ball in it
+    pub fn sync_with_packet<P: TickPacket + ?Sized>(&mut self, packet: &P) -> bool {
+        self.gravity = Vec3A::new(0., 0., packet.world_gravity_z());
+        self.ball.time = packet.seconds_elapsed();
+
+        let Some(physics) = packet.ball() else {
+            return false;
+        };
+
+        self.ball.update(packet.seconds_elapsed(), physics.location.into(), physics.velocity.into(), physics.angular_velocity.into());
+        true
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    struct Packet {
+        seconds_elapsed: f32,
+        world_gravity_z: f32,
+        ball: Option<PacketPhysics>,
+    }
+
+    impl TickPacket for Packet {
+        fn seconds_elapsed(&self) -> f32 {
+            self.seconds_elapsed
+        }
+
+        fn world_gravity_z(&self) -> f32 {
+            self.world_gravity_z
+        }
+
+        fn ball(&self) -> Option<PacketPhysics> {
+            self.ball
+        }
+    }
+
+    #[test]
+    fn sync_with_packet() {
+        let mut game = load_soccar();
+        let mut packet = Packet {
+            seconds_elapsed: 12.5,
+            world_gravity_z: -325.,
+            ball: Some(PacketPhysics {
+                location: [100., -200., 300.],
+                velocity: [400., 0., -100.],
+                angular_velocity: [0., 1., 0.],
+            }),
+        };
+
+        assert!(game.sync_with_packet(&packet));
+        assert_eq!(game.gravity, vec3a(0., 0., -325.));
+        assert_eq!(game.ball.time, 12.5);
+        assert_eq!(game.ball.location, vec3a(100., -200., 300.));
+        assert_eq!(game.ball.velocity, vec3a(400., 0., -100.));
+        assert_eq!(game.ball.angular_velocity, vec3a(0., 1., 0.));
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        assert!((prediction.slices[0].time - (12.5 + Ball::SIMULATION_DT)).abs() < 1e-4);
+
+        // without a ball, only the time and gravity change
+        packet.seconds_elapsed = 13.;
+        packet.world_gravity_z = -650.;
+        packet.ball = None;
+
+        let mut game = load_soccar();
+        let ball = game.ball;
+        assert!(!game.sync_with_packet(&packet));
+        assert_eq!(game.gravity, vec3a(0., 0., -650.));
+        assert_eq!(game.ball.time, 13.);
+        assert_eq!(game.ball.location, ball.location);
+    }
+}
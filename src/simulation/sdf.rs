@@ -0,0 +1,301 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Cursor, ErrorKind};
+
+#[cfg(feature = "std")]
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::bvh::Bvh;
+use super::geometry::{Aabb, Ray, Sphere};
+
+fn crosses_surface(bvh: &Bvh, a: Vec3A, b: Vec3A) -> bool {
+    let segment = Ray {
+        start: a,
+        direction: b - a,
+    };
+    let segment_box = Aabb {
+        min: a.min(b),
+        max: a.max(b),
+    };
+
+    bvh.intersect_aabb(&segment_box).iter().any(|tri| tri.intersect_ray(&segment).is_some())
+}
+
+// A grid of distances to the closest part of the field, sampled once so later lookups don't have to touch the BVH
+// Distances are positive inside of the field (where the ball can be) and negative outside of it
+#[derive(Clone, Debug, Default)]
+pub struct DistanceField {
+    pub origin: Vec3A,
+    pub cell_size: f32,
+    pub dims: [usize; 3],
+    pub distances: Vec<f32>,
+}
+
+impl DistanceField {
+    pub const DEFAULT_CELL_SIZE: f32 = 128.;
+
+    // `inside` is any point that's inside of the field, like where the ball spawns
+    // Everything that can be reached from it without going through the field counts as inside
+    pub fn from(bvh: &Bvh, cell_size: f32, inside: Vec3A) -> Self {
+        // one extra layer of cells all the way around, so the outside is connected
+        let origin = bvh.global_box.min - cell_size;
+        let size = (bvh.global_box.max - bvh.global_box.min) / cell_size;
+        let dims = [size.x.ceil() as usize + 3, size.y.ceil() as usize + 3, size.z.ceil() as usize + 3];
+
+        let mut field = Self {
+            origin,
+            cell_size,
+            dims,
+            distances: Vec::with_capacity(dims[0] * dims[1] * dims[2]),
+        };
+
+        for z in 0..dims[2] {
+            for y in 0..dims[1] {
+                for x in 0..dims[0] {
+                    let point = field.point([x, y, z]);
+                    field.distances.push(bvh.distance_to_segment(&Ray {
+                        start: point,
+                        direction: Vec3A::ZERO,
+                    }));
+                }
+            }
+        }
+
+        field.flood_fill_sign(bvh, inside);
+        field
+    }
+
+    fn flood_fill_sign(&mut self, bvh: &Bvh, inside: Vec3A) {
+        let mut reached = vec![false; self.distances.len()];
+        let mut queue = VecDeque::new();
+
+        // start from the corners of the cell around `inside` that can be seen from it without going through the field
+        let base = ((inside - self.origin) / self.cell_size).floor();
+        if base.cmpge(Vec3A::ZERO).all() {
+            for corner in 0..8 {
+                let cell = [base.x as usize + (corner & 1), base.y as usize + (corner >> 1 & 1), base.z as usize + (corner >> 2 & 1)];
+
+                if cell.iter().zip(self.dims).all(|(c, dim)| *c < dim) && !crosses_surface(bvh, inside, self.point(cell)) {
+                    reached[self.index(cell)] = true;
+                    queue.push_back(cell);
+                }
+            }
+        }
+
+        while let Some(cell) = queue.pop_front() {
+            for axis in 0..3 {
+                for step in [-1, 1] {
+                    let mut next = cell;
+                    next[axis] = match cell[axis].checked_add_signed(step) {
+                        Some(n) if n < self.dims[axis] => n,
+                        _ => continue,
+                    };
+
+                    let next_index = self.index(next);
+                    if reached[next_index] {
+                        continue;
+                    }
+
+                    // the surface can only be in between if both ends are close enough to it
+                    // (this is an equality when the segment goes straight through the surface, so it needs some slack)
+                    if self.distances[self.index(cell)] + self.distances[next_index] <= self.cell_size * 1.01 && crosses_surface(bvh, self.point(cell), self.point(next)) {
+                        continue;
+                    }
+
+                    reached[next_index] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        for (distance, reached) in self.distances.iter_mut().zip(reached) {
+            if !reached {
+                *distance = -*distance;
+            }
+        }
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        x + self.dims[0] * (y + self.dims[1] * z)
+    }
+
+    fn point(&self, [x, y, z]: [usize; 3]) -> Vec3A {
+        self.origin + Vec3A::new(x as f32, y as f32, z as f32) * self.cell_size
+    }
+
+    fn nearest_cell(&self, p: Vec3A) -> Option<[usize; 3]> {
+        let local = ((p - self.origin) / self.cell_size).round();
+        let cell = [local.x, local.y, local.z];
+
+        if cell.iter().zip(self.dims).any(|(c, dim)| *c < 0. || *c >= dim as f32) {
+            return None;
+        }
+
+        Some(cell.map(|c| c as usize))
+    }
+
+    // the trilinearly interpolated signed distance, or None if the point is outside of the grid
+    pub fn distance(&self, p: Vec3A) -> Option<f32> {
+        let local = (p - self.origin) / self.cell_size;
+        let base = local.floor();
+
+        if base.cmplt(Vec3A::ZERO).any() || base.x as usize + 1 >= self.dims[0] || base.y as usize + 1 >= self.dims[1] || base.z as usize + 1 >= self.dims[2] {
+            return None;
+        }
+
+        let t = local - base;
+        let [x, y, z] = [base.x as usize, base.y as usize, base.z as usize];
+
+        let mut distance = 0.;
+        for corner in 0..8 {
+            let (dx, dy, dz) = (corner & 1, corner >> 1 & 1, corner >> 2 & 1);
+            let weight = if dx == 1 {
+                t.x
+            } else {
+                1. - t.x
+            } * if dy == 1 {
+                t.y
+            } else {
+                1. - t.y
+            } * if dz == 1 {
+                t.z
+            } else {
+                1. - t.z
+            };
+            distance += weight * self.distances[self.index([x + dx, y + dy, z + dz])];
+        }
+
+        Some(distance)
+    }
+
+    // the direction that moves away from the closest surface, towards the inside of the field
+    pub fn normal(&self, p: Vec3A) -> Option<Vec3A> {
+        let h = self.cell_size * 0.5;
+        let gradient = Vec3A::new(self.distance(p + Vec3A::X * h)? - self.distance(p - Vec3A::X * h)?, self.distance(p + Vec3A::Y * h)? - self.distance(p - Vec3A::Y * h)?, self.distance(p + Vec3A::Z * h)? - self.distance(p - Vec3A::Z * h)?);
+
+        Some(gradient.normalize_or_zero())
+    }
+
+    // false only if the sphere definitely isn't touching the field
+    // The distance can't change faster than the distance moved, so the nearest sample gives a strict lower bound
+    pub fn may_collide(&self, s: &Sphere) -> bool {
+        let cell = match self.nearest_cell(s.center) {
+            Some(cell) => cell,
+            None => return true,
+        };
+
+        let lower_bound = self.distances[self.index(cell)].abs() - self.point(cell).distance(s.center);
+
+        // a little slack for rounding error in the samples
+        lower_bound <= s.radius + 0.01
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 * (7 + self.distances.len()));
+
+        for value in self.origin.to_array().into_iter().chain([self.cell_size]) {
+            bytes.write_f32::<LittleEndian>(value).unwrap();
+        }
+
+        for dim in self.dims {
+            bytes.write_u32::<LittleEndian>(dim as u32).unwrap();
+        }
+
+        for distance in &self.distances {
+            bytes.write_f32::<LittleEndian>(*distance).unwrap();
+        }
+
+        bytes
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut bytes = Cursor::new(bytes);
+
+        let origin = Vec3A::new(bytes.read_f32::<LittleEndian>()?, bytes.read_f32::<LittleEndian>()?, bytes.read_f32::<LittleEndian>()?);
+        let cell_size = bytes.read_f32::<LittleEndian>()?;
+        let dims = [bytes.read_u32::<LittleEndian>()? as usize, bytes.read_u32::<LittleEndian>()? as usize, bytes.read_u32::<LittleEndian>()? as usize];
+
+        let len = dims[0] * dims[1] * dims[2];
+        let mut distances = Vec::with_capacity(len);
+        for _ in 0..len {
+            distances.push(bytes.read_f32::<LittleEndian>()?);
+        }
+
+        if bytes.position() != bytes.get_ref().len() as u64 {
+            return Err(io::Error::new(ErrorKind::InvalidData, "Extra data after the distance field"));
+        }
+
+        Ok(Self {
+            origin,
+            cell_size,
+            dims,
+            distances,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "dropshot"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::{load_dropshot, load_soccar};
+
+    #[test]
+    fn soccar_distances() {
+        let game = load_soccar();
+        let field = DistanceField::from(&game.field.collision_mesh, 256., game.ball.location);
+
+        // right above the middle of the floor
+        let distance = field.distance(vec3a(0., 0., 500.)).unwrap();
+        assert!((distance - 500.).abs() < 1., "{distance}");
+        assert!(field.normal(vec3a(0., 0., 500.)).unwrap().distance(Vec3A::Z) < 1e-3);
+
+        // under the floor and outside of the walls are both outside
+        assert!(field.distance(vec3a(0., 0., -200.)).unwrap() < 0.);
+        assert!(field.distance(vec3a(4300., 0., 500.)).unwrap() < 0.);
+        assert!(field.distance(vec3a(0., 0., 1e5)).is_none());
+
+        assert!(!field.may_collide(&Sphere {
+            center: vec3a(0., 0., 1000.),
+            radius: 92.75,
+        }));
+        assert!(field.may_collide(&Sphere {
+            center: vec3a(0., 0., 90.),
+            radius: 92.75,
+        }));
+    }
+
+    #[test]
+    fn dropshot_double_walls() {
+        let game = load_dropshot();
+        let field = DistanceField::from(&game.field.collision_mesh, 256., game.ball.location);
+
+        assert!(field.distance(game.ball.location).unwrap() > 0.);
+        assert!(field.distance(vec3a(0., 0., 2300.)).unwrap() < 0.);
+    }
+
+    #[test]
+    fn round_trip() {
+        let game = load_soccar();
+        let field = DistanceField::from(&game.field.collision_mesh, 512., game.ball.location);
+
+        let bytes = field.to_bytes();
+        let loaded = DistanceField::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.origin, field.origin);
+        assert_eq!(loaded.cell_size, field.cell_size);
+        assert_eq!(loaded.dims, field.dims);
+        assert_eq!(loaded.distances, field.distances);
+
+        assert!(DistanceField::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use glam::Vec3A;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::ball::Ball;
+use super::dropshot::TileState;
+use super::game::{Accuracy, ContactModel, Game, GameMode, Integrator};
+use super::goal::GoalCriteria;
+use super::puck::Puck;
+use super::tuning::Tuning;
+
+// What's saved of a game, which is everything but the field
+// The field is loaded again from the game's mode when it's read back, so only games on the built-in fields can be read back,
+// and only with that field's feature on
+// The broadphase, distance field, materials and plugins aren't saved either, and are back to their defaults
+#[derive(Serialize, Deserialize)]
+struct GameSnapshot {
+    mode: GameMode,
+    gravity: Vec3A,
+    ball: Ball,
+    puck: Puck,
+    accuracy: Accuracy,
+    integrator: Integrator,
+    // missing from snapshots saved before there was a choice
+    #[serde(default)]
+    contact_model: ContactModel,
+    goal_criteria: GoalCriteria,
+    // only in dropshot
+    tiles: Option<Vec<TileState>>,
+    tuning: Tuning,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameSnapshot {
+            mode: self.mode,
+            gravity: self.gravity,
+            ball: self.ball,
+            puck: self.puck,
+            accuracy: self.accuracy,
+            integrator: self.integrator,
+            contact_model: self.contact_model,
+            goal_criteria: self.goal_criteria,
+            tiles: self.dropshot_tiles.as_ref().map(|tiles| tiles.states().to_vec()),
+            tuning: self.field.tuning,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = GameSnapshot::deserialize(deserializer)?;
+        let mut game = crate::try_load_mode(snapshot.mode)
+            .ok_or_else(|| D::Error::custom(format!("the {:?} field can't be loaded, custom fields aren't saved and built-in ones need their feature", snapshot.mode)))?
+            .map_err(D::Error::custom)?;
+
+        game.gravity = snapshot.gravity;
+        game.ball = snapshot.ball;
+        game.puck = snapshot.puck;
+        game.set_accuracy(snapshot.accuracy);
+        game.integrator = snapshot.integrator;
+        game.contact_model = snapshot.contact_model;
+        game.goal_criteria = snapshot.goal_criteria;
+        game.field.tuning = snapshot.tuning;
+
+        if let (Some(tiles), Some(states)) = (&mut game.dropshot_tiles, snapshot.tiles) {
+            if states.len() != tiles.len() {
+                return Err(D::Error::invalid_length(states.len(), &"one state for every dropshot tile"));
+            }
+
+            for (tile, state) in states.into_iter().enumerate() {
+                tiles.set_state(tile, state);
+            }
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "dropshot"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::{load_dropshot, load_soccar};
+
+    fn round_trip(game: &Game) -> Result<Game, serde_json::Error> {
+        serde_json::from_str(&serde_json::to_string(game).unwrap())
+    }
+
+    #[test]
+    fn games_round_trip() {
+        let mut game = load_soccar();
+        game.ball.update(1.5, vec3a(100., 200., 300.), vec3a(-400., 500., 600.), vec3a(1., 2., 3.));
+        game.gravity = vec3a(0., 0., -325.);
+        game.goal_criteria = GoalCriteria::Center;
+
+        let read = round_trip(&game).unwrap();
+        assert_eq!(read.mode, GameMode::Soccar);
+        assert_eq!(read.gravity, game.gravity);
+        assert_eq!(read.goal_criteria, GoalCriteria::Center);
+
+        // it predicts the same as the game it was saved from
+        let expected = Ball::get_ball_prediction_struct(&mut game);
+        assert_eq!(Ball::get_ball_prediction_struct(&mut read.clone()).checksum(), expected.checksum());
+    }
+
+    #[test]
+    fn dropshot_tiles() {
+        let mut game = load_dropshot();
+        game.dropshot_tiles.as_mut().unwrap().break_tile(3);
+
+        let read = round_trip(&game).unwrap();
+        assert_eq!(read.dropshot_tiles.unwrap().state(3), TileState::Broken);
+    }
+
+    #[test]
+    fn custom_fields_dont_load() {
+        let game = Game::default();
+        assert_eq!(game.mode, GameMode::Custom);
+        assert!(round_trip(&game).is_err());
+    }
+}
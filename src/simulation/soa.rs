@@ -0,0 +1,159 @@
+use alloc::vec::Vec;
+
+use glam::Vec3A;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+use super::ball::{Ball, BallPrediction, BounceEvent};
+use super::escape::EscapeEvent;
+use super::game::Game;
+
+// The same data as BallPrediction, but with each field in its own contiguous array
+// Batch math over every slice (like scoring all of them against a target) vectorizes much better this way
+#[derive(Clone, Debug, Default)]
+pub struct BallPredictionSoA {
+    pub num_slices: usize,
+    pub times: Vec<f32>,
+    pub locations: Vec<Vec3A>,
+    pub velocities: Vec<Vec3A>,
+    pub angular_velocities: Vec<Vec3A>,
+    pub flags: Vec<u8>,
+    // the same as the prediction's, the slices stop just before the ball escapes like they do there
+    pub bounces: Vec<BounceEvent>,
+    pub escape: Option<EscapeEvent>,
+}
+
+impl BallPredictionSoA {
+    pub fn with_capacity(num_slices: usize) -> Self {
+        Self {
+            num_slices: 0,
+            times: Vec::with_capacity(num_slices),
+            locations: Vec::with_capacity(num_slices),
+            velocities: Vec::with_capacity(num_slices),
+            angular_velocities: Vec::with_capacity(num_slices),
+            flags: Vec::with_capacity(num_slices),
+            bounces: Vec::new(),
+            escape: None,
+        }
+    }
+
+    pub fn push(&mut self, slice: &Ball) {
+        self.times.push(slice.time);
+        self.locations.push(slice.location);
+        self.velocities.push(slice.velocity);
+        self.angular_velocities.push(slice.angular_velocity);
+        self.flags.push(slice.flags);
+        self.num_slices += 1;
+    }
+
+    // rebuilds a single slice, with the ball's size taken from `template`
+    pub fn slice(&self, index: usize, template: &Ball) -> Option<Ball> {
+        Some(Ball {
+            time: *self.times.get(index)?,
+            location: self.locations[index],
+            velocity: self.velocities[index],
+            angular_velocity: self.angular_velocities[index],
+            flags: self.flags[index],
+            ..*template
+        })
+    }
+}
+
+impl From<&'_ BallPrediction> for BallPredictionSoA {
+    fn from(value: &'_ BallPrediction) -> Self {
+        let mut soa = Self::with_capacity(value.slices.len());
+
+        for slice in &value.slices {
+            soa.push(slice);
+        }
+
+        soa.bounces.clone_from(&value.bounces);
+        soa.escape = value.escape;
+
+        soa
+    }
+}
+
+impl Ball {
+    pub fn get_ball_prediction_soa_for_time(game: &mut Game, time: &f32) -> BallPredictionSoA {
+        Ball::get_ball_prediction_soa_for_slices(game, (time / Ball::SIMULATION_DT).round() as usize)
+    }
+
+    pub fn get_ball_prediction_soa(game: &mut Game) -> BallPredictionSoA {
+        Ball::get_ball_prediction_soa_for_slices(game, Ball::STANDARD_NUM_SLICES)
+    }
+
+    // simulated the same way as the normal prediction, and only rearranged afterwards, so the two can't disagree
+    pub fn get_ball_prediction_soa_for_slices(game: &mut Game, num_slices: usize) -> BallPredictionSoA {
+        BallPredictionSoA::from(&Ball::get_ball_prediction_struct_for_slices(game, num_slices))
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::simulation::mesh::Mesh;
+    use crate::{load_custom_mesh, load_soccar};
+
+    #[test]
+    fn soa_matches_aos() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(-2000., 1000., 800.), vec3a(1500., -500., 300.), vec3a(1., 2., 3.));
+        let mut soa_game = game.clone();
+
+        let aos = Ball::get_ball_prediction_struct(&mut game);
+        let soa = Ball::get_ball_prediction_soa(&mut soa_game);
+
+        assert_eq!(aos.num_slices, soa.num_slices);
+        assert_eq!(soa.times.len(), soa.num_slices);
+        assert_eq!(soa.locations.len(), soa.num_slices);
+        assert_eq!(soa.velocities.len(), soa.num_slices);
+        assert_eq!(soa.angular_velocities.len(), soa.num_slices);
+
+        for (i, slice) in aos.slices.iter().enumerate() {
+            let rebuilt = soa.slice(i, &game.ball).unwrap();
+            assert_eq!(slice.time, rebuilt.time);
+            assert_eq!(slice.location, rebuilt.location);
+            assert_eq!(slice.velocity, rebuilt.velocity);
+            assert_eq!(slice.angular_velocity, rebuilt.angular_velocity);
+            assert_eq!(slice.flags, rebuilt.flags);
+        }
+
+        assert!(soa.slice(soa.num_slices, &game.ball).is_none());
+
+        let converted = BallPredictionSoA::from(&aos);
+        assert_eq!(converted.locations, soa.locations);
+    }
+
+    #[test]
+    fn soa_matches_aos_when_the_ball_escapes() {
+        // a floor with nothing around it, so the ball bounces, rolls off of the edge and falls out of the field
+        let floor = Mesh {
+            ids: vec![0, 1, 2, 0, 2, 3],
+            vertices: vec![-1000., -1000., 0., 1000., -1000., 0., 1000., 1000., 0., -1000., 1000., 0.],
+        };
+
+        let mut game = load_custom_mesh(&floor);
+        game.ball.update(0., vec3a(0., 0., 300.), vec3a(1000., 0., 0.), Vec3A::ZERO);
+        let mut soa_game = game.clone();
+
+        let aos = Ball::get_ball_prediction_struct(&mut game);
+        let soa = Ball::get_ball_prediction_soa(&mut soa_game);
+
+        assert!(aos.escape.is_some());
+        assert!(!aos.bounces.is_empty());
+        assert_eq!(soa.escape.map(|escape| escape.slice), aos.escape.map(|escape| escape.slice));
+        assert_eq!(soa.num_slices, aos.num_slices);
+        assert_eq!(soa.locations.len(), aos.slices.len());
+        assert_eq!(soa.bounces, aos.bounces);
+
+        for (i, slice) in aos.slices.iter().enumerate() {
+            assert_eq!(soa.locations[i], slice.location);
+            assert_eq!(soa.velocities[i], slice.velocity);
+            assert_eq!(soa.flags[i], slice.flags);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use glam::Vec3A;
+use std::io::{self, ErrorKind, Read, Write};
+
+use super::ball::{Ball, BallPrediction};
+use super::game::{Game, GameMode};
+use super::mutators::{DragModel, Mutators};
+use crate::PHYSICS_VERSION;
+
+// A file of many predictions from the same kind of match, so they can be reloaded without simulating them again
+//
+// Everything is little endian:
+//   magic (4 bytes), format version (u16), game mode (u8), gravity (3 f32), ball radius (f32), ball collision radius (f32),
+//   drag model (u8), angular damping (f32), magnus (f32), rolling resistance (f32), tick rate (f32), crate version (u16 length then utf-8), physics version (u32), geometry hash (u64), number of predictions (u32)
+// then for each prediction:
+//   number of slices (u32), then each slice as time, location, velocity and angular velocity (10 f32), then its flags (u8)
+
+pub const MAGIC: [u8; 4] = *b"RLBP";
+pub const FORMAT_VERSION: u16 = 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PredictionHeader {
+    pub mode: GameMode,
+    pub mutators: Mutators,
+    // the version of this crate that made the predictions
+    pub crate_version: String,
+    // `PHYSICS_VERSION` of the crate that made the predictions
+    pub physics_version: u32,
+    // `Game::geometry_hash` of the field the predictions were made in
+    pub geometry_hash: u64,
+    // slices per second
+    pub tick_rate: f32,
+}
+
+impl PredictionHeader {
+    // a header describing predictions made by this version of the crate, with `game`'s settings
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            mode: game.mode,
+            mutators: Mutators::from_game(game),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            physics_version: PHYSICS_VERSION,
+            geometry_hash: game.geometry_hash(),
+            tick_rate: (1. / Ball::SIMULATION_DT).round(),
+        }
+    }
+
+    // if the predictions could've been made by this version of the crate, with `game`'s field and settings
+    // the crate version itself is allowed to differ, as long as the physics didn't change
+    pub fn is_compatible_with(&self, game: &Game) -> bool {
+        let current = PredictionHeader::from_game(game);
+
+        self.physics_version == current.physics_version && self.geometry_hash == current.geometry_hash && self.mode == current.mode && self.mutators == current.mutators && self.tick_rate == current.tick_rate
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+pub fn write_predictions<W: Write>(writer: &mut W, header: &PredictionHeader, predictions: &[BallPrediction]) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_u16::<LittleEndian>(FORMAT_VERSION)?;
+    writer.write_u8(header.mode as u8)?;
+
+    for value in header.mutators.gravity.to_array().into_iter().chain([header.mutators.ball_radius, header.mutators.ball_collision_radius]) {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+
+    writer.write_u8(header.mutators.drag_model as u8)?;
+    writer.write_f32::<LittleEndian>(header.mutators.angular_damping)?;
+    writer.write_f32::<LittleEndian>(header.mutators.magnus)?;
+    writer.write_f32::<LittleEndian>(header.mutators.rolling_resistance)?;
+    writer.write_f32::<LittleEndian>(header.tick_rate)?;
+
+    let crate_version = u16::try_from(header.crate_version.len()).map_err(|_| invalid_data("Crate version is too long"))?;
+    writer.write_u16::<LittleEndian>(crate_version)?;
+    writer.write_all(header.crate_version.as_bytes())?;
+    writer.write_u32::<LittleEndian>(header.physics_version)?;
+    writer.write_u64::<LittleEndian>(header.geometry_hash)?;
+
+    writer.write_u32::<LittleEndian>(predictions.len() as u32)?;
+
+    for prediction in predictions {
+        writer.write_u32::<LittleEndian>(prediction.slices.len() as u32)?;
+
+        for slice in &prediction.slices {
+            writer.write_f32::<LittleEndian>(slice.time)?;
+
+            for vector in [slice.location, slice.velocity, slice.angular_velocity] {
+                for value in vector.to_array() {
+                    writer.write_f32::<LittleEndian>(value)?;
+                }
+            }
+
+            writer.write_u8(slice.flags)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_vec3a<R: Read>(reader: &mut R) -> io::Result<Vec3A> {
+    Ok(Vec3A::new(reader.read_f32::<LittleEndian>()?, reader.read_f32::<LittleEndian>()?, reader.read_f32::<LittleEndian>()?))
+}
+
+pub fn read_predictions<R: Read>(reader: &mut R) -> io::Result<(PredictionHeader, Vec<BallPrediction>)> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid_data("Not a prediction file"));
+    }
+
+    let version = reader.read_u16::<LittleEndian>()?;
+    if version != FORMAT_VERSION {
+        return Err(invalid_data(&format!("Unsupported prediction format version {version}, expected {FORMAT_VERSION}")));
+    }
+
+    let mode = GameMode::try_from(reader.read_u8()?).map_err(|mode| invalid_data(&format!("Unknown game mode {mode}")))?;
+
+    let mutators = Mutators {
+        gravity: read_vec3a(reader)?,
+        ball_radius: reader.read_f32::<LittleEndian>()?,
+        ball_collision_radius: reader.read_f32::<LittleEndian>()?,
+        drag_model: DragModel::try_from(reader.read_u8()?).map_err(|drag_model| invalid_data(&format!("Unknown drag model {drag_model}")))?,
+        angular_damping: reader.read_f32::<LittleEndian>()?,
+        magnus: reader.read_f32::<LittleEndian>()?,
+        rolling_resistance: reader.read_f32::<LittleEndian>()?,
+    };
+    let tick_rate = reader.read_f32::<LittleEndian>()?;
+
+    let mut crate_version = vec![0; reader.read_u16::<LittleEndian>()? as usize];
+    reader.read_exact(&mut crate_version)?;
+    let crate_version = String::from_utf8(crate_version).map_err(|_| invalid_data("Crate version isn't valid utf-8"))?;
+    let physics_version = reader.read_u32::<LittleEndian>()?;
+    let geometry_hash = reader.read_u64::<LittleEndian>()?;
+
+    let header = PredictionHeader {
+        mode,
+        mutators,
+        crate_version,
+        physics_version,
+        geometry_hash,
+        tick_rate,
+    };
+
+    let mut template = Ball::default();
+    mutators.apply_to_ball(&mut template);
+
+    let num_predictions = reader.read_u32::<LittleEndian>()? as usize;
+    let mut predictions = Vec::new();
+
+    for _ in 0..num_predictions {
+        let num_slices = reader.read_u32::<LittleEndian>()? as usize;
+        let mut slices = Vec::new();
+
+        for _ in 0..num_slices {
+            let time = reader.read_f32::<LittleEndian>()?;
+
+            slices.push(Ball {
+                time,
+                location: read_vec3a(reader)?,
+                velocity: read_vec3a(reader)?,
+                angular_velocity: read_vec3a(reader)?,
+                flags: reader.read_u8()?,
+                ..template
+            });
+        }
+
+        // only the slices are stored, not the bounces
+        predictions.push(BallPrediction {
+            num_slices,
+            slices,
+            bounces: Vec::new(),
+            escape: None,
+        });
+    }
+
+    Ok((header, predictions))
+}
+
+#[cfg(all(test, feature = "soccar", feature = "hoops"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::{load_hoops, load_soccar};
+
+    #[test]
+    fn round_trip() {
+        let mut game = load_hoops();
+
+        let predictions: Vec<BallPrediction> = (0..3)
+            .map(|i| {
+                game.ball.update(0., vec3a(0., 0., 500.), vec3a(500. * i as f32, 1000., 0.), vec3a(0., 0., 1.));
+                Ball::get_ball_prediction_struct_for_slices(&mut game, 100 + i)
+            })
+            .collect();
+
+        let header = PredictionHeader::from_game(&game);
+        assert_eq!(header.mode, GameMode::Hoops);
+        assert_eq!(header.tick_rate, 120.);
+        assert_eq!(header.physics_version, PHYSICS_VERSION);
+        assert!(header.is_compatible_with(&game));
+        assert!(!header.is_compatible_with(&load_soccar()));
+
+        let mut bytes = Vec::new();
+        write_predictions(&mut bytes, &header, &predictions).unwrap();
+
+        let (loaded_header, loaded) = read_predictions(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded_header, header);
+        assert_eq!(loaded.len(), predictions.len());
+
+        for (prediction, loaded) in predictions.iter().zip(&loaded) {
+            assert_eq!(prediction.num_slices, loaded.num_slices);
+
+            for (slice, loaded) in prediction.slices.iter().zip(&loaded.slices) {
+                assert_eq!(slice.time, loaded.time);
+                assert_eq!(slice.location, loaded.location);
+                assert_eq!(slice.velocity, loaded.velocity);
+                assert_eq!(slice.angular_velocity, loaded.angular_velocity);
+                assert_eq!(slice.radius, loaded.radius);
+                assert_eq!(slice.moi, loaded.moi);
+            }
+        }
+
+        // truncated files and other versions are rejected
+        assert!(read_predictions(&mut &bytes[..bytes.len() - 1]).is_err());
+
+        bytes[4] = 1;
+        assert!(read_predictions(&mut bytes.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use super::ball::{Ball, BallPrediction};
+use super::game::Game;
+
+// How far the producer can get ahead of the consumer by default, in slices
+pub const DEFAULT_STREAM_CAPACITY: usize = Ball::STANDARD_NUM_SLICES;
+
+// A prediction with no time limit
+// A background thread keeps simulating the ball, blocking whenever `capacity` slices are waiting to be read
+// The slices are the same as a prediction's, flags included, and the stream ends at the last one before the ball escapes, if it does
+// Dropping the stream stops the thread
+pub struct PredictionStream {
+    receiver: Option<Receiver<Ball>>,
+    producer: Option<JoinHandle<()>>,
+    // counted by the producer once each slice is in the channel
+    produced: Arc<AtomicUsize>,
+    received: usize,
+}
+
+impl PredictionStream {
+    // the next slice if it's already been simulated, without waiting for it
+    pub fn try_next(&mut self) -> Option<Ball> {
+        let ball = self.receiver.as_ref()?.try_recv().ok()?;
+        self.received += 1;
+        Some(ball)
+    }
+
+    // how many slices have been simulated but not read yet, which is never more than the capacity
+    // a slice can be read before the producer gets around to counting it, so this can be briefly behind, but never ahead
+    pub fn buffered(&self) -> usize {
+        self.produced.load(Ordering::Acquire).saturating_sub(self.received)
+    }
+}
+
+impl Iterator for PredictionStream {
+    type Item = Ball;
+
+    // blocks until the next slice is ready
+    fn next(&mut self) -> Option<Ball> {
+        let ball = self.receiver.as_ref()?.recv().ok()?;
+        self.received += 1;
+        Some(ball)
+    }
+}
+
+impl Drop for PredictionStream {
+    fn drop(&mut self) {
+        // closing the channel makes the producer's next send fail, which ends its loop
+        drop(self.receiver.take());
+
+        if let Some(producer) = self.producer.take() {
+            let _ = producer.join();
+        }
+    }
+}
+
+impl Ball {
+    pub fn stream_ball_prediction(game: Game) -> PredictionStream {
+        Ball::stream_ball_prediction_with_capacity(game, DEFAULT_STREAM_CAPACITY)
+    }
+
+    pub fn stream_ball_prediction_with_capacity(game: Game, capacity: usize) -> PredictionStream {
+        let (sender, receiver) = sync_channel(capacity);
+        let produced = Arc::new(AtomicUsize::new(0));
+
+        let producer = {
+            let produced = produced.clone();
+
+            thread::spawn(move || {
+                let arena = game.arena();
+                let mut ball = game.ball;
+                let mut prediction = BallPrediction::default();
+
+                // a slice at a time, which is the same as one long prediction since each one starts from what the last one ended on
+                loop {
+                    Ball::predict_in(&arena, &mut ball, Ball::SIMULATION_DT, 1, 1, |_| false, &mut prediction);
+
+                    let Some(&slice) = prediction.slices.first() else {
+                        break;
+                    };
+
+                    if sender.send(slice).is_err() {
+                        break;
+                    }
+
+                    produced.fetch_add(1, Ordering::Release);
+                }
+            })
+        };
+
+        PredictionStream {
+            receiver: Some(receiver),
+            producer: Some(producer),
+            produced,
+            received: 0,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::simulation::mesh::Mesh;
+    use crate::{load_custom_mesh, load_soccar};
+
+    #[test]
+    fn stream_matches_prediction() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(-2000., 1000., 800.), vec3a(1500., -500., 300.), vec3a(1., 2., 3.));
+
+        let stream = Ball::stream_ball_prediction_with_capacity(game.clone(), 16);
+
+        // go well past the standard horizon
+        let num_slices = Ball::STANDARD_NUM_SLICES * 3;
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, num_slices);
+
+        let mut streamed = 0;
+        for (slice, expected) in stream.zip(&prediction.slices) {
+            assert_eq!(slice.time, expected.time);
+            assert_eq!(slice.location, expected.location);
+            assert_eq!(slice.velocity, expected.velocity);
+            assert_eq!(slice.flags, expected.flags);
+            streamed += 1;
+        }
+
+        assert_eq!(streamed, num_slices);
+    }
+
+    #[test]
+    fn bounded_producer() {
+        let mut stream = Ball::stream_ball_prediction_with_capacity(load_soccar(), 4);
+
+        let first = stream.next().unwrap();
+        assert_eq!(first.time, Ball::SIMULATION_DT);
+
+        // the producer fills the channel up, but can't get more than the capacity ahead of us, no matter how it's read
+        while stream.buffered() < 4 {
+            thread::yield_now();
+        }
+
+        for i in 0..1000 {
+            assert!(stream.buffered() <= 4);
+
+            if i % 2 == 0 {
+                stream.next().unwrap();
+            } else {
+                stream.try_next();
+            }
+        }
+
+        // dropping the stream joins the producer instead of leaving it blocked forever
+        drop(stream);
+    }
+
+    #[test]
+    fn stream_ends_when_the_ball_escapes() {
+        // a floor with nothing around it, so the ball rolls off of the edge and falls out of the field
+        let floor = Mesh {
+            ids: vec![0, 1, 2, 0, 2, 3],
+            vertices: vec![-1000., -1000., 0., 1000., -1000., 0., 1000., 1000., 0., -1000., 1000., 0.],
+        };
+
+        let mut game = load_custom_mesh(&floor);
+        game.ball.update(0., vec3a(0., 0., 200.), vec3a(1000., 0., 0.), Vec3A::ZERO);
+
+        let streamed: Vec<Ball> = Ball::stream_ball_prediction_with_capacity(game.clone(), 16).collect();
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, Ball::STANDARD_NUM_SLICES * 3);
+
+        assert!(prediction.escape.is_some());
+        assert_eq!(streamed.len(), prediction.slices.len());
+
+        for (slice, expected) in streamed.iter().zip(&prediction.slices) {
+            assert_eq!(slice.location, expected.location);
+            assert_eq!(slice.flags, expected.flags);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use core::fmt;
+
+use super::ball::BallPrediction;
+use super::game::Game;
+use super::goal::GoalEvent;
+use super::physics::BallState;
+
+// The gist of a prediction, small enough to log on one line
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PredictionSummary {
+    pub num_slices: usize,
+    // the time and state of the last slice, which are all 0 for an empty prediction
+    pub end_time: f32,
+    pub end: BallState,
+    // how many times the ball bounced off of the field, see `BallPrediction::bounces`
+    pub bounces: usize,
+    pub max_speed: f32,
+    pub min_z: f32,
+    pub max_z: f32,
+    pub first_goal: Option<GoalEvent>,
+    // when the ball got out of the field, see `EscapeEvent`
+    pub escape_time: Option<f32>,
+}
+
+impl BallPrediction {
+    // needs the game that made the prediction, for its field and goals
+    pub fn summary(&self, game: &Game) -> PredictionSummary {
+        let escape_time = self.escape.map(|escape| escape.time());
+
+        let Some(last) = self.slices.last() else {
+            return PredictionSummary {
+                escape_time,
+                ..PredictionSummary::default()
+            };
+        };
+
+        let (min_z, max_z) = self.slices.iter().fold((f32::MAX, f32::MIN), |(min_z, max_z), slice| (min_z.min(slice.location.z), max_z.max(slice.location.z)));
+
+        PredictionSummary {
+            num_slices: self.slices.len(),
+            end_time: last.time,
+            end: BallState::from_ball(last),
+            bounces: self.bounces.len(),
+            max_speed: self.slices.iter().map(|slice| slice.velocity.length()).fold(0., f32::max),
+            min_z,
+            max_z,
+            first_goal: self.first_goal(game),
+            escape_time,
+        }
+    }
+}
+
+impl fmt::Display for PredictionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.num_slices == 0 {
+            write!(f, "empty prediction")?;
+        } else {
+            let [x, y, z] = self.end.location.to_array();
+            let [vx, vy, vz] = self.end.velocity.to_array();
+            write!(f, "{} slices to {:.2}s, ends at ({x:.0}, {y:.0}, {z:.0}) moving ({vx:.0}, {vy:.0}, {vz:.0}), {} bounces, max speed {:.0}, z {:.0} to {:.0}", self.num_slices, self.end_time, self.bounces, self.max_speed, self.min_z, self.max_z)?;
+
+            match self.first_goal {
+                Some(goal) => write!(f, ", goal {} at {:.2}s", goal.goal, goal.time)?,
+                None => write!(f, ", no goal")?,
+            }
+        }
+
+        match self.escape_time {
+            Some(time) => write!(f, ", escaped the arena at {time:.2}s"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::{vec3a, Vec3A};
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn bouncing_in_midfield() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 1000.), vec3a(300., 0., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let summary = prediction.summary(&game);
+
+        assert_eq!(summary.num_slices, 720);
+        assert_eq!(summary.end, BallState::from_ball(prediction.slices.last().unwrap()));
+        assert_eq!(summary.bounces, prediction.bounces.len());
+        assert!(summary.bounces > 2);
+        assert!(summary.first_goal.is_none());
+        assert!(summary.max_z <= 1000. && summary.max_z > 990.);
+        assert!((summary.min_z - game.ball.collision_radius).abs() < 5., "{}", summary.min_z);
+
+        let line = summary.to_string();
+        assert!(line.starts_with("720 slices to 6.00s, ends at ("), "{line}");
+        assert!(line.ends_with("no goal") && !line.contains('\n'), "{line}");
+    }
+
+    #[test]
+    fn shot_on_goal() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 3000., 300.), vec3a(0., 2000., 0.), Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        let summary = prediction.summary(&game);
+
+        assert_eq!(summary.first_goal, prediction.first_goal(&game));
+        assert_eq!(summary.max_speed, prediction.slices.iter().map(|slice| slice.velocity.length()).fold(0., f32::max));
+        assert!(summary.max_speed > 1990.);
+        assert!(summary.to_string().contains(", goal 1 at "));
+    }
+
+    #[test]
+    fn empty() {
+        let summary = BallPrediction::default().summary(&load_soccar());
+
+        assert_eq!(summary, PredictionSummary::default());
+        assert_eq!(summary.to_string(), "empty prediction");
+    }
+}
@@ -0,0 +1,246 @@
+#[cfg(feature = "arrow")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, Float32Array, RecordBatch, UInt32Array};
+#[cfg(feature = "arrow")]
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+#[cfg(feature = "ndarray")]
+use ndarray::Array2;
+#[cfg(feature = "parquet")]
+use parquet::{arrow::ArrowWriter, errors::ParquetError};
+
+use super::ball::BallPrediction;
+
+// Many predictions flattened into one table, with a column per value
+// This is the layout data frame libraries (polars, pandas) expect, and `write_csv` can be loaded by both directly
+#[derive(Clone, Debug, Default)]
+pub struct PredictionTable {
+    pub rollout: Vec<u32>,
+    pub t: Vec<f32>,
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub z: Vec<f32>,
+    pub vx: Vec<f32>,
+    pub vy: Vec<f32>,
+    pub vz: Vec<f32>,
+    pub wx: Vec<f32>,
+    pub wy: Vec<f32>,
+    pub wz: Vec<f32>,
+}
+
+impl PredictionTable {
+    pub const COLUMNS: [&'static str; 11] = ["rollout", "t", "x", "y", "z", "vx", "vy", "vz", "wx", "wy", "wz"];
+
+    // each prediction's rollout id is its index in `batch`
+    pub fn from_batch(batch: &[BallPrediction]) -> Self {
+        let mut table = Self::default();
+
+        for (rollout, prediction) in batch.iter().enumerate() {
+            for slice in &prediction.slices {
+                table.rollout.push(rollout as u32);
+                table.t.push(slice.time);
+
+                for (column, value) in [&mut table.x, &mut table.y, &mut table.z].into_iter().zip(slice.location.to_array()) {
+                    column.push(value);
+                }
+
+                for (column, value) in [&mut table.vx, &mut table.vy, &mut table.vz].into_iter().zip(slice.velocity.to_array()) {
+                    column.push(value);
+                }
+
+                for (column, value) in [&mut table.wx, &mut table.wy, &mut table.wz].into_iter().zip(slice.angular_velocity.to_array()) {
+                    column.push(value);
+                }
+            }
+        }
+
+        table
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rollout.len()
+    }
+
+    #[cfg(feature = "std")]
+    fn float_columns(&self) -> [&[f32]; 10] {
+        [&self.t, &self.x, &self.y, &self.z, &self.vx, &self.vy, &self.vz, &self.wx, &self.wy, &self.wz]
+    }
+
+    #[cfg(feature = "std")]
+    // a header row with the column names, then one row per slice
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "{}", Self::COLUMNS.join(","))?;
+
+        let columns = self.float_columns();
+
+        for row in 0..self.num_rows() {
+            write!(writer, "{}", self.rollout[row])?;
+
+            for column in columns {
+                // the shortest representation that reads back to the same f32
+                write!(writer, ",{}", column[row])?;
+            }
+
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    // the rollout ids are u32, everything else is f32, and nothing is nullable
+    #[cfg(feature = "arrow")]
+    pub fn schema() -> Schema {
+        let fields: Vec<Field> = Self::COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Field::new(*name, if i == 0 { DataType::UInt32 } else { DataType::Float32 }, false))
+            .collect();
+
+        Schema::new(fields)
+    }
+
+    // fails if the columns were changed to have different lengths
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(UInt32Array::from(self.rollout.clone()))];
+        columns.extend(self.float_columns().map(|column| Arc::new(Float32Array::from(column.to_vec())) as ArrayRef));
+
+        RecordBatch::try_new(Arc::new(Self::schema()), columns)
+    }
+
+    // the whole table as a single row group
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<(), ParquetError> {
+        let batch = self.to_record_batch()?;
+
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        Ok(())
+    }
+}
+
+impl BallPrediction {
+    const ROW_LEN: usize = 10;
+
+    // every slice as a row of t, x, y, z, vx, vy, vz, wx, wy, wz, in one row major buffer
+    // this is the memory layout of a C-ordered (num_slices, 10) numpy array or ndarray `Array2`, so it can be handed over without copying
+    pub fn to_row_major(&self) -> (Vec<f32>, [usize; 2]) {
+        let mut data = Vec::with_capacity(self.slices.len() * BallPrediction::ROW_LEN);
+
+        for slice in &self.slices {
+            data.push(slice.time);
+            data.extend(slice.location.to_array());
+            data.extend(slice.velocity.to_array());
+            data.extend(slice.angular_velocity.to_array());
+        }
+
+        (data, [self.slices.len(), BallPrediction::ROW_LEN])
+    }
+
+    // the same rows as `to_row_major`, which ndarray takes over as is
+    #[cfg(feature = "ndarray")]
+    pub fn to_array2(&self) -> Array2<f32> {
+        let (data, shape) = self.to_row_major();
+        Array2::from_shape_vec(shape, data).expect("the shape always matches the data")
+    }
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use glam::vec3a;
+
+    use super::*;
+    use crate::load_soccar;
+    use crate::simulation::ball::Ball;
+
+    #[test]
+    fn batch_table() {
+        let mut game = load_soccar();
+
+        let batch: Vec<BallPrediction> = (0..2)
+            .map(|i| {
+                game.ball.update(0., vec3a(0., 0., 300.), vec3a(100. * i as f32, 0., 0.), vec3a(0., 0., 0.));
+                Ball::get_ball_prediction_struct_for_slices(&mut game, 10)
+            })
+            .collect();
+
+        let table = PredictionTable::from_batch(&batch);
+        assert_eq!(table.num_rows(), 20);
+        assert_eq!(table.rollout[9], 0);
+        assert_eq!(table.rollout[10], 1);
+        assert_eq!(table.vx[15], batch[1].slices[5].velocity.x);
+
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next().unwrap(), "rollout,t,x,y,z,vx,vy,vz,wx,wy,wz");
+
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 20);
+        assert!(rows.iter().all(|row| row.len() == PredictionTable::COLUMNS.len()));
+        assert_eq!(rows[13][0], "1");
+        assert_eq!(rows[13][4].parse::<f32>().unwrap(), table.z[13]);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn record_batch_and_parquet() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 300.), vec3a(100., 200., 0.), vec3a(1., 0., 0.));
+        let table = PredictionTable::from_batch(&[Ball::get_ball_prediction_struct_for_slices(&mut game, 30)]);
+
+        let batch = table.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 30);
+        assert_eq!(batch.num_columns(), PredictionTable::COLUMNS.len());
+
+        let path = std::env::temp_dir().join("rl_ball_sym_table.parquet");
+        table.write_parquet(std::fs::File::create(&path).unwrap()).unwrap();
+
+        let read: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap().build().unwrap().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, vec![batch]);
+
+        // mismatched columns are an error, not a panic
+        let mut broken = table;
+        broken.x.pop();
+        assert!(broken.to_record_batch().is_err());
+    }
+
+    #[test]
+    fn row_major() {
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 300.), vec3a(100., 200., 0.), vec3a(1., 0., 0.));
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 30);
+
+        let (data, shape) = prediction.to_row_major();
+        assert_eq!(shape, [30, 10]);
+        assert_eq!(data.len(), shape[0] * shape[1]);
+
+        // rows line up with the table's columns, minus the rollout id
+        let table = PredictionTable::from_batch(std::slice::from_ref(&prediction));
+        for (row, values) in data.chunks_exact(shape[1]).enumerate() {
+            let expected: Vec<f32> = table.float_columns().iter().map(|column| column[row]).collect();
+            assert_eq!(values, expected.as_slice());
+        }
+
+        #[cfg(feature = "ndarray")]
+        {
+            let array = prediction.to_array2();
+            assert_eq!(array.dim(), (30, 10));
+            assert_eq!(array[[12, 0]], prediction.slices[12].time);
+            assert_eq!(array[[12, 3]], prediction.slices[12].location.z);
+            assert_eq!(array[[29, 7]], prediction.slices[29].angular_velocity.x);
+        }
+    }
+}
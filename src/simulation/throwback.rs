@@ -0,0 +1,104 @@
+use glam::Vec3A;
+
+use super::bvh::Bvh;
+use super::heuristics::{cast, scan, FieldHeuristics, EDGE_PRECISION, PROBE_OFFSET};
+
+// Where Throwback Stadium differs from soccar, measured from its meshes
+// Its goals stand out in the field instead of being cut into the back walls, with more floor behind them,
+// and it has a second set of ramps (the upper deck) between the walls and the ceiling
+// Everything is a distance from the center of the field, and the same for both halves since the field is mirrored
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThrowbackLayout {
+    // along y to the goal lines, and how far the goals go back from there
+    pub goal_line: f32,
+    pub goal_depth: f32,
+    // how much floor there is between the back of a goal and the bottom of the back wall's ramp
+    pub behind_goal: f32,
+    // where the floor starts curving up into the side walls (along x) and the back walls (along y, beside the goals)
+    pub side_ramp_start: f32,
+    pub back_ramp_start: f32,
+    // the upper deck, the heights where the side walls and the back walls start curving into the ceiling
+    pub side_upper_ramp_height: f32,
+    pub back_upper_ramp_height: f32,
+    // where the ceiling is flat again, along x and along y
+    pub ceiling_half_width: f32,
+    pub ceiling_half_length: f32,
+}
+
+impl ThrowbackLayout {
+    // None if the field doesn't have goals with a back to them, or isn't closed around the center
+    pub fn measure(mesh: &Bvh, heuristics: &FieldHeuristics) -> Option<Self> {
+        let mouth = heuristics.goal_mouths.last()?;
+        let goal_line = mouth.min.y.abs().min(mouth.max.y.abs());
+        let goal_back = mouth.min.y.abs().max(mouth.max.y.abs());
+
+        let floor = heuristics.floor_height;
+        let ceiling = heuristics.ceiling_height;
+        let middle = (floor + ceiling) / 2.;
+
+        // beside the goal, and far enough from the corners that it's the back wall's ramp
+        let beside_goal = (mouth.max.x + heuristics.side_wall_distance - heuristics.corner_radius) / 2.;
+
+        let floor_at = |x: f32, y: f32| cast(mesh, Vec3A::new(x, y, middle), -Vec3A::Z).map(|(distance, _)| middle - distance);
+        let ceiling_at = |x: f32, y: f32| cast(mesh, Vec3A::new(x, y, middle), Vec3A::Z).map(|(distance, _)| middle + distance);
+        let is_at = |height: Option<f32>, expected: f32| height.is_some_and(|height| (height - expected).abs() < EDGE_PRECISION);
+
+        let side_ramp_start = scan(0., heuristics.side_wall_distance, |x| is_at(floor_at(x, PROBE_OFFSET), floor));
+        let back_ramp_start = scan(0., heuristics.back_wall_distance, |y| is_at(floor_at(beside_goal, y), floor));
+
+        let ceiling_half_width = scan(0., heuristics.side_wall_distance, |x| is_at(ceiling_at(x, PROBE_OFFSET), ceiling));
+        let ceiling_half_length = scan(0., heuristics.back_wall_distance, |y| is_at(ceiling_at(beside_goal, y), ceiling));
+
+        // the walls are flat from halfway up until they curve into the ceiling
+        let wall_at = |start: Vec3A, direction: Vec3A| cast(mesh, start, direction).map(|(distance, _)| distance);
+        let side_wall = wall_at(Vec3A::new(PROBE_OFFSET, PROBE_OFFSET, middle), Vec3A::X)?;
+        let back_wall = wall_at(Vec3A::new(beside_goal, PROBE_OFFSET, middle), Vec3A::Y)?;
+        let side_upper_ramp_height = scan(middle, ceiling, |z| is_at(wall_at(Vec3A::new(PROBE_OFFSET, PROBE_OFFSET, z), Vec3A::X), side_wall));
+        let back_upper_ramp_height = scan(middle, ceiling, |z| is_at(wall_at(Vec3A::new(beside_goal, PROBE_OFFSET, z), Vec3A::Y), back_wall));
+
+        Some(Self {
+            goal_line,
+            goal_depth: goal_back - goal_line,
+            behind_goal: back_ramp_start - goal_back,
+            side_ramp_start,
+            back_ramp_start,
+            side_upper_ramp_height,
+            back_upper_ramp_height,
+            ceiling_half_width,
+            ceiling_half_length,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "soccar", feature = "throwback"))]
+mod test {
+    use crate::simulation::game::GameMode;
+    use crate::{load_soccar, load_soccar_throwback};
+
+    #[test]
+    fn throwback() {
+        let game = load_soccar_throwback();
+        let layout = game.field.throwback.unwrap();
+
+        // the goals are in front of the back walls, which soccar's aren't
+        let goal = game.field.goals[1];
+        assert!((layout.goal_line - goal.location.y).abs() < 20., "{layout:?}");
+        assert!(layout.goal_depth > 700. && layout.goal_depth < 800., "{layout:?}");
+        assert!(layout.behind_goal > 900., "{layout:?}");
+
+        assert!((layout.side_ramp_start - 3840.).abs() < 10., "{layout:?}");
+        assert!((layout.back_ramp_start - 6660.).abs() < 10., "{layout:?}");
+
+        assert!(layout.side_upper_ramp_height > 1500. && layout.side_upper_ramp_height < 1560., "{layout:?}");
+        assert!(layout.back_upper_ramp_height > 1500. && layout.back_upper_ramp_height < 1570., "{layout:?}");
+        assert!(layout.ceiling_half_width < 3800. && layout.ceiling_half_width > 3000., "{layout:?}");
+        assert!(layout.ceiling_half_length < 6600. && layout.ceiling_half_length > 5800., "{layout:?}");
+    }
+
+    #[test]
+    fn only_throwback() {
+        let game = load_soccar();
+        assert_eq!(game.mode, GameMode::Soccar);
+        assert!(game.field.throwback.is_none());
+    }
+}
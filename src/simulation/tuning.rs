@@ -0,0 +1,139 @@
+use super::ball::Ball;
+
+// The physics constants for the ball's bounces and drag, which are always the game's values
+// With the tuning feature, they can be changed to calibrate the simulation against recorded games, or overridden with environment variables
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuning {
+    pub(crate) restitution: f32,
+    pub(crate) friction: f32,
+    pub(crate) drag: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            restitution: Ball::RESTITUTION,
+            friction: Ball::MU,
+            drag: Ball::DRAG,
+        }
+    }
+}
+
+impl Tuning {
+    #[inline]
+    pub const fn restitution(&self) -> f32 {
+        self.restitution
+    }
+
+    #[inline]
+    pub const fn friction(&self) -> f32 {
+        self.friction
+    }
+
+    #[inline]
+    pub const fn drag(&self) -> f32 {
+        self.drag
+    }
+}
+
+#[cfg(feature = "tuning")]
+impl Tuning {
+    pub const RESTITUTION_VAR: &'static str = "RL_BALL_SYM_RESTITUTION";
+    pub const FRICTION_VAR: &'static str = "RL_BALL_SYM_FRICTION";
+    pub const DRAG_VAR: &'static str = "RL_BALL_SYM_DRAG";
+
+    pub fn set_restitution(&mut self, restitution: f32) {
+        self.restitution = restitution;
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        self.friction = friction;
+    }
+
+    pub fn set_drag(&mut self, drag: f32) {
+        self.drag = drag;
+    }
+
+    // the default constants, with any of them overridden by the environment variables that are set
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        // the variable's value, if it's set, which has to be a number that passes `valid`
+        let parse = |name: &str, valid: fn(f32) -> bool, expected: &str| -> Result<Option<f32>, String> {
+            let Some(text) = var(name) else {
+                return Ok(None);
+            };
+
+            match text.trim().parse::<f32>() {
+                Ok(number) if number.is_finite() && valid(number) => Ok(Some(number)),
+                _ => Err(format!("{name} must be a number {expected}, got \"{text}\"")),
+            }
+        };
+
+        let mut tuning = Self::default();
+
+        if let Some(restitution) = parse(Self::RESTITUTION_VAR, |x| (0. ..=1.).contains(&x), "between 0 and 1")? {
+            tuning.restitution = restitution;
+        }
+
+        if let Some(friction) = parse(Self::FRICTION_VAR, |x| x >= 0., "at least 0")? {
+            tuning.friction = friction;
+        }
+
+        if let Some(drag) = parse(Self::DRAG_VAR, |x| x <= 0., "at most 0")? {
+            tuning.drag = drag;
+        }
+
+        Ok(tuning)
+    }
+}
+
+#[cfg(all(test, feature = "tuning"))]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<Tuning, String> {
+        let vars: HashMap<_, _> = vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        Tuning::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn overrides() {
+        assert_eq!(from_vars(&[]).unwrap(), Tuning::default());
+
+        let tuning = from_vars(&[(Tuning::RESTITUTION_VAR, "0.5"), (Tuning::DRAG_VAR, " -0.04 ")]).unwrap();
+        assert_eq!(tuning.restitution, 0.5);
+        assert_eq!(tuning.friction, Ball::MU);
+        assert_eq!(tuning.drag, -0.04);
+
+        assert_eq!(from_vars(&[(Tuning::RESTITUTION_VAR, "1.5")]).unwrap_err(), "RL_BALL_SYM_RESTITUTION must be a number between 0 and 1, got \"1.5\"");
+        assert_eq!(from_vars(&[(Tuning::FRICTION_VAR, "lots")]).unwrap_err(), "RL_BALL_SYM_FRICTION must be a number at least 0, got \"lots\"");
+        assert!(from_vars(&[(Tuning::DRAG_VAR, "0.1")]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "soccar")]
+    fn tuned_bounce() {
+        use glam::vec3a;
+
+        use crate::load_soccar;
+        use crate::simulation::game::Game;
+
+        let mut game = load_soccar();
+        game.ball.update(0., vec3a(0., 0., 500.), vec3a(0., 0., -1000.), vec3a(0., 0., 0.));
+
+        let mut dead_game = game.clone();
+        dead_game.field.tuning.set_restitution(0.);
+
+        let bounce = |game: &mut Game| Ball::get_ball_prediction_struct_for_slices(game, 60).slices.iter().map(|slice| slice.velocity.z).fold(f32::MIN, f32::max);
+
+        // a ball with no restitution doesn't bounce back up
+        assert!(bounce(&mut game) > 500.);
+        assert!(bounce(&mut dead_game) < 10.);
+    }
+}
@@ -0,0 +1,121 @@
+// Bindings for running predictions in the browser, through wasm-bindgen
+//
+//     const game = WasmGame.loadSoccar();
+//     game.setBall(0, [0, 0, 1000], [500, 0, 0], [0, 0, 0]);
+//     const slices = game.getBallPrediction(720);
+//
+// Predictions come back as a single Float32Array, with `SLICE_LEN` numbers for each slice:
+// the time, then the location, velocity and angular velocity
+use glam::Vec3A;
+use wasm_bindgen::prelude::*;
+
+use crate::simulation::ball::Ball;
+use crate::simulation::game::Game;
+#[cfg(feature = "dropshot")]
+use crate::load_dropshot;
+#[cfg(feature = "hoops")]
+use crate::load_hoops;
+#[cfg(feature = "soccar")]
+use crate::load_soccar;
+#[cfg(feature = "throwback")]
+use crate::load_soccar_throwback;
+
+pub const SLICE_LEN: usize = 10;
+
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: Game,
+}
+
+fn vec3(values: &[f32]) -> Result<Vec3A, JsError> {
+    match values {
+        [x, y, z] => Ok(Vec3A::new(*x, *y, *z)),
+        _ => Err(JsError::new(&format!("Expected 3 numbers, got {}", values.len()))),
+    }
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    #[cfg(feature = "soccar")]
+    #[wasm_bindgen(js_name = loadSoccar)]
+    pub fn load_soccar() -> WasmGame {
+        WasmGame {
+            game: load_soccar(),
+        }
+    }
+
+    #[cfg(feature = "hoops")]
+    #[wasm_bindgen(js_name = loadHoops)]
+    pub fn load_hoops() -> WasmGame {
+        WasmGame {
+            game: load_hoops(),
+        }
+    }
+
+    #[cfg(feature = "dropshot")]
+    #[wasm_bindgen(js_name = loadDropshot)]
+    pub fn load_dropshot() -> WasmGame {
+        WasmGame {
+            game: load_dropshot(),
+        }
+    }
+
+    #[cfg(feature = "throwback")]
+    #[wasm_bindgen(js_name = loadThrowback)]
+    pub fn load_throwback() -> WasmGame {
+        WasmGame {
+            game: load_soccar_throwback(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = setBall)]
+    pub fn set_ball(&mut self, time: f32, location: &[f32], velocity: &[f32], angular_velocity: &[f32]) -> Result<(), JsError> {
+        self.game.ball.update(time, vec3(location)?, vec3(velocity)?, vec3(angular_velocity)?);
+        Ok(())
+    }
+
+    // the current state of the ball, in the same layout as a slice
+    #[wasm_bindgen(js_name = getBall)]
+    pub fn get_ball(&self) -> Vec<f32> {
+        slice(&self.game.ball).to_vec()
+    }
+
+    // Like `Ball::get_ball_prediction_struct_for_slices`, this moves the ball to the last slice
+    #[wasm_bindgen(js_name = getBallPrediction)]
+    pub fn get_ball_prediction(&mut self, num_slices: usize) -> Vec<f32> {
+        Ball::get_ball_prediction_struct_for_slices(&mut self.game, num_slices).slices.iter().flat_map(slice).collect()
+    }
+}
+
+fn slice(ball: &Ball) -> [f32; SLICE_LEN] {
+    let [lx, ly, lz] = ball.location.to_array();
+    let [vx, vy, vz] = ball.velocity.to_array();
+    let [ax, ay, az] = ball.angular_velocity.to_array();
+
+    [ball.time, lx, ly, lz, vx, vy, vz, ax, ay, az]
+}
+
+#[cfg(all(test, feature = "soccar"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flat_prediction() {
+        let mut game = WasmGame::load_soccar();
+        game.set_ball(0., &[0., 0., 1000.], &[500., 0., 0.], &[0., 0., 0.]).unwrap();
+
+        let prediction = game.get_ball_prediction(120);
+        assert_eq!(prediction.len(), 120 * SLICE_LEN);
+
+        let mut expected = load_soccar();
+        expected.ball.update(0., Vec3A::new(0., 0., 1000.), Vec3A::new(500., 0., 0.), Vec3A::ZERO);
+        let expected = Ball::get_ball_prediction_struct_for_slices(&mut expected, 120);
+
+        for (slice, ball) in prediction.chunks(SLICE_LEN).zip(&expected.slices) {
+            assert_eq!(slice[0], ball.time);
+            assert_eq!(slice[1..4], ball.location.to_array());
+        }
+
+        assert_eq!(game.get_ball(), prediction[119 * SLICE_LEN..]);
+    }
+}
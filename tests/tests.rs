@@ -1,14 +1,23 @@
-use glam::{vec3a, Vec3A};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use glam::{vec3a, Mat3A, Vec3A};
 use rand::Rng;
 use rl_ball_sym::simulation::ball::Ball;
-use rl_ball_sym::simulation::game::Game;
-use rl_ball_sym::simulation::geometry::Aabb;
+use rl_ball_sym::simulation::bvh::Bvh;
+use rl_ball_sym::simulation::game::{Accuracy, ContactModel, Game, GameMode};
+use rl_ball_sym::simulation::geometry::{Aabb, Obb, Sphere, Tri};
+use rl_ball_sym::simulation::grid::Grid;
+use rl_ball_sym::simulation::material::Material;
+use rl_ball_sym::simulation::mesh::Mesh;
 use rl_ball_sym::simulation::morton::Morton;
-use rl_ball_sym::{load_dropshot, load_hoops, load_soccar, load_soccar_throwback};
+use rl_ball_sym::{load_all, load_all_instanced, load_custom, load_custom_meshes, load_dropshot, load_hoops, load_hoops_with_bvh_file, load_soccar, load_soccar_throwback, load_soccar_instanced, load_soccar_with_bvh_file, try_load_all, try_load_all_from_dir, try_load_dropshot_from_dir, try_load_hoops_from_dir, try_load_soccar, try_load_soccar_from_dir, LoadError};
 
 static mut GAME_0: Option<Game> = None;
 
 #[test]
+#[allow(static_mut_refs, clippy::needless_borrow)]
 fn init() {
     let mut game: &mut Game;
     unsafe {
@@ -74,9 +83,9 @@ fn gamemode_soccar() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.field.collision_mesh.root.box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 8028u64);
+    assert_eq!(game.field.collision_mesh.num_leaves, 8028u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -92,6 +101,24 @@ fn gamemode_soccar() {
     assert_eq!(game.ball.collision_radius as i64, 93);
 }
 
+#[test]
+fn soccar_goal_mouth_has_a_floor() {
+    let game = load_soccar();
+
+    // between the goal line and where the goal's own floor starts
+    for y in [5150., 5250., 5340.] {
+        for sign in [1., -1.] {
+            let ball = Sphere {
+                center: vec3a(0., y * sign, 90.),
+                radius: 93.,
+            };
+
+            let contact = game.collide(&ball).unwrap();
+            assert!(contact.direction.z > 0.99);
+        }
+    }
+}
+
 #[test]
 fn gamemode_hoops() {
     let game = load_hoops();
@@ -102,9 +129,9 @@ fn gamemode_hoops() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.field.collision_mesh.root.box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 15732u64);
+    assert_eq!(game.field.collision_mesh.num_leaves, 15732u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -130,9 +157,9 @@ fn gamemode_dropshot() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(game.collision_mesh.root.box_);
+    dbg!(game.field.collision_mesh.root.box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 3616u64);
+    assert_eq!(game.field.collision_mesh.num_leaves, 3616u64);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -158,11 +185,11 @@ fn gamemode_throwback_soccar() {
     assert_eq!(game.gravity.y as i64, 0);
     assert_eq!(game.gravity.z as i64, -650);
 
-    dbg!(&game.collision_mesh.root.box_);
-    dbg!(&game.collision_mesh.root.left.as_deref().unwrap().box_);
-    dbg!(&game.collision_mesh.root.right.as_deref().unwrap().box_);
+    dbg!(&game.field.collision_mesh.root.box_);
+    dbg!(&game.field.collision_mesh.root.left.as_deref().unwrap().box_);
+    dbg!(&game.field.collision_mesh.root.right.as_deref().unwrap().box_);
 
-    assert_eq!(game.collision_mesh.num_leaves, 9272);
+    assert_eq!(game.field.collision_mesh.num_leaves, 9272);
 
     assert_eq!(game.ball.time as i64, 0);
     assert_eq!(game.ball.location.x as i64, 0);
@@ -210,7 +237,7 @@ fn basic_predict() {
     let mut y_locs = Vec::with_capacity(num_slices);
     let mut z_locs = Vec::with_capacity(num_slices);
 
-    dbg!(game.collision_mesh.global_box);
+    dbg!(game.field.collision_mesh.global_box);
 
     for _ in 0..iters {
         game.ball.update(0., vec3a(rng.gen_range(-3900.0..3900.), rng.gen_range(-5000.0..5000.), rng.gen_range(100.0..1900.)), vec3a(rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.)), vec3a(rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.)));
@@ -237,14 +264,14 @@ fn basic_predict() {
     dbg!(*z_locs.iter().min().unwrap());
     dbg!(*z_locs.iter().max().unwrap());
 
-    assert!(*z_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.z as isize);
-    assert!(*z_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.z as isize);
+    assert!(*z_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.z as isize);
+    assert!(*z_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.z as isize);
 
-    assert!(*y_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.y as isize);
-    assert!(*y_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.y as isize);
+    assert!(*y_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.y as isize);
+    assert!(*y_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.y as isize);
 
-    assert!(*x_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.x as isize);
-    assert!(*x_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.x as isize);
+    assert!(*x_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.x as isize);
+    assert!(*x_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.x as isize);
 }
 
 #[test]
@@ -279,7 +306,7 @@ fn basic_predict_throwback() {
     let mut y_locs = Vec::with_capacity(num_slices);
     let mut z_locs = Vec::with_capacity(num_slices);
 
-    dbg!(game.collision_mesh.global_box);
+    dbg!(game.field.collision_mesh.global_box);
 
     for _ in 0..iters {
         game.ball.update(0., vec3a(rng.gen_range(-3900.0..3900.), rng.gen_range(-5000.0..5000.), rng.gen_range(100.0..1900.)), vec3a(rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.)), vec3a(rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.), rng.gen_range(-3.0..3.)));
@@ -306,14 +333,14 @@ fn basic_predict_throwback() {
     dbg!(*z_locs.iter().min().unwrap());
     dbg!(*z_locs.iter().max().unwrap());
 
-    assert!(*z_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.z as isize);
-    assert!(*z_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.z as isize);
+    assert!(*z_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.z as isize);
+    assert!(*z_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.z as isize);
 
-    assert!(*y_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.y as isize);
-    assert!(*y_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.y as isize);
+    assert!(*y_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.y as isize);
+    assert!(*y_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.y as isize);
 
-    assert!(*x_locs.iter().min().unwrap() > game.collision_mesh.global_box.min.x as isize);
-    assert!(*x_locs.iter().max().unwrap() < game.collision_mesh.global_box.max.x as isize);
+    assert!(*x_locs.iter().min().unwrap() > game.field.collision_mesh.global_box.min.x as isize);
+    assert!(*x_locs.iter().max().unwrap() < game.field.collision_mesh.global_box.max.x as isize);
 }
 
 #[test]
@@ -371,3 +398,532 @@ fn check_for_nans() {
         assert!(slice.angular_velocity.is_finite());
     }
 }
+
+#[test]
+fn grid_broadphase_matches_bvh() {
+    let bvh_game = load_soccar();
+    let mut grid_game = bvh_game.clone();
+    grid_game.field.use_grid_broadphase(Grid::DEFAULT_CELL_SIZE);
+
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..2000 {
+        let sphere = Sphere {
+            center: vec3a(rng.gen_range(-4200.0..4200.), rng.gen_range(-6000.0..6000.), rng.gen_range(-50.0..2100.)),
+            radius: bvh_game.ball.collision_radius,
+        };
+
+        let bvh_contact = bvh_game.collide(&sphere);
+        let grid_contact = grid_game.collide(&sphere);
+
+        assert_eq!(bvh_contact.is_some(), grid_contact.is_some());
+
+        if let (Some(a), Some(b)) = (bvh_contact, grid_contact) {
+            assert!((a.start - b.start).length() < 0.1);
+            assert!((a.direction - b.direction).length() < 0.001);
+        }
+    }
+}
+
+#[test]
+fn predict_soccar_grid_broadphase() {
+    let mut game = load_soccar();
+    game.field.use_grid_broadphase(Grid::DEFAULT_CELL_SIZE);
+
+    game.ball.update(0.098145, vec3a(-2294.5247, 1684.136, 317.17673), vec3a(1273.7537, -39.792305, 763.2827), vec3a(2.3894, -0.8755, 3.8078));
+
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert_eq!(ball_prediction.num_slices, 720);
+
+    for slice in ball_prediction.slices {
+        assert!(slice.location.is_finite());
+    }
+}
+
+#[test]
+fn predict_soccar_instanced_broadphase() {
+    let mut flat = load_soccar();
+    flat.ball.update(0.098145, vec3a(-2294.5247, 1684.136, 317.17673), vec3a(1273.7537, -39.792305, 763.2827), vec3a(2.3894, -0.8755, 3.8078));
+
+    let mut instanced = flat.clone();
+    instanced.field.use_instanced_broadphase(load_soccar_instanced());
+
+    let expected = Ball::get_ball_prediction_struct(&mut flat);
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut instanced);
+    assert_eq!(ball_prediction.num_slices, expected.num_slices);
+
+    // the same triangles, only found in a different order
+    for (slice, expected) in ball_prediction.slices.iter().zip(&expected.slices) {
+        assert!(slice.location.distance(expected.location) < 1., "{} vs {}", slice.location, expected.location);
+    }
+}
+
+#[test]
+fn geometry_is_public() {
+    use rl_ball_sym::geometry::{Ray, Tri};
+
+    let tri = Tri {
+        p: [vec3a(0., 0., 0.), vec3a(100., 0., 0.), vec3a(0., 100., 0.)],
+    };
+
+    assert_eq!(tri.unit_normal(), Vec3A::Z);
+    assert!(tri.intersect_sphere(&Sphere {
+        center: vec3a(10., 10., 50.),
+        radius: 50.,
+    }));
+
+    let ray = Ray::default();
+    assert_eq!(ray.start, Vec3A::ZERO);
+}
+
+#[test]
+fn obb_field_collision() {
+    let game = load_soccar();
+
+    // roughly an octane's hitbox
+    let mut car = Obb {
+        center: vec3a(0., 0., 17.),
+        orientation: Mat3A::IDENTITY,
+        half_extents: vec3a(59.0, 42.1, 18.1),
+    };
+
+    // sitting on the floor, but just slightly sunk into it
+    let contact = game.field.obb_collides(&car).unwrap();
+    assert!((contact.normal - Vec3A::Z).length() < 1e-4);
+    assert!((contact.depth - 1.1).abs() < 1e-3);
+
+    // floating in the middle of the field
+    car.center.z = 500.;
+    assert!(game.field.obb_collides(&car).is_none());
+
+    // driving into the side wall
+    car.center = vec3a(4096. - 50., 0., 500.);
+    let contact = game.field.obb_collides(&car).unwrap();
+    assert!(contact.normal.x < -0.99);
+}
+
+#[test]
+fn clearance_along_path() {
+    let game = load_soccar();
+    let radius = game.ball.collision_radius;
+
+    let path = [vec3a(0., 0., 1000.), vec3a(0., 0., 500.), vec3a(0., 0., 50.), vec3a(3000., 0., 500.)];
+    let clearance = game.field.clearance_along(&path, radius);
+
+    assert_eq!(clearance.len(), 3);
+    // the ceiling is 2048 up, the floor 500 down
+    assert!((clearance[0] - (500. - radius)).abs() < 0.01);
+    // dips into the floor
+    assert!((clearance[1] - (50. - radius)).abs() < 0.01);
+    assert!(clearance[1] < 0.);
+    // stays away from the side wall
+    assert!(clearance[2] < 50. - radius + 0.01);
+
+    let single = game.field.clearance_along(&[vec3a(0., 0., 1024.)], radius);
+    assert!((single[0] - (1024. - radius)).abs() < 0.01);
+}
+
+#[test]
+#[cfg_attr(feature = "fast-math", ignore = "fast math doesn't give bit-stable results")]
+fn prediction_checksum() {
+    let mut game = load_soccar();
+
+    game.ball.update(0.098145, vec3a(-2294.5247, 1684.136, 317.17673), vec3a(1273.7537, -39.792305, 763.2827), vec3a(2.3894, -0.8755, 3.8078));
+
+    // if the physics change on purpose, this has to be updated, along with `PHYSICS_VERSION`
+    let ball_prediction = Ball::get_ball_prediction_struct(&mut game);
+    ball_prediction.verify_checksum_hex("0243211013fde5a9").unwrap();
+}
+
+#[test]
+fn custom_field_boundary_edges() {
+    // a closed box, like a custom map author might build
+    let corners: Vec<Vec3A> = (0..8).map(|i| Vec3A::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32) * 1000.).collect();
+    let faces = [[0, 1, 3, 2], [4, 6, 7, 5], [0, 4, 5, 1], [2, 3, 7, 6], [0, 2, 6, 4], [1, 5, 7, 3]];
+
+    let triangles: Vec<Tri> = faces
+        .iter()
+        .flat_map(|f| {
+            [
+                Tri {
+                    p: [corners[f[0]], corners[f[1]], corners[f[2]]],
+                },
+                Tri {
+                    p: [corners[f[0]], corners[f[2]], corners[f[3]]],
+                },
+            ]
+        })
+        .collect();
+
+    assert!(Bvh::from(&triangles).find_boundary_edges().is_empty());
+    assert!(Bvh::from(&triangles).is_watertight());
+
+    // leaving out one side of the box opens up its outline
+    let edges = Bvh::from(&triangles[2..]).find_boundary_edges();
+    assert_eq!(edges.len(), 4);
+    assert!(edges.iter().flatten().all(|p| p.z == 0.));
+    assert_eq!(Bvh::from(&triangles[2..]).find_holes().len(), 4);
+    assert!(!Bvh::from(&triangles[2..]).is_watertight());
+}
+
+#[test]
+fn fields_contain_the_ball() {
+    for game in [load_soccar(), load_hoops(), load_dropshot(), load_soccar_throwback()] {
+        // the ball always starts inside of the field
+        assert!(game.field.collision_mesh.contains(game.ball.location));
+        assert!(game.field.collision_mesh.contains(game.ball.location + Vec3A::new(100., -100., 200.)));
+
+        assert!(!game.field.collision_mesh.contains(Vec3A::new(0., 0., -500.)));
+        assert!(!game.field.collision_mesh.contains(Vec3A::new(0., 0., 1e5)));
+    }
+
+    // just outside of the side wall
+    assert!(!load_soccar().field.collision_mesh.contains(Vec3A::new(4200., 0., 500.)));
+    assert!(load_soccar().field.collision_mesh.contains(Vec3A::new(4000., 0., 500.)));
+    // in front of a corner, where the side wall continues behind the corner's surface
+    assert!(load_soccar().field.collision_mesh.contains(Vec3A::new(3300., 4300., 125.)));
+}
+
+#[test]
+fn field_triangle_ids() {
+    let game = load_soccar();
+    let mut ids: Vec<_> = game.field.collision_mesh.primitives_with_ids().into_iter().map(|(id, _)| id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    assert_eq!(ids.len(), game.field.collision_mesh.num_leaves as usize);
+
+    // the same ids point at the same triangles after reloading the field
+    let reloaded: HashMap<_, _> = load_soccar().field.collision_mesh.primitives_with_ids().into_iter().collect();
+    for (id, tri) in game.field.collision_mesh.primitives_with_ids() {
+        assert_eq!(reloaded[&id].p, tri.p);
+    }
+}
+
+#[test]
+fn coarse_accuracy() {
+    let mut game = load_soccar();
+    game.set_accuracy(Accuracy::Coarse);
+
+    let coarse = game.field.coarse_collision_mesh.as_ref().unwrap();
+    assert!(coarse.num_leaves < game.field.collision_mesh.num_leaves);
+
+    // the floor is flat, so it's the same either way
+    let sphere = Sphere {
+        center: vec3a(0., 0., 90.),
+        radius: 92.75,
+    };
+    let full = game.collide_with_accuracy(&sphere, Accuracy::Full).unwrap();
+    let coarse = game.collide_with_accuracy(&sphere, Accuracy::Coarse).unwrap();
+    assert_eq!(full.start, coarse.start);
+    assert_eq!(full.direction, coarse.direction);
+
+    // the ball still stays inside of the field when rolling up into a corner
+    game.ball.update(0., vec3a(3000., 4000., 100.), vec3a(1500., 1500., 0.), Vec3A::ZERO);
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    for slice in &prediction.slices {
+        assert!(slice.location.z > 0.);
+        assert!(slice.location.x.abs() < 4096.);
+        assert!(slice.location.y.abs() < 6000.);
+    }
+}
+
+#[test]
+fn distance_field_culling_is_exact() {
+    let mut game = load_soccar();
+    game.ball.update(0., vec3a(-2000., 3000., 800.), vec3a(1500., 2000., -500.), vec3a(1., -2., 3.));
+    let mut culled_game = game.clone();
+    culled_game.build_distance_field(256.);
+
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+    let culled_prediction = Ball::get_ball_prediction_struct(&mut culled_game);
+
+    assert_eq!(prediction.checksum(), culled_prediction.checksum());
+}
+
+#[test]
+fn soccar_goal_floor() {
+    // the floor used to stop short of the goal's floor, which the ball would fall through
+    let mut game = load_soccar();
+    game.ball.update(0., vec3a(0., -5250., 200.), Vec3A::ZERO, Vec3A::ZERO);
+
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+    assert!(prediction.slices.last().unwrap().location.z > 0.);
+}
+
+#[test]
+fn corner_pinch_energy() {
+    // a ball driven into the corner where the side wall, ramp, and floor all meet
+    let mut game = load_soccar();
+
+    for contact_model in [ContactModel::Game, ContactModel::SplitImpulse] {
+        game.contact_model = contact_model;
+        game.ball.update(0., vec3a(3000., 4000., 150.), vec3a(2800., 2800., -200.), Vec3A::ZERO);
+
+        let gravity = game.gravity.z;
+        let energy = |ball: &Ball| 0.5 * ball.velocity.length_squared() - gravity * ball.location.z;
+        let start_energy = energy(&game.ball);
+
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+
+        for slice in &prediction.slices {
+            assert!(energy(slice) <= start_energy, "{contact_model:?}: {} > {}", energy(slice), start_energy);
+            assert!(slice.location.z > 0.);
+            assert!(slice.location.x.abs() < 4096.);
+            assert!(slice.location.y.abs() < 6000.);
+        }
+    }
+}
+
+#[test]
+fn custom_obj_field() {
+    // a closed box, 4000 wide and 2000 tall
+    let obj = "v -2000 -2000 0\nv 2000 -2000 0\nv 2000 2000 0\nv -2000 2000 0\nv -2000 -2000 2000\nv 2000 -2000 2000\nv 2000 2000 2000\nv -2000 2000 2000\nf 1 2 3 4\nf 5 8 7 6\nf 1 5 6 2\nf 2 6 7 3\nf 3 7 8 4\nf 4 8 5 1\n";
+
+    let path = std::env::temp_dir().join("rl_ball_sym_custom_obj_field.obj");
+    std::fs::write(&path, obj).unwrap();
+    let mut game = load_custom(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(game.field.collision_mesh.primitives().len(), 12);
+    assert!(game.field.collision_mesh.is_watertight());
+
+    game.ball.update(0., vec3a(0., 0., 1000.), vec3a(3000., 2000., 1500.), Vec3A::ZERO);
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+
+    // the ball bounces around the box instead of flying off
+    for slice in &prediction.slices {
+        assert!(slice.location.abs().max_element() < 2000., "{:?}", slice.location);
+        assert!(slice.location.z > 0. && slice.location.z < 2000.);
+    }
+
+    assert!(load_custom(std::env::temp_dir().join("rl_ball_sym_missing.obj")).is_err());
+}
+
+#[test]
+fn custom_materials() {
+    let floor = |x: f32| Mesh {
+        ids: vec![0, 1, 2, 0, 2, 3],
+        vertices: vec![x, -2000., 0., x + 2000., -2000., 0., x + 2000., 2000., 0., x, 2000., 0.],
+    };
+    let (normal, bouncy) = (floor(-2000.), floor(0.));
+
+    let mut game = load_custom_meshes(&[
+        (&normal, None),
+        (
+            &bouncy,
+            Some(Material {
+                restitution: 1.,
+                friction: 2.,
+            }),
+        ),
+    ]);
+
+    // the highest the ball gets after its first bounce
+    let mut bounce_height = |x: f32| {
+        game.ball.update(0., vec3a(x, 0., 500.), Vec3A::ZERO, Vec3A::ZERO);
+        let prediction = Ball::get_ball_prediction_struct_for_slices(&mut game, 240);
+        let bounce = prediction.slices.iter().position(|slice| slice.velocity.z > 0.).unwrap();
+        prediction.slices[bounce..].iter().map(|slice| slice.location.z).fold(f32::MIN, f32::max)
+    };
+
+    let (normal_height, bouncy_height) = (bounce_height(-1000.), bounce_height(1000.));
+    assert!(bouncy_height > 450., "{bouncy_height}");
+    assert!(normal_height < 300., "{normal_height}");
+
+    // a field without any materials doesn't look anything up
+    assert!(load_custom_meshes(&[(&normal, None)]).field.materials.is_none());
+}
+
+#[test]
+fn custom_gravity() {
+    // the first slice where a ball dropped from 1000 uu reaches the floor
+    let first_bounce = |gravity: Vec3A| {
+        let mut game = load_soccar();
+        game.gravity = gravity;
+        game.ball.update(0., vec3a(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+
+        let prediction = Ball::get_ball_prediction_struct(&mut game);
+        prediction.slices.iter().position(|slice| slice.velocity.z > 0.)
+    };
+
+    let normal = first_bounce(vec3a(0., 0., -650.)).unwrap() as f32;
+    let low = first_bounce(vec3a(0., 0., -325.)).unwrap() as f32;
+    let high = first_bounce(vec3a(0., 0., -1300.)).unwrap() as f32;
+
+    // the fall time goes with 1 / sqrt(g), other than a little drag
+    assert!((low / normal - 2f32.sqrt()).abs() < 0.05, "{low} {normal}");
+    assert!((normal / high - 2f32.sqrt()).abs() < 0.05, "{normal} {high}");
+
+    // and without any gravity the ball never comes down
+    assert_eq!(first_bounce(Vec3A::ZERO), None);
+}
+
+#[test]
+fn load_all_fields() {
+    let fields = load_all();
+
+    for (game, expected) in [(&fields.soccar, load_soccar()), (&fields.hoops, load_hoops()), (&fields.dropshot, load_dropshot()), (&fields.throwback, load_soccar_throwback())] {
+        assert_eq!(game.mode, expected.mode);
+        assert_eq!(game.field.collision_mesh.num_leaves, expected.field.collision_mesh.num_leaves);
+        assert!(game.field.collision_mesh.primitives_with_ids().iter().map(|(id, _)| *id).eq(expected.field.collision_mesh.primitives_with_ids().iter().map(|(id, _)| *id)));
+        assert_eq!(game.ball.location, expected.ball.location);
+    }
+
+    assert_eq!(fields.snowday.mode, GameMode::Snowday);
+    assert!(Arc::ptr_eq(&fields.snowday.field.collision_mesh, &fields.soccar.field.collision_mesh));
+}
+
+#[test]
+fn load_all_instanced_fields() {
+    let fields = load_all_instanced();
+
+    assert_eq!(fields.soccar.num_leaves(), load_soccar().field.collision_mesh.num_leaves);
+    assert_eq!(fields.hoops.num_leaves(), load_hoops().field.collision_mesh.num_leaves);
+    assert_eq!(fields.throwback.num_leaves(), load_soccar_throwback().field.collision_mesh.num_leaves);
+}
+
+#[test]
+fn cloned_games_share_the_field() {
+    let mut game = load_soccar();
+    game.field.use_grid_broadphase(Grid::DEFAULT_CELL_SIZE);
+    game.set_accuracy(Accuracy::Coarse);
+
+    let mut hypothetical = game.clone();
+    assert!(Arc::ptr_eq(&game.field.collision_mesh, &hypothetical.field.collision_mesh));
+    assert!(Arc::ptr_eq(game.field.coarse_collision_mesh.as_ref().unwrap(), hypothetical.field.coarse_collision_mesh.as_ref().unwrap()));
+
+    // but each has its own ball
+    hypothetical.ball.update(0., vec3a(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+    Ball::get_ball_prediction_struct_for_time(&mut hypothetical, &1.);
+    assert_eq!(game.ball.time, 0.);
+    assert!(hypothetical.ball.time > 0.9);
+}
+
+#[test]
+fn line_of_sight() {
+    let game = load_soccar();
+
+    // across the field, over the floor
+    assert!(game.field.line_of_sight(vec3a(-3000., -4000., 100.), vec3a(3000., 4000., 1500.)));
+    // through the side wall, and through the floor
+    assert!(!game.field.line_of_sight(vec3a(3000., 0.5, 500.), vec3a(5000., 0.5, 500.)));
+    assert!(!game.field.line_of_sight(vec3a(0.5, 0.5, 500.), vec3a(0.5, 0.5, -500.)));
+    // into the goal, but not around the post from behind the back wall
+    assert!(game.field.line_of_sight(vec3a(0.5, -3000., 300.), vec3a(0.5, -5500., 300.)));
+    assert!(!game.field.line_of_sight(vec3a(0.5, -4000., 300.), vec3a(2000., -5500., 300.)));
+
+    let segments = [(vec3a(0.5, 0.5, 500.), vec3a(1000., 1000., 500.)), (vec3a(3000., 0.5, 500.), vec3a(5000., 0.5, 500.))];
+    assert_eq!(game.field.lines_of_sight(&segments), vec![true, false]);
+}
+
+#[test]
+fn load_with_bvh_file() {
+    let path = std::env::temp_dir().join(format!("rl_ball_sym_hoops_{}.bvh", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // the first load builds and saves the BVH, and the second one reads it back
+    let built = load_hoops_with_bvh_file(&path);
+    assert!(path.exists());
+    let loaded = load_hoops_with_bvh_file(&path);
+
+    assert_eq!(loaded.mode, GameMode::Hoops);
+    assert_eq!(loaded.geometry_hash(), load_hoops().geometry_hash());
+    assert_eq!(loaded.field.collision_mesh.to_bytes(), built.field.collision_mesh.to_bytes());
+
+    // a broken file is replaced
+    std::fs::write(&path, b"not a bvh").unwrap();
+    assert_eq!(load_hoops_with_bvh_file(&path).geometry_hash(), built.geometry_hash());
+    assert!(Bvh::from_bytes_with_tag(&std::fs::read(&path).unwrap(), "hoops").is_ok());
+
+    // and so is one that was saved for a different field
+    assert_eq!(load_soccar_with_bvh_file(&path).geometry_hash(), load_soccar().geometry_hash());
+    assert!(Bvh::from_bytes_with_tag(&std::fs::read(&path).unwrap(), "soccar").is_ok());
+    assert_eq!(load_hoops_with_bvh_file(&path).geometry_hash(), built.geometry_hash());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn prelude() {
+    use rl_ball_sym::prelude::*;
+
+    let mut game: Game = load_soccar();
+    game.ball.update(0., vec3a(0., 0., 1000.), Vec3A::ZERO, Vec3A::ZERO);
+
+    let prediction: BallPrediction = Ball::get_ball_prediction_struct(&mut game);
+    assert_eq!(prediction.num_slices, PredictionConfig::default().num_slices);
+    assert_eq!(game.mode, GameMode::Soccar);
+}
+
+#[cfg(feature = "mint")]
+#[test]
+fn mint_conversions() {
+    let mut game = load_soccar();
+    let location = mint::Vector3 { x: 0., y: 0., z: 1000. };
+    game.ball.update(0., location.into(), Vec3A::ZERO, Vec3A::ZERO);
+
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+    let first: mint::Vector3<f32> = prediction.slices[0].location.into();
+    assert!(first.z < 1000. && first.x == 0.);
+}
+
+#[cfg(feature = "nalgebra")]
+#[test]
+fn nalgebra_conversions() {
+    use nalgebra::Vector3;
+
+    let mut game = load_soccar();
+    game.ball.update(0., Vector3::new(0., 0., 1000.).into(), Vector3::new(500., 0., 0.).into(), Vec3A::ZERO);
+
+    let prediction = Ball::get_ball_prediction_struct(&mut game);
+    let velocity: Vector3<f32> = prediction.slices[0].velocity.into();
+    assert_eq!(Vec3A::from(velocity), prediction.slices[0].velocity);
+    assert!(velocity.x > 0. && velocity.z < 0.);
+}
+
+#[test]
+fn fallible_loaders() {
+    // the assets built into the crate always decode
+    let soccar = try_load_soccar().unwrap();
+    assert_eq!(soccar.geometry_hash(), load_soccar().geometry_hash());
+
+    let fields = try_load_all().unwrap();
+    assert_eq!(fields.hoops.geometry_hash(), load_hoops().geometry_hash());
+    assert_eq!(fields.snowday.mode, GameMode::Snowday);
+}
+
+#[test]
+fn loaders_from_dir() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+
+    // the same meshes as the ones built in
+    let fields = try_load_all_from_dir(&assets).unwrap();
+    assert_eq!(fields.soccar.geometry_hash(), load_soccar().geometry_hash());
+    assert_eq!(fields.throwback.geometry_hash(), load_soccar_throwback().geometry_hash());
+    assert!(fields.throwback.field.materials.is_some());
+    assert_eq!(try_load_dropshot_from_dir(&assets).unwrap().geometry_hash(), load_dropshot().geometry_hash());
+
+    // a patched copy, with one of the meshes cut short
+    let dir = std::env::temp_dir().join(format!("rl_ball_sym_assets_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("soccar")).unwrap();
+    for entry in std::fs::read_dir(assets.join("soccar")).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), dir.join("soccar").join(entry.file_name())).unwrap();
+    }
+
+    assert!(try_load_soccar_from_dir(&dir).is_ok());
+
+    std::fs::write(dir.join("soccar/soccar_goal_ids.bin"), [0; 5]).unwrap();
+    assert!(matches!(try_load_soccar_from_dir(&dir), Err(LoadError::InvalidAsset { name: "soccar_goal", .. })));
+
+    // the folder for hoops is missing, and the error says which file it was looking for
+    let Err(error) = try_load_hoops_from_dir(&dir) else {
+        panic!("There aren't any hoops meshes to load");
+    };
+    assert!(matches!(error, LoadError::Io(_)));
+    assert!(error.to_string().contains("hoops_corner_ids.bin"), "{error}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}